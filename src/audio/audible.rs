@@ -9,7 +9,9 @@ use std::path::Path;
 use std::time::Duration;
 use thiserror::Error;
 
+use crate::error::ForgeError;
 use crate::models::{AudibleMetadata, AudibleRegion, AudibleAuthor, AudibleSeries};
+use crate::utils::AudibleCache;
 
 const AUDNEXUS_BASE_URL: &str = "https://api.audnex.us";
 const DEFAULT_TIMEOUT_SECS: u64 = 10;
@@ -17,12 +19,14 @@ const DEFAULT_TIMEOUT_SECS: u64 = 10;
 /// Audible API errors with detailed context
 #[derive(Error, Debug)]
 pub enum AudibleApiError {
-    #[error("HTTP {status}: {message}\nURL: {url}\nResponse: {body}")]
+    #[error("HTTP {status}: {message}\nURL: {url}\nResponse: {body}{suggestion}")]
     HttpError {
         status: u16,
         message: String,
         url: String,
         body: String,
+        /// What the user could do about it (empty if nothing specific)
+        suggestion: String,
     },
 
     #[error("Rate limit exceeded (429). Retry after {retry_after:?}")]
@@ -35,6 +39,41 @@ pub enum AudibleApiError {
 
     #[error("Invalid response format: {0}")]
     ParseError(String),
+
+    #[error("Offline mode: {what} is not in the local cache")]
+    NotCached {
+        what: String,
+    },
+}
+
+impl AudibleApiError {
+    /// Whether an error is an offline cache miss rather than a real failure
+    pub fn is_not_cached(err: &anyhow::Error) -> bool {
+        err.chain().any(|cause| {
+            let api_error = match cause.downcast_ref::<ForgeError>() {
+                Some(ForgeError::Http(e)) => Some(e),
+                _ => cause.downcast_ref::<AudibleApiError>(),
+            };
+            matches!(api_error, Some(AudibleApiError::NotCached { .. }))
+        })
+    }
+
+    /// Add a suggestion to an HTTP error
+    fn with_suggestion(mut self, text: &str) -> Self {
+        if let AudibleApiError::HttpError { suggestion, .. } = &mut self {
+            if !text.is_empty() {
+                *suggestion = format!("\nSuggestion: {}", text);
+            }
+        }
+        self
+    }
+
+    fn status(&self) -> Option<u16> {
+        match self {
+            AudibleApiError::HttpError { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
 }
 
 /// Extract detailed error information from HTTP response
@@ -74,6 +113,7 @@ async fn extract_error_details(
         message: message.to_string(),
         url: url.to_string(),
         body: body.chars().take(500).collect(), // Limit to 500 chars
+        suggestion: String::new(),
     }
 }
 
@@ -83,6 +123,8 @@ pub struct AudibleClient {
     rate_limiter: RateLimiter<DirectNotKeyed, InMemoryState, DefaultClock>,
     region: AudibleRegion,
     retry_config: crate::core::RetryConfig,
+    cache: Option<AudibleCache>,
+    offline: bool,
 }
 
 impl AudibleClient {
@@ -120,9 +162,39 @@ impl AudibleClient {
             rate_limiter,
             region,
             retry_config,
+            cache: None,
+            offline: false,
         })
     }
 
+    /// Serve metadata, search results, chapters and covers from a cache
+    pub fn with_cache(mut self, cache: AudibleCache) -> Self {
+        self.cache = Some(if self.offline { cache.serving_stale() } else { cache });
+        self
+    }
+
+    /// Only serve data from the cache and never touch the network
+    ///
+    /// Cache entries are served regardless of age, and misses fail with
+    /// [`AudibleApiError::NotCached`] instead of being retried.
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        if offline {
+            self.cache = self.cache.map(AudibleCache::serving_stale);
+        }
+        self
+    }
+
+    /// Whether this client is in offline mode
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    fn not_cached(what: String) -> anyhow::Error {
+        tracing::debug!("Offline cache miss: {}", what);
+        ForgeError::from(AudibleApiError::NotCached { what }).into()
+    }
+
     /// Execute HTTP request with retry logic
     async fn execute_with_retry<F, Fut>(&self, f: F) -> Result<reqwest::Response>
     where
@@ -145,7 +217,7 @@ impl AudibleClient {
                     return Ok(response);
                 }
                 Err(e) => {
-                    last_error = Some(anyhow::Error::from(e));
+                    last_error = Some(anyhow::Error::from(ForgeError::from(AudibleApiError::from(e))));
 
                     // Check if we should retry
                     let should_retry = if let Some(ref err) = last_error {
@@ -196,13 +268,40 @@ impl AudibleClient {
 
         // Retry the request
         self.rate_limiter.until_ready().await;
-        let retry_response = self.client.get(url).send().await?;
+        let retry_response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| ForgeError::from(AudibleApiError::from(e)))?;
 
         Ok(retry_response)
     }
 
     /// Fetch metadata by ASIN
     pub async fn fetch_by_asin(&self, asin: &str) -> Result<AudibleMetadata> {
+        if let Some(cache) = &self.cache {
+            if let Some(cached) = cache.get(asin).await {
+                return Ok(cached);
+            }
+        }
+
+        if self.offline {
+            return Err(Self::not_cached(format!("metadata for ASIN {}", asin)));
+        }
+
+        let metadata = self.fetch_by_asin_remote(asin).await?;
+
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.set(asin, &metadata).await {
+                tracing::warn!("Failed to cache metadata for {}: {}", asin, e);
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    async fn fetch_by_asin_remote(&self, asin: &str) -> Result<AudibleMetadata> {
         let url = format!("{}/books/{}?region={}",
             AUDNEXUS_BASE_URL, asin, self.region.tld());
 
@@ -224,17 +323,13 @@ impl AudibleClient {
             let error = extract_error_details(&url, response).await;
 
             // Add helpful suggestions based on error type
-            let suggestion = match error {
-                AudibleApiError::HttpError { status, .. } if status >= 500 => {
-                    "\nSuggestion: This is a server error. Try again later or use a different region."
-                }
-                AudibleApiError::HttpError { status, .. } if status == 404 => {
-                    "\nSuggestion: ASIN not found in this region. Try a different region."
-                }
-                _ => ""
+            let suggestion = match error.status() {
+                Some(status) if status >= 500 => "This is a server error. Try again later or use a different region.",
+                Some(404) => "ASIN not found in this region. Try a different region.",
+                _ => "",
             };
 
-            anyhow::bail!("{}{}", error, suggestion);
+            return Err(ForgeError::from(error.with_suggestion(suggestion)).into());
         }
 
         // Parse the response
@@ -248,6 +343,28 @@ impl AudibleClient {
 
     /// Fetch chapter data from Audnex API
     pub async fn fetch_chapters(&self, asin: &str) -> Result<Vec<crate::models::AudibleChapter>> {
+        if let Some(cache) = &self.cache {
+            if let Some(cached) = cache.get_chapters(asin).await {
+                return Ok(cached);
+            }
+        }
+
+        if self.offline {
+            return Err(Self::not_cached(format!("chapters for ASIN {}", asin)));
+        }
+
+        let chapters = self.fetch_chapters_remote(asin).await?;
+
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.set_chapters(asin, &chapters).await {
+                tracing::warn!("Failed to cache chapters for {}: {}", asin, e);
+            }
+        }
+
+        Ok(chapters)
+    }
+
+    async fn fetch_chapters_remote(&self, asin: &str) -> Result<Vec<crate::models::AudibleChapter>> {
         let url = format!("{}/books/{}/chapters?region={}",
             AUDNEXUS_BASE_URL, asin, self.region.tld());
 
@@ -268,17 +385,14 @@ impl AudibleClient {
         if !response.status().is_success() {
             let error = extract_error_details(&url, response).await;
 
-            let suggestion = match error {
-                AudibleApiError::HttpError { status, .. } if status >= 500 => {
-                    "\nSuggestion: This is a server error. Try again later or use a different region."
-                }
-                AudibleApiError::HttpError { status, .. } if status == 404 => {
-                    "\nSuggestion: No chapter data available for this ASIN in this region."
-                }
-                _ => ""
+            // Add helpful suggestions based on error type
+            let suggestion = match error.status() {
+                Some(status) if status >= 500 => "This is a server error. Try again later or use a different region.",
+                Some(404) => "No chapter data available for this ASIN in this region.",
+                _ => "",
             };
 
-            anyhow::bail!("{}{}", error, suggestion);
+            return Err(ForgeError::from(error.with_suggestion(suggestion)).into());
         }
 
         // Parse the response
@@ -303,6 +417,32 @@ impl AudibleClient {
             anyhow::bail!("Must provide at least title or author for search");
         }
 
+        if let Some(cache) = &self.cache {
+            if let Some(cached) = cache.get_search(title, author, self.region).await {
+                return Ok(cached);
+            }
+        }
+
+        if self.offline {
+            return Err(Self::not_cached(format!(
+                "search results for title={:?}, author={:?} ({})",
+                title, author, self.region
+            )));
+        }
+
+        let results = self.search_remote(title, author).await?;
+
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.set_search(title, author, self.region, &results).await {
+                tracing::warn!("Failed to cache search results: {}", e);
+            }
+        }
+
+        Ok(results)
+    }
+
+    async fn search_remote(&self, title: Option<&str>, author: Option<&str>) -> Result<Vec<AudibleMetadata>> {
+
         // Build query parameters for Audible's search API
         let mut query_params = vec![
             ("num_results", "10"),
@@ -337,17 +477,14 @@ impl AudibleClient {
         if !response.status().is_success() {
             let error = extract_error_details(&url, response).await;
 
-            let suggestion = match error {
-                AudibleApiError::HttpError { status, .. } if status >= 500 => {
-                    "\nSuggestion: Audible's API is experiencing issues. Try again later."
-                }
-                AudibleApiError::HttpError { status, .. } if status == 403 => {
-                    "\nSuggestion: Access forbidden. Check if Audible API has blocked this region/IP."
-                }
-                _ => ""
+            // Add helpful suggestions based on error type
+            let suggestion = match error.status() {
+                Some(status) if status >= 500 => "Audible's API is experiencing issues. Try again later.",
+                Some(403) => "Access forbidden. Check if Audible API has blocked this region/IP.",
+                _ => "",
             };
 
-            anyhow::bail!("{}{}", error, suggestion);
+            return Err(ForgeError::from(error.with_suggestion(suggestion)).into());
         }
 
         // Parse Audible's search response (just ASINs)
@@ -375,6 +512,17 @@ impl AudibleClient {
 
     /// Download cover image
     pub async fn download_cover(&self, cover_url: &str, dest_path: &Path) -> Result<()> {
+        if let Some(cached) = self.cache.as_ref().and_then(|c| c.get_cover(cover_url)) {
+            std::fs::copy(&cached, dest_path)
+                .context("Failed to write cover file")?;
+            tracing::debug!("Cover served from cache: {}", dest_path.display());
+            return Ok(());
+        }
+
+        if self.offline {
+            return Err(Self::not_cached(format!("cover {}", cover_url)));
+        }
+
         // Wait for rate limiter
         self.rate_limiter.until_ready().await;
 
@@ -393,9 +541,15 @@ impl AudibleClient {
             .await
             .context("Failed to read cover bytes")?;
 
-        std::fs::write(dest_path, bytes)
+        std::fs::write(dest_path, &bytes)
             .context("Failed to write cover file")?;
 
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.set_cover(cover_url, &bytes).await {
+                tracing::warn!("Failed to cache cover: {}", e);
+            }
+        }
+
         tracing::debug!("Cover saved to: {}", dest_path.display());
 
         Ok(())
@@ -625,6 +779,37 @@ mod tests {
         assert_eq!(clean_sequence("no numbers"), "no numbers");
     }
 
+    #[tokio::test]
+    async fn test_offline_serves_cache_and_reports_misses() {
+        use crate::utils::CacheTtls;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = AudibleCache::with_dir(
+            dir.path().to_path_buf(),
+            CacheTtls::uniform(Duration::from_secs(3600)),
+        )
+        .unwrap();
+
+        let client = AudibleClient::new(AudibleRegion::US)
+            .unwrap()
+            .with_offline(true)
+            .with_cache(cache.clone());
+        assert!(client.is_offline());
+
+        let err = client.fetch_by_asin("B002V5D7RU").await.unwrap_err();
+        assert!(AudibleApiError::is_not_cached(&err));
+        let err = client.search(Some("Dune"), None).await.unwrap_err();
+        assert!(AudibleApiError::is_not_cached(&err));
+        let err = client
+            .download_cover("https://example.com/c.jpg", &dir.path().join("c.jpg"))
+            .await
+            .unwrap_err();
+        assert!(AudibleApiError::is_not_cached(&err));
+
+        cache.set_chapters("B002V5D7RU", &[]).await.unwrap();
+        assert!(client.fetch_chapters("B002V5D7RU").await.unwrap().is_empty());
+    }
+
     #[test]
     fn test_client_creation() {
         let client = AudibleClient::new(AudibleRegion::US).unwrap();
@@ -0,0 +1,591 @@
+//! Audio backend abstraction
+//!
+//! The core processing types talk to an [`AudioBackend`] for everything that
+//! touches audio data: probing, encoding, concatenation, chapter injection and
+//! tag writing. [`FfmpegBackend`] is the real implementation (FFmpeg, MP4Box
+//! and AtomicParsley subprocesses); [`RecordingBackend`] is an in-memory fake
+//! that records every call, so whole pipelines can be tested without any of
+//! those tools installed.
+
+use crate::audio::{
+    extract_metadata, inject_chapters_mp4box, inject_metadata_atomicparsley, read_m4b_chapters,
+    write_mp4box_chapters, AacEncoder, AudioMetadata, Chapter, EncodeTarget, FFmpeg, TrackTrim,
+};
+use crate::models::{QualityProfile, Track};
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Audio operations used by the processing pipeline
+pub trait AudioBackend: Send + Sync {
+    /// Probe an audio file's quality (bitrate, sample rate, channels, codec, duration)
+    fn probe_audio_file<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, Result<QualityProfile>>;
+
+    /// Read a track's tags into the track
+    fn read_tags<'a>(&'a self, track: &'a mut Track) -> BoxFuture<'a, Result<()>>;
+
+    /// Probe a file's container tags
+    fn probe_metadata<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, Result<AudioMetadata>>;
+
+    /// Probe a file's duration (ms) and embedded title
+    fn probe_duration_and_title<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxFuture<'a, Result<(u64, Option<String>)>>;
+
+    /// Read the chapters embedded in an M4B file
+    fn read_chapters<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, Result<Vec<Chapter>>>;
+
+    /// Convert (or copy) a single file to M4A/M4B
+    fn convert_single_file<'a>(
+        &'a self,
+        input: &'a Path,
+        output: &'a Path,
+        quality: &'a QualityProfile,
+        use_copy: bool,
+        encoder: AacEncoder,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Concatenate the files listed in a concat file, copying or transcoding
+    fn concat_audio_files<'a>(
+        &'a self,
+        concat_file: &'a Path,
+        output: &'a Path,
+        quality: &'a QualityProfile,
+        use_copy: bool,
+        encoder: AacEncoder,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Cut a track (intro/outro, silence) to a lossless intermediate file
+    fn trim_track<'a>(
+        &'a self,
+        input: &'a Path,
+        output: &'a Path,
+        trim: &'a TrackTrim,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Decode once and write every target (`input` is a concat file if `concat_list`)
+    fn encode_targets<'a>(
+        &'a self,
+        input: &'a Path,
+        concat_list: bool,
+        targets: &'a [EncodeTarget],
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Concatenate the M4B files listed in a concat file losslessly
+    fn concat_m4b_files<'a>(
+        &'a self,
+        concat_file: &'a Path,
+        output: &'a Path,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Replace a file's chapters (`chapters_file` is scratch space for the backend)
+    fn inject_chapters<'a>(
+        &'a self,
+        file: &'a Path,
+        chapters: &'a [Chapter],
+        chapters_file: &'a Path,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Write tags and cover art to a file
+    fn write_metadata<'a>(
+        &'a self,
+        file: &'a Path,
+        metadata: &'a AudioMetadata,
+        cover: Option<&'a Path>,
+    ) -> BoxFuture<'a, Result<()>>;
+}
+
+/// The default backend: FFmpeg, MP4Box and AtomicParsley subprocesses
+#[derive(Clone)]
+pub struct FfmpegBackend {
+    ffmpeg: FFmpeg,
+}
+
+impl FfmpegBackend {
+    /// Create a backend using the tools from the registry
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            ffmpeg: FFmpeg::new()?,
+        })
+    }
+
+    /// Create a backend around an existing FFmpeg wrapper
+    pub fn with_ffmpeg(ffmpeg: FFmpeg) -> Self {
+        Self { ffmpeg }
+    }
+
+    /// Shared default backend for the core processing types
+    pub fn shared() -> Result<Arc<dyn AudioBackend>> {
+        Ok(Arc::new(Self::new()?))
+    }
+}
+
+impl AudioBackend for FfmpegBackend {
+    fn probe_audio_file<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, Result<QualityProfile>> {
+        Box::pin(self.ffmpeg.probe_audio_file(path))
+    }
+
+    fn read_tags<'a>(&'a self, track: &'a mut Track) -> BoxFuture<'a, Result<()>> {
+        Box::pin(extract_metadata(track))
+    }
+
+    fn probe_metadata<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, Result<AudioMetadata>> {
+        Box::pin(self.ffmpeg.probe_metadata(path))
+    }
+
+    fn probe_duration_and_title<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxFuture<'a, Result<(u64, Option<String>)>> {
+        Box::pin(self.ffmpeg.probe_duration_and_title(path))
+    }
+
+    fn read_chapters<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, Result<Vec<Chapter>>> {
+        Box::pin(read_m4b_chapters(path))
+    }
+
+    fn convert_single_file<'a>(
+        &'a self,
+        input: &'a Path,
+        output: &'a Path,
+        quality: &'a QualityProfile,
+        use_copy: bool,
+        encoder: AacEncoder,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.ffmpeg.convert_single_file(input, output, quality, use_copy, encoder))
+    }
+
+    fn concat_audio_files<'a>(
+        &'a self,
+        concat_file: &'a Path,
+        output: &'a Path,
+        quality: &'a QualityProfile,
+        use_copy: bool,
+        encoder: AacEncoder,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.ffmpeg.concat_audio_files(concat_file, output, quality, use_copy, encoder))
+    }
+
+    fn trim_track<'a>(
+        &'a self,
+        input: &'a Path,
+        output: &'a Path,
+        trim: &'a TrackTrim,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.ffmpeg.trim_track(input, output, trim))
+    }
+
+    fn encode_targets<'a>(
+        &'a self,
+        input: &'a Path,
+        concat_list: bool,
+        targets: &'a [EncodeTarget],
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.ffmpeg.encode_targets(input, concat_list, targets))
+    }
+
+    fn concat_m4b_files<'a>(
+        &'a self,
+        concat_file: &'a Path,
+        output: &'a Path,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.ffmpeg.concat_m4b_files(concat_file, output))
+    }
+
+    fn inject_chapters<'a>(
+        &'a self,
+        file: &'a Path,
+        chapters: &'a [Chapter],
+        chapters_file: &'a Path,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            write_mp4box_chapters(chapters, chapters_file)
+                .context("Failed to write chapter file")?;
+            inject_chapters_mp4box(file, chapters_file).await
+        })
+    }
+
+    fn write_metadata<'a>(
+        &'a self,
+        file: &'a Path,
+        metadata: &'a AudioMetadata,
+        cover: Option<&'a Path>,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(inject_metadata_atomicparsley(
+            file,
+            metadata.title.as_deref(),
+            metadata.artist.as_deref(),
+            metadata.album.as_deref(),
+            metadata.album_artist.as_deref(),
+            metadata.year,
+            metadata.genre.as_deref(),
+            metadata.composer.as_deref(),
+            metadata.comment.as_deref(),
+            cover,
+        ))
+    }
+}
+
+/// Backend operation, used to inspect recorded calls and inject failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BackendOp {
+    ProbeAudio,
+    ReadTags,
+    ProbeMetadata,
+    ProbeDuration,
+    ReadChapters,
+    Convert,
+    Concat,
+    Trim,
+    EncodeTargets,
+    ConcatM4b,
+    InjectChapters,
+    WriteMetadata,
+}
+
+/// One call made to a [`RecordingBackend`]
+#[derive(Debug, Clone)]
+pub struct BackendCall {
+    /// Operation
+    pub op: BackendOp,
+    /// Input files (the entries of a concat list, not the list itself)
+    pub inputs: Vec<PathBuf>,
+    /// Files written
+    pub outputs: Vec<PathBuf>,
+    /// Whether the audio was stream-copied
+    pub use_copy: bool,
+    /// Chapters injected
+    pub chapters: Vec<Chapter>,
+    /// Tags written
+    pub metadata: Option<AudioMetadata>,
+    /// Whether the call failed (from an injected failure)
+    pub failed: bool,
+}
+
+impl BackendCall {
+    fn new(op: BackendOp, inputs: Vec<PathBuf>, outputs: Vec<PathBuf>) -> Self {
+        Self {
+            op,
+            inputs,
+            outputs,
+            use_copy: false,
+            chapters: Vec::new(),
+            metadata: None,
+            failed: false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct RecordingState {
+    calls: Vec<BackendCall>,
+    failures: HashMap<BackendOp, VecDeque<anyhow::Error>>,
+    /// Durations (seconds) of files written by trims, reported by later probes
+    durations: HashMap<PathBuf, f64>,
+}
+
+/// In-memory backend that records calls instead of running any tools
+///
+/// Probes answer from the configured quality, tags and chapters (a trimmed
+/// file's duration reflects its intro/outro cuts). Encodes and
+/// concatenations write a small placeholder file to each output so later steps
+/// find it. Failures can be queued per operation with [`fail_next`](Self::fail_next).
+pub struct RecordingBackend {
+    quality: QualityProfile,
+    metadata: AudioMetadata,
+    chapters: HashMap<PathBuf, Vec<Chapter>>,
+    state: Mutex<RecordingState>,
+}
+
+impl RecordingBackend {
+    /// Create a backend that reports 10-minute 128k stereo MP3s
+    pub fn new() -> Self {
+        Self {
+            quality: QualityProfile::new(128, 44100, 2, "mp3".to_string(), 600.0)
+                .expect("valid quality profile"),
+            metadata: AudioMetadata::default(),
+            chapters: HashMap::new(),
+            state: Mutex::new(RecordingState::default()),
+        }
+    }
+
+    /// Quality reported by every probe
+    pub fn with_quality(mut self, quality: QualityProfile) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Tags reported by every probe
+    pub fn with_metadata(mut self, metadata: AudioMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Chapters embedded in a file (files without an entry have none)
+    pub fn with_chapters(mut self, path: impl Into<PathBuf>, chapters: Vec<Chapter>) -> Self {
+        self.chapters.insert(path.into(), chapters);
+        self
+    }
+
+    /// Make the next call of an operation fail with `error`
+    ///
+    /// Queue several to fail several calls in a row.
+    pub fn fail_next(&self, op: BackendOp, error: impl Into<anyhow::Error>) {
+        self.lock().failures.entry(op).or_default().push_back(error.into());
+    }
+
+    /// Every call made so far, in order
+    pub fn calls(&self) -> Vec<BackendCall> {
+        self.lock().calls.clone()
+    }
+
+    /// Calls of one operation, in order
+    pub fn calls_of(&self, op: BackendOp) -> Vec<BackendCall> {
+        self.lock().calls.iter().filter(|c| c.op == op).cloned().collect()
+    }
+
+    /// Number of calls of one operation
+    pub fn count(&self, op: BackendOp) -> usize {
+        self.lock().calls.iter().filter(|c| c.op == op).count()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RecordingState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record a call, failing it if a failure is queued for its operation
+    fn record(&self, mut call: BackendCall) -> Result<()> {
+        let mut state = self.lock();
+        let failure = state.failures.get_mut(&call.op).and_then(|q| q.pop_front());
+        call.failed = failure.is_some();
+        let outputs = call.outputs.clone();
+        state.calls.push(call);
+        drop(state);
+
+        if let Some(error) = failure {
+            return Err(error);
+        }
+        for output in outputs {
+            std::fs::write(&output, b"fake audio")
+                .with_context(|| format!("Failed to write {}", output.display()))?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for RecordingBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Files listed in an FFmpeg concat file (the file itself if it can't be read)
+fn concat_entries(concat_file: &Path) -> Vec<PathBuf> {
+    match std::fs::read_to_string(concat_file) {
+        Ok(content) => content
+            .lines()
+            .filter_map(|line| line.strip_prefix("file '")?.strip_suffix('\''))
+            .map(|path| PathBuf::from(path.replace(r"'\''", "'")))
+            .collect(),
+        Err(_) => vec![concat_file.to_path_buf()],
+    }
+}
+
+impl AudioBackend for RecordingBackend {
+    fn probe_audio_file<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, Result<QualityProfile>> {
+        let result = self
+            .record(BackendCall::new(BackendOp::ProbeAudio, vec![path.to_path_buf()], Vec::new()))
+            .map(|_| self.quality.clone());
+        Box::pin(std::future::ready(result))
+    }
+
+    fn read_tags<'a>(&'a self, track: &'a mut Track) -> BoxFuture<'a, Result<()>> {
+        let result = self
+            .record(BackendCall::new(BackendOp::ReadTags, vec![track.file_path.clone()], Vec::new()))
+            .map(|_| {
+                track.title = self.metadata.title.clone();
+                track.artist = self.metadata.artist.clone();
+                track.album = self.metadata.album.clone();
+                track.album_artist = self.metadata.album_artist.clone();
+                track.year = self.metadata.year;
+                track.genre = self.metadata.genre.clone();
+                track.composer = self.metadata.composer.clone();
+                track.comment = self.metadata.comment.clone();
+            });
+        Box::pin(std::future::ready(result))
+    }
+
+    fn probe_metadata<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, Result<AudioMetadata>> {
+        let result = self
+            .record(BackendCall::new(BackendOp::ProbeMetadata, vec![path.to_path_buf()], Vec::new()))
+            .map(|_| self.metadata.clone());
+        Box::pin(std::future::ready(result))
+    }
+
+    fn probe_duration_and_title<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxFuture<'a, Result<(u64, Option<String>)>> {
+        let result = self
+            .record(BackendCall::new(BackendOp::ProbeDuration, vec![path.to_path_buf()], Vec::new()))
+            .map(|_| {
+                let duration = self.lock().durations.get(path).copied().unwrap_or(self.quality.duration);
+                ((duration * 1000.0).round() as u64, self.metadata.title.clone())
+            });
+        Box::pin(std::future::ready(result))
+    }
+
+    fn read_chapters<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, Result<Vec<Chapter>>> {
+        let result = self
+            .record(BackendCall::new(BackendOp::ReadChapters, vec![path.to_path_buf()], Vec::new()))
+            .map(|_| self.chapters.get(path).cloned().unwrap_or_default());
+        Box::pin(std::future::ready(result))
+    }
+
+    fn convert_single_file<'a>(
+        &'a self,
+        input: &'a Path,
+        output: &'a Path,
+        _quality: &'a QualityProfile,
+        use_copy: bool,
+        _encoder: AacEncoder,
+    ) -> BoxFuture<'a, Result<()>> {
+        let mut call =
+            BackendCall::new(BackendOp::Convert, vec![input.to_path_buf()], vec![output.to_path_buf()]);
+        call.use_copy = use_copy;
+        Box::pin(std::future::ready(self.record(call)))
+    }
+
+    fn concat_audio_files<'a>(
+        &'a self,
+        concat_file: &'a Path,
+        output: &'a Path,
+        _quality: &'a QualityProfile,
+        use_copy: bool,
+        _encoder: AacEncoder,
+    ) -> BoxFuture<'a, Result<()>> {
+        let mut call =
+            BackendCall::new(BackendOp::Concat, concat_entries(concat_file), vec![output.to_path_buf()]);
+        call.use_copy = use_copy;
+        Box::pin(std::future::ready(self.record(call)))
+    }
+
+    fn trim_track<'a>(
+        &'a self,
+        input: &'a Path,
+        output: &'a Path,
+        trim: &'a TrackTrim,
+    ) -> BoxFuture<'a, Result<()>> {
+        let result = self
+            .record(BackendCall::new(BackendOp::Trim, vec![input.to_path_buf()], vec![output.to_path_buf()]))
+            .map(|_| {
+                let duration = trim.end.unwrap_or(self.quality.duration) - trim.start;
+                self.lock().durations.insert(output.to_path_buf(), duration);
+            });
+        Box::pin(std::future::ready(result))
+    }
+
+    fn encode_targets<'a>(
+        &'a self,
+        input: &'a Path,
+        concat_list: bool,
+        targets: &'a [EncodeTarget],
+    ) -> BoxFuture<'a, Result<()>> {
+        let inputs = if concat_list { concat_entries(input) } else { vec![input.to_path_buf()] };
+        let outputs = targets.iter().map(|t| t.path.clone()).collect();
+        Box::pin(std::future::ready(
+            self.record(BackendCall::new(BackendOp::EncodeTargets, inputs, outputs)),
+        ))
+    }
+
+    fn concat_m4b_files<'a>(
+        &'a self,
+        concat_file: &'a Path,
+        output: &'a Path,
+    ) -> BoxFuture<'a, Result<()>> {
+        let mut call =
+            BackendCall::new(BackendOp::ConcatM4b, concat_entries(concat_file), vec![output.to_path_buf()]);
+        call.use_copy = true;
+        Box::pin(std::future::ready(self.record(call)))
+    }
+
+    fn inject_chapters<'a>(
+        &'a self,
+        file: &'a Path,
+        chapters: &'a [Chapter],
+        _chapters_file: &'a Path,
+    ) -> BoxFuture<'a, Result<()>> {
+        let mut call = BackendCall::new(BackendOp::InjectChapters, vec![file.to_path_buf()], Vec::new());
+        call.chapters = chapters.to_vec();
+        Box::pin(std::future::ready(self.record(call)))
+    }
+
+    fn write_metadata<'a>(
+        &'a self,
+        file: &'a Path,
+        metadata: &'a AudioMetadata,
+        cover: Option<&'a Path>,
+    ) -> BoxFuture<'a, Result<()>> {
+        // Tags are written in place, so the file is an input (followed by the cover)
+        let inputs = std::iter::once(file).chain(cover).map(Path::to_path_buf).collect();
+        let mut call = BackendCall::new(BackendOp::WriteMetadata, inputs, Vec::new());
+        call.metadata = Some(metadata.clone());
+        Box::pin(std::future::ready(self.record(call)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_recording_backend_records_and_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("out.m4b");
+        let backend = RecordingBackend::new();
+        let quality = backend.quality.clone();
+
+        backend.fail_next(BackendOp::Convert, anyhow::anyhow!("ffmpeg exited with status 1"));
+        let err = backend
+            .convert_single_file(Path::new("a.mp3"), &output, &quality, false, AacEncoder::Native)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("status 1"));
+        assert!(!output.exists());
+
+        backend
+            .convert_single_file(Path::new("a.mp3"), &output, &quality, false, AacEncoder::Native)
+            .await
+            .unwrap();
+        assert!(output.exists());
+
+        let calls = backend.calls_of(BackendOp::Convert);
+        assert_eq!(calls.len(), 2);
+        assert!(calls[0].failed && !calls[1].failed);
+        assert_eq!(backend.count(BackendOp::Concat), 0);
+    }
+
+    #[tokio::test]
+    async fn test_recording_backend_reads_concat_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("it's.mp3"), dir.path().join("b.mp3"));
+        std::fs::write(&a, b"").unwrap();
+        std::fs::write(&b, b"").unwrap();
+        let concat = dir.path().join("concat.txt");
+        FFmpeg::create_concat_file(&[&a, &b], &concat).unwrap();
+
+        let backend = RecordingBackend::new();
+        let quality = backend.quality.clone();
+        backend
+            .concat_audio_files(&concat, &dir.path().join("out.m4b"), &quality, true, AacEncoder::Native)
+            .await
+            .unwrap();
+
+        let call = &backend.calls()[0];
+        assert_eq!(call.inputs, vec![a.canonicalize().unwrap(), b.canonicalize().unwrap()]);
+        assert!(call.use_copy);
+    }
+}
@@ -0,0 +1,343 @@
+//! Encoder benchmark
+//!
+//! Encodes a short synthetic speech-like sample with every available AAC
+//! encoder at increasing concurrency, and caches which encoder and how many
+//! parallel encodes give the best throughput on this machine.
+
+use super::{tools, AacEncoder, EncoderDetector, FFmpeg, Tool, ToolCommandExt};
+use crate::error::ForgeError;
+use crate::models::QualityProfile;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Default length of the benchmark sample in seconds
+pub const DEFAULT_SAMPLE_SECS: u32 = 60;
+
+/// Concurrency levels stop doubling once throughput improves less than this
+const MIN_SCALING_GAIN: f64 = 1.05;
+
+/// Highest concurrency level tried
+const MAX_CONCURRENCY: usize = 16;
+
+/// One timed run of an encoder
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BenchRun {
+    /// Number of encodes running at the same time
+    pub concurrency: usize,
+    /// Wall-clock time for all of them
+    pub wall_secs: f64,
+    /// Seconds of audio encoded per wall-clock second (summed over all encodes)
+    pub speed: f64,
+}
+
+/// Runs of one encoder, by increasing concurrency
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EncoderBench {
+    pub encoder: String,
+    pub runs: Vec<BenchRun>,
+}
+
+impl EncoderBench {
+    /// Fastest run of this encoder
+    pub fn best_run(&self) -> Option<&BenchRun> {
+        self.runs.iter().max_by(|a, b| a.speed.total_cmp(&b.speed))
+    }
+}
+
+/// Benchmark results for this machine
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BenchResult {
+    /// When the benchmark ran (RFC 3339)
+    pub measured_at: String,
+    /// FFmpeg version the benchmark ran with
+    pub ffmpeg_version: Option<String>,
+    /// CPU count at the time
+    pub cpus: usize,
+    /// Length of the encoded sample
+    pub sample_secs: u32,
+    pub encoders: Vec<EncoderBench>,
+}
+
+impl BenchResult {
+    /// Encoder with the highest throughput
+    pub fn best(&self) -> Option<(&EncoderBench, &BenchRun)> {
+        self.encoders
+            .iter()
+            .filter_map(|bench| bench.best_run().map(|run| (bench, run)))
+            .max_by(|(_, a), (_, b)| a.speed.total_cmp(&b.speed))
+    }
+
+    /// Fastest encoder, if it is still known
+    pub fn encoder(&self) -> Option<AacEncoder> {
+        self.best().and_then(|(bench, _)| AacEncoder::from_str(&bench.encoder))
+    }
+
+    /// Number of encodes to run at once for the fastest encoder
+    pub fn best_concurrency(&self) -> usize {
+        self.best().map(|(_, run)| run.concurrency).unwrap_or(1)
+    }
+
+    /// Split the best concurrency into encodes across books and files per
+    /// book, so that both together keep about that many encodes running
+    pub fn concurrency_split(&self) -> (usize, usize) {
+        let total = self.best_concurrency().max(1);
+        let encodes = ((total as f64).sqrt().floor() as usize).max(1);
+        (encodes, total.div_ceil(encodes))
+    }
+
+    /// Whether the results were measured on this machine as it is now
+    pub fn is_current(&self, ffmpeg_version: Option<&str>, cpus: usize) -> bool {
+        self.ffmpeg_version.as_deref() == ffmpeg_version && self.cpus == cpus
+    }
+
+    /// Location of the cached results
+    pub fn cache_path() -> Result<PathBuf> {
+        Ok(dirs::cache_dir()
+            .context("No cache directory found")?
+            .join("audiobook-forge")
+            .join("encoder-bench.json"))
+    }
+
+    /// Read results from a file
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Write results to a file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Cached results, unless missing or measured with a different FFmpeg or CPU count
+    pub fn load_cached() -> Option<Self> {
+        let path = Self::cache_path().ok()?;
+        if !path.exists() {
+            return None;
+        }
+        match Self::load(&path) {
+            Ok(result) if result.is_current(tools().version(Tool::FFmpeg), num_cpus::get()) => Some(result),
+            Ok(_) => {
+                tracing::info!("Encoder benchmark in {} is out of date", path.display());
+                None
+            }
+            Err(e) => {
+                tracing::warn!("Ignoring encoder benchmark: {:#}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Benchmark settings
+#[derive(Debug, Clone)]
+pub struct EncoderBenchmark {
+    /// Length of the synthetic sample
+    pub sample_secs: u32,
+    /// Highest concurrency to try
+    pub max_concurrency: usize,
+}
+
+impl Default for EncoderBenchmark {
+    fn default() -> Self {
+        Self {
+            sample_secs: DEFAULT_SAMPLE_SECS,
+            max_concurrency: num_cpus::get().min(MAX_CONCURRENCY),
+        }
+    }
+}
+
+impl EncoderBenchmark {
+    /// Benchmark every available encoder
+    pub async fn run(&self) -> Result<BenchResult> {
+        let ffmpeg = FFmpeg::new()?;
+        let temp_dir = std::env::temp_dir().join(format!("audiobook-forge-bench-{}", std::process::id()));
+        std::fs::create_dir_all(&temp_dir).context("Failed to create benchmark directory")?;
+        let result = self.run_in(&ffmpeg, &temp_dir).await;
+        std::fs::remove_dir_all(&temp_dir).ok();
+        result
+    }
+
+    async fn run_in(&self, ffmpeg: &FFmpeg, temp_dir: &Path) -> Result<BenchResult> {
+        let sample = temp_dir.join("sample.wav");
+        generate_sample(&sample, self.sample_secs).await?;
+
+        let mut encoders = Vec::new();
+        for encoder in EncoderDetector::get_available_encoders() {
+            tracing::info!("Benchmarking {}", encoder.name());
+            let mut runs: Vec<BenchRun> = Vec::new();
+            for concurrency in concurrency_levels(self.max_concurrency) {
+                let run = self
+                    .time_encodes(ffmpeg, &sample, temp_dir, encoder, concurrency)
+                    .await?;
+                tracing::debug!("{} x{}: {:.1}x realtime", encoder.name(), concurrency, run.speed);
+                let previous = runs.last().map(|r| r.speed);
+                let speed = run.speed;
+                runs.push(run);
+                if previous.is_some_and(|previous| speed < previous * MIN_SCALING_GAIN) {
+                    break;
+                }
+            }
+            encoders.push(EncoderBench {
+                encoder: encoder.name().to_string(),
+                runs,
+            });
+        }
+
+        Ok(BenchResult {
+            measured_at: chrono::Local::now().to_rfc3339(),
+            ffmpeg_version: tools().version(Tool::FFmpeg).map(str::to_string),
+            cpus: num_cpus::get(),
+            sample_secs: self.sample_secs,
+            encoders,
+        })
+    }
+
+    /// Encode the sample `concurrency` times at once
+    async fn time_encodes(
+        &self,
+        ffmpeg: &FFmpeg,
+        sample: &Path,
+        dir: &Path,
+        encoder: AacEncoder,
+        concurrency: usize,
+    ) -> Result<BenchRun> {
+        let quality = QualityProfile::new(64, 44100, 1, "aac".to_string(), self.sample_secs as f64)?;
+        let outputs: Vec<PathBuf> = (0..concurrency)
+            .map(|i| dir.join(format!("{}_{}.m4a", encoder.name(), i)))
+            .collect();
+
+        let start = Instant::now();
+        futures::future::try_join_all(
+            outputs
+                .iter()
+                .map(|output| ffmpeg.convert_single_file(sample, output, &quality, false, encoder)),
+        )
+        .await?;
+        let wall_secs = start.elapsed().as_secs_f64().max(f64::EPSILON);
+
+        for output in &outputs {
+            let _ = std::fs::remove_file(output);
+        }
+        Ok(BenchRun {
+            concurrency,
+            wall_secs,
+            speed: (self.sample_secs as usize * concurrency) as f64 / wall_secs,
+        })
+    }
+}
+
+/// Concurrency levels to try: 1, 2, 4, ... up to `max`
+fn concurrency_levels(max: usize) -> Vec<usize> {
+    let max = max.clamp(1, MAX_CONCURRENCY);
+    let mut levels: Vec<usize> = std::iter::successors(Some(1usize), |n| Some(n * 2))
+        .take_while(|&n| n <= max)
+        .collect();
+    if levels.last() != Some(&max) {
+        levels.push(max);
+    }
+    levels
+}
+
+/// Write a speech-like test signal: pink noise shaped to the voice band with
+/// a syllable-rate amplitude envelope, over a low hum for some tonal content
+async fn generate_sample(path: &Path, secs: u32) -> Result<()> {
+    let filter = format!(
+        "anoisesrc=color=pink:amplitude=0.4:duration={secs}:sample_rate=44100[noise];\
+         sine=frequency=140:duration={secs}:sample_rate=44100[voice];\
+         [noise][voice]amix=inputs=2,bandpass=frequency=1000:width_type=o:width=2,\
+         tremolo=f=4:d=0.8,aformat=channel_layouts=mono"
+    );
+    let output = tools()
+        .async_command(Tool::FFmpeg)?
+        .args(["-y", "-v", "error", "-filter_complex", &filter])
+        .arg(path)
+        .logged_output(Tool::FFmpeg)
+        .await
+        .context("Failed to execute ffmpeg")?;
+    if !output.status.success() {
+        return Err(ForgeError::tool_failed(Tool::FFmpeg, "Benchmark sample", &output, None).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(concurrency: usize, speed: f64) -> BenchRun {
+        BenchRun {
+            concurrency,
+            wall_secs: 1.0,
+            speed,
+        }
+    }
+
+    fn result(encoders: Vec<(&str, Vec<BenchRun>)>) -> BenchResult {
+        BenchResult {
+            measured_at: "2026-01-01T00:00:00+00:00".to_string(),
+            ffmpeg_version: Some("6.1".to_string()),
+            cpus: 8,
+            sample_secs: 60,
+            encoders: encoders
+                .into_iter()
+                .map(|(encoder, runs)| EncoderBench {
+                    encoder: encoder.to_string(),
+                    runs,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_best_encoder_and_concurrency() {
+        let result = result(vec![
+            ("aac", vec![run(1, 40.0), run(2, 75.0), run(4, 130.0), run(8, 128.0)]),
+            ("libfdk_aac", vec![run(1, 60.0), run(2, 110.0), run(4, 112.0)]),
+        ]);
+
+        assert_eq!(result.encoder(), Some(AacEncoder::Native));
+        assert_eq!(result.best_concurrency(), 4);
+        assert_eq!(result.concurrency_split(), (2, 2));
+    }
+
+    #[test]
+    fn test_concurrency_split() {
+        let split = |c| result(vec![("aac", vec![run(c, 1.0)])]).concurrency_split();
+        assert_eq!(split(1), (1, 1));
+        assert_eq!(split(3), (1, 3));
+        assert_eq!(split(8), (2, 4));
+        assert_eq!(split(16), (4, 4));
+        assert_eq!(result(vec![]).concurrency_split(), (1, 1));
+    }
+
+    #[test]
+    fn test_concurrency_levels() {
+        assert_eq!(concurrency_levels(1), vec![1]);
+        assert_eq!(concurrency_levels(6), vec![1, 2, 4, 6]);
+        assert_eq!(concurrency_levels(8), vec![1, 2, 4, 8]);
+        assert_eq!(concurrency_levels(64), vec![1, 2, 4, 8, 16]);
+    }
+
+    #[test]
+    fn test_cache_round_trip_and_staleness() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("bench.json");
+        let saved = result(vec![("aac", vec![run(1, 40.0)])]);
+        saved.save(&path).unwrap();
+
+        let loaded = BenchResult::load(&path).unwrap();
+        assert_eq!(loaded, saved);
+        assert!(loaded.is_current(Some("6.1"), 8));
+        assert!(!loaded.is_current(Some("7.0"), 8));
+        assert!(!loaded.is_current(Some("6.1"), 16));
+    }
+}
@@ -3,6 +3,8 @@
 use anyhow::{Context, Result};
 use std::path::Path;
 use crate::audio::Chapter;
+use crate::audio::tools::{tools, Tool, ToolCommandExt};
+use crate::error::ForgeError;
 
 /// Source of chapter data
 #[derive(Debug, Clone)]
@@ -273,7 +275,6 @@ pub fn parse_epub_chapters(path: &Path) -> Result<Vec<Chapter>> {
 /// Read existing chapters from M4B file using ffprobe
 pub async fn read_m4b_chapters(m4b_path: &Path) -> Result<Vec<Chapter>> {
     use serde::Deserialize;
-    use tokio::process::Command;
 
     #[derive(Debug, Deserialize)]
     struct FfprobeChapter {
@@ -295,19 +296,20 @@ pub async fn read_m4b_chapters(m4b_path: &Path) -> Result<Vec<Chapter>> {
         chapters: Vec<FfprobeChapter>,
     }
 
-    let output = Command::new("ffprobe")
+    let output = tools()
+        .async_command(Tool::FFprobe)?
         .args([
-            "-v", "quiet",
+            "-v", "error",
             "-print_format", "json",
             "-show_chapters",
         ])
         .arg(m4b_path)
-        .output()
+        .logged_output(Tool::FFprobe)
         .await
         .context("Failed to execute ffprobe")?;
 
     if !output.status.success() {
-        anyhow::bail!("ffprobe failed to read chapters from M4B file");
+        return Err(ForgeError::tool_failed(Tool::FFprobe, "FFprobe (M4B chapters)", &output, Some(m4b_path)).into());
     }
 
     let json_str = String::from_utf8(output.stdout)
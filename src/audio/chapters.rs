@@ -1,5 +1,7 @@
 //! Chapter generation and management
 
+use super::tools::{tools, Tool, ToolCommandExt};
+use crate::error::ForgeError;
 use anyhow::{Context, Result};
 use regex::Regex;
 use std::path::Path;
@@ -33,6 +35,16 @@ impl Chapter {
         self.end_time_ms - self.start_time_ms
     }
 
+    /// The chapter in audio played back `tempo` times as fast
+    pub fn at_tempo(&self, tempo: f64) -> Self {
+        let scale = |ms: u64| (ms as f64 / tempo).round() as u64;
+        Self {
+            start_time_ms: scale(self.start_time_ms),
+            end_time_ms: scale(self.end_time_ms),
+            ..self.clone()
+        }
+    }
+
     /// Format as MP4Box chapter format
     pub fn to_mp4box_format(&self) -> String {
         let start_time = format_time_ms(self.start_time_ms);
@@ -169,16 +181,16 @@ pub async fn inject_chapters_mp4box(
     m4b_file: &Path,
     chapters_file: &Path,
 ) -> Result<()> {
-    let output = tokio::process::Command::new("MP4Box")
+    let output = tools()
+        .async_command(Tool::MP4Box)?
         .args(&["-chap", &chapters_file.display().to_string()])
         .arg(m4b_file)
-        .output()
+        .logged_output(Tool::MP4Box)
         .await
         .context("Failed to execute MP4Box")?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("MP4Box failed: {}", stderr);
+        return Err(ForgeError::tool_failed(Tool::MP4Box, "MP4Box", &output, Some(m4b_file)).into());
     }
 
     Ok(())
@@ -205,6 +217,13 @@ mod tests {
         assert_eq!(chapter.duration_ms(), 60000);
     }
 
+    #[test]
+    fn test_chapter_at_tempo() {
+        let chapter = Chapter::new(2, "Two".to_string(), 60_000, 150_000).at_tempo(1.5);
+        assert_eq!((chapter.start_time_ms, chapter.end_time_ms), (40_000, 100_000));
+        assert_eq!(chapter.title, "Two");
+    }
+
     #[test]
     fn test_generate_chapters_from_files() {
         let files = vec![
@@ -1,6 +1,9 @@
-use std::process::Command;
+use crate::models::{AacProfile, QualityProfile, RateControl};
 use std::sync::OnceLock;
 
+/// Lowest AAC-LC bitrate per channel used in place of HE-AAC
+const LC_FALLBACK_KBPS_PER_CHANNEL: u32 = 64;
+
 /// AAC encoder types supported by audiobook-forge
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AacEncoder {
@@ -31,6 +34,93 @@ impl AacEncoder {
         }
     }
 
+    /// Supported AAC-LC bitrates in kbps (inclusive)
+    pub fn bitrate_range(&self) -> (u32, u32) {
+        match self {
+            Self::AppleSilicon => (16, 320),
+            Self::LibFdk => (8, 320),
+            Self::Native => (8, 512),
+        }
+    }
+
+    /// Whether the encoder can produce an AAC object type
+    pub fn supports_profile(&self, profile: AacProfile) -> bool {
+        match profile {
+            AacProfile::Lc => true,
+            AacProfile::He | AacProfile::HeV2 => matches!(self, Self::AppleSilicon | Self::LibFdk),
+        }
+    }
+
+    /// Whether the encoder has VBR modes 1-5 (`-vbr`)
+    pub fn supports_vbr(&self) -> bool {
+        matches!(self, Self::LibFdk)
+    }
+
+    /// Adjust a quality profile to what this encoder supports
+    ///
+    /// HE-AAC becomes AAC-LC at no less than 64 kbps per channel, HE-AACv2 on
+    /// a mono output becomes HE-AAC, and VBR becomes CBR at `bitrate`. Returns
+    /// the adjusted profile and a note for each change.
+    pub fn fit_quality(&self, quality: &QualityProfile) -> (QualityProfile, Vec<String>) {
+        let mut fitted = quality.clone();
+        let mut notes = Vec::new();
+
+        if fitted.aac_profile == AacProfile::HeV2 && fitted.channels < 2 {
+            fitted.aac_profile = AacProfile::He;
+            notes.push("HE-AACv2 needs stereo output; using HE-AAC".to_string());
+        }
+        if !self.supports_profile(fitted.aac_profile) {
+            let bitrate = fitted.bitrate.max(LC_FALLBACK_KBPS_PER_CHANNEL * fitted.channels as u32);
+            notes.push(format!(
+                "{} can't encode {}; using AAC-LC at {} kbps",
+                self.name(),
+                fitted.aac_profile,
+                bitrate
+            ));
+            fitted.aac_profile = AacProfile::Lc;
+            fitted.bitrate = bitrate;
+        }
+        if let RateControl::Vbr(level) = fitted.rate_control {
+            if !self.supports_vbr() {
+                notes.push(format!(
+                    "{} has no VBR mode {}; using CBR at {} kbps",
+                    self.name(),
+                    level,
+                    fitted.bitrate
+                ));
+                fitted.rate_control = RateControl::Cbr;
+            }
+        }
+
+        (fitted, notes)
+    }
+
+    /// FFmpeg encoder options for a quality profile (after [`AacEncoder::fit_quality`])
+    pub fn output_args(&self, quality: &QualityProfile) -> Vec<String> {
+        let (quality, _) = self.fit_quality(quality);
+        let mut args = vec!["-c:a".to_string(), self.name().to_string()];
+
+        if quality.aac_profile != AacProfile::Lc {
+            args.extend(["-profile:a".to_string(), quality.aac_profile.ffmpeg_name().to_string()]);
+        }
+        match quality.rate_control {
+            RateControl::Vbr(level) => args.extend(["-vbr".to_string(), level.clamp(1, 5).to_string()]),
+            RateControl::Cbr => args.extend(["-b:a".to_string(), format!("{}k", quality.bitrate)]),
+        }
+        args.extend([
+            "-ar".to_string(),
+            quality.sample_rate.to_string(),
+            "-ac".to_string(),
+            quality.channels.to_string(),
+        ]);
+
+        // Use multiple threads for encoding if encoder supports it
+        if self.supports_threading() {
+            args.extend(["-threads".to_string(), "0".to_string()]); // 0 = auto-detect optimal thread count
+        }
+        args
+    }
+
     /// Try to parse encoder from string
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
@@ -75,20 +165,7 @@ impl EncoderDetector {
 
     /// Check if a specific encoder is available in FFmpeg
     pub fn is_encoder_available(encoder: AacEncoder) -> bool {
-        let output = Command::new("ffmpeg").args(&["-encoders"]).output();
-
-        if let Ok(output) = output {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            // Look for the encoder name in the output
-            // Format: " A..... encodername     Description"
-            stdout.lines().any(|line| {
-                let trimmed = line.trim_start();
-                // Check if it's an audio encoder line and contains our encoder name
-                trimmed.starts_with('A') && line.contains(encoder.name())
-            })
-        } else {
-            false
-        }
+        super::tools().has_encoder(encoder.name())
     }
 
     /// Get all available AAC encoders
@@ -167,6 +244,44 @@ mod tests {
         assert!(encoders.contains(&AacEncoder::Native) || encoders.len() > 0);
     }
 
+    #[test]
+    fn test_fit_quality_falls_back() {
+        let mut speech = QualityProfile::new(32, 44100, 1, "aac".to_string(), 60.0).unwrap();
+        speech.aac_profile = AacProfile::HeV2;
+        speech.rate_control = RateControl::Vbr(2);
+
+        // HE-AACv2 needs stereo; VBR is libfdk_aac only
+        let (fitted, notes) = AacEncoder::AppleSilicon.fit_quality(&speech);
+        assert_eq!(fitted.aac_profile, AacProfile::He);
+        assert_eq!(fitted.rate_control, RateControl::Cbr);
+        assert_eq!(notes.len(), 2);
+
+        // Native aac has no HE-AAC: LC at 64 kbps per channel
+        let (fitted, _) = AacEncoder::Native.fit_quality(&speech);
+        assert_eq!((fitted.aac_profile, fitted.bitrate), (AacProfile::Lc, 64));
+
+        let (fitted, notes) = AacEncoder::LibFdk.fit_quality(&speech);
+        assert_eq!(fitted.rate_control, RateControl::Vbr(2));
+        assert_eq!(notes.len(), 1);
+    }
+
+    #[test]
+    fn test_output_args() {
+        let mut quality = QualityProfile::new(48, 44100, 2, "aac".to_string(), 60.0).unwrap();
+        quality.aac_profile = AacProfile::HeV2;
+        quality.rate_control = RateControl::Vbr(3);
+
+        let args = AacEncoder::LibFdk.output_args(&quality);
+        assert!(args.windows(2).any(|w| w == ["-profile:a", "aac_he_v2"]));
+        assert!(args.windows(2).any(|w| w == ["-vbr", "3"]));
+        assert!(!args.contains(&"-b:a".to_string()));
+
+        let args = AacEncoder::Native.output_args(&quality);
+        assert!(!args.contains(&"-profile:a".to_string()));
+        assert!(args.windows(2).any(|w| w == ["-b:a", "128k"]));
+        assert!(args.windows(2).any(|w| w == ["-threads", "0"]));
+    }
+
     #[test]
     fn test_get_encoder_cached() {
         // First call initializes
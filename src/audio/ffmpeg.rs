@@ -1,10 +1,12 @@
 //! FFmpeg wrapper for audio operations
 
+use crate::audio::tools::{tools, Tool, ToolCommandExt};
 use crate::audio::AacEncoder;
-use crate::models::QualityProfile;
+use crate::error::ForgeError;
+use crate::models::{AacProfile, QualityProfile};
 use anyhow::{Context, Result};
 use serde_json::Value;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tokio::process::Command;
 
@@ -21,6 +23,170 @@ pub struct AudioMetadata {
     pub comment: Option<String>,
 }
 
+/// One output of a multi-output encode
+#[derive(Debug, Clone)]
+pub struct EncodeTarget {
+    /// Output file
+    pub path: PathBuf,
+    /// Output quality
+    pub quality: QualityProfile,
+    /// AAC encoder
+    pub encoder: AacEncoder,
+    /// Target integrated loudness in LUFS (EBU R128 `loudnorm`), if any
+    pub loudness: Option<f64>,
+    /// Playback speed (pitch-preserving `atempo`), if not 1x
+    pub tempo: Option<f64>,
+}
+
+impl EncodeTarget {
+    /// FFmpeg output options for this target (everything after the inputs)
+    pub fn output_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = vec!["-map".into(), "0:a".into(), "-vn".into()];
+
+        let mut filters = Vec::new();
+        if let Some(lufs) = self.loudness {
+            filters.push(format!("loudnorm=I={}:TP=-1.5:LRA=11", lufs));
+        }
+        if let Some(tempo) = self.tempo {
+            filters.push(atempo_filter(tempo));
+        }
+        if !filters.is_empty() {
+            args.push("-af".into());
+            args.push(filters.join(","));
+        }
+
+        args.extend(self.encoder.output_args(&self.quality));
+        args.extend(["-movflags".to_string(), "+faststart".to_string()]);
+        args.push(self.path.to_string_lossy().to_string());
+        args
+    }
+}
+
+/// `atempo` filter chain for a speed factor
+///
+/// Older FFmpeg builds limit each `atempo` to 0.5-2.0, so larger changes are
+/// chained (e.g. 3x = 2x then 1.5x).
+pub fn atempo_filter(tempo: f64) -> String {
+    let mut remaining = tempo;
+    let mut stages = Vec::new();
+    while remaining > 2.0 {
+        stages.push("atempo=2".to_string());
+        remaining /= 2.0;
+    }
+    while remaining < 0.5 {
+        stages.push("atempo=0.5".to_string());
+        remaining /= 0.5;
+    }
+    stages.push(format!("atempo={}", (remaining * 10_000.0).round() / 10_000.0));
+    stages.join(",")
+}
+
+/// Leading/trailing silence removal
+///
+/// Leading silence is removed with FFmpeg's `silenceremove`; trailing silence
+/// is found with `silencedetect` first and cut off like an outro.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SilenceTrim {
+    /// Audio below this level (dBFS) counts as silence
+    pub threshold_db: f64,
+    /// Seconds of silence kept at each trimmed edge
+    pub keep_secs: f64,
+}
+
+/// How one track is cut before encoding
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackTrim {
+    /// Seconds cut from the start
+    pub start: f64,
+    /// Position (seconds) where the track is cut off, if before its end
+    pub end: Option<f64>,
+    /// Silence removal after the cuts, if any
+    pub silence: Option<SilenceTrim>,
+}
+
+impl TrackTrim {
+    /// Whether the track is left untouched
+    pub fn is_empty(&self) -> bool {
+        self.start <= 0.0 && self.end.is_none() && self.silence.is_none()
+    }
+
+    /// FFmpeg filter chain for the trim (None if there's nothing to do)
+    pub fn filter(&self) -> Option<String> {
+        let mut filters = Vec::new();
+        if self.start > 0.0 || self.end.is_some() {
+            let mut atrim = format!("atrim=start={:.3}", self.start.max(0.0));
+            if let Some(end) = self.end {
+                atrim.push_str(&format!(":end={:.3}", end));
+            }
+            filters.push(atrim);
+            filters.push("asetpts=PTS-STARTPTS".to_string());
+        }
+        if let Some(silence) = &self.silence {
+            // Only the start: trailing silence is cut by `end` (see FFmpeg::trim_track)
+            filters.push(format!(
+                "silenceremove=start_periods=1:start_threshold={}dB:start_silence={:.2}",
+                silence.threshold_db, silence.keep_secs
+            ));
+        }
+        (!filters.is_empty()).then(|| filters.join(","))
+    }
+
+    /// Also cut the track where the silence it ends with starts (plus the kept silence)
+    fn cut_trailing_silence(&mut self, silence_start: f64) {
+        let keep = self.silence.map(|s| s.keep_secs).unwrap_or_default();
+        let cut = silence_start + keep;
+        if cut > self.start && self.end.is_none_or(|end| cut < end) {
+            self.end = Some(cut);
+        }
+    }
+}
+
+/// Where the silence a file ends with starts, from `silencedetect` output
+///
+/// Newer FFmpeg builds close silence still running at the end of the input
+/// with a `silence_end` at the last timestamp, so a silence that ends within
+/// a few frames of the reported end time also counts.
+fn parse_trailing_silence(stderr: &str) -> Option<f64> {
+    let value = |line: &str, key: &str| {
+        line.split(key)
+            .nth(1)?
+            .split_whitespace()
+            .next()?
+            .parse::<f64>()
+            .ok()
+    };
+
+    let (mut start, mut end, mut total) = (None, None, None);
+    for line in stderr.split(['\n', '\r']) {
+        if let Some(s) = value(line, "silence_start: ") {
+            start = Some(s);
+            end = None;
+        }
+        if let Some(e) = value(line, "silence_end: ") {
+            end = Some(e);
+        }
+        if let Some(time) = line.split("time=").nth(1).and_then(|t| t.split_whitespace().next()) {
+            total = parse_timestamp(time).or(total);
+        }
+    }
+
+    let start = start?;
+    match (end, total) {
+        (None, _) => Some(start),
+        (Some(end), Some(total)) if end >= total - 0.05 => Some(start),
+        _ => None,
+    }
+}
+
+/// Seconds in an FFmpeg `HH:MM:SS.xx` timestamp
+fn parse_timestamp(time: &str) -> Option<f64> {
+    let mut secs = 0.0;
+    for part in time.split(':') {
+        secs = secs * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(secs)
+}
+
 /// FFmpeg operations wrapper
 #[derive(Clone)]
 pub struct FFmpeg {
@@ -31,17 +197,14 @@ pub struct FFmpeg {
 }
 
 impl FFmpeg {
-    /// Create a new FFmpeg wrapper with default paths
+    /// Create a new FFmpeg wrapper with the paths from the tool registry
     pub fn new() -> Result<Self> {
-        let ffmpeg_path = which::which("ffmpeg")
-            .context("FFmpeg not found in PATH")?
-            .to_string_lossy()
-            .to_string();
+        let registry = tools();
+        registry.check(Tool::FFmpeg)?;
+        registry.check(Tool::FFprobe)?;
 
-        let ffprobe_path = which::which("ffprobe")
-            .context("FFprobe not found in PATH")?
-            .to_string_lossy()
-            .to_string();
+        let ffmpeg_path = registry.path(Tool::FFmpeg)?.to_string_lossy().to_string();
+        let ffprobe_path = registry.path(Tool::FFprobe)?.to_string_lossy().to_string();
 
         Ok(Self {
             ffmpeg_path,
@@ -61,7 +224,7 @@ impl FFmpeg {
     pub async fn probe_audio_file(&self, path: &Path) -> Result<QualityProfile> {
         let output = Command::new(&self.ffprobe_path)
             .args(&[
-                "-v", "quiet",
+                "-v", "error",
                 "-print_format", "json",
                 "-show_streams",
                 "-show_format",
@@ -69,19 +232,19 @@ impl FFmpeg {
             .arg(path)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .output()
+            .logged_output(Tool::FFprobe)
             .await
             .context("Failed to execute ffprobe")?;
 
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("FFprobe failed: {}", stderr);
+            return Err(ForgeError::tool_failed(Tool::FFprobe, "FFprobe", &output, Some(path)).into());
         }
 
         let json: Value = serde_json::from_slice(&output.stdout)
             .context("Failed to parse ffprobe JSON output")?;
 
         self.parse_ffprobe_output(&json)
+            .map_err(|e| ForgeError::corrupt(path, format!("{:#}", e)).into())
     }
 
     /// Parse ffprobe JSON output into QualityProfile
@@ -134,7 +297,11 @@ impl FFmpeg {
                 .parse::<f64>()?
         };
 
-        QualityProfile::new(bitrate, sample_rate, channels, codec, duration)
+        let mut quality = QualityProfile::new(bitrate, sample_rate, channels, codec, duration)?;
+        if let Some(profile) = audio_stream["profile"].as_str().and_then(AacProfile::from_ffprobe) {
+            quality.aac_profile = profile;
+        }
+        Ok(quality)
     }
 
     /// Concatenate audio files using FFmpeg
@@ -164,25 +331,13 @@ impl FFmpeg {
             cmd.args(&["-c", "copy"]);
         } else {
             // Transcode mode
-            cmd.args(&[
-                "-c:a", encoder.name(),
-                "-b:a", &format!("{}k", quality.bitrate),
-                "-ar", &quality.sample_rate.to_string(),
-                "-ac", &quality.channels.to_string(),
-            ]);
-
-            // Use multiple threads for encoding if encoder supports it
-            if encoder.supports_threading() {
-                cmd.args(&["-threads", "0"]); // 0 = auto-detect optimal thread count
-            }
+            cmd.args(encoder.output_args(quality));
         }
 
         // Add faststart flag for better streaming
         cmd.args(&["-movflags", "+faststart"]);
         cmd.arg(output_file);
 
-        // Log command for debugging
-        tracing::debug!("FFmpeg concat command: {:?}", cmd.as_std());
         tracing::info!(
             "Concatenating {} ({}mode)",
             concat_file.display(),
@@ -193,21 +348,12 @@ impl FFmpeg {
         let output = cmd
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .output()
+            .logged_output(Tool::FFmpeg)
             .await
             .context("Failed to execute ffmpeg")?;
 
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            // Provide helpful error message if encoder is the issue
-            if stderr.to_lowercase().contains("encoder") {
-                anyhow::bail!(
-                    "FFmpeg encoding failed with encoder '{}': {}\nTip: Run 'audiobook-forge check' to verify encoder availability",
-                    encoder.name(),
-                    stderr
-                );
-            }
-            anyhow::bail!("FFmpeg concatenation failed: {}", stderr);
+            return Err(ForgeError::tool_failed(Tool::FFmpeg, "FFmpeg concatenation", &output, Some(concat_file)).into());
         }
 
         Ok(())
@@ -233,24 +379,12 @@ impl FFmpeg {
         if use_copy {
             cmd.args(&["-c", "copy"]);
         } else {
-            cmd.args(&[
-                "-c:a", encoder.name(),
-                "-b:a", &format!("{}k", quality.bitrate),
-                "-ar", &quality.sample_rate.to_string(),
-                "-ac", &quality.channels.to_string(),
-            ]);
-
-            // Use multiple threads for encoding if encoder supports it
-            if encoder.supports_threading() {
-                cmd.args(&["-threads", "0"]); // 0 = auto-detect optimal thread count
-            }
+            cmd.args(encoder.output_args(quality));
         }
 
         cmd.args(&["-movflags", "+faststart"]);
         cmd.arg(output_file);
 
-        // Log command for debugging
-        tracing::debug!("FFmpeg convert command: {:?}", cmd.as_std());
         tracing::info!(
             "Converting {} → {} (encoder: {}, {}kbps)",
             input_file.file_name().unwrap().to_string_lossy(),
@@ -262,21 +396,142 @@ impl FFmpeg {
         let output = cmd
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .output()
+            .logged_output(Tool::FFmpeg)
             .await
             .context("Failed to execute ffmpeg")?;
 
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            // Provide helpful error message if encoder is the issue
-            if stderr.to_lowercase().contains("encoder") {
-                anyhow::bail!(
-                    "FFmpeg encoding failed with encoder '{}': {}\nTip: Run 'audiobook-forge check' to verify encoder availability",
-                    encoder.name(),
-                    stderr
-                );
+            return Err(ForgeError::tool_failed(Tool::FFmpeg, "FFmpeg conversion", &output, Some(input_file)).into());
+        }
+
+        Ok(())
+    }
+
+    /// Trim a track to a lossless FLAC intermediate
+    pub async fn trim_track(&self, input_file: &Path, output_file: &Path, trim: &TrackTrim) -> Result<()> {
+        let mut trim = trim.clone();
+        if let Some(silence) = &trim.silence {
+            for filter in ["silenceremove", "silencedetect"] {
+                if !tools().has_filter(filter) {
+                    return Err(ForgeError::ToolUnavailable {
+                        tool: Tool::FFmpeg,
+                        message: format!("Silence trimming needs FFmpeg's {} filter, which this FFmpeg build lacks", filter),
+                    }
+                    .into());
+                }
+            }
+            if let Some(silence_start) = self.detect_trailing_silence(input_file, silence).await? {
+                trim.cut_trailing_silence(silence_start);
             }
-            anyhow::bail!("FFmpeg conversion failed: {}", stderr);
+        }
+
+        let mut cmd = Command::new(&self.ffmpeg_path);
+        cmd.args(["-y", "-i"]).arg(input_file).args(["-map", "0:a", "-vn"]);
+        if let Some(filter) = trim.filter() {
+            cmd.arg("-af").arg(filter);
+        }
+        cmd.args(["-c:a", "flac"]).arg(output_file);
+
+        tracing::info!(
+            "Trimming {}",
+            input_file.file_name().unwrap_or_default().to_string_lossy()
+        );
+
+        let output = cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .logged_output(Tool::FFmpeg)
+            .await
+            .context("Failed to execute ffmpeg")?;
+
+        if !output.status.success() {
+            return Err(ForgeError::tool_failed(Tool::FFmpeg, "FFmpeg trimming", &output, Some(input_file)).into());
+        }
+
+        Ok(())
+    }
+
+    /// Where the silence a file ends with starts (None if it doesn't end in silence)
+    ///
+    /// A separate pass, so the end can be cut without holding the decoded
+    /// track in memory, however long it is.
+    async fn detect_trailing_silence(&self, input_file: &Path, silence: &SilenceTrim) -> Result<Option<f64>> {
+        let output = Command::new(&self.ffmpeg_path)
+            .args(["-hide_banner", "-i"])
+            .arg(input_file)
+            .args(["-map", "0:a", "-af"])
+            .arg(format!(
+                "silencedetect=noise={}dB:d={:.2}",
+                silence.threshold_db,
+                silence.keep_secs.max(0.05)
+            ))
+            .args(["-f", "null", "-"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .logged_output(Tool::FFmpeg)
+            .await
+            .context("Failed to execute ffmpeg")?;
+
+        if !output.status.success() {
+            return Err(ForgeError::tool_failed(Tool::FFmpeg, "FFmpeg silence detection", &output, Some(input_file)).into());
+        }
+        Ok(parse_trailing_silence(&String::from_utf8_lossy(&output.stderr)))
+    }
+
+    /// Decode the input once and encode it to every target in a single FFmpeg run
+    ///
+    /// `input` is either an audio file or, with `concat_list`, a concat
+    /// demuxer list written by [`FFmpeg::create_concat_file`].
+    pub async fn encode_targets(
+        &self,
+        input: &Path,
+        concat_list: bool,
+        targets: &[EncodeTarget],
+    ) -> Result<()> {
+        if targets.is_empty() {
+            return Ok(());
+        }
+        if targets.iter().any(|t| t.loudness.is_some()) && !tools().has_filter("loudnorm") {
+            return Err(ForgeError::ToolUnavailable {
+                tool: Tool::FFmpeg,
+                message: "Loudness normalization needs FFmpeg's loudnorm filter, which this FFmpeg build lacks".to_string(),
+            }
+            .into());
+        }
+        if targets.iter().any(|t| t.tempo.is_some()) && !tools().has_filter("atempo") {
+            return Err(ForgeError::ToolUnavailable {
+                tool: Tool::FFmpeg,
+                message: "Speed variants need FFmpeg's atempo filter, which this FFmpeg build lacks".to_string(),
+            }
+            .into());
+        }
+
+        let mut cmd = Command::new(&self.ffmpeg_path);
+        cmd.arg("-y");
+        if concat_list {
+            cmd.args(["-f", "concat", "-safe", "0"]);
+        }
+        cmd.arg("-i").arg(input);
+
+        for target in targets {
+            cmd.args(target.output_args());
+        }
+
+        tracing::info!(
+            "Encoding {} → {} output(s)",
+            input.display(),
+            targets.len()
+        );
+
+        let output = cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .logged_output(Tool::FFmpeg)
+            .await
+            .context("Failed to execute ffmpeg")?;
+
+        if !output.status.success() {
+            return Err(ForgeError::tool_failed(Tool::FFmpeg, "FFmpeg encoding", &output, Some(input)).into());
         }
 
         Ok(())
@@ -288,7 +543,11 @@ impl FFmpeg {
         for file in files {
             // Verify file exists before adding to concat list
             if !file.exists() {
-                anyhow::bail!("File not found: {}", file.display());
+                return Err(ForgeError::io(
+                    format!("File not found: {}", file.display()),
+                    std::io::ErrorKind::NotFound.into(),
+                )
+                .into());
             }
 
             // Get absolute path for better compatibility
@@ -339,19 +598,17 @@ impl FFmpeg {
         ])
         .arg(output_file);
 
-        tracing::debug!("FFmpeg M4B concat command: {:?}", cmd.as_std());
         tracing::info!("Concatenating M4B files (lossless copy mode)");
 
         let output = cmd
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .output()
+            .logged_output(Tool::FFmpeg)
             .await
             .context("Failed to execute ffmpeg")?;
 
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("FFmpeg M4B concatenation failed: {}", stderr);
+            return Err(ForgeError::tool_failed(Tool::FFmpeg, "FFmpeg M4B concatenation", &output, Some(concat_file)).into());
         }
 
         Ok(())
@@ -361,20 +618,19 @@ impl FFmpeg {
     pub async fn probe_metadata(&self, path: &Path) -> Result<AudioMetadata> {
         let output = Command::new(&self.ffprobe_path)
             .args([
-                "-v", "quiet",
+                "-v", "error",
                 "-print_format", "json",
                 "-show_format",
             ])
             .arg(path)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .output()
+            .logged_output(Tool::FFprobe)
             .await
             .context("Failed to execute ffprobe")?;
 
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("FFprobe failed: {}", stderr);
+            return Err(ForgeError::tool_failed(Tool::FFprobe, "FFprobe", &output, Some(path)).into());
         }
 
         let json: Value = serde_json::from_slice(&output.stdout)
@@ -402,20 +658,19 @@ impl FFmpeg {
     pub async fn probe_duration_and_title(&self, path: &Path) -> Result<(u64, Option<String>)> {
         let output = Command::new(&self.ffprobe_path)
             .args([
-                "-v", "quiet",
+                "-v", "error",
                 "-print_format", "json",
                 "-show_format",
             ])
             .arg(path)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .output()
+            .logged_output(Tool::FFprobe)
             .await
             .context("Failed to execute ffprobe")?;
 
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("FFprobe failed: {}", stderr);
+            return Err(ForgeError::tool_failed(Tool::FFprobe, "FFprobe", &output, Some(path)).into());
         }
 
         let json: Value = serde_json::from_slice(&output.stdout)
@@ -424,7 +679,7 @@ impl FFmpeg {
         let duration_secs = json["format"]["duration"]
             .as_str()
             .and_then(|s| s.parse::<f64>().ok())
-            .with_context(|| format!("No duration found for {}", path.display()))?;
+            .ok_or_else(|| ForgeError::corrupt(path, "no duration found"))?;
         let duration_ms = (duration_secs * 1000.0).round() as u64;
 
         let title = json["format"]["tags"]["title"]
@@ -452,6 +707,80 @@ mod tests {
         assert!(ffmpeg.is_ok());
     }
 
+    #[test]
+    fn test_encode_target_args() {
+        let target = EncodeTarget {
+            path: PathBuf::from("/out/book.m4a"),
+            quality: QualityProfile::new(64, 22050, 1, "aac".to_string(), 60.0).unwrap(),
+            encoder: AacEncoder::Native,
+            loudness: Some(-16.0),
+            tempo: None,
+        };
+        let args = target.output_args();
+
+        assert_eq!(&args[..3], ["-map", "0:a", "-vn"]);
+        assert!(args.contains(&"loudnorm=I=-16:TP=-1.5:LRA=11".to_string()));
+        assert!(args.windows(2).any(|w| w == ["-b:a", "64k"]));
+        assert!(args.windows(2).any(|w| w == ["-ac", "1"]));
+        assert_eq!(args.last().unwrap(), "/out/book.m4a");
+    }
+
+    #[test]
+    fn test_atempo_filter() {
+        assert_eq!(atempo_filter(1.25), "atempo=1.25");
+        assert_eq!(atempo_filter(3.0), "atempo=2,atempo=1.5");
+        assert_eq!(atempo_filter(0.4), "atempo=0.5,atempo=0.8");
+
+        let target = EncodeTarget {
+            path: PathBuf::from("/out/book (1.5x).m4b"),
+            quality: QualityProfile::new(64, 44100, 1, "aac".to_string(), 60.0).unwrap(),
+            encoder: AacEncoder::Native,
+            loudness: Some(-16.0),
+            tempo: Some(1.5),
+        };
+        let args = target.output_args();
+        assert!(args.windows(2).any(|w| w == ["-af", "loudnorm=I=-16:TP=-1.5:LRA=11,atempo=1.5"]));
+    }
+
+    #[test]
+    fn test_track_trim_filter() {
+        assert_eq!(TrackTrim::default().filter(), None);
+
+        let trim = TrackTrim { start: 2.5, end: Some(590.0), silence: None };
+        assert_eq!(trim.filter().unwrap(), "atrim=start=2.500:end=590.000,asetpts=PTS-STARTPTS");
+
+        let silence = SilenceTrim { threshold_db: -50.0, keep_secs: 0.25 };
+        let mut trim = TrackTrim { silence: Some(silence), ..TrackTrim::default() };
+        let remove = "silenceremove=start_periods=1:start_threshold=-50dB:start_silence=0.25";
+        assert_eq!(trim.filter().unwrap(), remove);
+
+        // Trailing silence is cut like an outro, keeping `keep_secs` of it
+        trim.cut_trailing_silence(7195.5);
+        assert_eq!(trim.filter().unwrap(), format!("atrim=start=0.000:end=7195.750,asetpts=PTS-STARTPTS,{remove}"));
+        // ...unless an outro cut already goes further
+        let mut trim = TrackTrim { end: Some(7000.0), silence: Some(silence), ..TrackTrim::default() };
+        trim.cut_trailing_silence(7195.5);
+        assert_eq!(trim.end, Some(7000.0));
+    }
+
+    #[test]
+    fn test_parse_trailing_silence() {
+        let stats = "size=N/A time=02:00:00.00 bitrate=N/A speed= 900x";
+
+        // Silence still running at the end, reported without an end...
+        let open = "[silencedetect @ 0x1] silence_start: 12.5\n[silencedetect @ 0x1] silence_end: 14 | silence_duration: 1.5\n\
+                    [silencedetect @ 0x1] silence_start: 7195.5\n";
+        assert_eq!(parse_trailing_silence(&format!("{open}{stats}")), Some(7195.5));
+        // ...or closed at the last timestamp
+        let closed = format!("{open}[silencedetect @ 0x1] silence_end: 7200 | silence_duration: 4.5\n{stats}");
+        assert_eq!(parse_trailing_silence(&closed), Some(7195.5));
+
+        // Silence in the middle only
+        let middle = format!("[silencedetect @ 0x1] silence_start: 12.5\n[silencedetect @ 0x1] silence_end: 14 | silence_duration: 1.5\n{stats}");
+        assert_eq!(parse_trailing_silence(&middle), None);
+        assert_eq!(parse_trailing_silence(stats), None);
+    }
+
     #[test]
     fn test_parse_ffprobe_json() {
         let json_str = r#"{
@@ -1,5 +1,7 @@
 //! Audio metadata extraction and manipulation
 
+use super::tools::{tools, Tool, ToolCommandExt};
+use crate::error::ForgeError;
 use crate::models::{Track, AudibleMetadata};
 use anyhow::{Context, Result};
 use id3::TagLike;
@@ -20,8 +22,9 @@ pub fn extract_mp3_metadata(track: &mut Track) -> Result<()> {
     track.comment = tag.comments().next().map(|c| c.text.clone());
     track.composer = tag.get("TCOM").and_then(|frame| frame.content().text()).map(|s| s.to_string());
 
-    // Extract track number
+    // Extract track and disc number
     track.track_number = tag.track();
+    track.disc_number = tag.disc();
 
     Ok(())
 }
@@ -45,6 +48,9 @@ pub fn extract_m4a_metadata(track: &mut Track) -> Result<()> {
     if let Some(track_num) = tag.track_number() {
         track.track_number = Some(track_num as u32);
     }
+    if let Some(disc_num) = tag.disc_number() {
+        track.disc_number = Some(disc_num as u32);
+    }
 
     Ok(())
 }
@@ -59,19 +65,20 @@ pub async fn extract_flac_metadata(track: &mut Track) -> Result<()> {
     // Use tokio's async Command so this does not block the runtime worker thread
     // when called from the parallel analysis pipeline, matching every other
     // ffprobe/ffmpeg call site in the codebase.
-    let output = tokio::process::Command::new("ffprobe")
+    let output = tools()
+        .async_command(Tool::FFprobe)?
         .args([
-            "-v", "quiet",
+            "-v", "error",
             "-print_format", "json",
             "-show_format",
         ])
         .arg(&track.file_path)
-        .output()
+        .logged_output(Tool::FFprobe)
         .await
         .context("Failed to execute ffprobe for FLAC metadata")?;
 
     if !output.status.success() {
-        anyhow::bail!("ffprobe failed to read FLAC metadata");
+        return Err(ForgeError::tool_failed(Tool::FFprobe, "FFprobe (FLAC metadata)", &output, Some(&track.file_path)).into());
     }
 
     let json: serde_json::Value = serde_json::from_slice(&output.stdout)
@@ -103,10 +110,10 @@ pub async fn extract_flac_metadata(track: &mut Track) -> Result<()> {
     track.comment = get("comment").or_else(|| get("description"));
     track.composer = get("composer");
 
-    // TRACKNUMBER may be "5" or "5/12"; take the part before the slash.
-    track.track_number = get("tracknumber")
-        .or_else(|| get("track"))
-        .and_then(|s| s.split('/').next().and_then(|n| n.trim().parse::<u32>().ok()));
+    // TRACKNUMBER/DISCNUMBER may be "5" or "5/12"; take the part before the slash.
+    let number = |s: String| s.split('/').next().and_then(|n| n.trim().parse::<u32>().ok());
+    track.track_number = get("tracknumber").or_else(|| get("track")).and_then(number);
+    track.disc_number = get("discnumber").or_else(|| get("disc")).and_then(number);
 
     Ok(())
 }
@@ -222,7 +229,7 @@ pub async fn inject_metadata_atomicparsley(
     comment: Option<&str>,
     cover_art: Option<&Path>,
 ) -> Result<()> {
-    let mut cmd = tokio::process::Command::new("AtomicParsley");
+    let mut cmd = tools().async_command(Tool::AtomicParsley)?;
     cmd.arg(file_path);
 
     if let Some(title) = title {
@@ -256,17 +263,13 @@ pub async fn inject_metadata_atomicparsley(
 
     cmd.args(&["--overWrite"]);
 
-    // Log command for debugging
-    tracing::debug!("AtomicParsley command: {:?}", cmd.as_std());
-
     let output = cmd
-        .output()
+        .logged_output(Tool::AtomicParsley)
         .await
         .context("Failed to execute AtomicParsley")?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("AtomicParsley failed: {}", stderr);
+        return Err(ForgeError::tool_failed(Tool::AtomicParsley, "AtomicParsley", &output, Some(file_path)).into());
     }
 
     Ok(())
@@ -278,7 +281,7 @@ pub async fn inject_audible_metadata(
     audible: &AudibleMetadata,
     cover_art: Option<&Path>,
 ) -> Result<()> {
-    let mut cmd = tokio::process::Command::new("AtomicParsley");
+    let mut cmd = tools().async_command(Tool::AtomicParsley)?;
     cmd.arg(file_path);
 
     // Title (with subtitle if present)
@@ -345,13 +348,12 @@ pub async fn inject_audible_metadata(
     cmd.args(&["--overWrite"]);
 
     let output = cmd
-        .output()
+        .logged_output(Tool::AtomicParsley)
         .await
         .context("Failed to execute AtomicParsley")?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("AtomicParsley failed: {}", stderr);
+        return Err(ForgeError::tool_failed(Tool::AtomicParsley, "AtomicParsley", &output, Some(file_path)).into());
     }
 
     Ok(())
@@ -6,6 +6,9 @@
 //! - Chapters: Chapter generation and management
 //! - Audible: Audible metadata fetching and integration
 //! - Encoder: AAC encoder detection and selection
+//! - Tools: Resolution of external binaries (paths, versions, capabilities)
+//! - Backend: The audio operations the core pipeline runs, and a recording fake
+//! - Bench: Encoder throughput benchmark used for auto encoder and concurrency
 
 mod ffmpeg;
 mod metadata;
@@ -13,10 +16,16 @@ mod chapters;
 pub mod audible;
 pub mod encoder;
 mod chapter_import;
+pub mod tools;
+pub mod backend;
+pub mod bench;
 
-pub use ffmpeg::{FFmpeg, AudioMetadata};
+pub use ffmpeg::{atempo_filter, FFmpeg, AudioMetadata, EncodeTarget, SilenceTrim, TrackTrim};
 pub use metadata::{extract_metadata, extract_mp3_metadata, extract_m4a_metadata, extract_flac_metadata, inject_metadata_atomicparsley, inject_audible_metadata, extract_embedded_cover};
 pub use chapters::{Chapter, generate_chapters_from_files, parse_cue_file, write_mp4box_chapters, inject_chapters_mp4box};
-pub use audible::{AudibleClient, detect_asin, clean_sequence};
+pub use audible::{AudibleClient, AudibleApiError, detect_asin, clean_sequence};
 pub use encoder::{AacEncoder, get_encoder, EncoderDetector};
+pub use bench::{BenchResult, BenchRun, EncoderBench, EncoderBenchmark};
+pub use backend::{AudioBackend, FfmpegBackend, RecordingBackend, BackendCall, BackendOp};
+pub use tools::{command_line, init_tools, tools, Tool, ToolCommandExt, ToolInfo, ToolRegistry, FEATURE_FILTERS};
 pub use chapter_import::{ChapterSource, ChapterMergeStrategy, ChapterComparison, parse_text_chapters, parse_epub_chapters, merge_chapters, merge_chapter_lists, read_m4b_chapters};
@@ -0,0 +1,423 @@
+//! External tool registry
+//!
+//! Every subprocess (ffmpeg, ffprobe, AtomicParsley, MP4Box) is started through
+//! the registry, which resolves each binary once: a path from the `advanced`
+//! config section first, then `PATH`. Versions and FFmpeg capabilities
+//! (encoders, filters) are probed on first use and cached. Pipeline commands
+//! run through [`ToolCommandExt::logged_output`], which logs their command line
+//! and stderr inside a `tool` span.
+
+use crate::error::ForgeError;
+use crate::models::AdvancedConfig;
+use anyhow::Result;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::OnceLock;
+use tracing::Instrument;
+
+/// External tools audiobook-forge runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    FFmpeg,
+    FFprobe,
+    AtomicParsley,
+    MP4Box,
+}
+
+impl Tool {
+    /// Every tool, in display order
+    pub const ALL: [Tool; 4] = [Tool::FFmpeg, Tool::FFprobe, Tool::AtomicParsley, Tool::MP4Box];
+
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tool::FFmpeg => "FFmpeg",
+            Tool::FFprobe => "FFprobe",
+            Tool::AtomicParsley => "AtomicParsley",
+            Tool::MP4Box => "MP4Box",
+        }
+    }
+
+    /// Binary name looked up in `PATH`
+    pub fn binary(&self) -> &'static str {
+        match self {
+            Tool::FFmpeg => "ffmpeg",
+            Tool::FFprobe => "ffprobe",
+            Tool::AtomicParsley => "AtomicParsley",
+            Tool::MP4Box => "MP4Box",
+        }
+    }
+
+    /// Config key that overrides the binary
+    pub fn config_key(&self) -> &'static str {
+        match self {
+            Tool::FFmpeg => "advanced.ffmpeg_path",
+            Tool::FFprobe => "advanced.ffprobe_path",
+            Tool::AtomicParsley => "advanced.atomic_parsley_path",
+            Tool::MP4Box => "advanced.mp4box_path",
+        }
+    }
+
+    /// Oldest supported (major, minor) version, if there is a requirement
+    pub fn min_version(&self) -> Option<(u32, u32)> {
+        match self {
+            // loudnorm, -movflags +faststart and the concat demuxer options we use
+            Tool::FFmpeg | Tool::FFprobe => Some((4, 0)),
+            Tool::AtomicParsley | Tool::MP4Box => None,
+        }
+    }
+
+    fn version_arg(&self) -> &'static str {
+        match self {
+            Tool::FFmpeg | Tool::FFprobe | Tool::MP4Box => "-version",
+            Tool::AtomicParsley => "--version",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Tool::ALL.iter().position(|t| t == self).unwrap_or(0)
+    }
+}
+
+fn unavailable(tool: Tool, message: String) -> anyhow::Error {
+    ForgeError::ToolUnavailable { tool, message }.into()
+}
+
+impl fmt::Display for Tool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Where a tool was found
+#[derive(Debug, Clone)]
+pub struct ToolInfo {
+    pub tool: Tool,
+    /// Resolved binary (None = not found)
+    pub path: Option<PathBuf>,
+    /// Whether the path came from the config file
+    pub configured: bool,
+}
+
+/// Resolved external tools and their capabilities
+#[derive(Debug)]
+pub struct ToolRegistry {
+    tools: [ToolInfo; 4],
+    versions: [OnceLock<Option<String>>; 4],
+    encoders: OnceLock<Vec<String>>,
+    filters: OnceLock<Vec<String>>,
+}
+
+/// FFmpeg filters some features depend on (reported by `check`)
+pub const FEATURE_FILTERS: &[(&str, &str)] = &[
+    ("loudnorm", "profile loudness normalization"),
+    ("silenceremove", "silence trimming"),
+    ("silencedetect", "silence trimming"),
+    ("atempo", "speed variants"),
+];
+
+static REGISTRY: OnceLock<ToolRegistry> = OnceLock::new();
+
+/// Resolve the tools from config (call once at startup, before any subprocess)
+pub fn init_tools(config: &AdvancedConfig) -> &'static ToolRegistry {
+    if REGISTRY.set(ToolRegistry::from_config(config)).is_err() {
+        tracing::debug!("Tool registry already initialized; keeping the existing paths");
+    }
+    tools()
+}
+
+/// The process-wide tool registry (resolved from defaults if `init_tools` wasn't called)
+pub fn tools() -> &'static ToolRegistry {
+    REGISTRY.get_or_init(|| ToolRegistry::from_config(&AdvancedConfig::default()))
+}
+
+impl ToolRegistry {
+    /// Resolve every tool from the config, falling back to `PATH`
+    pub fn from_config(config: &AdvancedConfig) -> Self {
+        let resolve = |tool: Tool, configured: Option<&PathBuf>| match configured {
+            Some(path) => ToolInfo { tool, path: Some(path.clone()), configured: true },
+            None => ToolInfo {
+                tool,
+                path: which::which(tool.binary()).ok(),
+                configured: false,
+            },
+        };
+
+        // ffprobe ships with ffmpeg, so look next to a custom ffmpeg first
+        let sibling_ffprobe = config.ffprobe_path.clone().or_else(|| {
+            let ffmpeg = config.ffmpeg_path.as_ref()?;
+            let mut name = "ffprobe".to_string();
+            if let Some(ext) = ffmpeg.extension() {
+                name = format!("{}.{}", name, ext.to_string_lossy());
+            }
+            Some(ffmpeg.with_file_name(name)).filter(|p| p.is_file())
+        });
+
+        Self {
+            tools: [
+                resolve(Tool::FFmpeg, config.ffmpeg_path.as_ref()),
+                resolve(Tool::FFprobe, sibling_ffprobe.as_ref()),
+                resolve(Tool::AtomicParsley, config.atomic_parsley_path.as_ref()),
+                resolve(Tool::MP4Box, config.mp4box_path.as_ref()),
+            ],
+            versions: Default::default(),
+            encoders: OnceLock::new(),
+            filters: OnceLock::new(),
+        }
+    }
+
+    /// Resolution details for a tool
+    pub fn info(&self, tool: Tool) -> &ToolInfo {
+        &self.tools[tool.index()]
+    }
+
+    /// Path of a tool, or an error explaining how to provide it
+    pub fn path(&self, tool: Tool) -> Result<&Path> {
+        let info = self.info(tool);
+        match &info.path {
+            Some(path) if !info.configured || path.is_file() => Ok(path),
+            Some(path) => Err(unavailable(
+                tool,
+                format!("{} not found at {} (set by {})", tool, path.display(), tool.config_key()),
+            )),
+            None => Err(unavailable(
+                tool,
+                format!(
+                    "{} not found in PATH (install it or set {} in the config file)",
+                    tool,
+                    tool.config_key()
+                ),
+            )),
+        }
+    }
+
+    /// Blocking command for a tool
+    pub fn command(&self, tool: Tool) -> Result<Command> {
+        Ok(Command::new(self.path(tool)?))
+    }
+
+    /// Async command for a tool
+    pub fn async_command(&self, tool: Tool) -> Result<tokio::process::Command> {
+        Ok(tokio::process::Command::new(self.path(tool)?))
+    }
+
+    /// Version string reported by a tool (probed once)
+    pub fn version(&self, tool: Tool) -> Option<&str> {
+        self.versions[tool.index()]
+            .get_or_init(|| {
+                let output = self.command(tool).ok()?.arg(tool.version_arg()).output().ok()?;
+                let text = format!(
+                    "{}\n{}",
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                parse_version_output(&text)
+            })
+            .as_deref()
+    }
+
+    /// Fail if a tool is missing or older than its minimum version
+    pub fn check(&self, tool: Tool) -> Result<()> {
+        let path = self.path(tool)?;
+        let (Some(min), Some(version)) = (tool.min_version(), self.version(tool)) else {
+            return Ok(());
+        };
+        // Unparseable versions (e.g. git snapshot builds) are assumed recent
+        if let Some(found) = numeric_version(version) {
+            if found < min {
+                return Err(unavailable(
+                    tool,
+                    format!(
+                        "{} {} at {} is too old: audiobook-forge needs {}.{} or newer",
+                        tool,
+                        version,
+                        path.display(),
+                        min.0,
+                        min.1
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Encoders FFmpeg was built with (probed once)
+    pub fn encoders(&self) -> &[String] {
+        self.encoders.get_or_init(|| self.ffmpeg_listing("-encoders"))
+    }
+
+    /// Filters FFmpeg was built with (probed once)
+    pub fn filters(&self) -> &[String] {
+        self.filters.get_or_init(|| self.ffmpeg_listing("-filters"))
+    }
+
+    /// Whether FFmpeg has an encoder
+    pub fn has_encoder(&self, name: &str) -> bool {
+        self.encoders().iter().any(|e| e == name)
+    }
+
+    /// Whether FFmpeg has a filter
+    pub fn has_filter(&self, name: &str) -> bool {
+        self.filters().iter().any(|f| f == name)
+    }
+
+    fn ffmpeg_listing(&self, flag: &str) -> Vec<String> {
+        let Ok(mut cmd) = self.command(Tool::FFmpeg) else {
+            return Vec::new();
+        };
+        match cmd.args(["-hide_banner", flag]).output() {
+            Ok(output) => parse_listing(&String::from_utf8_lossy(&output.stdout)),
+            Err(e) => {
+                tracing::warn!("Failed to run ffmpeg {}: {}", flag, e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Running tool commands with logging
+pub trait ToolCommandExt {
+    /// Run to completion inside a `tool` span, logging the command line, exit
+    /// status and stderr (at debug level, so they reach per-book logs)
+    ///
+    /// The tool is killed if the future is dropped before it finishes.
+    fn logged_output(&mut self, tool: Tool) -> impl Future<Output = io::Result<Output>> + Send;
+}
+
+impl ToolCommandExt for tokio::process::Command {
+    fn logged_output(&mut self, tool: Tool) -> impl Future<Output = io::Result<Output>> + Send {
+        let span = tracing::info_span!("tool", tool = %tool);
+        async move {
+            tracing::debug!(command = %command_line(self.as_std()), "Running {}", tool);
+            // Cancelling the run (e.g. on Ctrl-C) kills the tool
+            self.kill_on_drop(true);
+            let output = self.output().await?;
+            tracing::debug!(
+                status = %output.status,
+                stderr = %String::from_utf8_lossy(&output.stderr).trim(),
+                "{} finished",
+                tool
+            );
+            Ok(output)
+        }
+        .instrument(span)
+    }
+}
+
+/// Command line as it could be pasted into a shell
+pub fn command_line(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| shell_quote(&arg.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=+,@%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// First version token in `<tool> version X` style output
+fn parse_version_output(text: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        words.find(|w| w.trim_end_matches(':').eq_ignore_ascii_case("version"))?;
+        words.next().map(|v| v.trim_end_matches(',').to_string())
+    })
+}
+
+/// (major, minor) of a version like "6.1.1", "n7.0" or "4.4.2-0ubuntu0.22.04.1"
+fn numeric_version(version: &str) -> Option<(u32, u32)> {
+    let version = version.strip_prefix('n').unwrap_or(version);
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
+
+/// Names from `ffmpeg -encoders` / `-filters` (the column after the flags)
+fn parse_listing(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let flags = columns.next()?;
+            let name = columns.next()?;
+            // Flags are dots and capitals ("A....D", "TSC"); legend lines read "X = ..."
+            let is_flags = flags.chars().all(|c| c == '.' || c.is_ascii_uppercase());
+            (is_flags && name != "=").then(|| name.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_line_quoting() {
+        let mut cmd = Command::new("/usr/bin/ffmpeg");
+        cmd.args(["-i", "/books/Tom's Book/01 intro.mp3", "-c:a", "aac", "out.m4a"]);
+        assert_eq!(
+            command_line(&cmd),
+            r"/usr/bin/ffmpeg -i '/books/Tom'\''s Book/01 intro.mp3' -c:a aac out.m4a"
+        );
+    }
+
+    #[test]
+    fn test_parse_versions() {
+        assert_eq!(
+            parse_version_output("ffmpeg version 6.1.1 Copyright (c) 2000-2023").as_deref(),
+            Some("6.1.1")
+        );
+        assert_eq!(
+            parse_version_output("AtomicParsley version: 20210715.151551.0 (utf8)").as_deref(),
+            Some("20210715.151551.0")
+        );
+        assert_eq!(
+            parse_version_output("MP4Box - GPAC version 2.2.1-revrelease").as_deref(),
+            Some("2.2.1-revrelease")
+        );
+
+        assert_eq!(numeric_version("6.1.1"), Some((6, 1)));
+        assert_eq!(numeric_version("n7.0"), Some((7, 0)));
+        assert_eq!(numeric_version("4.4.2-0ubuntu0.22.04.1"), Some((4, 4)));
+        assert_eq!(numeric_version("N-112233-gabcdef"), None);
+        assert!(numeric_version("3.4.8").unwrap() < Tool::FFmpeg.min_version().unwrap());
+    }
+
+    #[test]
+    fn test_parse_listing() {
+        let encoders = "Encoders:\n V..... = Video\n A..... = Audio\n ------\n A....D aac                  AAC (Advanced Audio Coding)\n A..... libfdk_aac           Fraunhofer FDK AAC\n";
+        assert_eq!(parse_listing(encoders), vec!["aac", "libfdk_aac"]);
+
+        let filters = "Filters:\n  T.. = Timeline support\n  ... loudnorm           A->A       EBU R128 loudness normalization\n TSC atempo            A->A       Adjust audio tempo.\n";
+        assert_eq!(parse_listing(filters), vec!["loudnorm", "atempo"]);
+    }
+
+    #[test]
+    fn test_configured_path_errors() {
+        let config = AdvancedConfig {
+            ffmpeg_path: Some(PathBuf::from("/nonexistent/ffmpeg")),
+            mp4box_path: Some(PathBuf::from("/nonexistent/MP4Box")),
+            ..Default::default()
+        };
+        let registry = ToolRegistry::from_config(&config);
+
+        assert!(registry.info(Tool::FFmpeg).configured);
+        let err = registry.path(Tool::MP4Box).unwrap_err().to_string();
+        assert!(err.contains("advanced.mp4box_path"), "{}", err);
+        assert!(registry.check(Tool::FFmpeg).is_err());
+        assert!(registry.encoders().is_empty());
+    }
+}
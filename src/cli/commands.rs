@@ -1,6 +1,7 @@
 //! CLI commands and arguments
 
 use clap::{Parser, Subcommand, Args};
+use serde_yaml::Value;
 use std::path::PathBuf;
 
 use crate::VERSION;
@@ -28,8 +29,187 @@ pub struct Cli {
     /// Enable verbose output
     #[arg(global = true, short, long)]
     pub verbose: bool,
+
+    /// Serve Audible data from the local cache only (no network access)
+    #[arg(global = true, long)]
+    pub offline: bool,
+
+    /// Print a JSON report on stdout instead of human-readable output
+    #[arg(global = true, long)]
+    pub json: bool,
 }
 
+impl Cli {
+    /// Command name used in the `--json` report, e.g. "config show"
+    pub fn command_name(&self) -> &'static str {
+        match &self.command {
+            Commands::Build(_) => "build",
+            Commands::Organize(_) => "organize",
+            Commands::Config(command) => match command {
+                ConfigCommands::Init { .. } => "config init",
+                ConfigCommands::Show { .. } => "config show",
+                ConfigCommands::Schema { .. } => "config schema",
+                ConfigCommands::Migrate { .. } => "config migrate",
+                ConfigCommands::Path => "config path",
+                ConfigCommands::Validate { .. } => "config validate",
+                ConfigCommands::Edit => "config edit",
+            },
+            Commands::Metadata(MetadataCommands::Fetch { .. }) => "metadata fetch",
+            Commands::Metadata(MetadataCommands::Enrich { .. }) => "metadata enrich",
+            Commands::Match(_) => "match",
+            Commands::Cache(_) => "cache",
+            Commands::Undo(_) => "undo",
+            Commands::Bench(_) => "bench",
+            Commands::Check => "check",
+            Commands::Version => "version",
+        }
+    }
+
+    /// Whether the command can produce a `--json` report
+    pub fn supports_json(&self) -> bool {
+        match &self.command {
+            Commands::Build(_)
+            | Commands::Organize(_)
+            | Commands::Bench(_)
+            | Commands::Check
+            | Commands::Config(ConfigCommands::Show { .. })
+            | Commands::Metadata(MetadataCommands::Fetch { .. }) => true,
+            Commands::Match(args) => args.dry_run,
+            _ => false,
+        }
+    }
+
+    /// Config file given with `--config`, if any
+    pub fn config_file(&self) -> Option<PathBuf> {
+        match &self.command {
+            Commands::Build(args) => args.config.clone(),
+            Commands::Organize(args) => args.config.clone(),
+            Commands::Config(ConfigCommands::Show { config, .. })
+            | Commands::Config(ConfigCommands::Validate { config, .. }) => config.clone(),
+            _ => None,
+        }
+    }
+
+    /// Library root given with `--root`, searched for a project config file
+    pub fn root_dir(&self) -> Option<PathBuf> {
+        match &self.command {
+            Commands::Build(args) => args.root.clone(),
+            Commands::Organize(args) => args.root.clone(),
+            _ => None,
+        }
+    }
+
+    /// Settings set by command-line flags, as (dotted key, value, flag)
+    pub fn config_overrides(&self) -> Vec<(String, Value, String)> {
+        let mut overrides = Vec::new();
+        let mut set = |key: &str, value: Value, flag: &str| {
+            overrides.push((key.to_string(), value, flag.to_string()));
+        };
+
+        if self.offline {
+            set("metadata.audible.offline", Value::Bool(true), "--offline");
+        }
+
+        match &self.command {
+            Commands::Build(args) => {
+                if let Some(parallel) = args.parallel {
+                    set("processing.parallel_workers", Value::from(parallel), "--parallel");
+                }
+                if let Some(skip) = args.skip_existing {
+                    set("processing.skip_existing", Value::Bool(skip), "--skip-existing");
+                }
+                if args.keep_temp {
+                    set("processing.keep_temp_files", Value::Bool(true), "--keep-temp");
+                }
+                if let Some(stereo) = args.prefer_stereo {
+                    set("quality.prefer_stereo", Value::Bool(stereo), "--prefer-stereo");
+                }
+                // A budget given on the command line replaces either one from config files
+                if let Some(size) = &args.target_size {
+                    set("quality.target_size", Value::from(size.as_str()), "--target-size");
+                    set("quality.size_per_hour", Value::Null, "--target-size");
+                }
+                if let Some(size) = &args.size_per_hour {
+                    set("quality.size_per_hour", Value::from(size.as_str()), "--size-per-hour");
+                    set("quality.target_size", Value::Null, "--size-per-hour");
+                }
+                if args.trim_silence {
+                    set("quality.trim_silence", Value::Bool(true), "--trim-silence");
+                }
+                if let Some(secs) = args.trim_intro {
+                    set("quality.trim_intro", Value::from(secs), "--trim-intro");
+                }
+                if let Some(secs) = args.trim_outro {
+                    set("quality.trim_outro", Value::from(secs), "--trim-outro");
+                }
+                if !args.speed.is_empty() {
+                    let speeds = args.speed.iter().map(|&s| Value::from(s)).collect();
+                    set("quality.speeds", Value::Sequence(speeds), "--speed");
+                }
+                if let Some(source) = &args.chapter_source {
+                    set("quality.chapter_source", Value::from(source.as_str()), "--chapter-source");
+                }
+                if let Some(order) = &args.track_order {
+                    set("processing.track_order", Value::from(order.as_str()), "--track-order");
+                }
+                if let Some(names) = &args.cover_names {
+                    let names = names
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|n| !n.is_empty())
+                        .map(Value::from)
+                        .collect();
+                    set("metadata.cover_filenames", Value::Sequence(names), "--cover-names");
+                }
+                if let Some(language) = &args.language {
+                    set("metadata.default_language", Value::from(language.as_str()), "--language");
+                }
+                if let Some(encoder) = &args.aac_encoder {
+                    set("advanced.aac_encoder", Value::from(encoder.as_str()), "--aac-encoder");
+                } else if let Some(encoder) = args.legacy_aac_encoder() {
+                    set("advanced.aac_encoder", Value::from(encoder), "--use-apple-silicon-encoder");
+                }
+                if args.fetch_audible {
+                    set("metadata.audible.enabled", Value::Bool(true), "--fetch-audible");
+                }
+                if let Some(region) = &args.audible_region {
+                    set("metadata.audible.region", Value::from(region.as_str()), "--audible-region");
+                }
+                if args.audible_auto_match {
+                    set("metadata.audible.auto_match", Value::Bool(true), "--audible-auto-match");
+                }
+                if let Some(template) = &args.template {
+                    set("organization.path_template", Value::from(template.as_str()), "--template");
+                }
+            }
+            Commands::Organize(args) => {
+                if let Some(template) = &args.template {
+                    set("organization.path_template", Value::from(template.as_str()), "--template");
+                }
+            }
+            _ => {}
+        }
+
+        overrides
+    }
+
+    /// Warnings for deprecated flags that were used
+    pub fn deprecation_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if let Commands::Build(args) = &self.command {
+            if let Some(encoder) = args.legacy_aac_encoder() {
+                warnings.push(format!(
+                    "--use-apple-silicon-encoder is deprecated; use --aac-encoder {} instead",
+                    encoder
+                ));
+            }
+        }
+        warnings
+    }
+}
+
+// Parsed once per run, so the size of `BuildArgs` doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 pub enum Commands {
     /// Process audiobooks and convert to M4B
@@ -49,6 +229,16 @@ pub enum Commands {
     /// Interactive metadata matching for M4B files
     Match(MatchArgs),
 
+    /// Manage the local Audible cache
+    #[command(subcommand)]
+    Cache(CacheCommands),
+
+    /// Revert the file changes made by a build or organize run
+    Undo(UndoArgs),
+
+    /// Benchmark AAC encoders and cache the fastest settings
+    Bench(BenchArgs),
+
     /// Check system dependencies
     Check,
 
@@ -90,6 +280,10 @@ pub struct BuildArgs {
     #[arg(long)]
     pub dry_run: bool,
 
+    /// Don't build books with missing, duplicated or foreign tracks
+    #[arg(long)]
+    pub strict: bool,
+
     /// Prefer stereo over mono
     #[arg(long)]
     pub prefer_stereo: Option<bool>,
@@ -98,6 +292,10 @@ pub struct BuildArgs {
     #[arg(long, value_parser = ["auto", "files", "cue", "id3", "none"])]
     pub chapter_source: Option<String>,
 
+    /// How tracks are put in order
+    #[arg(long, value_parser = ["auto", "filename", "tags", "title", "order_file"])]
+    pub track_order: Option<String>,
+
     /// Cover art filenames (comma-separated)
     #[arg(long)]
     pub cover_names: Option<String>,
@@ -110,20 +308,49 @@ pub struct BuildArgs {
     #[arg(long)]
     pub keep_temp: bool,
 
-    /// Delete original files after conversion
+    /// Delete original files after conversion (with the journal on, they are
+    /// kept in the library's trash until the run is pruned, so `undo` can restore them)
     #[arg(long)]
     pub delete_originals: bool,
 
     /// Quality preset for output audio
-    #[arg(long, value_parser = ["low", "medium", "high", "ultra", "maximum", "source"])]
+    #[arg(long, value_parser = ["low", "medium", "high", "ultra", "maximum", "speech", "speech_stereo", "source"])]
     pub quality: Option<String>,
 
+    /// Named profile(s) from the config file; several (comma-separated) are encoded from one decode
+    #[arg(long, value_name = "NAME", value_delimiter = ',', conflicts_with = "quality")]
+    pub profile: Vec<String>,
+
+    /// Re-encode books to fit this size (e.g. 300MB, 1.2GB)
+    #[arg(long, value_name = "SIZE", conflicts_with_all = ["size_per_hour", "profile"])]
+    pub target_size: Option<String>,
+
+    /// Re-encode books to fit this size per hour of audio (e.g. 30MB)
+    #[arg(long, value_name = "SIZE", conflicts_with = "profile")]
+    pub size_per_hour: Option<String>,
+
+    /// Trim leading and trailing silence from every track
+    #[arg(long)]
+    pub trim_silence: bool,
+
+    /// Cut this many seconds from the start of each book (e.g. 2.5 for a publisher intro)
+    #[arg(long, value_name = "SECS")]
+    pub trim_intro: Option<f64>,
+
+    /// Cut this many seconds from the end of each book
+    #[arg(long, value_name = "SECS")]
+    pub trim_outro: Option<f64>,
+
+    /// Also write each book at these playback speeds (comma-separated, e.g. 1.25,1.5)
+    #[arg(long, value_name = "SPEED", value_delimiter = ',')]
+    pub speed: Vec<f64>,
+
     /// AAC encoder to use (auto, aac_at, libfdk_aac, aac)
     #[arg(long)]
     pub aac_encoder: Option<String>,
 
     /// DEPRECATED: Use --aac-encoder instead
-    #[arg(long, hide = true)]
+    #[arg(long, hide = true, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
     pub use_apple_silicon_encoder: Option<bool>,
 
     /// Fetch metadata from Audible during build
@@ -138,11 +365,29 @@ pub struct BuildArgs {
     #[arg(long)]
     pub audible_auto_match: bool,
 
+    /// Output path template, e.g. "{author}/{series}/{title}/{title}.m4b"
+    #[arg(long)]
+    pub template: Option<String>,
+
     /// Configuration file path
     #[arg(long)]
     pub config: Option<PathBuf>,
 }
 
+impl BuildArgs {
+    /// AAC encoder from `--aac-encoder`, or from the deprecated `--use-apple-silicon-encoder`
+    pub fn aac_encoder(&self) -> Option<&str> {
+        self.aac_encoder.as_deref().or(self.legacy_aac_encoder())
+    }
+
+    /// Encoder the deprecated `--use-apple-silicon-encoder` flag stands for
+    /// (mapped as the v1 -> v2 config migration maps the config key)
+    fn legacy_aac_encoder(&self) -> Option<&'static str> {
+        self.use_apple_silicon_encoder
+            .map(|flag| if flag { "aac_at" } else { "aac" })
+    }
+}
+
 #[derive(Args)]
 pub struct OrganizeArgs {
     /// Root directory to organize
@@ -153,11 +398,61 @@ pub struct OrganizeArgs {
     #[arg(long)]
     pub dry_run: bool,
 
+    /// Library layout template for finished M4B files, e.g. "{author}/{title}/{title}.m4b"
+    #[arg(long)]
+    pub template: Option<String>,
+
     /// Configuration file path
     #[arg(long)]
     pub config: Option<PathBuf>,
 }
 
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Length of the synthetic test sample in seconds
+    #[arg(long, default_value_t = 60, value_parser = clap::value_parser!(u32).range(5..=600))]
+    pub duration: u32,
+
+    /// Highest number of parallel encodes to try (default: CPU count, up to 16)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..=16))]
+    pub max_concurrency: Option<u64>,
+
+    /// Print the results without caching them
+    #[arg(long)]
+    pub no_save: bool,
+}
+
+#[derive(Args)]
+pub struct UndoArgs {
+    /// Undo the most recent run that hasn't been undone (default)
+    #[arg(long, conflicts_with = "run")]
+    pub last: bool,
+
+    /// Undo a specific run by id
+    #[arg(long, value_name = "ID")]
+    pub run: Option<String>,
+
+    /// List recorded runs
+    #[arg(long, conflicts_with_all = ["last", "run"])]
+    pub list: bool,
+
+    /// Show what would be reverted, including conflicts
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Skip changes that conflict instead of aborting
+    #[arg(long)]
+    pub force: bool,
+
+    /// Delete old runs and the originals they keep, freeing their space
+    #[arg(long, conflicts_with_all = ["last", "run", "list"])]
+    pub prune: bool,
+
+    /// With --prune: delete runs older than this many days (default: processing.journal_retention_days)
+    #[arg(long, value_name = "DAYS", requires = "prune")]
+    pub older_than: Option<u32>,
+}
+
 #[derive(Subcommand)]
 pub enum ConfigCommands {
     /// Initialize config file with defaults
@@ -172,6 +467,14 @@ pub enum ConfigCommands {
         /// Configuration file path
         #[arg(long)]
         config: Option<PathBuf>,
+
+        /// Show the resolved settings of a named profile instead
+        #[arg(long, value_name = "NAME", conflicts_with = "origin")]
+        profile: Option<String>,
+
+        /// Show which layer (default, file, environment, flag) set each value
+        #[arg(long)]
+        origin: bool,
     },
 
     /// Validate configuration file
@@ -179,6 +482,32 @@ pub enum ConfigCommands {
         /// Configuration file path
         #[arg(long)]
         config: Option<PathBuf>,
+
+        /// Also report unknown keys, and fail on any problem
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Upgrade an older config file to the current format
+    Migrate {
+        /// Configuration file path (default: user config file)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Show the changes without rewriting the file
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Rewrite without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Print a JSON Schema of the config file (for editor autocompletion)
+    Schema {
+        /// Write the schema to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 
     /// Show config file path
@@ -249,6 +578,38 @@ pub enum MetadataCommands {
     },
 }
 
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    /// Show cache size and entry counts per kind
+    Stats,
+
+    /// Remove cached entries
+    Clear {
+        /// Only clear one kind of entry
+        #[arg(long, value_parser = ["metadata", "search", "chapters", "covers"])]
+        kind: Option<String>,
+    },
+
+    /// Remove entries older than their configured TTL
+    Prune,
+
+    /// Copy the cache into a directory or bundle file (e.g. to move it to another machine)
+    Export {
+        /// Destination directory (or bundle file with --bundle)
+        dest: PathBuf,
+
+        /// Write a single-file bundle instead of a directory
+        #[arg(long)]
+        bundle: bool,
+    },
+
+    /// Merge a directory or bundle written by `cache export` into the cache
+    Import {
+        /// Exported cache directory or bundle file
+        src: PathBuf,
+    },
+}
+
 /// Arguments for the match command
 #[derive(Args)]
 pub struct MatchArgs {
@@ -283,4 +644,48 @@ pub struct MatchArgs {
     /// Dry run (show matches but don't apply)
     #[arg(long)]
     pub dry_run: bool,
+
+    /// Write all candidates to a review queue file instead of applying (YAML, or JSON with .json)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["auto", "apply_queue"])]
+    pub queue: Option<PathBuf>,
+
+    /// Apply the reviewed decisions from a queue file written by --queue
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["file", "dir", "auto"])]
+    pub apply_queue: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deprecated_apple_silicon_flag() {
+        let cli = Cli::try_parse_from(["audiobook-forge", "build", "--use-apple-silicon-encoder"]).unwrap();
+        let Commands::Build(args) = &cli.command else { unreachable!() };
+        assert_eq!(args.aac_encoder(), Some("aac_at"));
+        assert_eq!(
+            cli.config_overrides(),
+            vec![(
+                "advanced.aac_encoder".to_string(),
+                Value::from("aac_at"),
+                "--use-apple-silicon-encoder".to_string()
+            )]
+        );
+        assert!(cli.deprecation_warnings()[0].contains("--aac-encoder aac_at"));
+
+        let cli = Cli::try_parse_from(["audiobook-forge", "build", "--use-apple-silicon-encoder", "false"]).unwrap();
+        let Commands::Build(args) = &cli.command else { unreachable!() };
+        assert_eq!(args.aac_encoder(), Some("aac"));
+
+        // --aac-encoder wins over the deprecated flag
+        let cli = Cli::try_parse_from([
+            "audiobook-forge", "build", "--aac-encoder", "libfdk_aac", "--use-apple-silicon-encoder",
+        ])
+        .unwrap();
+        let Commands::Build(args) = &cli.command else { unreachable!() };
+        assert_eq!(args.aac_encoder(), Some("libfdk_aac"));
+
+        let cli = Cli::try_parse_from(["audiobook-forge", "build"]).unwrap();
+        assert!(cli.deprecation_warnings().is_empty());
+    }
 }
@@ -1,18 +1,25 @@
 //! CLI command handlers
 
-use crate::cli::commands::{BuildArgs, ConfigCommands, OrganizeArgs, MetadataCommands, MatchArgs};
-use crate::core::{Analyzer, BatchProcessor, M4bMerger, Organizer, RetryConfig, Scanner};
-use crate::models::{BookCase, Config, AudibleRegion, CurrentMetadata, MetadataSource};
-use crate::utils::{ConfigManager, DependencyChecker, AudibleCache, scoring, extraction};
-use crate::audio::{AacEncoder, AudibleClient, detect_asin};
+use crate::cli::commands::{BenchArgs, BuildArgs, CacheCommands, ConfigCommands, OrganizeArgs, MetadataCommands, MatchArgs, UndoArgs};
+use crate::cli::output::{say, CommandReport, Outcome};
+use crate::core::{check_integrity, format_size, min_free_space, plan_batch, plan_for_budget, Analyzer, BookTrim, DiskSpace, SpaceNeeds, sweep_orphaned_temp_dirs, SizeBudget, BatchProcessor, IntegrityIssue, TrackOrdering, Journal, JournalRun, M4bMerger, OrganizeAction, Organizer, OutputProfile, RetryConfig, Scanner};
+use crate::models::{AudibleMetadata, BookCase, BookFolder, BookOverrides, Config, ProcessingResult, ProfileConfig, QualityProfile, ResolvedProfile, AudibleRegion, CurrentMetadata, MetadataSource, ReviewQueue, ReviewEntry, ReviewDecision};
+use crate::utils::{config_schema, find_unknown_keys, line_diff, migrate_config, ConfigManager, ConfigOrigin, LayeredConfig, DependencyChecker, BookLogs, book_span, AudibleCache, CacheKind, PathTemplate, TemplateValues, scoring, extraction, interrupt, template};
+use crate::error::{ErrorKind, ForgeError};
+use crate::audio::{AacEncoder, AudibleApiError, BenchResult, EncoderBenchmark, EncoderDetector, AudibleClient, Tool, FEATURE_FILTERS, detect_asin};
 use crate::ui::{prompt_match_selection, prompt_manual_metadata, prompt_custom_search, UserChoice};
 use anyhow::{Context, Result, bail};
 use console::style;
-use std::path::PathBuf;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use tracing::Instrument;
 
 /// Resolve which AAC encoder to use based on config (handles backward compatibility)
-fn resolve_encoder(config: &Config, cli_override: Option<&str>) -> AacEncoder {
+///
+/// With "auto", the fastest encoder from the benchmark is preferred over the
+/// fixed priority order.
+fn resolve_encoder(config: &Config, cli_override: Option<&str>, bench: Option<&BenchResult>) -> AacEncoder {
     // CLI argument takes highest priority
     if let Some(encoder_str) = cli_override {
         if let Some(encoder) = AacEncoder::from_str(encoder_str) {
@@ -23,20 +30,16 @@ fn resolve_encoder(config: &Config, cli_override: Option<&str>) -> AacEncoder {
         }
     }
 
-    // Handle backward compatibility with old use_apple_silicon_encoder field
-    if let Some(use_apple_silicon) = config.advanced.use_apple_silicon_encoder {
-        let encoder = if use_apple_silicon {
-            AacEncoder::AppleSilicon
-        } else {
-            AacEncoder::Native
-        };
-        tracing::info!("Using encoder from legacy config: {}", encoder.name());
-        return encoder;
-    }
-
     // Use new aac_encoder field
     match config.advanced.aac_encoder.to_lowercase().as_str() {
         "auto" => {
+            let benchmarked = bench
+                .and_then(|b| b.encoder())
+                .filter(|&e| EncoderDetector::is_encoder_available(e));
+            if let Some(encoder) = benchmarked {
+                tracing::info!("Using fastest benchmarked encoder: {}", encoder.name());
+                return encoder;
+            }
             let encoder = crate::audio::get_encoder();
             tracing::info!("Auto-detected encoder: {}", encoder.name());
             encoder
@@ -58,6 +61,30 @@ fn resolve_encoder(config: &Config, cli_override: Option<&str>) -> AacEncoder {
     }
 }
 
+/// Cached encoder benchmark, running it first if calibration is enabled
+async fn load_bench(config: &Config) -> Option<BenchResult> {
+    if let Some(bench) = BenchResult::load_cached() {
+        return Some(bench);
+    }
+    if !config.performance.calibrate {
+        return None;
+    }
+
+    say!("{} Calibrating encoders (first run, this takes a minute)...", style("→").cyan());
+    let bench = match EncoderBenchmark::default().run().await {
+        Ok(bench) => bench,
+        Err(e) => {
+            tracing::warn!("Encoder calibration failed: {:#}", e);
+            return None;
+        }
+    };
+    match BenchResult::cache_path().and_then(|path| bench.save(&path)) {
+        Ok(()) => {}
+        Err(e) => tracing::warn!("Failed to cache encoder benchmark: {:#}", e),
+    }
+    Some(bench)
+}
+
 /// Try to detect if current directory is an audiobook folder
 fn try_detect_current_as_audiobook() -> Result<Option<PathBuf>> {
     let current_dir = std::env::current_dir()
@@ -119,12 +146,140 @@ fn is_audiobook_folder(path: &std::path::Path) -> Result<bool> {
 }
 
 /// Handle the build command
-pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
+/// `--json` result of the build command
+#[derive(Debug, Default, Serialize)]
+struct BuildReport {
+    root: PathBuf,
+    output_dir: Option<PathBuf>,
+    dry_run: bool,
+    /// Books that would be processed (dry run only)
+    planned: Vec<PlannedBook>,
+    /// How each analyzed book's tracks were ordered
+    track_order: Vec<BookTrackOrder>,
+    /// Result for every merged or converted book
+    books: Vec<ProcessingResult>,
+    successful: usize,
+    failed: usize,
+    deleted_originals: usize,
+    /// Journal run to pass to `undo`, if anything was recorded
+    journal_run: Option<String>,
+}
+
+/// Track order chosen for a book
+#[derive(Debug, Serialize)]
+struct BookTrackOrder {
+    name: String,
+    #[serde(flatten)]
+    ordering: TrackOrdering,
+}
+
+/// A book a dry run would process
+#[derive(Debug, Serialize)]
+struct PlannedBook {
+    name: String,
+    case: BookCase,
+    files: usize,
+    duration_secs: f64,
+    /// Missing, duplicated or foreign tracks found before building
+    issues: Vec<IntegrityIssue>,
+    /// Estimated output size in bytes
+    estimated_bytes: u64,
+    /// Size budget in bytes, if one is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_budget: Option<u64>,
+    /// Quality chosen to fit the size budget (absent if the source fits)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_quality: Option<QualityProfile>,
+    /// Why the book can't fit its size budget
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_error: Option<String>,
+}
+
+/// How a book's size budget is met
+struct SizePlan {
+    budget: SizeBudget,
+    bytes: u64,
+    /// Quality to re-encode at, or the reason the budget can't be met
+    /// (None = the source already fits)
+    target: Result<Option<QualityProfile>>,
+}
+
+impl SizePlan {
+    /// Plan for a book with a budget in its (possibly overridden) config
+    fn for_book(book: &BookFolder, config: &Config, encoder: AacEncoder) -> Result<Option<Self>> {
+        if book.case == BookCase::E {
+            return Ok(None); // M4B merges aren't re-encoded
+        }
+        let book_config = book.effective_config(config);
+        let Some(budget) = SizeBudget::from_config(&book_config.quality)? else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            budget,
+            bytes: budget.bytes_for(book.get_total_duration()),
+            target: plan_for_budget(book, budget, encoder, book_config.quality.prefer_stereo),
+        }))
+    }
+
+    /// Quality the book will be encoded at, if re-encoded for the budget
+    fn target_quality(&self) -> Option<QualityProfile> {
+        self.target.as_ref().ok().cloned().flatten()
+    }
+
+    /// One-line description for the console
+    fn describe(&self, book: &BookFolder) -> String {
+        match &self.target {
+            Ok(Some(quality)) => format!(
+                "Size budget {}: {} (est. {})",
+                self.budget,
+                quality,
+                format_size(quality.estimate_size(book.get_total_duration()))
+            ),
+            Ok(None) => format!(
+                "Size budget {}: source fits (est. {})",
+                self.budget,
+                format_size(book.estimate_output_size())
+            ),
+            Err(e) => format!("Size budget {}: {:#}", self.budget, e),
+        }
+    }
+}
+
+pub async fn handle_build(mut args: BuildArgs, mut config: Config) -> Result<CommandReport> {
+    // The deprecated --use-apple-silicon-encoder stands in for --aac-encoder
+    args.aac_encoder = args.aac_encoder().map(str::to_string);
+
+    // Resolve named profiles; a lone profile also sets the run's chapter source and template
+    let mut profiles = Vec::new();
+    for name in &args.profile {
+        if profiles.iter().any(|p: &ResolvedProfile| &p.name == name) {
+            bail!("Profile '{}' is listed more than once", name);
+        }
+        profiles.push(config.profile(name)?);
+    }
+    if let [profile] = profiles.as_slice() {
+        // Explicit --chapter-source / --template still win over the profile
+        if args.chapter_source.is_none() {
+            config.quality.chapter_source = profile.chapter_source.clone();
+        }
+        if args.template.is_none() {
+            config.organization.path_template = profile.template.clone();
+        }
+    }
+
+    // Parse the output path template up front so typos fail fast
+    let path_template = args
+        .template
+        .as_deref()
+        .or(config.organization.path_template.as_deref())
+        .map(PathTemplate::parse)
+        .transpose()?;
+
     // Determine root directory (CLI arg > config > auto-detect > error)
     let (root, auto_detected) = if let Some(root_path) = args.root.or(config.directories.source.clone()) {
         // Check if root itself is an audiobook folder
         if is_audiobook_folder(&root_path)? {
-            println!(
+            say!(
                 "{} Detected audiobook folder (not library): {}",
                 style("→").cyan(),
                 style(root_path.display()).yellow()
@@ -136,7 +291,7 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
     } else {
         // Try auto-detecting current directory
         if let Some(current) = try_detect_current_as_audiobook()? {
-            println!(
+            say!(
                 "{} Auto-detected audiobook folder: {}",
                 style("→").cyan(),
                 style(current.display()).yellow()
@@ -150,15 +305,30 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
     };
 
     if !auto_detected {
-        println!(
+        say!(
             "{} Scanning audiobooks in: {}",
             style("→").cyan(),
             style(root.display()).yellow()
         );
     }
 
+    // Journal extracted covers, outputs (keeping files they overwrite) and
+    // deleted originals so the run can be undone
+    if !args.dry_run {
+        prune_journal(&config)?;
+    }
+    let journal = if !args.dry_run && config.processing.journal {
+        Some(Journal::start(&Journal::default_dir()?))
+    } else {
+        None
+    };
+    // Trashed files stay on the library's filesystem
+    if let Some(journal) = &journal {
+        journal.add_trash_root(&root);
+    }
+
     // Scan for audiobooks
-    let scanner = Scanner::from_config(&config);
+    let scanner = Scanner::from_config(&config).with_journal(journal.clone());
     let mut book_folders = if auto_detected {
         // Auto-detect mode: treat current dir as single book
         vec![scanner.scan_single_directory(&root)?]
@@ -169,28 +339,44 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
             .context("Failed to scan directory")?
     };
 
+    // Size budgets re-encode each book on its own; profiles set their own quality
+    if SizeBudget::from_config(&config.quality)?.is_some() && !profiles.is_empty() {
+        bail!("A size budget (--target-size / --size-per-hour) can't be combined with --profile");
+    }
+
+    let mut report = BuildReport {
+        root: root.clone(),
+        dry_run: args.dry_run,
+        ..Default::default()
+    };
+
     if book_folders.is_empty() {
-        println!("{} No audiobooks found", style("✗").red());
-        return Ok(());
+        say!("{} No audiobooks found", style("✗").red());
+        return Ok(CommandReport::new(Outcome::NothingToDo, report));
     }
 
-    println!(
+    say!(
         "{} Found {} audiobook(s)",
         style("✓").green(),
         style(book_folders.len()).cyan()
     );
 
-    // Filter by skip_existing if configured
-    if config.processing.skip_existing && !args.force {
+    // Filter by skip_existing (which a book's override file may also turn on)
+    if !args.force {
+        let found = book_folders.len();
         book_folders.retain(|b| {
             // Keep if no M4B files OR if it's a mergeable case (E)
-            b.m4b_files.is_empty() || b.case == BookCase::E
+            !b.effective_config(&config).processing.skip_existing
+                || b.m4b_files.is_empty()
+                || b.case == BookCase::E
         });
-        println!(
-            "{} After filtering existing: {} audiobook(s)",
-            style("→").cyan(),
-            style(book_folders.len()).cyan()
-        );
+        if config.processing.skip_existing || book_folders.len() < found {
+            say!(
+                "{} After filtering existing: {} audiobook(s)",
+                style("→").cyan(),
+                style(book_folders.len()).cyan()
+            );
+        }
     }
 
     // Handle --merge-m4b flag: force Case E for multi-M4B folders
@@ -207,45 +393,108 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
     }
 
     if book_folders.is_empty() {
-        println!(
+        say!(
             "{} All audiobooks already processed (use --force to reprocess)",
             style("ℹ").blue()
         );
-        return Ok(());
-    }
-
-    // Dry run mode
-    if args.dry_run {
-        println!("\n{} DRY RUN MODE - No changes will be made\n", style("ℹ").blue());
-        for book in &book_folders {
-            println!(
-                "  {} {} ({} files, {:.1} min)",
-                style("→").cyan(),
-                style(&book.name).yellow(),
-                book.mp3_files.len(),
-                book.get_total_duration() / 60.0
-            );
-        }
-        return Ok(());
+        return Ok(CommandReport::new(Outcome::NothingToDo, report));
     }
 
     // Analyze all books
-    println!("\n{} Analyzing tracks...", style("→").cyan());
+    say!("\n{} Analyzing tracks...", style("→").cyan());
     let analyzer_workers = args.parallel.unwrap_or(config.processing.parallel_workers);
-    let analyzer = Analyzer::with_workers(analyzer_workers as usize)?;
+    let analyzer = Analyzer::with_workers(analyzer_workers as usize)?
+        .with_track_order(config.processing.track_order);
 
     for book in &mut book_folders {
         analyzer
             .analyze_book_folder(book)
             .await
             .with_context(|| format!("Failed to analyze {}", book.name))?;
+        if let Some(ordering) = &book.track_ordering {
+            report.track_order.push(BookTrackOrder {
+                name: book.name.clone(),
+                ordering: ordering.clone(),
+            });
+        }
+    }
+
+    say!("{} Analysis complete", style("✓").green());
+
+    // Look for missing, duplicated and foreign tracks
+    let integrity: Vec<Vec<IntegrityIssue>> = book_folders.iter().map(check_integrity).collect();
+
+    // Dry run mode
+    if args.dry_run {
+        say!("\n{} DRY RUN MODE - No changes will be made\n", style("ℹ").blue());
+        let encoder = resolve_encoder(&config, args.aac_encoder.as_deref(), BenchResult::load_cached().as_ref());
+        for (book, issues) in book_folders.iter().zip(integrity) {
+            say!(
+                "  {} {} ({} files, {:.1} min){}",
+                style("→").cyan(),
+                style(&book.name).yellow(),
+                book.mp3_files.len(),
+                book.get_total_duration() / 60.0,
+                if args.strict && !issues.is_empty() {
+                    style(" - would not be built (--strict)").red().to_string()
+                } else {
+                    String::new()
+                }
+            );
+            print_integrity_issues(&issues);
+            let size_plan = SizePlan::for_book(book, &config, encoder)?;
+            if let Some(plan) = &size_plan {
+                let mark = if plan.target.is_ok() { style("→").cyan() } else { style("✗").red() };
+                say!("    {} {}", mark, plan.describe(book));
+            }
+            let target_quality = size_plan.as_ref().and_then(SizePlan::target_quality);
+            report.planned.push(PlannedBook {
+                name: book.name.clone(),
+                case: book.case,
+                files: book.mp3_files.len(),
+                duration_secs: book.get_total_duration(),
+                issues,
+                estimated_bytes: match &target_quality {
+                    Some(quality) => quality.estimate_size(book.get_total_duration()),
+                    None => book.estimate_output_size(),
+                },
+                size_budget: size_plan.as_ref().map(|p| p.bytes),
+                target_quality,
+                size_error: size_plan.and_then(|p| p.target.err()).map(|e| format!("{:#}", e)),
+            });
+        }
+        return Ok(CommandReport::new(Outcome::Success, report));
     }
 
-    println!("{} Analysis complete", style("✓").green());
+    let mut checked = Vec::with_capacity(book_folders.len());
+    for (book, issues) in book_folders.into_iter().zip(integrity) {
+        if issues.is_empty() {
+            checked.push(book);
+            continue;
+        }
+        say!(
+            "{} {}: {} integrity issue(s)",
+            style("⚠").yellow(),
+            style(&book.name).yellow(),
+            issues.len()
+        );
+        print_integrity_issues(&issues);
+        if args.strict {
+            say!("    {} Not building (--strict)", style("✗").red());
+            let error = anyhow::Error::new(ForgeError::CorruptInput {
+                path: book.folder_path.clone(),
+                reason: format!("{} integrity issue(s); not built with --strict", issues.len()),
+            });
+            report.books.push(ProcessingResult::new(book.name.clone()).failure_from(&error, 0.0));
+        } else {
+            checked.push(book);
+        }
+    }
+    let mut book_folders = checked;
 
     // Fetch Audible metadata if enabled
     if args.fetch_audible || config.metadata.audible.enabled {
-        println!("\n{} Fetching Audible metadata...", style("→").cyan());
+        say!("\n{} Fetching Audible metadata...", style("→").cyan());
 
         let audible_region = args.audible_region
             .as_deref()
@@ -259,24 +508,29 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
             std::time::Duration::from_secs(config.metadata.audible.api_max_retry_delay_secs),
             2.0,
         );
+        let cache = AudibleCache::from_config(&config.metadata.audible)?;
         let client = AudibleClient::with_config(
             audible_region,
             config.metadata.audible.rate_limit_per_minute,
             retry_config,
-        )?;
-        let cache = AudibleCache::with_ttl_hours(config.metadata.audible.cache_duration_hours)?;
+        )?
+        .with_cache(cache.clone())
+        .with_offline(config.metadata.audible.offline);
 
         for book in &mut book_folders {
-            // Try ASIN detection first
-            if let Some(asin) = detect_asin(&book.name) {
-                tracing::debug!("Detected ASIN {} in folder: {}", asin, book.name);
+            let book_audible = book.effective_config(&config).metadata.audible.clone();
+
+            // Use an ASIN pinned in the override file, then try detection
+            let asin = book.detected_asin.clone().or_else(|| detect_asin(&book.name));
+            if let Some(asin) = asin {
+                tracing::debug!("Using ASIN {} for folder: {}", asin, book.name);
                 book.detected_asin = Some(asin.clone());
 
                 // Try cache first
                 match cache.get(&asin).await {
                     Some(cached) => {
                         book.audible_metadata = Some(cached);
-                        println!("  {} {} (ASIN: {}, cached)", style("✓").green(), book.name, asin);
+                        say!("  {} {} (ASIN: {}, cached)", style("✓").green(), book.name, asin);
                     }
                     None => {
                         // Fetch from API
@@ -285,10 +539,10 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
                                 // Cache the result
                                 let _ = cache.set(&asin, &metadata).await;
                                 book.audible_metadata = Some(metadata);
-                                println!("  {} {} (ASIN: {})", style("✓").green(), book.name, asin);
+                                say!("  {} {} (ASIN: {})", style("✓").green(), book.name, asin);
 
                                 // Fetch chapters if enabled
-                                if config.metadata.audible.fetch_chapters {
+                                if book_audible.fetch_chapters {
                                     match client.fetch_chapters(&asin).await {
                                         Ok(chapters) => {
                                             tracing::debug!("Fetched {} chapters for ASIN: {}", chapters.len(), asin);
@@ -299,18 +553,24 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
                                     }
                                 }
                             }
+                            Err(e) if AudibleApiError::is_not_cached(&e) => {
+                                say!("  {} {} - not cached (offline)", style("○").dim(), book.name);
+                            }
                             Err(e) => {
                                 tracing::warn!("Failed to fetch metadata for {}: {:?}", book.name, e);
-                                println!("  {} {} - fetch failed", style("⚠").yellow(), book.name);
+                                say!("  {} {} - fetch failed", style("⚠").yellow(), book.name);
                             }
                         }
                     }
                 }
-            } else if args.audible_auto_match || config.metadata.audible.auto_match {
-                // Try auto-matching by title
+            } else if args.audible_auto_match || book_audible.auto_match {
+                // Try auto-matching by title (and author, when overridden)
                 tracing::debug!("Attempting auto-match for: {}", book.name);
+                let tags = book.tag_overrides();
+                let title = tags.and_then(|t| t.title.clone()).unwrap_or_else(|| book.name.clone());
+                let author = tags.and_then(|t| t.author.clone());
 
-                match client.search(Some(&book.name), None).await {
+                match client.search(Some(&title), author.as_deref()).await {
                     Ok(results) if !results.is_empty() => {
                         let asin = &results[0].asin;
                         tracing::debug!("Auto-matched {} to ASIN: {}", book.name, asin);
@@ -320,7 +580,7 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
                         match cache.get(asin).await {
                             Some(cached) => {
                                 book.audible_metadata = Some(cached);
-                                println!("  {} {} (matched: {}, cached)", style("✓").green(), book.name, asin);
+                                say!("  {} {} (matched: {}, cached)", style("✓").green(), book.name, asin);
                             }
                             None => {
                                 // Fetch from API
@@ -329,10 +589,10 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
                                         // Cache the result
                                         let _ = cache.set(asin, &metadata).await;
                                         book.audible_metadata = Some(metadata);
-                                        println!("  {} {} (matched: {})", style("✓").green(), book.name, asin);
+                                        say!("  {} {} (matched: {})", style("✓").green(), book.name, asin);
 
                                         // Fetch chapters if enabled
-                                        if config.metadata.audible.fetch_chapters {
+                                        if book_audible.fetch_chapters {
                                             match client.fetch_chapters(asin).await {
                                                 Ok(chapters) => {
                                                     tracing::debug!("Fetched {} chapters for ASIN: {}", chapters.len(), asin);
@@ -343,9 +603,12 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
                                             }
                                         }
                                     }
+                                    Err(e) if AudibleApiError::is_not_cached(&e) => {
+                                        say!("  {} {} - not cached (offline)", style("○").dim(), book.name);
+                                    }
                                     Err(e) => {
                                         tracing::warn!("Failed to fetch metadata after match for {}: {:?}", book.name, e);
-                                        println!("  {} {} - fetch failed", style("⚠").yellow(), book.name);
+                                        say!("  {} {} - fetch failed", style("⚠").yellow(), book.name);
                                     }
                                 }
                             }
@@ -353,11 +616,14 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
                     }
                     Ok(_) => {
                         tracing::debug!("No Audible match found for: {}", book.name);
-                        println!("  {} {} - no match found", style("○").dim(), book.name);
+                        say!("  {} {} - no match found", style("○").dim(), book.name);
+                    }
+                    Err(e) if AudibleApiError::is_not_cached(&e) => {
+                        say!("  {} {} - search not cached (offline)", style("○").dim(), book.name);
                     }
                     Err(e) => {
                         tracing::warn!("Search failed for {}: {:?}", book.name, e);
-                        println!("  {} {} - search failed", style("⚠").yellow(), book.name);
+                        say!("  {} {} - search failed", style("⚠").yellow(), book.name);
                     }
                 }
             } else {
@@ -368,7 +634,7 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
             // art directly, without requiring a separate `enrich` run (issue #10).
             // Only when a cover was fetched, none is already present locally, and
             // cover downloading is enabled.
-            if config.metadata.audible.download_covers && book.cover_file.is_none() {
+            if book_audible.download_covers && book.cover_file.is_none() {
                 if let Some(metadata) = &book.audible_metadata {
                     if let Some(cover_url) = metadata.cover_url.clone() {
                         let temp_cover = std::env::temp_dir()
@@ -396,7 +662,7 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
         }
 
         let fetched_count = book_folders.iter().filter(|b| b.audible_metadata.is_some()).count();
-        println!("{} Fetched metadata for {}/{} books",
+        say!("{} Fetched metadata for {}/{} books",
             style("✓").green(),
             style(fetched_count).cyan(),
             book_folders.len()
@@ -417,17 +683,36 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
             }
         }).context("No output directory specified")?
     };
+    report.output_dir = Some(output_dir.clone());
+    if let Some(journal) = &journal {
+        journal.add_trash_root(&output_dir);
+    }
+
+    // Render templated output paths; books already built there are skipped
+    if let Some(path_template) = &path_template {
+        let built = assign_output_paths(&mut book_folders, path_template, &output_dir, &config, args.force)?;
+        for (name, path) in &built {
+            say!(
+                "{} Skipping {}: already built at {}",
+                style("→").cyan(),
+                style(name).yellow(),
+                path.display()
+            );
+        }
+    }
 
     // Create batch processor with config settings
     let workers = args.parallel.unwrap_or(config.processing.parallel_workers) as usize;
     let keep_temp = args.keep_temp || config.processing.keep_temp_files;
 
     // Resolve encoder (handles backward compatibility with legacy config)
-    let encoder = resolve_encoder(&config, args.aac_encoder.as_deref());
+    let bench = load_bench(&config).await;
+    let encoder = resolve_encoder(&config, args.aac_encoder.as_deref(), bench.as_ref());
+    let (auto_encodes, auto_files) = BatchProcessor::recommended_concurrency(bench.as_ref());
 
     // Parse max concurrent encodes from config
-    let max_concurrent = if config.performance.max_concurrent_encodes == "auto" {
-        num_cpus::get() // Use all CPU cores
+    let mut max_concurrent = if config.performance.max_concurrent_encodes == "auto" {
+        auto_encodes
     } else {
         config.performance.max_concurrent_encodes
             .parse::<usize>()
@@ -437,7 +722,7 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
 
     // Parse max concurrent files per book from config
     let max_concurrent_files = if config.performance.max_concurrent_files_per_book == "auto" {
-        num_cpus::get()
+        auto_files
     } else {
         config.performance.max_concurrent_files_per_book
             .parse::<usize>()
@@ -445,6 +730,31 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
             .clamp(1, 32)
     };
 
+    // Pick the quality that fits each book's size budget, and how its tracks are trimmed
+    if let Some(trim) = BookTrim::from_config(&config.quality) {
+        say!("{} Trimming: {}", style("→").cyan(), style(trim).cyan());
+    }
+    let mut fitted = Vec::with_capacity(book_folders.len());
+    for mut book in book_folders {
+        if book.case != BookCase::E {
+            book.trim = BookTrim::from_config(&book.effective_config(&config).quality);
+        }
+        let Some(plan) = SizePlan::for_book(&book, &config, encoder)? else {
+            fitted.push(book);
+            continue;
+        };
+        if plan.target.is_ok() {
+            say!("  {} {}: {}", style("→").cyan(), style(&book.name).yellow(), plan.describe(&book));
+            book.target_quality = plan.target_quality();
+            fitted.push(book);
+        } else {
+            say!("  {} {}: {}", style("✗").red(), style(&book.name).yellow(), plan.describe(&book));
+            let error = anyhow::Error::new(ForgeError::Config(plan.describe(&book)));
+            report.books.push(ProcessingResult::new(book.name.clone()).failure_from(&error, 0.0));
+        }
+    }
+    let book_folders = fitted;
+
     // Create retry config from settings
     let retry_config = RetryConfig::with_settings(
         config.processing.max_retries as usize,
@@ -453,7 +763,105 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
         2.0,
     );
 
-    let batch_processor = BatchProcessor::with_options(
+    let mut output_profiles = Vec::new();
+    for profile in profiles {
+        let encoder_override = args
+            .aac_encoder
+            .clone()
+            .or_else(|| Some(profile.encoder.clone()).filter(|e| e != "auto"));
+        let encoder = resolve_encoder(&config, encoder_override.as_deref(), bench.as_ref());
+        output_profiles.push(OutputProfile::new(profile, encoder)?);
+    }
+    // Speed variants of every output, encoded from the same decode
+    if !config.quality.speeds.is_empty() {
+        if output_profiles.is_empty() {
+            // The usual output, as a profile to derive the variants from
+            let mut default = ProfileConfig::default().resolve("default", &config)?;
+            default.template = None;
+            output_profiles.push(OutputProfile::new(default, encoder)?);
+        }
+        let mut variants = Vec::new();
+        for profile in &output_profiles {
+            for &speed in &config.quality.speeds {
+                variants.push(profile.at_speed(speed)?);
+            }
+        }
+        output_profiles.extend(variants);
+    }
+    if !output_profiles.is_empty() {
+        say!(
+            "{} Profiles: {}",
+            style("→").cyan(),
+            style(
+                output_profiles
+                    .iter()
+                    .map(|p| p.settings.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .cyan()
+        );
+    }
+
+    // From here on, Ctrl-C stops the build cleanly instead of killing it
+    let _cleanup = interrupt::cleanup_scope();
+
+    // Remove temp directories left by earlier runs that crashed
+    let temp_dir = config.advanced.temp_directory.clone();
+    let temp_base = temp_dir.clone().unwrap_or_else(std::env::temp_dir);
+    let swept = sweep_orphaned_temp_dirs(&temp_base);
+    if !swept.is_empty() {
+        say!(
+            "{} Removed {} temp folder(s) left by an earlier run",
+            style("→").cyan(),
+            style(swept.len()).cyan()
+        );
+    }
+
+    // Check the batch fits the free temp and output space
+    let disk_space = if config.processing.check_disk_space {
+        let disk_space = DiskSpace::new(
+            temp_base,
+            output_dir.clone(),
+            min_free_space(&config.processing)?,
+        );
+        let needs: Vec<SpaceNeeds> = book_folders
+            .iter()
+            .map(|b| SpaceNeeds::for_book(b, if b.case == BookCase::E { 1 } else { output_profiles.len() }))
+            .collect();
+        let free = disk_space.free()?;
+        let space = plan_batch(&needs, max_concurrent, free, disk_space.reserve())?;
+        say!(
+            "{} Disk space: {} of output, up to {} of temp files ({} free)",
+            style("→").cyan(),
+            style(format_size(space.output_bytes)).cyan(),
+            style(format_size(space.temp_bytes)).cyan(),
+            free
+        );
+        if space.concurrency < max_concurrent {
+            say!(
+                "{} Not enough temp space for {} books at once: building {} at a time",
+                style("⚠").yellow(),
+                max_concurrent,
+                space.concurrency
+            );
+            max_concurrent = space.concurrency;
+        }
+        if space.fitting_books < needs.len() {
+            say!(
+                "{} Only the first {} of {} books fit the free space; the rest fail unless space is freed",
+                style("⚠").yellow(),
+                space.fitting_books,
+                needs.len()
+            );
+        }
+        Some(disk_space)
+    } else {
+        None
+    };
+
+    let book_logs = BookLogs::from_config(&config.logging)?;
+    let mut batch_processor = BatchProcessor::with_options(
         workers,
         keep_temp,
         encoder,
@@ -462,7 +870,35 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
         max_concurrent_files,
         args.quality.clone(),
         retry_config,
-    );
+    )
+    .with_profiles(output_profiles)
+    .with_book_logs(book_logs.clone())
+    .with_temp_dir(temp_dir.clone())
+    .with_journal(journal.clone());
+    if let Some(disk_space) = &disk_space {
+        batch_processor = batch_processor.with_disk_space(disk_space.clone());
+    }
+    if let BookLogs::RunDir(dir) = &book_logs {
+        say!("{} Book logs: {}", style("→").cyan(), style(dir.display()).yellow());
+    }
+
+    // Remember source files before the books are consumed
+    let originals: std::collections::HashMap<String, Vec<PathBuf>> = if args.delete_originals {
+        book_folders
+            .iter()
+            .map(|b| {
+                let files = if b.case == BookCase::E {
+                    b.m4b_files.clone()
+                } else {
+                    b.tracks.iter().map(|t| t.file_path.clone()).collect()
+                };
+                (b.name.clone(), files)
+            })
+            .collect()
+    } else {
+        std::collections::HashMap::new()
+    };
+    let mut completed: Vec<String> = Vec::new();
 
     // Separate Case E (M4B merge) from other cases
     let (merge_books, convert_books): (Vec<_>, Vec<_>) = book_folders
@@ -471,40 +907,66 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
 
     // Process M4B merges
     if !merge_books.is_empty() {
-        println!(
+        say!(
             "\n{} Merging {} M4B audiobook(s)...",
             style("→").cyan(),
             style(merge_books.len()).cyan()
         );
 
-        let merger = M4bMerger::with_options(args.keep_temp)?;
+        let merger = M4bMerger::with_options(args.keep_temp)?
+            .with_temp_dir(temp_dir)
+            .with_journal(journal.clone());
 
         for book in merge_books {
-            println!(
+            let start = std::time::Instant::now();
+            say!(
                 "  {} {} ({} files)",
                 style("→").cyan(),
                 style(&book.name).yellow(),
                 book.m4b_files.len()
             );
 
-            match merger.merge_m4b_files(&book, &output_dir).await {
+            let span = book_span(&book, book_logs.path_for(&book, &output_dir).as_deref());
+            let claim = disk_space
+                .as_ref()
+                .map(|d| d.claim(&book.name, SpaceNeeds::for_book(&book, 1)))
+                .transpose();
+            let merged = match claim {
+                // Ctrl-C drops the merge, which kills its tools and removes its temp files
+                Ok(_claim) => tokio::select! {
+                    merged = merger.merge_m4b_files(&book, &output_dir).instrument(span) => merged,
+                    _ = interrupt::interrupted() => Err(ForgeError::Interrupted.into()),
+                },
+                Err(e) => Err(e),
+            };
+            match merged {
                 Ok(output_path) => {
-                    println!(
+                    say!(
                         "  {} Merged: {}",
                         style("✓").green(),
                         output_path.display()
                     );
+                    completed.push(book.name.clone());
+                    report.books.push(
+                        ProcessingResult::new(book.name.clone())
+                            .success(output_path, start.elapsed().as_secs_f64(), true),
+                    );
                 }
                 Err(e) => {
                     // Use the alternate formatter so the full anyhow context chain
                     // (including the underlying ffmpeg stderr) is shown, not just the
                     // top-level "Failed to concatenate M4B files" message (issue #15).
-                    println!(
-                        "  {} Failed to merge {}: {:#}",
+                    say!(
+                        "  {} Failed to merge {} {}: {:#}",
                         style("✗").red(),
                         book.name,
+                        style(format!("[{}]", ErrorKind::of(&e))).dim(),
                         e
                     );
+                    report.books.push(
+                        ProcessingResult::new(book.name.clone())
+                            .failure_from(&e, start.elapsed().as_secs_f64()),
+                    );
                 }
             }
         }
@@ -515,7 +977,7 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
 
     // Process batch (regular conversions)
     if !book_folders.is_empty() {
-        println!("\n{} Processing {} audiobook(s)...\n", style("→").cyan(), book_folders.len());
+        say!("\n{} Processing {} audiobook(s)...\n", style("→").cyan(), book_folders.len());
     }
 
     let results = batch_processor
@@ -527,34 +989,38 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
         .await;
 
     // Print results
-    println!();
-    let successful = results.iter().filter(|r| r.success).count();
-    let failed = results.len() - successful;
+    say!();
 
     for result in &results {
         if result.success {
-            println!(
+            completed.push(result.book_name.clone());
+            say!(
                 "  {} {} ({:.1}s, {})",
                 style("✓").green(),
                 style(&result.book_name).yellow(),
                 result.processing_time,
-                if result.used_copy_mode {
-                    "copy mode"
-                } else {
-                    "transcode"
-                }
+                result.encode_mode()
             );
         } else {
-            println!(
-                "  {} {} - {}",
+            let kind = result.error_kind.map(|k| format!("[{}] ", k)).unwrap_or_default();
+            say!(
+                "  {} {} - {}{}",
                 style("✗").red(),
                 style(&result.book_name).yellow(),
+                style(kind).dim(),
                 result.error_message.as_deref().unwrap_or("Unknown error")
             );
         }
     }
 
-    println!(
+    // Merges count toward the batch too
+    report.books.extend(results);
+    let successful = report.books.iter().filter(|r| r.success).count();
+    let failed = report.books.len() - successful;
+    report.successful = successful;
+    report.failed = failed;
+
+    say!(
         "\n{} Batch complete: {} successful, {} failed",
         style("✓").green(),
         style(successful).green(),
@@ -565,60 +1031,215 @@ pub async fn handle_build(args: BuildArgs, config: Config) -> Result<()> {
         }
     );
 
+    // Delete originals of books that converted successfully
+    if args.delete_originals {
+        let mut deleted = 0;
+        for file in completed.iter().filter_map(|name| originals.get(name)).flatten() {
+            let removed = match &journal {
+                Some(journal) => journal.remove_file(file),
+                None => std::fs::remove_file(file)
+                    .with_context(|| format!("Failed to delete {}", file.display())),
+            };
+            match removed {
+                Ok(()) => deleted += 1,
+                Err(e) => say!("  {} {:#}", style("⚠").yellow(), e),
+            }
+        }
+        say!(
+            "{} Deleted {} original file(s)",
+            style("✓").green(),
+            style(deleted).cyan()
+        );
+        report.deleted_originals = deleted;
+    }
+
+    print_undo_hint(journal.as_ref());
+    report.journal_run = journal_run_id(journal.as_ref());
+
+    let command_report = CommandReport::new(Outcome::of_batch(successful, failed), report);
+    if interrupt::is_interrupted() {
+        return Ok(command_report.with_error(ForgeError::Interrupted.into()));
+    }
+    Ok(command_report)
+}
+
+/// List a book's integrity issues under its name
+fn print_integrity_issues(issues: &[IntegrityIssue]) {
+    for issue in issues {
+        say!("    {} {}", style("⚠").yellow(), issue);
+    }
+}
+
+/// Render each book's templated output path, dropping books already built
+///
+/// A file already at a book's path is its output from an earlier run: the
+/// book is skipped when `skip_existing` is on (and `force` is off), and
+/// otherwise overwritten. Only books of this batch rendering to the same path
+/// get a numeric suffix. Returns the skipped books with their outputs.
+fn assign_output_paths(
+    books: &mut Vec<BookFolder>,
+    path_template: &PathTemplate,
+    output_dir: &Path,
+    config: &Config,
+    force: bool,
+) -> Result<Vec<(String, PathBuf)>> {
+    let mut claimed = std::collections::HashSet::new();
+    let mut built = Vec::new();
+    let mut kept = Vec::new();
+    for mut book in books.drain(..) {
+        let relative = path_template.render(&TemplateValues::from_book(&book))?;
+        let output_path = template::resolve_collision(&output_dir.join(relative), |p| claimed.contains(p))?;
+        claimed.insert(output_path.clone());
+
+        if !force && output_path.exists() && book.effective_config(config).processing.skip_existing {
+            built.push((book.name.clone(), output_path));
+            continue;
+        }
+        tracing::debug!("Output path for {}: {}", book.name, output_path.display());
+        book.output_path = Some(output_path.strip_prefix(output_dir)?.to_path_buf());
+        kept.push(book);
+    }
+    *books = kept;
+    Ok(built)
+}
+
+/// Delete journal runs older than `processing.journal_retention_days`
+fn prune_journal(config: &Config) -> Result<()> {
+    let days = config.processing.journal_retention_days;
+    if days == 0 {
+        return Ok(());
+    }
+    let report = JournalRun::prune(&Journal::default_dir()?, days_duration(days))?;
+    if report.runs > 0 {
+        say!(
+            "{} Pruned {} journal run(s) older than {} days, freeing {}",
+            style("→").cyan(),
+            style(report.runs).cyan(),
+            days,
+            style(format_size(report.bytes)).cyan()
+        );
+    }
     Ok(())
 }
 
+fn days_duration(days: u32) -> std::time::Duration {
+    std::time::Duration::from_secs(u64::from(days) * 24 * 3600)
+}
+
+/// Tell the user how to revert a journaled run
+fn print_undo_hint(journal: Option<&Journal>) {
+    if let Some(run_id) = journal_run_id(journal) {
+        say!(
+            "{} Changes recorded as run {} (revert with: audiobook-forge undo)",
+            style("ℹ").blue(),
+            style(run_id).cyan()
+        );
+    }
+}
+
+/// Id of a journaled run that recorded changes
+fn journal_run_id(journal: Option<&Journal>) -> Option<String> {
+    journal.filter(|j| !j.is_empty()).map(Journal::run_id)
+}
+
+/// `--json` result of the organize command
+#[derive(Debug, Default, Serialize)]
+struct OrganizeReport {
+    root: PathBuf,
+    dry_run: bool,
+    books: Vec<OrganizedBook>,
+    moved: usize,
+    skipped: usize,
+    failed: usize,
+    /// Journal run to pass to `undo`, if anything was recorded
+    journal_run: Option<String>,
+}
+
+/// What organize did with one book
+#[derive(Debug, Serialize)]
+struct OrganizedBook {
+    name: String,
+    source: PathBuf,
+    destination: Option<PathBuf>,
+    action: OrganizeAction,
+    success: bool,
+    error: Option<String>,
+}
+
 /// Handle the organize command
-pub fn handle_organize(args: OrganizeArgs, config: Config) -> Result<()> {
+pub fn handle_organize(args: OrganizeArgs, config: Config) -> Result<CommandReport> {
     // Determine root directory
     let root = args
         .root
         .or(config.directories.source.clone())
         .context("No root directory specified. Use --root or configure directories.source")?;
 
-    println!(
+    say!(
         "{} Scanning audiobooks in: {}",
         style("→").cyan(),
         style(root.display()).yellow()
     );
 
+    if !args.dry_run {
+        prune_journal(&config)?;
+    }
+    let journal = if !args.dry_run && config.processing.journal {
+        Some(Journal::start(&Journal::default_dir()?))
+    } else {
+        None
+    };
+
     // Scan for audiobooks
-    let scanner = Scanner::from_config(&config);
+    let scanner = Scanner::from_config(&config).with_journal(journal.clone());
     let book_folders = scanner
         .scan_directory(&root)
         .context("Failed to scan directory")?;
 
+    let mut report = OrganizeReport {
+        root: root.clone(),
+        dry_run: args.dry_run,
+        ..Default::default()
+    };
+
     if book_folders.is_empty() {
-        println!("{} No audiobooks found", style("✗").red());
-        return Ok(());
+        say!("{} No audiobooks found", style("✗").red());
+        return Ok(CommandReport::new(Outcome::NothingToDo, report));
     }
 
-    println!(
+    say!(
         "{} Found {} audiobook(s)",
         style("✓").green(),
         style(book_folders.len()).cyan()
     );
 
     // Create organizer
-    let organizer = Organizer::with_dry_run(root, &config, args.dry_run);
+    let path_template = args
+        .template
+        .as_deref()
+        .or(config.organization.path_template.as_deref())
+        .map(PathTemplate::parse)
+        .transpose()?;
+    let organizer = Organizer::with_dry_run(root, &config, args.dry_run)
+        .with_template(path_template)
+        .with_journal(journal.clone());
 
     // Dry run notice
     if args.dry_run {
-        println!("\n{} DRY RUN MODE - No changes will be made\n", style("ℹ").blue());
+        say!("\n{} DRY RUN MODE - No changes will be made\n", style("ℹ").blue());
     }
 
     // Organize books
     let results = organizer.organize_batch(book_folders);
 
     // Print results
-    println!();
+    say!();
     for result in &results {
         let action_str = result.action.description();
 
         if result.success {
             match result.destination_path {
                 Some(ref dest) => {
-                    println!(
+                    say!(
                         "  {} {} → {}",
                         style("✓").green(),
                         style(&result.book_name).yellow(),
@@ -626,7 +1247,7 @@ pub fn handle_organize(args: OrganizeArgs, config: Config) -> Result<()> {
                     );
                 }
                 None => {
-                    println!(
+                    say!(
                         "  {} {} ({})",
                         style("→").dim(),
                         style(&result.book_name).dim(),
@@ -635,7 +1256,7 @@ pub fn handle_organize(args: OrganizeArgs, config: Config) -> Result<()> {
                 }
             }
         } else {
-            println!(
+            say!(
                 "  {} {} - {}",
                 style("✗").red(),
                 style(&result.book_name).yellow(),
@@ -651,7 +1272,7 @@ pub fn handle_organize(args: OrganizeArgs, config: Config) -> Result<()> {
     let skipped = results.iter().filter(|r| r.destination_path.is_none()).count();
     let failed = results.iter().filter(|r| !r.success).count();
 
-    println!(
+    say!(
         "\n{} Organization complete: {} moved, {} skipped, {} failed",
         style("✓").green(),
         style(moved).green(),
@@ -663,23 +1284,125 @@ pub fn handle_organize(args: OrganizeArgs, config: Config) -> Result<()> {
         }
     );
 
+    print_undo_hint(journal.as_ref());
+
+    report.books = results
+        .into_iter()
+        .map(|r| OrganizedBook {
+            action: r.action,
+            name: r.book_name,
+            source: r.source_path,
+            destination: r.destination_path,
+            success: r.success,
+            error: r.error_message,
+        })
+        .collect();
+    report.moved = moved;
+    report.skipped = skipped;
+    report.failed = failed;
+    report.journal_run = journal_run_id(journal.as_ref());
+
+    Ok(CommandReport::new(Outcome::of_batch(moved, failed), report))
+}
+
+/// Handle the undo command
+pub fn handle_undo(args: UndoArgs, config: Config) -> Result<()> {
+    let journal_dir = Journal::default_dir()?;
+
+    if args.prune {
+        let days = match (args.older_than, config.processing.journal_retention_days) {
+            (Some(days), _) => days,
+            (None, 0) => bail!(
+                "processing.journal_retention_days is 0 (keep forever); pass --older-than DAYS to prune"
+            ),
+            (None, days) => days,
+        };
+        let report = JournalRun::prune(&journal_dir, days_duration(days))?;
+        say!(
+            "{} Pruned {} run(s) older than {} days, freeing {}",
+            style("✓").green(),
+            style(report.runs).green(),
+            days,
+            style(format_size(report.bytes)).cyan()
+        );
+        return Ok(());
+    }
+
+    if args.list {
+        let runs = JournalRun::list(&journal_dir)?;
+        if runs.is_empty() {
+            say!("{} No recorded runs", style("ℹ").blue());
+            return Ok(());
+        }
+        for run in &runs {
+            say!(
+                "  {} {}  {} change(s)  {}",
+                if run.undone { style("○").dim() } else { style("●").green() },
+                style(&run.header.run_id).cyan(),
+                run.entries.len(),
+                style(&run.header.command).dim()
+            );
+        }
+        return Ok(());
+    }
+
+    let run = match &args.run {
+        Some(id) => JournalRun::load(&journal_dir, id)?,
+        None => JournalRun::latest(&journal_dir)?.context("No run left to undo")?,
+    };
+
+    say!(
+        "{} Run {} ({})",
+        style("→").cyan(),
+        style(&run.header.run_id).cyan(),
+        run.header.started_at
+    );
+    say!("  {}", style(&run.header.command).dim());
+
+    if args.dry_run {
+        for entry in run.entries.iter().rev() {
+            match entry.op.conflict() {
+                Some(conflict) => say!("  {} {}", style("✗").red(), conflict),
+                None => say!("  {} undo {}", style("→").cyan(), entry.op.describe()),
+            }
+        }
+        say!("
+{} DRY RUN MODE - No changes were made", style("ℹ").blue());
+        return Ok(());
+    }
+
+    let report = run.undo(args.force)?;
+    for skipped in &report.skipped {
+        say!("  {} Skipped: {}", style("⚠").yellow(), skipped);
+    }
+    say!(
+        "{} Reverted {} change(s){}",
+        style("✓").green(),
+        style(report.reverted).green(),
+        if report.skipped.is_empty() {
+            String::new()
+        } else {
+            format!(", {} skipped", report.skipped.len())
+        }
+    );
+
     Ok(())
 }
 
 /// Handle the config command
-pub fn handle_config(command: ConfigCommands) -> Result<()> {
+pub fn handle_config(command: ConfigCommands, layered: &LayeredConfig) -> Result<CommandReport> {
     match command {
         ConfigCommands::Init { force } => {
             let config_path = ConfigManager::default_config_path()?;
 
             if config_path.exists() && !force {
-                println!(
+                say!(
                     "{} Configuration file already exists: {}",
                     style("✗").red(),
                     style(config_path.display()).yellow()
                 );
-                println!("Use --force to overwrite");
-                return Ok(());
+                say!("Use --force to overwrite");
+                return Ok(CommandReport::empty());
             }
 
             // Create config directory if needed
@@ -689,29 +1412,170 @@ pub fn handle_config(command: ConfigCommands) -> Result<()> {
             let config = Config::default();
             ConfigManager::save(&config, Some(&config_path))?;
 
-            println!(
+            say!(
                 "{} Configuration file created: {}",
                 style("✓").green(),
                 style(config_path.display()).yellow()
             );
         }
 
-        ConfigCommands::Show { config: _ } => {
-            let config_path = ConfigManager::default_config_path()?;
-            let config = ConfigManager::load(&config_path)?;
-            let yaml = serde_yaml::to_string(&config)?;
-            println!("{}", yaml);
+        ConfigCommands::Show { config: _, profile, origin } => {
+            let config = &layered.config;
+            if origin {
+                for file in &layered.files {
+                    say!("# {}", file);
+                }
+                let mut settings = Vec::new();
+                for (key, value, origin) in layered.entries() {
+                    say!("{} = {}  {}", key, value, style(format!("[{}]", origin)).dim());
+                    settings.push(serde_json::json!({
+                        "key": key,
+                        "value": value,
+                        "origin": origin.to_string(),
+                    }));
+                }
+                let files: Vec<String> = layered.files.iter().map(ToString::to_string).collect();
+                return Ok(CommandReport::new(
+                    Outcome::Success,
+                    serde_json::json!({ "files": files, "settings": settings }),
+                ));
+            }
+
+            let (yaml, shown) = match profile {
+                Some(name) => {
+                    let profile = config.profile(&name)?;
+                    (serde_yaml::to_string(&profile)?, serde_json::to_value(&profile)?)
+                }
+                None => (serde_yaml::to_string(config)?, serde_json::to_value(config)?),
+            };
+            say!("{}", yaml);
+            return Ok(CommandReport::new(Outcome::Success, shown));
+        }
+
+        ConfigCommands::Schema { output } => {
+            let schema = serde_json::to_string_pretty(&config_schema())?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, schema + "\n")
+                        .with_context(|| format!("Failed to write schema: {}", path.display()))?;
+                    say!(
+                        "{} Schema written to {}",
+                        style("✓").green(),
+                        style(path.display()).yellow()
+                    );
+                }
+                None => say!("{}", schema),
+            }
+        }
+
+        ConfigCommands::Migrate { config, dry_run, yes } => {
+            let config_path = match config {
+                Some(path) => path,
+                None => ConfigManager::default_config_path()?,
+            };
+            if !config_path.is_file() {
+                bail!("Config file not found: {}", config_path.display());
+            }
+
+            let contents = std::fs::read_to_string(&config_path)?;
+            let mut value: serde_yaml::Value = serde_yaml::from_str(&contents)
+                .with_context(|| format!("Failed to parse config file: {}", config_path.display()))?;
+            let before = serde_yaml::to_string(&value)?;
+            let report = migrate_config(&mut value)?;
+
+            if !report.is_outdated() {
+                say!(
+                    "{} {} is already at version {}",
+                    style("✓").green(),
+                    style(config_path.display()).yellow(),
+                    report.to
+                );
+                return Ok(CommandReport::empty());
+            }
+
+            // Make sure the result loads before offering to write it
+            serde_yaml::from_value::<Config>(value.clone())
+                .with_context(|| format!("Migrated config is invalid: {}", config_path.display()))?;
+            let after = serde_yaml::to_string(&value)?;
+
+            say!(
+                "{} Migrating {} from version {} to {}:",
+                style("→").cyan(),
+                style(config_path.display()).yellow(),
+                report.from,
+                report.to
+            );
+            for change in &report.changes {
+                say!("  • {}", change);
+            }
+            say!();
+            for line in line_diff(&before, &after) {
+                match line.chars().next() {
+                    Some('+') => say!("{}", style(line).green()),
+                    Some('-') => say!("{}", style(line).red()),
+                    _ => say!("{}", style(line).dim()),
+                }
+            }
+            say!();
+            say!(
+                "{} Comments are not preserved; the original is kept as a .bak file",
+                style("ℹ").blue()
+            );
+
+            if dry_run {
+                say!("{} Dry run - file not changed", style("ℹ").blue());
+                return Ok(CommandReport::empty());
+            }
+            if !yes && !inquire::Confirm::new("Rewrite the config file?").with_default(true).prompt()? {
+                say!("{} Migration cancelled", style("○").dim());
+                return Ok(CommandReport::empty());
+            }
+
+            let mut backup = config_path.clone().into_os_string();
+            backup.push(".bak");
+            let backup = PathBuf::from(backup);
+            std::fs::copy(&config_path, &backup)
+                .with_context(|| format!("Failed to back up config file to {}", backup.display()))?;
+            std::fs::write(&config_path, &after)
+                .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
+
+            say!(
+                "{} Config file migrated (backup: {})",
+                style("✓").green(),
+                style(backup.display()).yellow()
+            );
         }
 
         ConfigCommands::Path => {
             let config_path = ConfigManager::default_config_path()?;
-            println!("{}", config_path.display());
+            say!("{}", config_path.display());
         }
 
-        ConfigCommands::Validate { config: _ } => {
-            let config_path = ConfigManager::default_config_path()?;
-            ConfigManager::load(&config_path)?;
-            println!(
+        ConfigCommands::Validate { config: _, strict } => {
+            // Loading already parsed every layer; check the resolved values
+            let warnings = ConfigManager::validate(&layered.config)?;
+            for warning in layered.warnings.iter().chain(&warnings) {
+                say!("{} {}", style("⚠").yellow(), warning);
+            }
+
+            if strict {
+                let mut problems = layered.warnings.len() + warnings.len();
+                for file in &layered.files {
+                    let (ConfigOrigin::System(path) | ConfigOrigin::User(path) | ConfigOrigin::Project(path)) = file else {
+                        continue;
+                    };
+                    let contents = std::fs::read_to_string(path)?;
+                    for unknown in find_unknown_keys(&contents)? {
+                        say!("{} {}: {}", style("✗").red(), path.display(), unknown);
+                        problems += 1;
+                    }
+                }
+                if problems > 0 {
+                    return Err(ForgeError::Config(format!("Configuration has {} problem(s)", problems)).into());
+                }
+            }
+
+            say!(
                 "{} Configuration is valid",
                 style("✓").green()
             );
@@ -719,94 +1583,350 @@ pub fn handle_config(command: ConfigCommands) -> Result<()> {
 
         ConfigCommands::Edit => {
             let config_path = ConfigManager::default_config_path()?;
-            println!("{} Opening editor for: {}", style("→").cyan(), style(config_path.display()).yellow());
+            say!("{} Opening editor for: {}", style("→").cyan(), style(config_path.display()).yellow());
             // TODO: Implement editor opening
-            println!("{} Editor integration not yet implemented", style("ℹ").blue());
+            say!("{} Editor integration not yet implemented", style("ℹ").blue());
+        }
+    }
+
+    Ok(CommandReport::empty())
+}
+
+/// Handle the cache command
+pub fn handle_cache(command: CacheCommands, config: Config) -> Result<()> {
+    let cache = AudibleCache::from_config(&config.metadata.audible)?;
+
+    match command {
+        CacheCommands::Stats => {
+            let stats = cache.stats()?;
+            let ttls = cache.ttls();
+
+            say!(
+                "{} Cache directory: {}",
+                style("→").cyan(),
+                style(cache.cache_dir().display()).yellow()
+            );
+            for kind in &stats.kinds {
+                say!(
+                    "  {:<9} {:>6} file(s) {:>10.2} MB  (TTL {}h, {} expired)",
+                    kind.kind.to_string(),
+                    kind.file_count,
+                    kind.total_size_bytes as f64 / (1024.0 * 1024.0),
+                    ttls.get(kind.kind).as_secs() / 3600,
+                    kind.expired_count
+                );
+            }
+            say!(
+                "{} Total: {} file(s), {:.2} MB",
+                style("✓").green(),
+                stats.file_count,
+                stats.size_mb()
+            );
+        }
+
+        CacheCommands::Clear { kind } => {
+            if let Some(kind) = kind {
+                let kind = CacheKind::from_str(&kind)?;
+                let removed = cache.clear_kind(kind)?;
+                say!("{} Cleared {} {} entr{}", style("✓").green(), removed, kind, if removed == 1 { "y" } else { "ies" });
+            } else {
+                let stats = cache.stats()?;
+                cache.clear_all()?;
+                say!("{} Cleared {} cache entr{}", style("✓").green(), stats.file_count, if stats.file_count == 1 { "y" } else { "ies" });
+            }
+        }
+
+        CacheCommands::Prune => {
+            let removed = cache.prune()?;
+            say!("{} Pruned {} expired entr{}", style("✓").green(), removed, if removed == 1 { "y" } else { "ies" });
+        }
+
+        CacheCommands::Export { dest, bundle } => {
+            let copied = if bundle {
+                cache.export_bundle(&dest)?
+            } else {
+                cache.export_to(&dest)?
+            };
+            say!(
+                "{} Exported {} entr{} to {}",
+                style("✓").green(),
+                copied,
+                if copied == 1 { "y" } else { "ies" },
+                style(dest.display()).yellow()
+            );
+        }
+
+        CacheCommands::Import { src } => {
+            let imported = if src.is_file() {
+                cache.import_bundle(&src)?
+            } else {
+                cache.import_from(&src)?
+            };
+            say!(
+                "{} Imported {} entr{} from {}",
+                style("✓").green(),
+                imported,
+                if imported == 1 { "y" } else { "ies" },
+                style(src.display()).yellow()
+            );
         }
     }
 
     Ok(())
 }
 
-/// Handle the check command
-pub fn handle_check() -> Result<()> {
-    println!("{} Checking system dependencies...\n", style("→").cyan());
+/// `--json` result of the bench command
+#[derive(Debug, Serialize)]
+struct BenchReport {
+    #[serde(flatten)]
+    result: BenchResult,
+    best_encoder: Option<String>,
+    max_concurrent_encodes: usize,
+    max_concurrent_files_per_book: usize,
+    /// Where the results were cached (absent with `--no-save`)
+    saved_to: Option<PathBuf>,
+}
 
-    let results = vec![
-        ("FFmpeg", DependencyChecker::check_ffmpeg().found),
-        ("AtomicParsley", DependencyChecker::check_atomic_parsley().found),
-        ("MP4Box", DependencyChecker::check_mp4box().found),
-    ];
+/// Handle the bench command
+pub async fn handle_bench(args: BenchArgs) -> Result<CommandReport> {
+    let mut benchmark = EncoderBenchmark {
+        sample_secs: args.duration,
+        ..Default::default()
+    };
+    if let Some(max) = args.max_concurrency {
+        benchmark.max_concurrency = max as usize;
+    }
+
+    say!(
+        "{} Benchmarking AAC encoders ({}s sample, up to {} parallel encodes)...\n",
+        style("→").cyan(),
+        benchmark.sample_secs,
+        benchmark.max_concurrency
+    );
+    let result = benchmark.run().await?;
+
+    let best = result.best().map(|(bench, run)| (bench.encoder.clone(), run.concurrency));
+    for bench in &result.encoders {
+        say!("  {}", style(&bench.encoder).cyan());
+        for run in &bench.runs {
+            let fastest = best.as_ref() == Some(&(bench.encoder.clone(), run.concurrency));
+            say!(
+                "    {:>2} parallel: {:>7.1}x realtime in {:.1}s{}",
+                run.concurrency,
+                run.speed,
+                run.wall_secs,
+                if fastest { format!(" {}", style("(fastest)").green()) } else { String::new() }
+            );
+        }
+    }
+
+    let (encodes, files) = result.concurrency_split();
+    say!();
+    if let Some((encoder, concurrency)) = &best {
+        say!(
+            "{} Fastest: {} with {} parallel encodes ({} books x {} files)",
+            style("✓").green(),
+            style(encoder).green(),
+            concurrency,
+            encodes,
+            files
+        );
+    }
 
-    let all_found = results.iter().all(|(_, found)| *found);
+    let saved_to = if args.no_save {
+        None
+    } else {
+        let path = BenchResult::cache_path()?;
+        result.save(&path)?;
+        say!("{} Saved to {}", style("✓").green(), path.display());
+        say!("  Used when advanced.aac_encoder or the performance concurrency settings are \"auto\"");
+        Some(path)
+    };
 
-    for (tool, found) in &results {
-        if *found {
-            println!("  {} {}", style("✓").green(), style(tool).cyan());
+    Ok(CommandReport::new(
+        Outcome::Success,
+        BenchReport {
+            best_encoder: best.map(|(encoder, _)| encoder),
+            max_concurrent_encodes: encodes,
+            max_concurrent_files_per_book: files,
+            result,
+            saved_to,
+        },
+    ))
+}
 
-            // Show encoder information for FFmpeg
-            if *tool == "FFmpeg" {
-                let available_encoders = DependencyChecker::get_available_encoders();
-                let selected_encoder = DependencyChecker::get_selected_encoder();
+/// `--json` result of the check command
+#[derive(Debug, Default, Serialize)]
+struct CheckReport {
+    tools: Vec<ToolReport>,
+    aac_encoders: Vec<String>,
+    selected_encoder: Option<String>,
+    /// FFmpeg filters, and whether each is available
+    filters: Vec<FilterReport>,
+    problems: usize,
+}
 
-                if !available_encoders.is_empty() {
-                    print!("    AAC Encoders: ");
-                    for (i, encoder) in available_encoders.iter().enumerate() {
-                        if i > 0 {
-                            print!(", ");
-                        }
+/// Status of one external tool
+#[derive(Debug, Serialize)]
+struct ToolReport {
+    name: String,
+    found: bool,
+    version: Option<String>,
+    path: Option<String>,
+    /// Whether the path comes from the config rather than PATH
+    configured: bool,
+    problem: Option<String>,
+}
+
+/// Availability of an FFmpeg filter and the feature needing it
+#[derive(Debug, Serialize)]
+struct FilterReport {
+    name: String,
+    feature: String,
+    available: bool,
+}
+
+/// Handle the check command
+///
+/// Missing or unusable tools fail the command, after every tool is reported.
+pub fn handle_check() -> CommandReport {
+    say!("{} Checking system dependencies...\n", style("→").cyan());
+
+    let registry = crate::audio::tools();
+    let results: Vec<_> = Tool::ALL.iter().map(|&tool| DependencyChecker::check(tool)).collect();
+    let mut report = CheckReport::default();
+
+    for (tool, status) in Tool::ALL.iter().zip(&results) {
+        let configured = registry.info(*tool).configured;
+        report.tools.push(ToolReport {
+            name: tool.to_string(),
+            found: status.found,
+            version: status.version.clone(),
+            path: status.path.clone(),
+            configured,
+            problem: status.problem.clone(),
+        });
+
+        if !status.found {
+            say!("  {} {} (not found)", style("✗").red(), style(tool).yellow());
+            if let Some(problem) = &status.problem {
+                say!("    {}", style(problem).dim());
+            }
+            continue;
+        }
+
+        let mark = if status.problem.is_some() { style("✗").red() } else { style("✓").green() };
+        let version = status.version.as_deref().unwrap_or("unknown version");
+        say!("  {} {} {}", mark, style(tool).cyan(), style(version).dim());
+        let source = if configured { "from config" } else { "from PATH" };
+        say!(
+            "    Path: {} {}",
+            status.path.as_deref().unwrap_or_default(),
+            style(format!("({})", source)).dim()
+        );
+        if let Some(problem) = &status.problem {
+            say!("    {}", style(problem).red());
+        }
+
+        // Show encoder and filter information for FFmpeg
+        if *tool == Tool::FFmpeg {
+            let available_encoders = DependencyChecker::get_available_encoders();
+            let selected_encoder = DependencyChecker::get_selected_encoder();
+
+            if !available_encoders.is_empty() {
+                let listed: Vec<String> = available_encoders
+                    .iter()
+                    .map(|encoder| {
                         if *encoder == selected_encoder {
-                            print!("{} {}", style(encoder).green(), style("(selected)").dim());
+                            format!("{} {}", style(encoder).green(), style("(selected)").dim())
                         } else {
-                            print!("{}", style(encoder).dim());
+                            style(encoder).dim().to_string()
                         }
-                    }
-                    println!();
+                    })
+                    .collect();
+                say!("    AAC Encoders: {}", listed.join(", "));
+            }
+
+            for (filter, feature) in FEATURE_FILTERS {
+                let available = registry.has_filter(filter);
+                if available {
+                    say!("    Filter {} {}", style(filter).green(), style(format!("({})", feature)).dim());
+                } else {
+                    say!(
+                        "    Filter {} missing - {} unavailable",
+                        style(filter).yellow(),
+                        feature
+                    );
                 }
+                report.filters.push(FilterReport {
+                    name: filter.to_string(),
+                    feature: feature.to_string(),
+                    available,
+                });
             }
-        } else {
-            println!("  {} {} (not found)", style("✗").red(), style(tool).yellow());
+
+            report.aac_encoders = available_encoders;
+            report.selected_encoder = Some(selected_encoder);
         }
     }
 
-    println!();
-    if all_found {
-        println!("{} All dependencies found", style("✓").green());
-    } else {
-        println!("{} Some dependencies are missing", style("✗").red());
-        println!("\nInstall missing dependencies:");
-        println!("  macOS:   brew install ffmpeg atomicparsley gpac");
-        println!("  Ubuntu:  apt install ffmpeg atomicparsley gpac");
+    let problems = results.iter().filter(|s| s.problem.is_some()).count();
+    report.problems = problems;
+
+    say!();
+    if problems == 0 {
+        say!("{} All dependencies found", style("✓").green());
+        return CommandReport::new(Outcome::Success, report);
     }
 
-    Ok(())
+    say!("{} Some dependencies are missing or too old", style("✗").red());
+    say!("\nInstall missing dependencies:");
+    say!("  macOS:   brew install ffmpeg atomicparsley gpac");
+    say!("  Ubuntu:  apt install ffmpeg atomicparsley gpac");
+    say!("Or point advanced.ffmpeg_path / ffprobe_path / atomic_parsley_path / mp4box_path at them.");
+    let tool = results
+        .iter()
+        .zip(Tool::ALL)
+        .find(|(status, _)| status.problem.is_some())
+        .map(|(_, tool)| tool)
+        .unwrap_or(Tool::FFmpeg);
+    CommandReport::new(Outcome::Success, report).with_error(
+        ForgeError::ToolUnavailable {
+            tool,
+            message: format!("{} dependency problem(s) found", problems),
+        }
+        .into(),
+    )
 }
 
 /// Handle the metadata command
-pub async fn handle_metadata(command: MetadataCommands, config: Config) -> Result<()> {
+pub async fn handle_metadata(command: MetadataCommands, config: Config) -> Result<CommandReport> {
     match command {
         MetadataCommands::Fetch { asin, title, author, region, output } => {
-            println!("{} Fetching Audible metadata...", style("→").cyan());
+            say!("{} Fetching Audible metadata...", style("→").cyan());
 
             // Parse region
             let audible_region = AudibleRegion::from_str(&region)
                 .unwrap_or(AudibleRegion::US);
 
             // Create client and cache
+            let cache = AudibleCache::from_config(&config.metadata.audible)?;
             let client = AudibleClient::with_rate_limit(
                 audible_region,
                 config.metadata.audible.rate_limit_per_minute
-            )?;
-            let cache = AudibleCache::with_ttl_hours(config.metadata.audible.cache_duration_hours)?;
+            )?
+            .with_cache(cache.clone())
+            .with_offline(config.metadata.audible.offline);
 
             // Fetch metadata
+            let mut search_results = Vec::new();
             let metadata = if let Some(asin_val) = asin {
                 // Direct ASIN lookup
-                println!("  {} Looking up ASIN: {}", style("→").cyan(), asin_val);
+                say!("  {} Looking up ASIN: {}", style("→").cyan(), asin_val);
 
                 // Try cache first
                 if let Some(cached) = cache.get(&asin_val).await {
-                    println!("  {} Using cached metadata", style("✓").green());
+                    say!("  {} Using cached metadata", style("✓").green());
                     cached
                 } else {
                     let fetched = client.fetch_by_asin(&asin_val).await?;
@@ -815,7 +1935,7 @@ pub async fn handle_metadata(command: MetadataCommands, config: Config) -> Resul
                 }
             } else if title.is_some() || author.is_some() {
                 // Search by title/author
-                println!("  {} Searching: title={:?}, author={:?}",
+                say!("  {} Searching: title={:?}, author={:?}",
                     style("→").cyan(), title, author);
 
                 let results = client.search(title.as_deref(), author.as_deref()).await?;
@@ -825,9 +1945,9 @@ pub async fn handle_metadata(command: MetadataCommands, config: Config) -> Resul
                 }
 
                 // Display search results
-                println!("\n{} Found {} result(s):", style("✓").green(), results.len());
+                say!("\n{} Found {} result(s):", style("✓").green(), results.len());
                 for (i, result) in results.iter().enumerate().take(5) {
-                    println!("  {}. {} by {}",
+                    say!("  {}. {} by {}",
                         i + 1,
                         style(&result.title).yellow(),
                         style(result.authors_string()).cyan()
@@ -835,8 +1955,16 @@ pub async fn handle_metadata(command: MetadataCommands, config: Config) -> Resul
                 }
 
                 // Fetch first result
-                println!("\n{} Fetching details for first result...", style("→").cyan());
+                say!("\n{} Fetching details for first result...", style("→").cyan());
                 let asin_to_fetch = &results[0].asin;
+                search_results = results
+                    .iter()
+                    .map(|r| serde_json::json!({
+                        "asin": r.asin,
+                        "title": r.title,
+                        "authors": r.authors_string(),
+                    }))
+                    .collect();
 
                 if let Some(cached) = cache.get(asin_to_fetch).await {
                     cached
@@ -850,33 +1978,33 @@ pub async fn handle_metadata(command: MetadataCommands, config: Config) -> Resul
             };
 
             // Display metadata
-            println!("\n{}", style("=".repeat(60)).dim());
-            println!("{}: {}", style("Title").bold(), metadata.title);
+            say!("\n{}", style("=".repeat(60)).dim());
+            say!("{}: {}", style("Title").bold(), metadata.title);
             if let Some(subtitle) = &metadata.subtitle {
-                println!("{}: {}", style("Subtitle").bold(), subtitle);
+                say!("{}: {}", style("Subtitle").bold(), subtitle);
             }
             if !metadata.authors.is_empty() {
-                println!("{}: {}", style("Author(s)").bold(), metadata.authors_string());
+                say!("{}: {}", style("Author(s)").bold(), metadata.authors_string());
             }
             if !metadata.narrators.is_empty() {
-                println!("{}: {}", style("Narrator(s)").bold(), metadata.narrators_string());
+                say!("{}: {}", style("Narrator(s)").bold(), metadata.narrators_string());
             }
             if let Some(publisher) = &metadata.publisher {
-                println!("{}: {}", style("Publisher").bold(), publisher);
+                say!("{}: {}", style("Publisher").bold(), publisher);
             }
             if let Some(year) = metadata.published_year {
-                println!("{}: {}", style("Published").bold(), year);
+                say!("{}: {}", style("Published").bold(), year);
             }
             if let Some(duration_min) = metadata.runtime_minutes() {
                 let hours = duration_min / 60;
                 let mins = duration_min % 60;
-                println!("{}: {}h {}m", style("Duration").bold(), hours, mins);
+                say!("{}: {}h {}m", style("Duration").bold(), hours, mins);
             }
             if let Some(lang) = &metadata.language {
-                println!("{}: {}", style("Language").bold(), lang);
+                say!("{}: {}", style("Language").bold(), lang);
             }
             if !metadata.genres.is_empty() {
-                println!("{}: {}", style("Genres").bold(), metadata.genres.join(", "));
+                say!("{}: {}", style("Genres").bold(), metadata.genres.join(", "));
             }
             if !metadata.series.is_empty() {
                 for series in &metadata.series {
@@ -885,23 +2013,30 @@ pub async fn handle_metadata(command: MetadataCommands, config: Config) -> Resul
                     } else {
                         String::new()
                     };
-                    println!("{}: {}{}", style("Series").bold(), series.name, seq_info);
+                    say!("{}: {}{}", style("Series").bold(), series.name, seq_info);
                 }
             }
-            println!("{}: {}", style("ASIN").bold(), metadata.asin);
-            println!("{}", style("=".repeat(60)).dim());
+            say!("{}: {}", style("ASIN").bold(), metadata.asin);
+            say!("{}", style("=".repeat(60)).dim());
 
             // Save to file if requested
-            if let Some(output_path) = output {
+            if let Some(output_path) = &output {
                 let json = serde_json::to_string_pretty(&metadata)?;
-                std::fs::write(&output_path, json)?;
-                println!("\n{} Saved metadata to: {}",
+                std::fs::write(output_path, json)?;
+                say!("\n{} Saved metadata to: {}",
                     style("✓").green(),
                     style(output_path.display()).yellow()
                 );
             }
 
-            Ok(())
+            Ok(CommandReport::new(
+                Outcome::Success,
+                serde_json::json!({
+                    "metadata": metadata,
+                    "search_results": search_results,
+                    "saved_to": output,
+                }),
+            ))
         }
 
         MetadataCommands::Enrich {
@@ -922,7 +2057,7 @@ pub async fn handle_metadata(command: MetadataCommands, config: Config) -> Resul
             } else {
                 "Enriching M4B file with Audible metadata"
             };
-            println!("{} {}...", style("→").cyan(), action);
+            say!("{} {}...", style("→").cyan(), action);
 
             if !file.exists() {
                 bail!("File does not exist: {}", file.display());
@@ -939,48 +2074,50 @@ pub async fn handle_metadata(command: MetadataCommands, config: Config) -> Resul
 
             // Handle chapter update if requested
             let chapter_update_performed = if chapters.is_some() || chapters_asin.is_some() {
-                println!("  {} Reading existing chapters from M4B...", style("→").cyan());
+                say!("  {} Reading existing chapters from M4B...", style("→").cyan());
                 let existing_chapters = read_m4b_chapters(&file).await?;
-                println!("  {} Found {} existing chapters", style("✓").green(), existing_chapters.len());
+                say!("  {} Found {} existing chapters", style("✓").green(), existing_chapters.len());
 
                 // Fetch new chapters based on source
                 let new_chapters = if let Some(chapters_file) = chapters {
-                    println!("  {} Parsing chapters from file...", style("→").cyan());
+                    say!("  {} Parsing chapters from file...", style("→").cyan());
                     if chapters_file.extension().and_then(|s| s.to_str()) == Some("epub") {
                         parse_epub_chapters(&chapters_file)?
                     } else {
                         parse_text_chapters(&chapters_file)?
                     }
                 } else if let Some(asin_val) = chapters_asin {
-                    println!("  {} Fetching chapters from Audnex API...", style("→").cyan());
+                    say!("  {} Fetching chapters from Audnex API...", style("→").cyan());
                     let audible_region = AudibleRegion::from_str(&region).unwrap_or(AudibleRegion::US);
                     let client = crate::audio::AudibleClient::with_rate_limit(
                         audible_region,
                         config.metadata.audible.rate_limit_per_minute
-                    )?;
+                    )?
+                    .with_cache(AudibleCache::from_config(&config.metadata.audible)?)
+                    .with_offline(config.metadata.audible.offline);
                     let audible_chapters = client.fetch_chapters(&asin_val).await?;
                     audible_chapters.into_iter().enumerate().map(|(i, ch)| ch.to_chapter((i + 1) as u32)).collect()
                 } else {
                     vec![]
                 };
 
-                println!("  {} Loaded {} new chapters", style("✓").green(), new_chapters.len());
+                say!("  {} Loaded {} new chapters", style("✓").green(), new_chapters.len());
 
                 // Merge chapters
-                println!("  {} Merging chapters (strategy: {})...", style("→").cyan(), merge_strategy);
+                say!("  {} Merging chapters (strategy: {})...", style("→").cyan(), merge_strategy);
                 let merged = merge_chapters(&existing_chapters, &new_chapters, strategy)?;
-                println!("  {} Merged into {} chapters", style("✓").green(), merged.len());
+                say!("  {} Merged into {} chapters", style("✓").green(), merged.len());
 
                 // Write chapters to temp file
                 let temp_chapters = std::env::temp_dir().join(format!("chapters_{}.txt", file.file_stem().unwrap().to_string_lossy()));
                 write_mp4box_chapters(&merged, &temp_chapters)?;
 
                 // Inject chapters back into M4B
-                println!("  {} Injecting chapters into M4B...", style("→").cyan());
+                say!("  {} Injecting chapters into M4B...", style("→").cyan());
                 inject_chapters_mp4box(&file, &temp_chapters).await?;
                 std::fs::remove_file(&temp_chapters)?;
 
-                println!("  {} Chapters updated successfully", style("✓").green());
+                say!("  {} Chapters updated successfully", style("✓").green());
                 true
             } else {
                 false
@@ -991,11 +2128,11 @@ pub async fn handle_metadata(command: MetadataCommands, config: Config) -> Resul
                 if !chapter_update_performed {
                     bail!("--update-chapters-only specified but no chapter source provided (use --chapters or --chapters-asin)");
                 }
-                println!("\n{} Successfully updated chapters: {}",
+                say!("\n{} Successfully updated chapters: {}",
                     style("✓").green(),
                     style(file.display()).yellow()
                 );
-                return Ok(());
+                return Ok(CommandReport::empty());
             }
 
             // Detect or use provided ASIN
@@ -1008,39 +2145,41 @@ pub async fn handle_metadata(command: MetadataCommands, config: Config) -> Resul
                 bail!("Must provide --asin or use --auto-detect");
             };
 
-            println!("  {} Using ASIN: {}", style("→").cyan(), asin_to_use);
+            say!("  {} Using ASIN: {}", style("→").cyan(), asin_to_use);
 
             // Parse region
             let audible_region = AudibleRegion::from_str(&region)
                 .unwrap_or(AudibleRegion::US);
 
             // Create client and cache
+            let cache = AudibleCache::from_config(&config.metadata.audible)?;
             let client = AudibleClient::with_rate_limit(
                 audible_region,
                 config.metadata.audible.rate_limit_per_minute
-            )?;
-            let cache = AudibleCache::with_ttl_hours(config.metadata.audible.cache_duration_hours)?;
+            )?
+            .with_cache(cache.clone())
+            .with_offline(config.metadata.audible.offline);
 
             // Fetch metadata
             let metadata = if let Some(cached) = cache.get(&asin_to_use).await {
-                println!("  {} Using cached metadata", style("✓").green());
+                say!("  {} Using cached metadata", style("✓").green());
                 cached
             } else {
-                println!("  {} Fetching from Audible...", style("→").cyan());
+                say!("  {} Fetching from Audible...", style("→").cyan());
                 let fetched = client.fetch_by_asin(&asin_to_use).await?;
                 cache.set(&asin_to_use, &fetched).await?;
                 fetched
             };
 
-            println!("  {} Found: {}", style("✓").green(), metadata.title);
+            say!("  {} Found: {}", style("✓").green(), metadata.title);
 
             // Download cover if available and enabled
             let cover_path = if config.metadata.audible.download_covers {
                 if let Some(cover_url) = &metadata.cover_url {
-                    println!("  {} Downloading cover art...", style("→").cyan());
+                    say!("  {} Downloading cover art...", style("→").cyan());
                     let temp_cover = std::env::temp_dir().join(format!("{}.jpg", asin_to_use));
                     client.download_cover(cover_url, &temp_cover).await?;
-                    println!("  {} Cover downloaded", style("✓").green());
+                    say!("  {} Cover downloaded", style("✓").green());
                     Some(temp_cover)
                 } else {
                     None
@@ -1050,30 +2189,99 @@ pub async fn handle_metadata(command: MetadataCommands, config: Config) -> Resul
             };
 
             // Inject metadata (this will be implemented in metadata.rs)
-            println!("  {} Injecting metadata...", style("→").cyan());
+            say!("  {} Injecting metadata...", style("→").cyan());
             crate::audio::inject_audible_metadata(&file, &metadata, cover_path.as_deref()).await?;
 
-            println!("\n{} Successfully enriched: {}",
+            say!("\n{} Successfully enriched: {}",
                 style("✓").green(),
                 style(file.display()).yellow()
             );
 
-            Ok(())
+            Ok(CommandReport::empty())
+        }
+    }
+}
+
+/// `--json` result of the match command
+#[derive(Debug, Default, Serialize)]
+struct MatchReport {
+    dry_run: bool,
+    files: Vec<MatchedFile>,
+    processed: usize,
+    skipped: usize,
+    not_cached: usize,
+    failed: usize,
+    /// Review queue written with `--queue`
+    queue: Option<PathBuf>,
+}
+
+/// What match did (or would do) with one file
+#[derive(Debug, Serialize)]
+struct MatchedFile {
+    file: PathBuf,
+    status: MatchStatus,
+    asin: Option<String>,
+    title: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum MatchStatus {
+    Applied,
+    Queued,
+    Skipped,
+    NotCached,
+    Failed,
+}
+
+impl MatchReport {
+    fn new(args: &MatchArgs) -> Self {
+        Self {
+            dry_run: args.dry_run,
+            ..Default::default()
+        }
+    }
+
+    /// Record a file's outcome and count it
+    fn push(&mut self, file: &Path, status: MatchStatus, metadata: Option<&AudibleMetadata>, error: Option<String>) {
+        match status {
+            MatchStatus::Applied | MatchStatus::Queued => self.processed += 1,
+            MatchStatus::Skipped => self.skipped += 1,
+            MatchStatus::NotCached => self.not_cached += 1,
+            MatchStatus::Failed => self.failed += 1,
         }
+        self.files.push(MatchedFile {
+            file: file.to_path_buf(),
+            status,
+            asin: metadata.map(|m| m.asin.clone()),
+            title: metadata.map(|m| m.title.clone()),
+            error,
+        });
+    }
+
+    fn into_command_report(self) -> CommandReport {
+        let outcome = Outcome::of_batch(self.processed + self.skipped, self.failed + self.not_cached);
+        CommandReport::new(outcome, self)
     }
 }
 
 /// Handle the match command
-pub async fn handle_match(args: MatchArgs, config: Config) -> Result<()> {
+pub async fn handle_match(args: MatchArgs, config: Config) -> Result<CommandReport> {
+    // Applying a reviewed queue doesn't need a search
+    if let Some(queue_path) = &args.apply_queue {
+        return apply_review_queue(queue_path, &args, &config).await;
+    }
+
     // Determine files to process
     let files = get_files_to_process(&args)?;
 
     if files.is_empty() {
-        println!("{} No M4B files found", style("✗").red());
-        return Ok(());
+        say!("{} No M4B files found", style("✗").red());
+        return Ok(CommandReport::new(Outcome::NothingToDo, MatchReport::new(&args)));
     }
 
-    println!(
+    say!(
         "{} Found {} M4B file(s)",
         style("✓").green(),
         style(files.len()).cyan()
@@ -1087,22 +2295,25 @@ pub async fn handle_match(args: MatchArgs, config: Config) -> Result<()> {
         std::time::Duration::from_secs(config.metadata.audible.api_max_retry_delay_secs),
         2.0,
     );
+    let cache = AudibleCache::from_config(&config.metadata.audible)?;
     let client = AudibleClient::with_config(
         region,
         config.metadata.audible.rate_limit_per_minute,
         retry_config,
-    )?;
-    let cache = AudibleCache::with_ttl_hours(
-        config.metadata.audible.cache_duration_hours
-    )?;
+    )?
+    .with_cache(cache.clone())
+    .with_offline(config.metadata.audible.offline);
+
+    // Queue mode: collect candidates for offline review
+    if let Some(queue_path) = &args.queue {
+        return write_review_queue(queue_path, &files, &args, &client).await;
+    }
 
     // Process each file
-    let mut processed = 0;
-    let mut skipped = 0;
-    let mut failed = 0;
+    let mut report = MatchReport::new(&args);
 
     for (idx, file_path) in files.iter().enumerate() {
-        println!(
+        say!(
             "\n{} [{}/{}] Processing: {}",
             style("→").cyan(),
             idx + 1,
@@ -1111,29 +2322,178 @@ pub async fn handle_match(args: MatchArgs, config: Config) -> Result<()> {
         );
 
         match process_single_file(&file_path, &args, &client, &cache, &config).await {
-            Ok(ProcessResult::Applied) => processed += 1,
-            Ok(ProcessResult::Skipped) => skipped += 1,
+            Ok(ProcessResult::Applied(metadata)) => {
+                report.push(file_path, MatchStatus::Applied, Some(&metadata), None)
+            }
+            Ok(ProcessResult::Skipped) => report.push(file_path, MatchStatus::Skipped, None, None),
+            Err(e) if AudibleApiError::is_not_cached(&e) => {
+                say!("{} {}", style("○").dim(), e);
+                report.push(file_path, MatchStatus::NotCached, None, Some(format!("{:#}", e)));
+            }
             Err(e) => {
                 eprintln!("{} Error: {}", style("✗").red(), e);
-                failed += 1;
+                report.push(file_path, MatchStatus::Failed, None, Some(format!("{:#}", e)));
             }
         }
     }
 
     // Summary
-    println!("\n{}", style("Summary:").bold().cyan());
-    println!("  {} Processed: {}", style("✓").green(), processed);
-    println!("  {} Skipped: {}", style("→").yellow(), skipped);
-    if failed > 0 {
-        println!("  {} Failed: {}", style("✗").red(), failed);
+    say!("\n{}", style("Summary:").bold().cyan());
+    say!("  {} Processed: {}", style("✓").green(), report.processed);
+    say!("  {} Skipped: {}", style("→").yellow(), report.skipped);
+    if report.not_cached > 0 {
+        say!("  {} Not cached (offline): {}", style("○").dim(), report.not_cached);
+    }
+    if report.failed > 0 {
+        say!("  {} Failed: {}", style("✗").red(), report.failed);
     }
 
-    Ok(())
+    Ok(report.into_command_report())
+}
+
+/// Search every file and write the scored candidates to a review queue
+async fn write_review_queue(
+    queue_path: &Path,
+    files: &[PathBuf],
+    args: &MatchArgs,
+    client: &AudibleClient,
+) -> Result<CommandReport> {
+    let mut queue = ReviewQueue::new(&args.region);
+    let mut report = MatchReport::new(args);
+    report.queue = Some(queue_path.to_path_buf());
+
+    for (idx, file_path) in files.iter().enumerate() {
+        say!(
+            "\n{} [{}/{}] Searching: {}",
+            style("→").cyan(),
+            idx + 1,
+            files.len(),
+            style(file_path.display()).yellow()
+        );
+
+        let current = match load_folder_overrides(file_path)
+            .and_then(|overrides| current_metadata_for(file_path, args, overrides.as_ref()))
+        {
+            Ok(current) => current,
+            Err(e) => {
+                eprintln!("{} Error: {}", style("✗").red(), e);
+                report.push(file_path, MatchStatus::Failed, None, Some(format!("{:#}", e)));
+                continue;
+            }
+        };
+
+        let candidates = match search_audible(&current, client).await {
+            Ok(results) => scoring::score_and_sort(&current, results),
+            Err(e) => {
+                eprintln!("{} Error: {}", style("✗").red(), e);
+                report.push(file_path, MatchStatus::Failed, None, Some(format!("{:#}", e)));
+                continue;
+            }
+        };
+
+        match candidates.first() {
+            Some(best) => say!(
+                "  {} {} candidate(s), best: {} ({:.1}%)",
+                style("✓").green(),
+                candidates.len(),
+                best.metadata.title,
+                (1.0 - best.distance.total_distance()) * 100.0
+            ),
+            None => say!("  {} No matches found on Audible", style("⚠").yellow()),
+        }
+        report.push(
+            file_path,
+            MatchStatus::Queued,
+            candidates.first().map(|best| &best.metadata),
+            None,
+        );
+
+        queue.entries.push(ReviewEntry::new(file_path.clone(), current, candidates));
+    }
+
+    queue.save(queue_path)?;
+
+    say!(
+        "\n{} Review queue written: {}",
+        style("✓").green(),
+        style(queue_path.display()).yellow()
+    );
+    say!("  {} Pre-accepted (strong): {}", style("✓").green(), queue.count(ReviewDecision::Accept));
+    say!("  {} Pending review: {}", style("→").yellow(), queue.count(ReviewDecision::Pending));
+    if report.failed > 0 {
+        say!("  {} Failed: {}", style("✗").red(), report.failed);
+    }
+    say!(
+        "\n{} Edit each entry's decision (accept/reject) and selected index, then run with --apply-queue",
+        style("ℹ").blue()
+    );
+
+    Ok(report.into_command_report())
+}
+
+/// Apply accepted entries from a reviewed queue
+async fn apply_review_queue(queue_path: &Path, args: &MatchArgs, config: &Config) -> Result<CommandReport> {
+    let queue = ReviewQueue::load(queue_path)?;
+
+    say!(
+        "{} Loaded review queue with {} entr{}",
+        style("✓").green(),
+        queue.entries.len(),
+        if queue.entries.len() == 1 { "y" } else { "ies" }
+    );
+
+    let mut report = MatchReport::new(args);
+    report.queue = Some(queue_path.to_path_buf());
+
+    for entry in &queue.entries {
+        let metadata = match entry.resolve() {
+            Ok(Some(metadata)) => metadata,
+            Ok(None) => {
+                report.push(&entry.file, MatchStatus::Skipped, None, None);
+                continue;
+            }
+            Err(e) => {
+                eprintln!("{} Error: {}", style("✗").red(), e);
+                report.push(&entry.file, MatchStatus::Failed, None, Some(format!("{:#}", e)));
+                continue;
+            }
+        };
+
+        say!(
+            "{} {} → {}",
+            style("→").cyan(),
+            style(entry.file.display()).yellow(),
+            metadata.title
+        );
+
+        if args.dry_run {
+            report.push(&entry.file, MatchStatus::Applied, Some(metadata), None);
+            continue;
+        }
+
+        match apply_metadata(&entry.file, metadata, args, config).await {
+            Ok(()) => report.push(&entry.file, MatchStatus::Applied, Some(metadata), None),
+            Err(e) => {
+                eprintln!("{} Error: {}", style("✗").red(), e);
+                report.push(&entry.file, MatchStatus::Failed, Some(metadata), Some(format!("{:#}", e)));
+            }
+        }
+    }
+
+    say!("\n{}", style("Summary:").bold().cyan());
+    say!("  {} Processed: {}", style("✓").green(), report.processed);
+    say!("  {} Skipped: {}", style("→").yellow(), report.skipped);
+    if report.failed > 0 {
+        say!("  {} Failed: {}", style("✗").red(), report.failed);
+    }
+
+    Ok(report.into_command_report())
 }
 
 /// Result of processing a single file
 enum ProcessResult {
-    Applied,
+    /// Metadata was applied (or would be, in a dry run)
+    Applied(Box<AudibleMetadata>),
     Skipped,
 }
 
@@ -1145,20 +2505,25 @@ async fn process_single_file(
     _cache: &AudibleCache,
     config: &Config,
 ) -> Result<ProcessResult> {
-    // Extract current metadata
-    let mut current = if args.title.is_some() || args.author.is_some() {
-        // Manual override
-        CurrentMetadata {
-            title: args.title.clone(),
-            author: args.author.clone(),
-            year: None,
-            duration: None,
-            source: MetadataSource::Manual,
+    // A folder override file can pin the ASIN or supply search terms
+    let overrides = load_folder_overrides(file_path)?;
+
+    if let Some(asin) = overrides.as_ref().and_then(|o| o.asin.as_deref()) {
+        let metadata = client.fetch_by_asin(asin).await?;
+        say!(
+            "  {} Pinned ASIN {}: {}",
+            style("✓").green(),
+            style(asin).cyan(),
+            metadata.title
+        );
+        if !args.dry_run {
+            apply_metadata(file_path, &metadata, args, config).await?;
         }
-    } else {
-        // Auto-extract
-        extraction::extract_current_metadata(file_path)?
-    };
+        return Ok(ProcessResult::Applied(Box::new(metadata)));
+    }
+
+    // Extract current metadata
+    let mut current = current_metadata_for(file_path, args, overrides.as_ref())?;
 
     // Search loop (allows re-search)
     loop {
@@ -1166,7 +2531,7 @@ async fn process_single_file(
         let search_results = search_audible(&current, client).await?;
 
         if search_results.is_empty() {
-            println!("{} No matches found on Audible", style("⚠").yellow());
+            say!("{} No matches found on Audible", style("⚠").yellow());
 
             if args.auto {
                 return Ok(ProcessResult::Skipped);
@@ -1177,7 +2542,7 @@ async fn process_single_file(
                 NoResultsAction::ManualEntry => {
                     let manual_metadata = prompt_manual_metadata()?;
                     apply_metadata(file_path, &manual_metadata, args, config).await?;
-                    return Ok(ProcessResult::Applied);
+                    return Ok(ProcessResult::Applied(Box::new(manual_metadata)));
                 }
                 NoResultsAction::CustomSearch => {
                     let (title, author) = prompt_custom_search()?;
@@ -1198,7 +2563,7 @@ async fn process_single_file(
         // Auto mode: select best match
         if args.auto {
             let best = &candidates[0];
-            println!(
+            say!(
                 "  {} Auto-selected: {} ({:.1}%)",
                 style("✓").green(),
                 best.metadata.title,
@@ -1208,7 +2573,7 @@ async fn process_single_file(
             if !args.dry_run {
                 apply_metadata(file_path, &best.metadata, args, config).await?;
             }
-            return Ok(ProcessResult::Applied);
+            return Ok(ProcessResult::Applied(Box::new(best.metadata.clone())));
         }
 
         // Interactive mode
@@ -1217,7 +2582,7 @@ async fn process_single_file(
                 let selected = &candidates[idx];
 
                 // Show what's about to be applied
-                println!(
+                say!(
                     "  {} Applying: {} by {}",
                     style("→").cyan(),
                     style(&selected.metadata.title).yellow(),
@@ -1228,9 +2593,9 @@ async fn process_single_file(
                 if !args.dry_run {
                     apply_metadata(file_path, &selected.metadata, args, config).await?;
                 } else {
-                    println!("  {} Dry run - metadata not applied", style("→").yellow());
+                    say!("  {} Dry run - metadata not applied", style("→").yellow());
                 }
-                return Ok(ProcessResult::Applied);
+                return Ok(ProcessResult::Applied(Box::new(selected.metadata.clone())));
             }
             UserChoice::Skip => {
                 return Ok(ProcessResult::Skipped);
@@ -1240,7 +2605,7 @@ async fn process_single_file(
                 if !args.dry_run {
                     apply_metadata(file_path, &manual_metadata, args, config).await?;
                 }
-                return Ok(ProcessResult::Applied);
+                return Ok(ProcessResult::Applied(Box::new(manual_metadata)));
             }
             UserChoice::CustomSearch => {
                 let (title, author) = prompt_custom_search()?;
@@ -1253,6 +2618,45 @@ async fn process_single_file(
     }
 }
 
+/// Metadata to search with: CLI overrides, then the folder's override file, then the file's tags
+fn current_metadata_for(
+    file_path: &Path,
+    args: &MatchArgs,
+    overrides: Option<&BookOverrides>,
+) -> Result<CurrentMetadata> {
+    if args.title.is_some() || args.author.is_some() {
+        // Manual override
+        return Ok(CurrentMetadata {
+            title: args.title.clone(),
+            author: args.author.clone(),
+            year: None,
+            duration: None,
+            source: MetadataSource::Manual,
+        });
+    }
+
+    // Auto-extract
+    let mut current = extraction::extract_current_metadata(file_path)?;
+
+    if let Some(tags) = overrides.map(|o| &o.tags) {
+        if tags.title.is_some() || tags.author.is_some() {
+            current.title = tags.title.clone().or(current.title);
+            current.author = tags.author.clone().or(current.author);
+            current.source = MetadataSource::Manual;
+        }
+    }
+
+    Ok(current)
+}
+
+/// Load the override file from the folder containing an M4B
+fn load_folder_overrides(file_path: &Path) -> Result<Option<BookOverrides>> {
+    match file_path.parent() {
+        Some(folder) => BookOverrides::load_from_folder(folder),
+        None => Ok(None),
+    }
+}
+
 /// Get list of M4B files to process
 fn get_files_to_process(args: &MatchArgs) -> Result<Vec<PathBuf>> {
     if let Some(file) = &args.file {
@@ -1335,7 +2739,9 @@ async fn apply_metadata(
                 AudibleRegion::US, // Region doesn't matter for covers
                 config.metadata.audible.rate_limit_per_minute,
                 retry_config,
-            )?;
+            )?
+            .with_cache(AudibleCache::from_config(&config.metadata.audible)?)
+            .with_offline(config.metadata.audible.offline);
             client.download_cover(cover_url, &temp_cover).await?;
             Some(temp_cover)
         } else {
@@ -1348,7 +2754,7 @@ async fn apply_metadata(
     // Inject metadata
     crate::audio::inject_audible_metadata(file_path, metadata, cover_path.as_deref()).await?;
 
-    println!(
+    say!(
         "  {} Metadata applied successfully{}",
         style("✓").green(),
         if cover_path.is_some() {
@@ -1387,3 +2793,45 @@ fn prompt_no_results_action() -> Result<NoResultsAction> {
         _ => Ok(NoResultsAction::Skip),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rerun_reuses_templated_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path().join("out");
+        let template = PathTemplate::parse("{title}.m4b").unwrap();
+        let books = || vec![BookFolder::new(dir.path().join("A/Book")), BookFolder::new(dir.path().join("B/Book"))];
+        let mut config = Config::default();
+        config.processing.skip_existing = false;
+
+        // Books of one batch rendering to the same path get a suffix
+        let mut first = books();
+        assert!(assign_output_paths(&mut first, &template, &output_dir, &config, false).unwrap().is_empty());
+        let outputs: Vec<PathBuf> = first.iter().map(|b| b.get_output_path(&output_dir)).collect();
+        assert_eq!(outputs[1], output_dir.join("Book_2.m4b"));
+        for output in &outputs {
+            std::fs::create_dir_all(output.parent().unwrap()).unwrap();
+            std::fs::write(output, b"audio").unwrap();
+        }
+
+        // A second run overwrites the same files instead of adding new ones
+        let mut second = books();
+        assign_output_paths(&mut second, &template, &output_dir, &config, false).unwrap();
+        let rerun: Vec<PathBuf> = second.iter().map(|b| b.get_output_path(&output_dir)).collect();
+        assert_eq!(rerun, outputs);
+
+        // ...or skips them as already built
+        config.processing.skip_existing = true;
+        let mut third = books();
+        let built = assign_output_paths(&mut third, &template, &output_dir, &config, false).unwrap();
+        assert!(third.is_empty());
+        assert_eq!(built.len(), 2);
+        let mut forced = books();
+        assign_output_paths(&mut forced, &template, &output_dir, &config, true).unwrap();
+        assert_eq!(forced.len(), 2);
+        assert!(!output_dir.join("Book_3.m4b").exists());
+    }
+}
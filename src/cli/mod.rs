@@ -2,6 +2,8 @@
 
 mod commands;
 mod handlers;
+pub mod output;
 
 pub use commands::{Cli, Commands};
-pub use handlers::{handle_build, handle_check, handle_config, handle_organize, handle_metadata, handle_match};
+pub use output::{CommandReport, Outcome};
+pub use handlers::{handle_build, handle_check, handle_config, handle_organize, handle_metadata, handle_match, handle_cache, handle_undo, handle_bench};
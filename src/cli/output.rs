@@ -0,0 +1,204 @@
+//! Command output: exit codes and the `--json` report
+//!
+//! Exit codes:
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | 0 | Success |
+//! | 1 | Error (tool, filesystem, network or unclassified failure) |
+//! | 2 | Invalid command-line usage |
+//! | 3 | Dependency missing or unusable |
+//! | 4 | Invalid configuration |
+//! | 5 | Corrupt or unusable input |
+//! | 6 | Batch partially failed |
+//! | 7 | Every item in the batch failed |
+//! | 8 | Nothing to do |
+//!
+//! With `--json`, stdout carries only the JSON document; human-readable
+//! progress and logs go to stderr.
+
+use crate::error::ErrorKind;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Version of the `--json` document layout
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+static JSON_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Switch human-readable output to stderr (for `--json`)
+pub fn set_json_mode(enabled: bool) {
+    JSON_MODE.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether stdout is reserved for the JSON report
+pub fn json_mode() -> bool {
+    JSON_MODE.load(Ordering::Relaxed)
+}
+
+/// Print a line of human-readable output (to stderr in JSON mode)
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if $crate::cli::output::json_mode() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+pub(crate) use say;
+
+/// How a command that ran to completion went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// Everything succeeded
+    Success,
+    /// Some items of a batch failed
+    PartialFailure,
+    /// Every item of a batch failed
+    AllFailed,
+    /// There was nothing to process
+    NothingToDo,
+}
+
+impl Outcome {
+    /// Outcome of a batch from its success and failure counts
+    pub fn of_batch(succeeded: usize, failed: usize) -> Self {
+        match (succeeded, failed) {
+            (0, 0) => Self::NothingToDo,
+            (_, 0) => Self::Success,
+            (0, _) => Self::AllFailed,
+            _ => Self::PartialFailure,
+        }
+    }
+
+    /// Process exit code
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Success => 0,
+            Self::PartialFailure => 6,
+            Self::AllFailed => 7,
+            Self::NothingToDo => 8,
+        }
+    }
+}
+
+/// What a command did: its outcome, its result for `--json`, and any error
+#[derive(Debug)]
+pub struct CommandReport {
+    pub outcome: Outcome,
+    /// Command-specific result (`null` for commands without one)
+    pub result: Value,
+    /// Error the command failed with, after producing `result`
+    pub error: Option<anyhow::Error>,
+}
+
+impl CommandReport {
+    /// Report with an outcome and a serializable result
+    pub fn new(outcome: Outcome, result: impl Serialize) -> Self {
+        Self {
+            outcome,
+            result: serde_json::to_value(result).unwrap_or(Value::Null),
+            error: None,
+        }
+    }
+
+    /// Successful report with no result
+    pub fn empty() -> Self {
+        Self::new(Outcome::Success, Value::Null)
+    }
+
+    /// Report for a command that failed before producing a result
+    pub fn failed(error: anyhow::Error) -> Self {
+        Self::empty().with_error(error)
+    }
+
+    /// Attach the error the command failed with
+    pub fn with_error(mut self, error: anyhow::Error) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    /// Process exit code (the error's, if there is one)
+    pub fn exit_code(&self) -> u8 {
+        match &self.error {
+            Some(error) => ErrorKind::of(error).exit_code(),
+            None => self.outcome.exit_code(),
+        }
+    }
+
+    /// The `--json` document for a command
+    pub fn to_json(&self, command: &str) -> Value {
+        let (status, error) = match &self.error {
+            Some(error) => (
+                "error".to_string(),
+                json!({
+                    "kind": ErrorKind::of(error),
+                    "message": format!("{:#}", error),
+                }),
+            ),
+            None => (
+                serde_json::to_value(self.outcome)
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_default(),
+                Value::Null,
+            ),
+        };
+        json!({
+            "schema_version": JSON_SCHEMA_VERSION,
+            "command": command,
+            "status": status,
+            "exit_code": self.exit_code(),
+            "result": self.result,
+            "error": error,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ForgeError;
+
+    #[test]
+    fn test_batch_outcomes() {
+        assert_eq!(Outcome::of_batch(0, 0), Outcome::NothingToDo);
+        assert_eq!(Outcome::of_batch(3, 0), Outcome::Success);
+        assert_eq!(Outcome::of_batch(2, 1), Outcome::PartialFailure);
+        assert_eq!(Outcome::of_batch(0, 2), Outcome::AllFailed);
+
+        let codes: Vec<u8> = [Outcome::Success, Outcome::PartialFailure, Outcome::AllFailed, Outcome::NothingToDo]
+            .iter()
+            .map(Outcome::exit_code)
+            .collect();
+        assert_eq!(codes, vec![0, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_json_document() {
+        let report = CommandReport::new(Outcome::PartialFailure, json!({"successful": 1, "failed": 1}));
+        let doc = report.to_json("build");
+        assert_eq!(doc["schema_version"], 1);
+        assert_eq!(doc["command"], "build");
+        assert_eq!(doc["status"], "partial_failure");
+        assert_eq!(doc["exit_code"], 6);
+        assert_eq!(doc["result"]["failed"], 1);
+        assert!(doc["error"].is_null());
+    }
+
+    #[test]
+    fn test_error_overrides_outcome() {
+        let error = anyhow::Error::new(ForgeError::Config("bad value".into())).context("Failed to load config");
+        let report = CommandReport::failed(error);
+        assert_eq!(report.exit_code(), 4);
+
+        let doc = report.to_json("config show");
+        assert_eq!(doc["status"], "error");
+        assert_eq!(doc["error"]["kind"], "config");
+        assert_eq!(doc["error"]["message"], "Failed to load config: bad value");
+        assert!(doc["result"].is_null());
+    }
+}
@@ -1,31 +1,43 @@
 //! Audio track analyzer
 
-use crate::audio::{extract_metadata, FFmpeg};
-use crate::models::{BookFolder, Track};
+use crate::audio::{AudioBackend, FfmpegBackend};
+use super::ordering::{order_tracks, TrackOrdering};
+use crate::models::{BookFolder, Track, TrackOrder};
 use anyhow::Result;
 use futures::stream::{self, StreamExt};
+use std::sync::Arc;
 
 /// Analyzer for audio tracks
 pub struct Analyzer {
-    ffmpeg: FFmpeg,
+    backend: Arc<dyn AudioBackend>,
     parallel_workers: usize,
+    track_order: TrackOrder,
 }
 
 impl Analyzer {
     /// Create a new analyzer
     pub fn new() -> Result<Self> {
-        Ok(Self {
-            ffmpeg: FFmpeg::new()?,
-            parallel_workers: 8,
-        })
+        Self::with_workers(8)
     }
 
     /// Create analyzer with custom parallel workers
     pub fn with_workers(workers: usize) -> Result<Self> {
-        Ok(Self {
-            ffmpeg: FFmpeg::new()?,
+        Ok(Self::with_backend(FfmpegBackend::shared()?, workers))
+    }
+
+    /// Create analyzer with a specific audio backend
+    pub fn with_backend(backend: Arc<dyn AudioBackend>, workers: usize) -> Self {
+        Self {
+            backend,
             parallel_workers: workers.clamp(1, 16),
-        })
+            track_order: TrackOrder::default(),
+        }
+    }
+
+    /// Set how tracks are ordered (a book's own config takes precedence)
+    pub fn with_track_order(mut self, track_order: TrackOrder) -> Self {
+        self.track_order = track_order;
+        self
     }
 
     /// Analyze all MP3 files in a book folder
@@ -34,13 +46,13 @@ impl Analyzer {
         let results = stream::iter(&book_folder.mp3_files)
             .map(|mp3_file| async {
                 // Probe audio file
-                let quality = self.ffmpeg.probe_audio_file(mp3_file).await?;
+                let quality = self.backend.probe_audio_file(mp3_file).await?;
 
                 // Create track
                 let mut track = Track::new(mp3_file.clone(), quality);
 
                 // Extract metadata
-                if let Err(e) = extract_metadata(&mut track).await {
+                if let Err(e) = self.backend.read_tags(&mut track).await {
                     tracing::warn!(
                         "Failed to extract metadata from {}: {}",
                         mp3_file.display(),
@@ -66,11 +78,35 @@ impl Analyzer {
             }
         }
 
-        // Sort tracks by filename (they should already be sorted from scanner)
-        // This is just to ensure consistency
-        tracks.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        // An explicit order from the folder's override file is kept as the
+        // scanner left it; otherwise the configured strategy decides
+        let explicit_order = book_folder
+            .overrides
+            .as_ref()
+            .filter(|o| !o.order.is_empty())
+            .map(|o| o.source.clone());
+        let ordering = match explicit_order {
+            Some(source) => {
+                let files = &book_folder.mp3_files;
+                tracks.sort_by_key(|t| files.iter().position(|f| f == &t.file_path));
+                TrackOrdering::explicit(source)
+            }
+            None => {
+                let strategy = book_folder
+                    .config
+                    .as_ref()
+                    .map_or(self.track_order, |c| c.processing.track_order);
+                order_tracks(&mut tracks, strategy, &book_folder.folder_path)
+            }
+        };
+
+        for conflict in &ordering.conflicts {
+            tracing::warn!("{}: track order: {}", book_folder.name, conflict);
+        }
+        tracing::debug!("{}: tracks ordered by {}", book_folder.name, ordering.strategy);
 
         book_folder.tracks = tracks;
+        book_folder.track_ordering = Some(ordering);
 
         Ok(())
     }
@@ -95,8 +131,9 @@ impl Default for Analyzer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audio::{AudioMetadata, BackendOp, RecordingBackend};
     use crate::models::QualityProfile;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn test_analyzer_creation() {
@@ -137,4 +174,39 @@ mod tests {
 
         assert!(!analyzer.can_use_copy_mode(&book));
     }
+
+    #[tokio::test]
+    async fn test_analyze_with_fake_backend() {
+        let metadata = AudioMetadata {
+            artist: Some("Author".to_string()),
+            ..Default::default()
+        };
+        let backend = Arc::new(RecordingBackend::new().with_metadata(metadata));
+        let analyzer = Analyzer::with_backend(backend.clone(), 4);
+
+        let mut book = BookFolder::new(PathBuf::from("/test"));
+        book.mp3_files = vec![PathBuf::from("/test/2.mp3"), PathBuf::from("/test/1.mp3")];
+        analyzer.analyze_book_folder(&mut book).await.unwrap();
+
+        let files: Vec<&Path> = book.tracks.iter().map(|t| t.file_path.as_path()).collect();
+        assert_eq!(files, vec![Path::new("/test/1.mp3"), Path::new("/test/2.mp3")]);
+        assert_eq!(book.tracks[0].artist.as_deref(), Some("Author"));
+        assert_eq!(book.tracks[0].quality.duration, 600.0);
+        assert_eq!(backend.count(BackendOp::ProbeAudio), 2);
+        assert_eq!(backend.count(BackendOp::ReadTags), 2);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_orders_file_names_naturally() {
+        let backend = Arc::new(RecordingBackend::new());
+        let analyzer = Analyzer::with_backend(backend, 4).with_track_order(TrackOrder::Filename);
+
+        let mut book = BookFolder::new(PathBuf::from("/test"));
+        book.mp3_files = vec![PathBuf::from("/test/10.mp3"), PathBuf::from("/test/2.mp3")];
+        analyzer.analyze_book_folder(&mut book).await.unwrap();
+
+        let files: Vec<&Path> = book.tracks.iter().map(|t| t.file_path.as_path()).collect();
+        assert_eq!(files, vec![Path::new("/test/2.mp3"), Path::new("/test/10.mp3")]);
+        assert_eq!(book.track_ordering.unwrap().strategy, TrackOrder::Filename);
+    }
 }
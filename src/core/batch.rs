@@ -1,12 +1,15 @@
 //! Batch processor for parallel audiobook processing
 
-use crate::audio::AacEncoder;
-use crate::core::{Processor, RetryConfig, smart_retry_async};
+use crate::audio::{AacEncoder, AudioBackend, BenchResult, FfmpegBackend};
+use crate::core::{DiskSpace, Journal, OutputProfile, Processor, RetryConfig, SpaceNeeds, smart_retry_async};
+use crate::error::ForgeError;
 use crate::models::{BookFolder, ProcessingResult};
+use crate::utils::{book_span, interrupt, BookLogs};
 use anyhow::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{mpsc, Semaphore};
+use tracing::Instrument;
 
 /// Batch processor for converting multiple audiobooks in parallel
 pub struct BatchProcessor {
@@ -26,6 +29,18 @@ pub struct BatchProcessor {
     quality_preset: Option<String>,
     /// Retry configuration
     retry_config: RetryConfig,
+    /// Output profiles (empty = single default output)
+    profiles: Vec<OutputProfile>,
+    /// Audio backend (None = FFmpeg, created per book)
+    backend: Option<Arc<dyn AudioBackend>>,
+    /// Where to write a log file per book
+    book_logs: BookLogs,
+    /// Where temp directories are created (None = system temp dir)
+    temp_dir: Option<PathBuf>,
+    /// Free space checked before each book starts
+    disk_space: Option<DiskSpace>,
+    /// Journal recording the outputs written
+    journal: Option<Journal>,
 }
 
 impl BatchProcessor {
@@ -40,6 +55,12 @@ impl BatchProcessor {
             max_concurrent_files: 8, // Default: 8 concurrent files per book
             quality_preset: None,
             retry_config: RetryConfig::new(),
+            profiles: Vec::new(),
+            backend: None,
+            book_logs: BookLogs::Off,
+            temp_dir: None,
+            disk_space: None,
+            journal: None,
         }
     }
 
@@ -63,9 +84,51 @@ impl BatchProcessor {
             max_concurrent_files: max_concurrent_files.clamp(1, 32),
             quality_preset,
             retry_config,
+            profiles: Vec::new(),
+            backend: None,
+            book_logs: BookLogs::Off,
+            temp_dir: None,
+            disk_space: None,
+            journal: None,
         }
     }
 
+    /// Produce one output per profile for every book
+    pub fn with_profiles(mut self, profiles: Vec<OutputProfile>) -> Self {
+        self.profiles = profiles;
+        self
+    }
+
+    /// Write a log file per book
+    pub fn with_book_logs(mut self, book_logs: BookLogs) -> Self {
+        self.book_logs = book_logs;
+        self
+    }
+
+    /// Create temp directories under `dir` instead of the system temp dir
+    pub fn with_temp_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.temp_dir = dir;
+        self
+    }
+
+    /// Check (and claim) each book's disk space before it starts
+    pub fn with_disk_space(mut self, disk_space: DiskSpace) -> Self {
+        self.disk_space = Some(disk_space);
+        self
+    }
+
+    /// Record outputs (and the files they replace) in a journal
+    pub fn with_journal(mut self, journal: Option<Journal>) -> Self {
+        self.journal = journal;
+        self
+    }
+
+    /// Process books with a specific audio backend instead of FFmpeg
+    pub fn with_backend(mut self, backend: Arc<dyn AudioBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
     /// Process multiple books in parallel
     pub async fn process_batch(
         &self,
@@ -106,42 +169,74 @@ impl BatchProcessor {
             let quality_preset = self.quality_preset.clone();
             let encode_semaphore = Arc::clone(&encode_semaphore);
             let retry_config = self.retry_config.clone();
+            let profiles = self.profiles.clone();
+            let backend = self.backend.clone();
+            let temp_dir = self.temp_dir.clone();
+            let disk_space = self.disk_space.clone();
+            let journal = self.journal.clone();
+            let span = book_span(&book, self.book_logs.path_for(&book, &output_dir).as_deref());
 
             let handle = tokio::spawn(async move {
-                // Acquire semaphore permit before encoding (limits concurrent encodes)
-                let _permit = encode_semaphore.acquire().await.unwrap();
-
-                tracing::info!(
-                    "[{}/{}] Processing: {}",
-                    index + 1,
-                    total_books,
-                    book.name
-                );
-
-                // Process with retry logic
-                let result = smart_retry_async(&retry_config, || {
-                    Self::process_single_book(
-                        &book,
-                        &output_dir,
-                        &chapter_source,
-                        keep_temp,
-                        encoder,
-                        enable_parallel_encoding,
-                        max_concurrent_files,
-                        quality_preset.clone(),
-                    )
-                })
-                .await
-                .unwrap_or_else(|e| {
-                    // If all retries fail, return a failure result
-                    tracing::error!("✗ {}: {:?}", book.name, e);
-                    ProcessingResult::new(book.name.clone())
-                        .failure(format!("All retries failed: {:?}", e), 0.0)
-                });
+                let work = async {
+                    // Acquire semaphore permit before encoding (limits concurrent encodes)
+                    let _permit = encode_semaphore.acquire().await.unwrap();
+
+                    // Re-check free space now that the book is about to start
+                    let needs = SpaceNeeds::for_book(&book, profiles.len());
+                    let _claim = match disk_space.as_ref().map(|d| d.claim(&book.name, needs)).transpose() {
+                        Ok(claim) => claim,
+                        Err(e) => {
+                            tracing::error!("✗ {}: {:#}", book.name, e);
+                            return ProcessingResult::new(book.name.clone()).failure_from(&e, 0.0);
+                        }
+                    };
+
+                    tracing::info!(
+                        "[{}/{}] Processing: {}",
+                        index + 1,
+                        total_books,
+                        book.name
+                    );
+
+                    // Process with retry logic
+                    smart_retry_async(&retry_config, || {
+                        Self::process_single_book(
+                            &book,
+                            &output_dir,
+                            &chapter_source,
+                            keep_temp,
+                            encoder,
+                            enable_parallel_encoding,
+                            max_concurrent_files,
+                            quality_preset.clone(),
+                            profiles.clone(),
+                            backend.clone(),
+                            temp_dir.clone(),
+                            journal.clone(),
+                        )
+                    })
+                    .await
+                    .unwrap_or_else(|e| {
+                        // Retries failed or the error was permanent
+                        tracing::error!("✗ {}: {:?}", book.name, e);
+                        ProcessingResult::new(book.name.clone()).failure_from(&e, 0.0)
+                    })
+                };
+
+                // Ctrl-C drops the book's work, which kills its tools and
+                // removes its temp files and unfinished outputs
+                let result = tokio::select! {
+                    result = work => result,
+                    _ = interrupt::interrupted() => {
+                        tracing::warn!("✗ {}: interrupted", book.name);
+                        ProcessingResult::new(book.name.clone()).failure_from(&ForgeError::Interrupted.into(), 0.0)
+                    }
+                };
 
                 // Send result through channel
                 let _ = result_tx.send(result).await;
-            });
+            }
+            .instrument(span));
 
             handles.push(handle);
         }
@@ -170,6 +265,7 @@ impl BatchProcessor {
     }
 
     /// Process a single book (internal helper)
+    #[allow(clippy::too_many_arguments)]
     async fn process_single_book(
         book: &BookFolder,
         output_dir: &Path,
@@ -179,14 +275,26 @@ impl BatchProcessor {
         enable_parallel_encoding: bool,
         max_concurrent_files: usize,
         quality_preset: Option<String>,
+        profiles: Vec<OutputProfile>,
+        backend: Option<Arc<dyn AudioBackend>>,
+        temp_dir: Option<PathBuf>,
+        journal: Option<Journal>,
     ) -> Result<ProcessingResult> {
-        let processor = Processor::with_options(
+        let backend = match backend {
+            Some(backend) => backend,
+            None => FfmpegBackend::shared()?,
+        };
+        let processor = Processor::with_backend(
+            backend,
             keep_temp,
             encoder,
             enable_parallel_encoding,
             max_concurrent_files,
             quality_preset,
-        )?;
+        )
+        .with_profiles(profiles)
+        .with_temp_dir(temp_dir)
+        .with_journal(journal);
 
         let result = processor
             .process_book(book, output_dir, chapter_source)
@@ -204,6 +312,15 @@ impl BatchProcessor {
         // (reserves cores for FFmpeg itself which is multi-threaded)
         (cpu_count / 2).max(1).min(8)
     }
+
+    /// Concurrent encodes and files per book for "auto" settings: the
+    /// fastest split measured by the encoder benchmark, or all CPU cores
+    pub fn recommended_concurrency(bench: Option<&BenchResult>) -> (usize, usize) {
+        match bench {
+            Some(bench) => bench.concurrency_split(),
+            None => (num_cpus::get(), num_cpus::get()),
+        }
+    }
 }
 
 impl Default for BatchProcessor {
@@ -215,6 +332,10 @@ impl Default for BatchProcessor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audio::{BackendOp, RecordingBackend, Tool};
+    use crate::error::{ErrorKind, ForgeError};
+    use crate::models::{QualityProfile, Track};
+    use std::time::Duration;
 
     #[test]
     fn test_batch_processor_creation() {
@@ -263,6 +384,24 @@ mod tests {
         assert!(workers <= 8);
     }
 
+    #[test]
+    fn test_recommended_concurrency() {
+        let bench = BenchResult {
+            measured_at: String::new(),
+            ffmpeg_version: None,
+            cpus: 8,
+            sample_secs: 60,
+            encoders: vec![crate::audio::EncoderBench {
+                encoder: "aac".to_string(),
+                runs: vec![crate::audio::BenchRun { concurrency: 8, wall_secs: 1.0, speed: 480.0 }],
+            }],
+        };
+        assert_eq!(BatchProcessor::recommended_concurrency(Some(&bench)), (2, 4));
+
+        let cpus = num_cpus::get();
+        assert_eq!(BatchProcessor::recommended_concurrency(None), (cpus, cpus));
+    }
+
     #[tokio::test]
     async fn test_empty_batch() {
         let processor = BatchProcessor::new(4);
@@ -271,4 +410,109 @@ mod tests {
             .await;
         assert_eq!(results.len(), 0);
     }
+
+    fn fake_batch(backend: &Arc<RecordingBackend>) -> BatchProcessor {
+        let retry = RetryConfig::with_settings(2, Duration::ZERO, Duration::ZERO, 1.0);
+        BatchProcessor::with_options(2, false, AacEncoder::Native, false, 1, 4, None, retry)
+            .with_backend(backend.clone())
+    }
+
+    fn tool_failure(tool: Tool, stderr: &str) -> ForgeError {
+        ForgeError::ToolFailed {
+            tool,
+            operation: tool.name().to_string(),
+            exit_code: Some(1),
+            stderr: stderr.to_string(),
+        }
+    }
+
+    fn fake_book(dir: &Path, name: &str) -> BookFolder {
+        let mut book = BookFolder::new(dir.join(name));
+        for i in 1..=2 {
+            let path = dir.join(format!("{} {}.mp3", name, i));
+            std::fs::write(&path, b"").unwrap();
+            let quality = QualityProfile::new(128, 44100, 2, "mp3".to_string(), 60.0).unwrap();
+            book.tracks.push(Track::new(path, quality));
+        }
+        book
+    }
+
+    #[tokio::test]
+    async fn test_batch_retries_transient_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(RecordingBackend::new());
+        backend.fail_next(BackendOp::Concat, tool_failure(Tool::FFmpeg, "Resource temporarily unavailable"));
+
+        let books = vec![fake_book(dir.path(), "batch-retry-test")];
+        let results = fake_batch(&backend)
+            .process_batch(books, &dir.path().join("out"), "files")
+            .await;
+
+        assert!(results[0].success, "{:?}", results[0].error_message);
+        let concats = backend.calls_of(BackendOp::Concat);
+        assert_eq!(concats.len(), 2);
+        assert!(concats[0].failed && !concats[1].failed);
+        assert_eq!(backend.count(BackendOp::WriteMetadata), 1);
+    }
+
+    #[tokio::test]
+    async fn test_batch_gives_up_on_permanent_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(RecordingBackend::new());
+        backend.fail_next(BackendOp::Concat, ForgeError::corrupt("01.mp3", "Invalid data found when processing input"));
+
+        let books = vec![fake_book(dir.path(), "batch-permanent-test")];
+        let results = fake_batch(&backend)
+            .process_batch(books, &dir.path().join("out"), "files")
+            .await;
+
+        assert!(!results[0].success);
+        assert_eq!(results[0].error_kind, Some(ErrorKind::CorruptInput));
+        assert_eq!(backend.count(BackendOp::Concat), 1);
+        assert_eq!(backend.count(BackendOp::WriteMetadata), 0);
+    }
+
+    #[tokio::test]
+    async fn test_batch_exhausts_retries() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(RecordingBackend::new());
+        for _ in 0..3 {
+            backend.fail_next(BackendOp::InjectChapters, tool_failure(Tool::MP4Box, "MP4Box timed out"));
+        }
+
+        let books = vec![fake_book(dir.path(), "batch-exhausted-test")];
+        let results = fake_batch(&backend)
+            .process_batch(books, &dir.path().join("out"), "files")
+            .await;
+
+        assert!(!results[0].success);
+        assert!(results[0].error_message.as_deref().unwrap().contains("MP4Box timed out"));
+        assert_eq!(backend.count(BackendOp::InjectChapters), 3);
+    }
+
+    #[tokio::test]
+    async fn test_batch_writes_log_per_book() {
+        use crate::utils::BookLogLayer;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(RecordingBackend::new());
+        let run_dir = dir.path().join("logs");
+
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(BookLogLayer));
+        let books = vec![fake_book(dir.path(), "log-first"), fake_book(dir.path(), "log-second")];
+        let results = fake_batch(&backend)
+            .with_book_logs(BookLogs::RunDir(run_dir.clone()))
+            .process_batch(books, &dir.path().join("out"), "files")
+            .await;
+        assert_eq!(results.len(), 2);
+
+        for name in ["log-first", "log-second"] {
+            let log = std::fs::read_to_string(run_dir.join(format!("{}.log", name))).unwrap();
+            assert!(log.contains(&format!("book{{name={} case=", name)));
+            assert!(log.contains(&format!("Starting book processing: {}", name)));
+            let other = if name == "log-first" { "log-second" } else { "log-first" };
+            assert!(!log.contains(other));
+        }
+    }
 }
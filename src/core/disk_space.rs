@@ -0,0 +1,359 @@
+//! Free disk space checks
+//!
+//! A build estimates what each book needs in the temp directory (per-track
+//! encodes, trimmed intermediates) and next to its output, checks both
+//! filesystems before the batch starts, and reserves the space again before
+//! each book so books running side by side can't overcommit it.
+
+use crate::core::{format_size, parse_size, EncodePlan};
+use crate::error::ForgeError;
+use crate::models::{BookCase, BookFolder, ProcessingConfig};
+use anyhow::{Context, Result};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Headroom added to every estimate (bitrate overshoot, container, tags)
+const ESTIMATE_MARGIN: f64 = 0.05;
+
+/// FLAC intermediates are roughly this share of 16-bit PCM
+const FLAC_RATIO: f64 = 0.6;
+
+/// Space a book needs while it's built
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpaceNeeds {
+    /// Peak bytes in the temp directory (freed when the book is done)
+    pub temp: u64,
+    /// Bytes of finished output
+    pub output: u64,
+}
+
+impl SpaceNeeds {
+    /// Estimate for a book written to `outputs` files (profiles, speed variants)
+    pub fn for_book(book: &BookFolder, outputs: usize) -> Self {
+        let with_margin = |bytes: f64| (bytes * (1.0 + ESTIMATE_MARGIN)) as u64;
+
+        if book.case == BookCase::E {
+            // M4B merges are stream-copied straight to the output
+            let bytes: u64 = book
+                .m4b_files
+                .iter()
+                .filter_map(|f| std::fs::metadata(f).ok())
+                .map(|m| m.len())
+                .sum();
+            return Self { temp: 0, output: with_margin(bytes as f64) };
+        }
+
+        let output = book.estimate_output_size() as f64;
+        let copies = book.target_quality.is_none() && EncodePlan::for_tracks(&book.tracks).copies_all();
+        // Transcoded tracks are encoded one by one into the temp directory first
+        let mut temp = if copies { 0.0 } else { output };
+
+        if let Some(trim) = &book.trim {
+            let count = book.tracks.len();
+            for (i, track) in book.tracks.iter().enumerate() {
+                let trimmed = trim.silence.is_some()
+                    || (i == 0 && trim.intro_secs > 0.0)
+                    || (i + 1 == count && trim.outro_secs > 0.0);
+                if trimmed {
+                    let q = &track.quality;
+                    let pcm = q.duration * q.sample_rate as f64 * q.channels as f64 * 2.0;
+                    temp += pcm * FLAC_RATIO;
+                }
+            }
+        }
+
+        Self {
+            temp: with_margin(temp),
+            output: with_margin(output * outputs.max(1) as f64),
+        }
+    }
+}
+
+/// Bytes always left free, from `processing.min_free_space` ("0" = none)
+pub fn min_free_space(config: &ProcessingConfig) -> Result<u64> {
+    if config.min_free_space.trim() == "0" {
+        return Ok(0);
+    }
+    parse_size(&config.min_free_space).context("Invalid processing.min_free_space")
+}
+
+/// Bytes free on the filesystem holding `path` (or its nearest existing parent)
+pub fn available_space(path: &Path) -> Result<u64> {
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .unwrap_or_else(|| Path::new("."));
+    fs2::available_space(existing).map_err(|e| {
+        ForgeError::io(format!("Failed to read free space of {}", existing.display()), e).into()
+    })
+}
+
+/// Whether two paths are on the same filesystem (assumed not, where unknown)
+fn same_filesystem(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let device = |p: &Path| {
+            p.ancestors()
+                .find_map(|p| std::fs::metadata(p).ok())
+                .map(|m| m.dev())
+        };
+        matches!((device(a), device(b)), (Some(x), Some(y)) if x == y)
+    }
+    #[cfg(not(unix))]
+    {
+        a.components().next() == b.components().next()
+    }
+}
+
+/// Free bytes on the temp and output filesystems
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreeSpace {
+    pub temp: u64,
+    pub output: u64,
+    /// Whether temp and output share a filesystem (and so `temp == output`)
+    pub shared: bool,
+}
+
+impl FreeSpace {
+    /// Whether `needs` fits, on top of `pending` already spoken for, keeping `reserve` free
+    fn fits(&self, needs: SpaceNeeds, pending: SpaceNeeds, reserve: u64) -> bool {
+        let temp = needs.temp + pending.temp;
+        let output = needs.output + pending.output;
+        if self.shared {
+            temp + output + reserve <= self.output
+        } else {
+            temp + reserve <= self.temp && output + reserve <= self.output
+        }
+    }
+}
+
+/// How a batch fits the free space
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchSpace {
+    /// Books built at once (lowered when temp space is short)
+    pub concurrency: usize,
+    /// Books, in order, whose output fits
+    pub fitting_books: usize,
+    /// Total output of the batch
+    pub output_bytes: u64,
+    /// Peak temp usage at `concurrency`
+    pub temp_bytes: u64,
+}
+
+/// Plan a batch: the highest concurrency whose peak temp usage fits with
+/// every output, and how many books fit at all (error if none does)
+pub fn plan_batch(needs: &[SpaceNeeds], concurrency: usize, free: FreeSpace, reserve: u64) -> Result<BatchSpace> {
+    // Peak temp: the largest books running at the same time
+    let mut temps: Vec<u64> = needs.iter().map(|n| n.temp).collect();
+    temps.sort_unstable_by(|a, b| b.cmp(a));
+    let peak_temp = |n: usize| temps.iter().take(n).sum::<u64>();
+    let output_bytes: u64 = needs.iter().map(|n| n.output).sum();
+
+    let all_outputs = SpaceNeeds { temp: 0, output: output_bytes };
+    let concurrency = concurrency.max(1);
+    let fitting_concurrency = (1..=concurrency)
+        .rev()
+        .find(|&n| free.fits(SpaceNeeds { temp: peak_temp(n), output: 0 }, all_outputs, reserve))
+        .unwrap_or(1);
+
+    // Books whose outputs still fit, in build order
+    let mut written = SpaceNeeds::default();
+    let mut fitting_books = 0;
+    for book in needs {
+        let pending = SpaceNeeds { temp: peak_temp(fitting_concurrency).max(book.temp), output: written.output };
+        if !free.fits(SpaceNeeds { temp: 0, output: book.output }, pending, reserve) {
+            break;
+        }
+        written.output += book.output;
+        fitting_books += 1;
+    }
+
+    if fitting_books == 0 && !needs.is_empty() {
+        return Err(storage_full(format!(
+            "Not enough disk space for the first book: it needs {} of output and {} of temp space, {} free{}",
+            format_size(needs[0].output),
+            format_size(needs[0].temp),
+            free,
+            if reserve > 0 { format!(" (keeping {} free)", format_size(reserve)) } else { String::new() }
+        )));
+    }
+
+    Ok(BatchSpace {
+        concurrency: fitting_concurrency,
+        fitting_books,
+        output_bytes,
+        temp_bytes: peak_temp(fitting_concurrency),
+    })
+}
+
+impl fmt::Display for FreeSpace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.shared {
+            write!(f, "{}", format_size(self.output))
+        } else {
+            write!(f, "{} (temp) / {} (output)", format_size(self.temp), format_size(self.output))
+        }
+    }
+}
+
+fn storage_full(message: String) -> anyhow::Error {
+    ForgeError::io(message, io::Error::from(io::ErrorKind::StorageFull)).into()
+}
+
+/// Free space on a build's temp and output filesystems, shared by its books
+#[derive(Debug, Clone)]
+pub struct DiskSpace {
+    temp_dir: PathBuf,
+    output_dir: PathBuf,
+    /// Bytes always left free
+    reserve: u64,
+    /// Space claimed by books in progress
+    claimed: Arc<Mutex<SpaceNeeds>>,
+}
+
+impl DiskSpace {
+    /// Track free space for temp files in `temp_dir` and outputs in `output_dir`
+    pub fn new(temp_dir: impl Into<PathBuf>, output_dir: impl Into<PathBuf>, reserve: u64) -> Self {
+        Self {
+            temp_dir: temp_dir.into(),
+            output_dir: output_dir.into(),
+            reserve,
+            claimed: Arc::default(),
+        }
+    }
+
+    /// Current free space on both filesystems
+    pub fn free(&self) -> Result<FreeSpace> {
+        let shared = same_filesystem(&self.temp_dir, &self.output_dir);
+        let output = available_space(&self.output_dir)?;
+        let temp = if shared { output } else { available_space(&self.temp_dir)? };
+        Ok(FreeSpace { temp, output, shared })
+    }
+
+    /// Bytes always left free
+    pub fn reserve(&self) -> u64 {
+        self.reserve
+    }
+
+    /// Claim a book's space until the returned guard is dropped
+    ///
+    /// Fails (without claiming anything) if the space isn't free right now,
+    /// counting what books already in progress will still write.
+    pub fn claim(&self, book: &str, needs: SpaceNeeds) -> Result<SpaceClaim> {
+        let free = self.free()?;
+        let mut claimed = self.claimed.lock().unwrap_or_else(|e| e.into_inner());
+        if !free.fits(needs, *claimed, self.reserve) {
+            return Err(storage_full(format!(
+                "Not enough disk space for {}: it needs {} of output and {} of temp space, {} free",
+                book,
+                format_size(needs.output),
+                format_size(needs.temp),
+                free
+            )));
+        }
+        claimed.temp += needs.temp;
+        claimed.output += needs.output;
+        Ok(SpaceClaim {
+            claimed: Arc::clone(&self.claimed),
+            needs,
+        })
+    }
+}
+
+/// Space claimed for a book in progress (released on drop)
+#[derive(Debug)]
+pub struct SpaceClaim {
+    claimed: Arc<Mutex<SpaceNeeds>>,
+    needs: SpaceNeeds,
+}
+
+impl Drop for SpaceClaim {
+    fn drop(&mut self) {
+        // The output is on disk now (or was never written), so free space already reflects it
+        let mut claimed = self.claimed.lock().unwrap_or_else(|e| e.into_inner());
+        claimed.temp -= self.needs.temp;
+        claimed.output -= self.needs.output;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::BookTrim;
+    use crate::models::{QualityProfile, Track};
+
+    const MB: u64 = 1_000_000;
+
+    fn needs(temp: u64, output: u64) -> SpaceNeeds {
+        SpaceNeeds { temp: temp * MB, output: output * MB }
+    }
+
+    fn book(codec: &str) -> BookFolder {
+        let mut book = BookFolder::new(PathBuf::from("/books/Space"));
+        for i in 0..2 {
+            // 1 hour at 64 kbps = 28.8 MB per track
+            let quality = QualityProfile::new(64, 44100, 2, codec.to_string(), 3600.0).unwrap();
+            book.tracks.push(Track::new(PathBuf::from(format!("/books/Space/{}.m4a", i)), quality));
+        }
+        book
+    }
+
+    #[test]
+    fn test_needs_for_book() {
+        // Copied books need no temp space; transcoded ones hold a second copy there
+        let copied = SpaceNeeds::for_book(&book("aac"), 1);
+        assert_eq!(copied.temp, 0);
+        assert_eq!(copied.output, 60_480_000);
+        let transcoded = SpaceNeeds::for_book(&book("mp3"), 2);
+        assert_eq!(transcoded.temp, 60_480_000);
+        assert_eq!(transcoded.output, 2 * 60_480_000);
+
+        // Trimming the intro adds a FLAC copy of the first track
+        let mut trimmed = book("aac");
+        trimmed.trim = Some(BookTrim { intro_secs: 3.0, outro_secs: 0.0, silence: None });
+        let needs = SpaceNeeds::for_book(&trimmed, 1);
+        assert_eq!(needs.temp, (3600.0 * 44100.0 * 4.0 * FLAC_RATIO * 1.05) as u64);
+    }
+
+    #[test]
+    fn test_plan_batch_throttles_and_limits() {
+        let books = [needs(300, 100), needs(200, 100), needs(100, 100)];
+
+        // Plenty of space: nothing changes
+        let free = FreeSpace { temp: 10_000 * MB, output: 10_000 * MB, shared: false };
+        let plan = plan_batch(&books, 3, free, 0).unwrap();
+        assert_eq!((plan.concurrency, plan.fitting_books), (3, 3));
+
+        // Temp space for two books at once
+        let free = FreeSpace { temp: 550 * MB, output: 10_000 * MB, shared: false };
+        let plan = plan_batch(&books, 3, free, 0).unwrap();
+        assert_eq!((plan.concurrency, plan.fitting_books, plan.temp_bytes), (2, 3, 500 * MB));
+
+        // One shared disk: outputs and temp compete
+        let free = FreeSpace { temp: 650 * MB, output: 650 * MB, shared: true };
+        let plan = plan_batch(&books, 3, free, 100 * MB).unwrap();
+        assert_eq!((plan.concurrency, plan.fitting_books), (1, 2));
+
+        let free = FreeSpace { temp: 350 * MB, output: 350 * MB, shared: true };
+        let error = plan_batch(&books, 3, free, 100 * MB).unwrap_err();
+        assert!(error.to_string().contains("first book"), "{}", error);
+        assert_eq!(crate::error::ErrorKind::of(&error), crate::error::ErrorKind::Io);
+    }
+
+    #[test]
+    fn test_claims_count_books_in_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let space = DiskSpace::new(dir.path(), dir.path().join("out"), 0);
+        let free = space.free().unwrap();
+        assert!(free.shared);
+
+        let half = SpaceNeeds { temp: 0, output: free.output / 2 + 1 };
+        let claim = space.claim("first", half).unwrap();
+        assert!(space.claim("second", half).is_err());
+        drop(claim);
+        assert!(space.claim("second", half).is_ok());
+    }
+}
@@ -0,0 +1,405 @@
+//! Pre-build integrity checks
+//!
+//! Looks at an analyzed book for the usual signs of a broken rip: gaps or
+//! duplicates in the track numbering, the same audio present twice, tracks
+//! whose album/artist tags disagree with the rest of the book, and tracks
+//! whose format or bitrate stands out.
+
+use super::OrderConflict;
+use crate::models::{BookFolder, Track, TrackOrder};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// Fewest tracks for "the majority" of a book to mean anything
+const MIN_TRACKS_FOR_MAJORITY: usize = 3;
+
+/// Something suspicious about a book's tracks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IntegrityIssue {
+    /// Numbers missing from the track sequence
+    MissingTracks {
+        signal: TrackOrder,
+        disc: Option<u32>,
+        missing: Vec<u32>,
+    },
+    /// Several tracks carry the same number
+    DuplicateNumber {
+        signal: TrackOrder,
+        number: String,
+        files: Vec<String>,
+    },
+    /// Files with identical contents
+    DuplicateAudio { files: Vec<String> },
+    /// Different files with exactly the same duration
+    SameDuration { duration_secs: f64, files: Vec<String> },
+    /// A track's album or artist tag differs from the rest of the book
+    TagMismatch {
+        field: String,
+        file: String,
+        value: String,
+        majority: String,
+    },
+    /// A track's codec, sample rate or channels differ from the rest of the book
+    FormatOutlier {
+        file: String,
+        format: String,
+        majority: String,
+    },
+    /// A track's bitrate is less than half or more than twice the median
+    BitrateOutlier { file: String, bitrate: u32, median: u32 },
+}
+
+impl fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingTracks { signal, disc, missing } => {
+                let missing: Vec<String> = missing.iter().map(u32::to_string).collect();
+                write!(f, "missing track(s) {}", missing.join(", "))?;
+                if let Some(disc) = disc {
+                    write!(f, " on disc {}", disc)?;
+                }
+                write!(f, " (by {})", signal)
+            }
+            Self::DuplicateNumber { signal, number, files } => {
+                write!(f, "track {} appears more than once (by {}): {}", number, signal, files.join(", "))
+            }
+            Self::DuplicateAudio { files } => write!(f, "identical audio files: {}", files.join(", ")),
+            Self::SameDuration { duration_secs, files } => write!(
+                f,
+                "same duration ({:.3}s), possibly duplicates: {}",
+                duration_secs,
+                files.join(", ")
+            ),
+            Self::TagMismatch { field, file, value, majority } => write!(
+                f,
+                "{} has {} '{}' but the other tracks have '{}'",
+                file, field, value, majority
+            ),
+            Self::FormatOutlier { file, format, majority } => {
+                write!(f, "{} is {} but the other tracks are {}", file, format, majority)
+            }
+            Self::BitrateOutlier { file, bitrate, median } => {
+                write!(f, "{} is {} kbps but the median is {} kbps", file, bitrate, median)
+            }
+        }
+    }
+}
+
+/// Check an analyzed book for missing, duplicated and foreign tracks
+pub fn check_integrity(book: &BookFolder) -> Vec<IntegrityIssue> {
+    let mut issues = numbering_issues(book);
+    issues.extend(duplicate_issues(&book.tracks));
+    issues.extend(tag_issues(&book.tracks, "album", |t| t.album.as_deref()));
+    issues.extend(tag_issues(&book.tracks, "artist", |t| t.artist.as_deref()));
+    issues.extend(quality_issues(&book.tracks));
+    issues
+}
+
+/// Gaps and duplicates found while ordering the tracks
+fn numbering_issues(book: &BookFolder) -> Vec<IntegrityIssue> {
+    let Some(ordering) = &book.track_ordering else {
+        return Vec::new();
+    };
+    ordering
+        .conflicts
+        .iter()
+        .filter_map(|conflict| match conflict {
+            OrderConflict::Gap { signal, disc, missing } => Some(IntegrityIssue::MissingTracks {
+                signal: *signal,
+                disc: *disc,
+                missing: missing.clone(),
+            }),
+            OrderConflict::Duplicate { signal, number, files } => Some(IntegrityIssue::DuplicateNumber {
+                signal: *signal,
+                number: number.clone(),
+                files: files.clone(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Files with identical contents, then distinct files with identical durations
+fn duplicate_issues(tracks: &[Track]) -> Vec<IntegrityIssue> {
+    let mut issues = Vec::new();
+
+    // Only files of the same size can be identical, so only those are read
+    let mut by_size: HashMap<u64, Vec<&Track>> = HashMap::new();
+    for track in tracks {
+        if let Ok(metadata) = std::fs::metadata(&track.file_path) {
+            by_size.entry(metadata.len()).or_default().push(track);
+        }
+    }
+    let mut identical: Vec<Vec<&Track>> = Vec::new();
+    for group in by_size.into_values().filter(|g| g.len() > 1) {
+        let mut by_hash: HashMap<u64, Vec<&Track>> = HashMap::new();
+        for track in group {
+            if let Some(hash) = content_hash(&track.file_path) {
+                by_hash.entry(hash).or_default().push(track);
+            }
+        }
+        identical.extend(by_hash.into_values().filter(|g| g.len() > 1));
+    }
+    identical.sort_by_key(|group| position_of(tracks, group[0]));
+    for group in &identical {
+        issues.push(IntegrityIssue::DuplicateAudio { files: file_names(group) });
+    }
+
+    let mut by_duration: Vec<(i64, Vec<&Track>)> = Vec::new();
+    for track in tracks.iter().filter(|t| t.quality.duration >= 1.0) {
+        let millis = (track.quality.duration * 1000.0).round() as i64;
+        match by_duration.iter_mut().find(|(d, _)| *d == millis) {
+            Some((_, group)) => group.push(track),
+            None => by_duration.push((millis, vec![track])),
+        }
+    }
+    for (millis, group) in by_duration {
+        let already_identical = identical
+            .iter()
+            .any(|same| group.iter().all(|t| same.iter().any(|s| s.file_path == t.file_path)));
+        if group.len() > 1 && !already_identical {
+            issues.push(IntegrityIssue::SameDuration {
+                duration_secs: millis as f64 / 1000.0,
+                files: file_names(&group),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Tracks whose tag differs from the value most of the book shares
+fn tag_issues(tracks: &[Track], field: &str, tag: impl Fn(&Track) -> Option<&str>) -> Vec<IntegrityIssue> {
+    let values: Vec<(&Track, &str)> = tracks
+        .iter()
+        .filter_map(|t| tag(t).map(str::trim).filter(|v| !v.is_empty()).map(|v| (t, v)))
+        .collect();
+    let Some(majority) = majority(values.iter().map(|(_, v)| v.to_lowercase())) else {
+        return Vec::new();
+    };
+    let majority_value = values
+        .iter()
+        .find(|(_, v)| v.to_lowercase() == majority)
+        .map_or(majority.clone(), |(_, v)| v.to_string());
+
+    values
+        .iter()
+        .filter(|(_, v)| v.to_lowercase() != majority)
+        .map(|(track, value)| IntegrityIssue::TagMismatch {
+            field: field.to_string(),
+            file: file_name(&track.file_path),
+            value: value.to_string(),
+            majority: majority_value.clone(),
+        })
+        .collect()
+}
+
+/// Tracks whose format or bitrate stands out from the rest of the book
+fn quality_issues(tracks: &[Track]) -> Vec<IntegrityIssue> {
+    if tracks.len() < MIN_TRACKS_FOR_MAJORITY {
+        return Vec::new();
+    }
+    let format = |t: &Track| {
+        format!(
+            "{} {} Hz {}",
+            t.quality.codec,
+            t.quality.sample_rate,
+            if t.quality.channels == 1 { "mono" } else { "stereo" }
+        )
+    };
+
+    let mut issues = Vec::new();
+    let majority_format = majority(tracks.iter().map(format));
+    if let Some(majority) = &majority_format {
+        for track in tracks.iter().filter(|t| &format(t) != majority) {
+            issues.push(IntegrityIssue::FormatOutlier {
+                file: file_name(&track.file_path),
+                format: format(track),
+                majority: majority.clone(),
+            });
+        }
+    }
+
+    let mut bitrates: Vec<u32> = tracks.iter().map(|t| t.quality.bitrate).collect();
+    bitrates.sort_unstable();
+    let median = bitrates[bitrates.len() / 2];
+    for track in tracks {
+        let bitrate = track.quality.bitrate;
+        let format_flagged = majority_format.as_ref().is_some_and(|m| &format(track) != m);
+        if !format_flagged && (bitrate * 2 < median || bitrate > median * 2) {
+            issues.push(IntegrityIssue::BitrateOutlier {
+                file: file_name(&track.file_path),
+                bitrate,
+                median,
+            });
+        }
+    }
+
+    issues
+}
+
+/// The value more than half of at least three values share
+fn majority(values: impl Iterator<Item = String>) -> Option<String> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    let mut total = 0;
+    for value in values {
+        total += 1;
+        match counts.iter_mut().find(|(v, _)| *v == value) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((value, 1)),
+        }
+    }
+    if total < MIN_TRACKS_FOR_MAJORITY {
+        return None;
+    }
+    counts
+        .into_iter()
+        .find(|(_, count)| count * 2 > total)
+        .map(|(value, _)| value)
+}
+
+/// Hash of a file's contents (None if it can't be read)
+fn content_hash(path: &Path) -> Option<u64> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer).ok()?;
+        if read == 0 {
+            return Some(hasher.finish());
+        }
+        buffer[..read].hash(&mut hasher);
+    }
+}
+
+fn position_of(tracks: &[Track], track: &Track) -> usize {
+    tracks.iter().position(|t| t.file_path == track.file_path).unwrap_or(usize::MAX)
+}
+
+fn file_names(tracks: &[&Track]) -> Vec<String> {
+    tracks.iter().map(|t| file_name(&t.file_path)).collect()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::TrackOrdering;
+    use crate::models::QualityProfile;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    fn track(path: PathBuf, bitrate: u32, duration: f64) -> Track {
+        let quality = QualityProfile::new(bitrate, 44100, 2, "mp3".to_string(), duration).unwrap();
+        let mut track = Track::new(path, quality);
+        track.album = Some("The Book".to_string());
+        track.artist = Some("Jane Doe".to_string());
+        track
+    }
+
+    fn book(tracks: Vec<Track>) -> BookFolder {
+        let mut book = BookFolder::new(PathBuf::from("/library/The Book"));
+        book.tracks = tracks;
+        book
+    }
+
+    #[test]
+    fn test_clean_book_has_no_issues() {
+        let tracks = (1..=4).map(|n| track(PathBuf::from(format!("/b/{:02}.mp3", n)), 64, 600.0 + n as f64)).collect();
+        assert!(check_integrity(&book(tracks)).is_empty());
+    }
+
+    #[test]
+    fn test_numbering_issues_come_from_the_ordering() {
+        let mut book = book(Vec::new());
+        book.track_ordering = Some(TrackOrdering {
+            strategy: TrackOrder::Filename,
+            order_file: None,
+            conflicts: vec![
+                OrderConflict::Gap { signal: TrackOrder::Filename, disc: None, missing: vec![7] },
+                OrderConflict::Unlisted { files: vec!["x.mp3".to_string()] },
+            ],
+        });
+        let issues = check_integrity(&book);
+        assert_eq!(
+            issues,
+            vec![IntegrityIssue::MissingTracks { signal: TrackOrder::Filename, disc: None, missing: vec![7] }]
+        );
+        assert_eq!(issues[0].to_string(), "missing track(s) 7 (by filename)");
+    }
+
+    #[test]
+    fn test_duplicate_files_and_durations() {
+        let dir = tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        std::fs::write(path("11.mp3"), b"eleven").unwrap();
+        std::fs::write(path("12.mp3"), b"twelve").unwrap();
+        std::fs::write(path("12 (1).mp3"), b"twelve").unwrap();
+        std::fs::write(path("13.mp3"), b"thirteen, re-encoded").unwrap();
+
+        let tracks = vec![
+            track(path("11.mp3"), 64, 300.0),
+            track(path("12.mp3"), 64, 412.5),
+            track(path("12 (1).mp3"), 64, 412.5),
+            track(path("13.mp3"), 64, 300.0),
+        ];
+        let issues = check_integrity(&book(tracks));
+        assert_eq!(
+            issues,
+            vec![
+                IntegrityIssue::DuplicateAudio { files: vec!["12.mp3".to_string(), "12 (1).mp3".to_string()] },
+                IntegrityIssue::SameDuration {
+                    duration_secs: 300.0,
+                    files: vec!["11.mp3".to_string(), "13.mp3".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_foreign_track() {
+        let mut sample = track(PathBuf::from("/b/sample.mp3"), 128, 30.0);
+        sample.album = Some("Another Book".to_string());
+        sample.quality.sample_rate = 22050;
+        let mut tracks: Vec<Track> = (1..=3).map(|n| track(PathBuf::from(format!("/b/{}.mp3", n)), 64, 600.0 + n as f64)).collect();
+        tracks[1].artist = Some("jane doe ".to_string());
+        tracks.push(sample);
+
+        let issues = check_integrity(&book(tracks));
+        assert_eq!(
+            issues,
+            vec![
+                IntegrityIssue::TagMismatch {
+                    field: "album".to_string(),
+                    file: "sample.mp3".to_string(),
+                    value: "Another Book".to_string(),
+                    majority: "The Book".to_string(),
+                },
+                IntegrityIssue::FormatOutlier {
+                    file: "sample.mp3".to_string(),
+                    format: "mp3 22050 Hz stereo".to_string(),
+                    majority: "mp3 44100 Hz stereo".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bitrate_outlier() {
+        let mut tracks: Vec<Track> = (1..=3).map(|n| track(PathBuf::from(format!("/b/{}.mp3", n)), 64, 600.0 + n as f64)).collect();
+        tracks.push(track(PathBuf::from("/b/4.mp3"), 320, 700.0));
+        assert_eq!(
+            check_integrity(&book(tracks)),
+            vec![IntegrityIssue::BitrateOutlier { file: "4.mp3".to_string(), bitrate: 320, median: 64 }]
+        );
+    }
+}
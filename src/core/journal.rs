@@ -0,0 +1,811 @@
+//! Transaction journal for filesystem changes, and `undo` support
+//!
+//! Every run of `build` or `organize` that touches the library gets its own
+//! JSON Lines file in the journal directory: a header with the command line,
+//! followed by one entry per move, created file, deletion or directory change.
+//! Deleted and overwritten files are renamed into the run's trash folder, a
+//! hidden [`TRASH_DIR`] in the library (or output) folder, so they stay on
+//! their own filesystem and can be restored without copying.
+//! `undo` replays a run in reverse after checking that nothing has changed since.
+//! Runs older than the retention period are pruned along with their trash, so
+//! deleted originals only free their space once their run is pruned.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Hidden folder holding each run's trash, inside the folder it was trashed from
+pub const TRASH_DIR: &str = ".audiobook-forge-trash";
+
+/// Size and modification time of a file, used to detect later changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub size: u64,
+    /// Modification time in milliseconds since the Unix epoch
+    pub modified_ms: u64,
+}
+
+impl Fingerprint {
+    /// Fingerprint a file (None for directories and missing paths)
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        if !metadata.is_file() {
+            return None;
+        }
+        let modified_ms = metadata
+            .modified()
+            .ok()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()?
+            .as_millis() as u64;
+
+        Some(Self {
+            size: metadata.len(),
+            modified_ms,
+        })
+    }
+}
+
+/// A single recorded filesystem change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum JournalOp {
+    /// File or folder moved/renamed
+    Move {
+        from: PathBuf,
+        to: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fingerprint: Option<Fingerprint>,
+    },
+    /// New file written (output M4B, cover)
+    Create {
+        path: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fingerprint: Option<Fingerprint>,
+    },
+    /// File overwritten (the previous version kept in the run's trash folder)
+    Replace {
+        path: PathBuf,
+        trashed: PathBuf,
+        /// Fingerprint of the new file
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fingerprint: Option<Fingerprint>,
+    },
+    /// File deleted (kept in the run's trash folder)
+    Delete {
+        path: PathBuf,
+        trashed: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fingerprint: Option<Fingerprint>,
+    },
+    /// Directory created
+    CreateDir { path: PathBuf },
+    /// Empty directory removed
+    RemoveDir { path: PathBuf },
+}
+
+impl JournalOp {
+    /// Human-readable description of the change
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Move { from, to, .. } => format!("move {} -> {}", from.display(), to.display()),
+            Self::Create { path, .. } => format!("create {}", path.display()),
+            Self::Replace { path, .. } => format!("replace {}", path.display()),
+            Self::Delete { path, .. } => format!("delete {}", path.display()),
+            Self::CreateDir { path } => format!("mkdir {}", path.display()),
+            Self::RemoveDir { path } => format!("rmdir {}", path.display()),
+        }
+    }
+
+    /// Why this change can no longer be reverted, if it can't
+    pub fn conflict(&self) -> Option<String> {
+        match self {
+            Self::Move { from, to, fingerprint } => {
+                if !to.exists() {
+                    Some(format!("{} no longer exists", to.display()))
+                } else if from.exists() {
+                    Some(format!("{} already exists", from.display()))
+                } else if fingerprint.is_some() && Fingerprint::of(to) != *fingerprint {
+                    Some(format!("{} has changed since it was moved", to.display()))
+                } else {
+                    None
+                }
+            }
+            Self::Create { path, fingerprint } => {
+                if path.exists() && fingerprint.is_some() && Fingerprint::of(path) != *fingerprint {
+                    Some(format!("{} has changed since it was created", path.display()))
+                } else {
+                    None
+                }
+            }
+            Self::Replace { path, trashed, fingerprint } => {
+                if !trashed.exists() {
+                    Some(format!("backup {} is missing", trashed.display()))
+                } else if path.exists() && fingerprint.is_some() && Fingerprint::of(path) != *fingerprint {
+                    Some(format!("{} has changed since it was replaced", path.display()))
+                } else {
+                    None
+                }
+            }
+            Self::Delete { path, trashed, .. } => {
+                if path.exists() {
+                    Some(format!("{} already exists", path.display()))
+                } else if !trashed.exists() {
+                    Some(format!("backup {} is missing", trashed.display()))
+                } else {
+                    None
+                }
+            }
+            Self::CreateDir { .. } | Self::RemoveDir { .. } => None,
+        }
+    }
+
+    /// Revert this change
+    fn revert(&self) -> Result<()> {
+        match self {
+            Self::Move { from, to, .. } => {
+                if let Some(parent) = from.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                move_path(to, from)
+            }
+            Self::Create { path, .. } => {
+                if path.exists() {
+                    fs::remove_file(path)
+                        .with_context(|| format!("Failed to remove {}", path.display()))?;
+                }
+                Ok(())
+            }
+            Self::Replace { path, trashed, .. } => {
+                if path.exists() {
+                    fs::remove_file(path)
+                        .with_context(|| format!("Failed to remove {}", path.display()))?;
+                }
+                move_path(trashed, path)?;
+                remove_empty_trash_dir(trashed);
+                Ok(())
+            }
+            Self::Delete { path, trashed, .. } => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                move_path(trashed, path)?;
+                remove_empty_trash_dir(trashed);
+                Ok(())
+            }
+            Self::CreateDir { path } => {
+                // Only remove directories we left empty
+                if is_empty_dir(path) {
+                    fs::remove_dir(path)
+                        .with_context(|| format!("Failed to remove {}", path.display()))?;
+                }
+                Ok(())
+            }
+            Self::RemoveDir { path } => fs::create_dir_all(path)
+                .with_context(|| format!("Failed to recreate {}", path.display())),
+        }
+    }
+}
+
+/// First line of a run file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunHeader {
+    pub run_id: String,
+    /// RFC 3339 start time
+    pub started_at: String,
+    /// Command line that produced the run
+    pub command: String,
+    pub cwd: PathBuf,
+}
+
+/// A recorded change with its timestamp
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// RFC 3339 time of the change
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub op: JournalOp,
+}
+
+#[derive(Debug)]
+struct JournalInner {
+    journal_dir: PathBuf,
+    header: RunHeader,
+    file: Option<fs::File>,
+    /// Folders whose files are trashed into their own trash folder
+    trash_roots: Vec<PathBuf>,
+}
+
+/// Writer for the current run's journal (cheap to clone, safe to share)
+#[derive(Debug, Clone)]
+pub struct Journal {
+    inner: Arc<Mutex<JournalInner>>,
+}
+
+impl Journal {
+    /// Default journal directory (`~/.local/share/audiobook-forge/journal`)
+    pub fn default_dir() -> Result<PathBuf> {
+        Ok(dirs::data_local_dir()
+            .context("No data directory found")?
+            .join("audiobook-forge")
+            .join("journal"))
+    }
+
+    /// Start a new run; the run file is only created once something is recorded
+    pub fn start(journal_dir: &Path) -> Self {
+        let now = chrono::Local::now();
+        let header = RunHeader {
+            run_id: format!("{}-{}", now.format("%Y%m%d-%H%M%S"), std::process::id()),
+            started_at: now.to_rfc3339(),
+            command: std::env::args().collect::<Vec<_>>().join(" "),
+            cwd: std::env::current_dir().unwrap_or_default(),
+        };
+
+        Self {
+            inner: Arc::new(Mutex::new(JournalInner {
+                journal_dir: journal_dir.to_path_buf(),
+                header,
+                file: None,
+                trash_roots: Vec::new(),
+            })),
+        }
+    }
+
+    /// Trash files below `dir` into `<dir>/.audiobook-forge-trash` (files
+    /// outside every such folder are trashed next to where they are)
+    pub fn add_trash_root(&self, dir: &Path) {
+        self.lock().trash_roots.push(dir.to_path_buf());
+    }
+
+    /// Identifier of this run
+    pub fn run_id(&self) -> String {
+        self.lock().header.run_id.clone()
+    }
+
+    /// Whether anything has been recorded in this run
+    pub fn is_empty(&self) -> bool {
+        self.lock().file.is_none()
+    }
+
+    /// Move a file or folder and record it
+    pub fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        move_path(from, to)?;
+        self.record(JournalOp::Move {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+            fingerprint: Fingerprint::of(to),
+        })
+    }
+
+    /// Create a directory (and missing parents), recording each one created
+    pub fn create_dir_all(&self, path: &Path) -> Result<()> {
+        let mut missing = Vec::new();
+        let mut current = Some(path);
+        while let Some(dir) = current {
+            if dir.as_os_str().is_empty() || dir.exists() {
+                break;
+            }
+            missing.push(dir.to_path_buf());
+            current = dir.parent();
+        }
+
+        fs::create_dir_all(path)
+            .with_context(|| format!("Failed to create folder: {}", path.display()))?;
+
+        for dir in missing.into_iter().rev() {
+            self.record(JournalOp::CreateDir { path: dir })?;
+        }
+        Ok(())
+    }
+
+    /// Remove an empty directory and record it
+    pub fn remove_dir(&self, path: &Path) -> Result<()> {
+        fs::remove_dir(path)
+            .with_context(|| format!("Failed to remove {}", path.display()))?;
+        self.record(JournalOp::RemoveDir { path: path.to_path_buf() })
+    }
+
+    /// "Delete" a file by moving it into the run's trash folder
+    pub fn remove_file(&self, path: &Path) -> Result<()> {
+        let fingerprint = Fingerprint::of(path);
+        let trashed = self.trash(path)?;
+
+        self.record(JournalOp::Delete {
+            path: path.to_path_buf(),
+            trashed,
+            fingerprint,
+        })
+    }
+
+    /// Move a finished file into place, keeping any file it replaces in the
+    /// run's trash folder, and record it as created or replaced
+    pub fn commit_file(&self, from: &Path, to: &Path) -> Result<()> {
+        let trashed = if to.is_file() { Some(self.trash(to)?) } else { None };
+        if let Err(e) = move_path(from, to) {
+            // Put the previous version back
+            if let Some(trashed) = &trashed {
+                let _ = move_path(trashed, to);
+            }
+            return Err(e);
+        }
+
+        let fingerprint = Fingerprint::of(to);
+        self.record(match trashed {
+            Some(trashed) => JournalOp::Replace { path: to.to_path_buf(), trashed, fingerprint },
+            None => JournalOp::Create { path: to.to_path_buf(), fingerprint },
+        })
+    }
+
+    /// Move a file into the run's trash folder, returning where it went
+    ///
+    /// Only ever renames: a trash folder on another filesystem would mean
+    /// copying whole books and freeing nothing.
+    fn trash(&self, path: &Path) -> Result<PathBuf> {
+        let trashed = {
+            let inner = self.lock();
+            let root = inner
+                .trash_roots
+                .iter()
+                .filter(|root| path.starts_with(root))
+                .max_by_key(|root| root.components().count())
+                .map(PathBuf::as_path)
+                .or(path.parent())
+                .context("Invalid file path")?;
+            let trash_dir = root.join(TRASH_DIR).join(&inner.header.run_id);
+            let name = path.file_name().context("Invalid file path")?.to_string_lossy().into_owned();
+            crate::utils::template::resolve_collision(&trash_dir.join(name), |p| p.exists())?
+        };
+
+        if let Some(parent) = trashed.parent() {
+            fs::create_dir_all(parent).context("Failed to create journal trash folder")?;
+        }
+        fs::rename(path, &trashed).with_context(|| {
+            format!(
+                "Failed to move {} into the trash folder {} (it must be on the same filesystem); \
+                 set processing.journal: false to delete files outright",
+                path.display(),
+                trashed.parent().unwrap_or(&trashed).display()
+            )
+        })?;
+        Ok(trashed)
+    }
+
+    /// Record a file this run has written
+    pub fn record_created(&self, path: &Path) -> Result<()> {
+        self.record(JournalOp::Create {
+            path: path.to_path_buf(),
+            fingerprint: Fingerprint::of(path),
+        })
+    }
+
+    /// Append an entry to the run file
+    pub fn record(&self, op: JournalOp) -> Result<()> {
+        let mut inner = self.lock();
+
+        if inner.file.is_none() {
+            fs::create_dir_all(&inner.journal_dir).context("Failed to create journal directory")?;
+            let path = run_file(&inner.journal_dir, &inner.header.run_id);
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("Failed to open journal: {}", path.display()))?;
+            writeln!(file, "{}", serde_json::to_string(&inner.header)?)?;
+            inner.file = Some(file);
+        }
+
+        tracing::debug!("Journal: {}", op.describe());
+        let entry = JournalEntry {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            op,
+        };
+        let line = serde_json::to_string(&entry)?;
+        if let Some(file) = inner.file.as_mut() {
+            writeln!(file, "{}", line).context("Failed to write journal entry")?;
+            file.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, JournalInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A recorded run loaded from disk
+#[derive(Debug, Clone)]
+pub struct JournalRun {
+    pub header: RunHeader,
+    pub entries: Vec<JournalEntry>,
+    /// Whether the run has already been undone
+    pub undone: bool,
+    path: PathBuf,
+}
+
+impl JournalRun {
+    /// Load a run by id
+    pub fn load(journal_dir: &Path, run_id: &str) -> Result<Self> {
+        let path = run_file(journal_dir, run_id);
+        if !path.exists() {
+            bail!("No journal found for run '{}'", run_id);
+        }
+        Self::load_file(&path)
+    }
+
+    /// Load the most recent run that hasn't been undone
+    pub fn latest(journal_dir: &Path) -> Result<Option<Self>> {
+        Ok(Self::list(journal_dir)?.into_iter().rev().find(|r| !r.undone))
+    }
+
+    /// List all recorded runs, oldest first
+    pub fn list(journal_dir: &Path) -> Result<Vec<Self>> {
+        if !journal_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(journal_dir)?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && p.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+            .collect();
+        paths.sort();
+
+        let mut runs = Vec::new();
+        for path in paths {
+            match Self::load_file(&path) {
+                Ok(run) => runs.push(run),
+                Err(e) => tracing::warn!("Skipping unreadable journal {}: {}", path.display(), e),
+            }
+        }
+        Ok(runs)
+    }
+
+    /// Delete runs that started more than `max_age` ago, with their trash
+    pub fn prune(journal_dir: &Path, max_age: std::time::Duration) -> Result<PruneReport> {
+        let mut report = PruneReport::default();
+        let cutoff = chrono::Duration::from_std(max_age)
+            .ok()
+            .and_then(|age| chrono::Local::now().checked_sub_signed(age));
+        let Some(cutoff) = cutoff else {
+            return Ok(report);
+        };
+
+        for run in Self::list(journal_dir)? {
+            let started = chrono::DateTime::parse_from_rfc3339(&run.header.started_at)
+                .with_context(|| format!("Invalid start time in {}", run.path.display()))?;
+            if started > cutoff {
+                continue;
+            }
+
+            for trashed in run.trashed_files() {
+                if let Ok(metadata) = fs::metadata(trashed) {
+                    report.bytes += metadata.len();
+                    fs::remove_file(trashed)
+                        .with_context(|| format!("Failed to remove {}", trashed.display()))?;
+                }
+            }
+            run.remove_empty_trash_dirs();
+            // Runs from older versions kept their trash in the journal directory
+            let legacy_trash = run.path.with_extension("");
+            if legacy_trash.is_dir() {
+                report.bytes += dir_size(&legacy_trash);
+                fs::remove_dir_all(&legacy_trash)
+                    .with_context(|| format!("Failed to remove {}", legacy_trash.display()))?;
+            }
+            let _ = fs::remove_file(undone_marker(&run.path));
+            fs::remove_file(&run.path)
+                .with_context(|| format!("Failed to remove {}", run.path.display()))?;
+            report.runs += 1;
+        }
+
+        Ok(report)
+    }
+
+    /// Files this run moved into trash folders
+    fn trashed_files(&self) -> impl Iterator<Item = &Path> {
+        self.entries.iter().filter_map(|entry| match &entry.op {
+            JournalOp::Replace { trashed, .. } | JournalOp::Delete { trashed, .. } => Some(trashed.as_path()),
+            _ => None,
+        })
+    }
+
+    /// Remove this run's trash folders once empty
+    fn remove_empty_trash_dirs(&self) {
+        for trashed in self.trashed_files() {
+            remove_empty_trash_dir(trashed);
+        }
+        let legacy = self.path.with_extension("");
+        if is_empty_dir(&legacy) {
+            let _ = fs::remove_dir(&legacy);
+        }
+    }
+
+    fn load_file(path: &Path) -> Result<Self> {
+        let file = fs::File::open(path)
+            .with_context(|| format!("Failed to open journal: {}", path.display()))?;
+        let mut lines = BufReader::new(file).lines();
+
+        let header: RunHeader = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)
+                .with_context(|| format!("Invalid journal header in {}", path.display()))?,
+            None => bail!("Empty journal: {}", path.display()),
+        };
+
+        let mut entries = Vec::new();
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(
+                serde_json::from_str(&line)
+                    .with_context(|| format!("Invalid journal entry in {}", path.display()))?,
+            );
+        }
+
+        Ok(Self {
+            header,
+            entries,
+            undone: undone_marker(path).exists(),
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Changes that can no longer be reverted cleanly
+    pub fn conflicts(&self) -> Vec<String> {
+        self.entries.iter().rev().filter_map(|e| e.op.conflict()).collect()
+    }
+
+    /// Revert every change in reverse order and mark the run as undone
+    ///
+    /// Fails without touching anything when there are conflicts, unless
+    /// `force` is set, in which case conflicting changes are skipped.
+    pub fn undo(&self, force: bool) -> Result<UndoReport> {
+        if self.undone {
+            bail!("Run {} has already been undone", self.header.run_id);
+        }
+
+        let conflicts = self.conflicts();
+        if !conflicts.is_empty() && !force {
+            bail!(
+                "Cannot undo run {}: {} conflict(s):\n  {}",
+                self.header.run_id,
+                conflicts.len(),
+                conflicts.join("\n  ")
+            );
+        }
+
+        let mut report = UndoReport::default();
+        for entry in self.entries.iter().rev() {
+            if let Some(conflict) = entry.op.conflict() {
+                report.skipped.push(conflict);
+                continue;
+            }
+            entry
+                .op
+                .revert()
+                .with_context(|| format!("Failed to revert: {}", entry.op.describe()))?;
+            report.reverted += 1;
+        }
+
+        // Drop the trash folders once everything in them has been restored
+        self.remove_empty_trash_dirs();
+
+        fs::write(undone_marker(&self.path), chrono::Local::now().to_rfc3339())
+            .context("Failed to mark run as undone")?;
+
+        Ok(report)
+    }
+}
+
+/// Outcome of pruning the journal
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Runs deleted
+    pub runs: usize,
+    /// Bytes of kept originals freed
+    pub bytes: u64,
+}
+
+/// Outcome of an undo
+#[derive(Debug, Default)]
+pub struct UndoReport {
+    /// Number of changes reverted
+    pub reverted: usize,
+    /// Conflicting changes skipped with `force`
+    pub skipped: Vec<String>,
+}
+
+fn run_file(journal_dir: &Path, run_id: &str) -> PathBuf {
+    journal_dir.join(format!("{}.jsonl", run_id))
+}
+
+fn undone_marker(run_file: &Path) -> PathBuf {
+    run_file.with_extension("undone")
+}
+
+/// Total size of the files below a directory
+fn dir_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Remove the run folder a trashed file was in, and the trash folder above
+/// it, if nothing else is left in them
+fn remove_empty_trash_dir(trashed: &Path) {
+    let Some(run_dir) = trashed.parent() else {
+        return;
+    };
+    if is_empty_dir(run_dir) && fs::remove_dir(run_dir).is_ok() {
+        if let Some(trash_dir) = run_dir.parent().filter(|p| p.ends_with(TRASH_DIR) && is_empty_dir(p)) {
+            let _ = fs::remove_dir(trash_dir);
+        }
+    }
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path).map(|mut d| d.next().is_none()).unwrap_or(false)
+}
+
+/// Rename, falling back to copy + delete across filesystems
+fn move_path(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    if from.is_file() {
+        fs::copy(from, to)
+            .with_context(|| format!("Failed to move {} to {}", from.display(), to.display()))?;
+        fs::remove_file(from)
+            .with_context(|| format!("Failed to remove {}", from.display()))?;
+        return Ok(());
+    }
+
+    fs::rename(from, to)
+        .with_context(|| format!("Failed to move {} to {}", from.display(), to.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_run_file_is_created_lazily() {
+        let dir = tempdir().unwrap();
+        let journal_dir = dir.path().join("journal");
+        let journal = Journal::start(&journal_dir);
+        assert!(!journal_dir.exists());
+
+        let file = dir.path().join("out.m4b");
+        fs::write(&file, b"audio").unwrap();
+        journal.record_created(&file).unwrap();
+
+        let run = JournalRun::load(&journal_dir, &journal.run_id()).unwrap();
+        assert_eq!(run.entries.len(), 1);
+        assert!(!run.undone);
+    }
+
+    #[test]
+    fn test_undo_reverts_moves_deletes_and_dirs() {
+        let dir = tempdir().unwrap();
+        let journal_dir = dir.path().join("journal");
+        let journal = Journal::start(&journal_dir);
+
+        let library = dir.path().join("library");
+        let book = library.join("Book");
+        fs::create_dir_all(&book).unwrap();
+        fs::write(book.join("01.mp3"), b"one").unwrap();
+        fs::write(book.join("02.mp3"), b"two").unwrap();
+
+        let target = library.join("M4B").join("Author");
+        journal.create_dir_all(&target).unwrap();
+        journal.rename(&book, &target.join("Book")).unwrap();
+        journal.remove_file(&target.join("Book").join("02.mp3")).unwrap();
+        fs::write(target.join("Book").join("Book.m4b"), b"m4b").unwrap();
+        journal.record_created(&target.join("Book").join("Book.m4b")).unwrap();
+
+        let run = JournalRun::latest(&journal_dir).unwrap().unwrap();
+        assert!(run.conflicts().is_empty());
+        let report = run.undo(false).unwrap();
+        assert_eq!(report.reverted, 5);
+
+        assert_eq!(fs::read(book.join("01.mp3")).unwrap(), b"one");
+        assert_eq!(fs::read(book.join("02.mp3")).unwrap(), b"two");
+        assert!(!book.join("Book.m4b").exists());
+        assert!(!book.join(TRASH_DIR).exists());
+        assert!(!library.join("M4B").exists());
+
+        // Already undone
+        assert!(JournalRun::latest(&journal_dir).unwrap().is_none());
+        assert!(JournalRun::load(&journal_dir, &journal.run_id()).unwrap().undo(false).is_err());
+    }
+
+    #[test]
+    fn test_undo_restores_replaced_file() {
+        let dir = tempdir().unwrap();
+        let journal_dir = dir.path().join("journal");
+        let journal = Journal::start(&journal_dir);
+
+        // `build --force` over an existing output, and a fresh output next to it
+        let output = dir.path().join("Book.m4b");
+        fs::write(&output, b"previous build").unwrap();
+        let partial = dir.path().join(".Book.partial.m4b");
+        fs::write(&partial, b"new build").unwrap();
+        journal.commit_file(&partial, &output).unwrap();
+        let fresh = dir.path().join("Other.m4b");
+        fs::write(&partial, b"other").unwrap();
+        journal.commit_file(&partial, &fresh).unwrap();
+        assert_eq!(fs::read(&output).unwrap(), b"new build");
+
+        let run = JournalRun::load(&journal_dir, &journal.run_id()).unwrap();
+        assert!(matches!(run.entries[0].op, JournalOp::Replace { .. }));
+        assert!(matches!(run.entries[1].op, JournalOp::Create { .. }));
+        assert_eq!(run.undo(false).unwrap().reverted, 2);
+        assert_eq!(fs::read(&output).unwrap(), b"previous build");
+        assert!(!fresh.exists());
+    }
+
+    #[test]
+    fn test_prune_frees_kept_originals() {
+        let dir = tempdir().unwrap();
+        let journal_dir = dir.path().join("journal");
+        let journal = Journal::start(&journal_dir);
+
+        // Originals are kept in the library's own trash folder, not the journal's
+        let library = dir.path().join("library");
+        let book = library.join("Book");
+        fs::create_dir_all(&book).unwrap();
+        journal.add_trash_root(&library);
+        let original = book.join("01.mp3");
+        fs::write(&original, b"original audio").unwrap();
+        journal.remove_file(&original).unwrap();
+        let trash_dir = library.join(TRASH_DIR).join(journal.run_id());
+        assert!(trash_dir.join("01.mp3").exists());
+        assert!(!journal_dir.join(journal.run_id()).exists());
+
+        // Recent runs are kept
+        let week = std::time::Duration::from_secs(7 * 24 * 3600);
+        assert_eq!(JournalRun::prune(&journal_dir, week).unwrap(), PruneReport::default());
+        assert_eq!(JournalRun::list(&journal_dir).unwrap().len(), 1);
+
+        let report = JournalRun::prune(&journal_dir, std::time::Duration::ZERO).unwrap();
+        assert_eq!(report, PruneReport { runs: 1, bytes: 14 });
+        assert!(!library.join(TRASH_DIR).exists());
+        assert!(JournalRun::list(&journal_dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_undo_detects_conflicts() {
+        let dir = tempdir().unwrap();
+        let journal_dir = dir.path().join("journal");
+        let journal = Journal::start(&journal_dir);
+
+        let src = dir.path().join("a.m4b");
+        let dst = dir.path().join("b.m4b");
+        fs::write(&src, b"audio").unwrap();
+        journal.rename(&src, &dst).unwrap();
+
+        // Someone edits the file afterwards
+        fs::write(&dst, b"changed audio").unwrap();
+
+        let run = JournalRun::load(&journal_dir, &journal.run_id()).unwrap();
+        assert_eq!(run.conflicts().len(), 1);
+        assert!(run.undo(false).is_err());
+        assert!(dst.exists());
+
+        let report = run.undo(true).unwrap();
+        assert_eq!(report.reverted, 0);
+        assert_eq!(report.skipped.len(), 1);
+    }
+}
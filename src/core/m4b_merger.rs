@@ -1,33 +1,50 @@
 //! M4B file merger for lossless concatenation
 
-use crate::audio::{read_m4b_chapters, merge_chapter_lists, Chapter, FFmpeg};
-use crate::audio::{write_mp4box_chapters, inject_chapters_mp4box, inject_metadata_atomicparsley};
+use crate::audio::{merge_chapter_lists, AudioBackend, Chapter, FFmpeg, FfmpegBackend};
+use crate::core::{BookTempDir, Journal, PartialOutput};
 use crate::models::BookFolder;
-use crate::utils::sort_by_part_number;
+use crate::utils::{sort_by_part_number, stage_span};
 use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::Instrument;
 
 /// Merger for combining multiple M4B files
 pub struct M4bMerger {
-    ffmpeg: FFmpeg,
+    backend: Arc<dyn AudioBackend>,
     keep_temp: bool,
+    /// Where temp directories are created (None = system temp dir)
+    temp_root: Option<PathBuf>,
+    /// Journal recording the merged outputs
+    journal: Option<Journal>,
 }
 
 impl M4bMerger {
     /// Create a new M4B merger
     pub fn new() -> Result<Self> {
-        Ok(Self {
-            ffmpeg: FFmpeg::new()?,
-            keep_temp: false,
-        })
+        Self::with_options(false)
     }
 
     /// Create merger with options
     pub fn with_options(keep_temp: bool) -> Result<Self> {
-        Ok(Self {
-            ffmpeg: FFmpeg::new()?,
-            keep_temp,
-        })
+        Ok(Self::with_backend(FfmpegBackend::shared()?, keep_temp))
+    }
+
+    /// Create merger with a specific audio backend
+    pub fn with_backend(backend: Arc<dyn AudioBackend>, keep_temp: bool) -> Self {
+        Self { backend, keep_temp, temp_root: None, journal: None }
+    }
+
+    /// Create temp directories under `dir` instead of the system temp dir
+    pub fn with_temp_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.temp_root = dir;
+        self
+    }
+
+    /// Record merged outputs (and the files they replace) in a journal
+    pub fn with_journal(mut self, journal: Option<Journal>) -> Self {
+        self.journal = journal;
+        self
     }
 
     /// Merge multiple M4B files into one
@@ -55,7 +72,7 @@ impl M4bMerger {
         let mut all_chapters: Vec<Vec<Chapter>> = Vec::new();
 
         for m4b_file in &m4b_files {
-            let chapters = match read_m4b_chapters(m4b_file).await {
+            let chapters = match self.backend.read_chapters(m4b_file).await {
                 Ok(chapters) => {
                     tracing::debug!(
                         "  {} chapters from: {}",
@@ -106,13 +123,19 @@ impl M4bMerger {
         FFmpeg::create_concat_file(&file_refs, &concat_file)?;
 
         // Step 3: Concatenate audio losslessly
-        let output_filename = book_folder.get_output_filename();
-        let output_path = output_dir.join(&output_filename);
+        let output_path = book_folder.get_output_path(output_dir);
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create output directory")?;
+        }
 
         tracing::info!("Concatenating audio (lossless copy mode)...");
 
-        self.ffmpeg
-            .concat_m4b_files(&concat_file, &output_path)
+        // Finish the file under a temp name, so it only appears once complete
+        let output = PartialOutput::new(&output_path);
+        self.backend
+            .concat_m4b_files(&concat_file, output.path())
+            .instrument(stage_span("merge"))
             .await
             .context("Failed to concatenate M4B files")?;
 
@@ -121,23 +144,22 @@ impl M4bMerger {
             tracing::info!("Injecting {} merged chapters...", merged_chapters.len());
 
             let chapters_file = temp_dir.join("chapters.txt");
-            write_mp4box_chapters(&merged_chapters, &chapters_file)?;
-
-            inject_chapters_mp4box(&output_path, &chapters_file)
+            self.backend
+                .inject_chapters(output.path(), &merged_chapters, &chapters_file)
+                .instrument(stage_span("chapters"))
                 .await
                 .context("Failed to inject chapters")?;
         }
 
         // Step 5: Copy metadata from first file
         tracing::info!("Copying metadata from first source file...");
-        self.copy_metadata_from_first(&m4b_files[0], &output_path, book_folder).await?;
+        self.copy_metadata_from_first(&m4b_files[0], output.path(), book_folder)
+            .instrument(stage_span("metadata"))
+            .await?;
+        let output_path = output.commit(self.journal.as_ref())?;
 
         // Clean up
-        if !self.keep_temp {
-            if let Err(e) = std::fs::remove_dir_all(&temp_dir) {
-                tracing::warn!("Failed to remove temp directory: {}", e);
-            }
-        }
+        drop(temp_dir);
 
         tracing::info!("M4B merge complete: {}", output_path.display());
 
@@ -152,7 +174,7 @@ impl M4bMerger {
     /// `merge_chapter_lists` renumbers chapters sequentially across all files.
     async fn synthesize_file_chapter(&self, m4b_file: &Path) -> Result<Chapter> {
         let (duration_ms, embedded_title) =
-            self.ffmpeg.probe_duration_and_title(m4b_file).await?;
+            self.backend.probe_duration_and_title(m4b_file).await?;
 
         let title = embedded_title.unwrap_or_else(|| {
             m4b_file
@@ -172,49 +194,25 @@ impl M4bMerger {
         book_folder: &BookFolder,
     ) -> Result<()> {
         // Extract metadata from first file using ffprobe
-        let metadata = self.ffmpeg.probe_metadata(source).await?;
+        let mut metadata = self.backend.probe_metadata(source).await?;
 
         // Use folder name as title if not in metadata
-        let title = metadata.title.or_else(|| Some(book_folder.name.clone()));
-        let artist = metadata.artist;
-        let album = metadata.album.or_else(|| title.clone());
-        let album_artist = metadata.album_artist;
-        let year = metadata.year;
-        let genre = metadata.genre;
-        let composer = metadata.composer;
-        let comment = metadata.comment;
-
-        inject_metadata_atomicparsley(
-            output,
-            title.as_deref(),
-            artist.as_deref(),
-            album.as_deref(),
-            album_artist.as_deref(),
-            year,
-            genre.as_deref(),
-            composer.as_deref(),
-            comment.as_deref(),
-            book_folder.cover_file.as_deref(),
-        )
-        .await
-        .context("Failed to inject metadata")?;
+        let title = metadata.title.take().unwrap_or_else(|| book_folder.name.clone());
+        metadata.album = metadata.album.or_else(|| Some(title.clone()));
+        metadata.title = Some(title);
+
+        self.backend
+            .write_metadata(output, &metadata, book_folder.cover_file.as_deref())
+            .await
+            .context("Failed to inject metadata")?;
 
         Ok(())
     }
 
     /// Create temporary directory
-    fn create_temp_dir(&self, book_name: &str) -> Result<PathBuf> {
-        let temp_base = std::env::temp_dir();
-        let sanitized_name = sanitize_filename::sanitize(book_name);
-        let temp_dir = temp_base.join(format!("audiobook-forge-merge-{}", sanitized_name));
-
-        if temp_dir.exists() {
-            std::fs::remove_dir_all(&temp_dir).ok();
-        }
-
-        std::fs::create_dir_all(&temp_dir).context("Failed to create temp directory")?;
-
-        Ok(temp_dir)
+    fn create_temp_dir(&self, book_name: &str) -> Result<BookTempDir> {
+        let temp_base = self.temp_root.clone().unwrap_or_else(std::env::temp_dir);
+        BookTempDir::create(&temp_base, "merge-", book_name, self.keep_temp)
     }
 }
 
@@ -223,3 +221,52 @@ impl Default for M4bMerger {
         Self::new().expect("Failed to create M4B merger")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{BackendOp, RecordingBackend};
+
+    #[tokio::test]
+    async fn test_merge_m4b_files_with_fake_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        let parts: Vec<PathBuf> = (1..=3)
+            .map(|i| dir.path().join(format!("Book - Part {}.m4b", i)))
+            .collect();
+        for part in &parts {
+            std::fs::write(part, b"").unwrap();
+        }
+
+        // Part 2 has no chapters, so it becomes one chapter spanning the file
+        let backend = Arc::new(
+            RecordingBackend::new()
+                .with_chapters(&parts[0], vec![Chapter::new(1, "One".into(), 0, 60_000), Chapter::new(2, "Two".into(), 60_000, 120_000)])
+                .with_chapters(&parts[2], vec![Chapter::new(1, "Four".into(), 0, 30_000)]),
+        );
+        let mut book = BookFolder::new(dir.path().join("merge-fake-test"));
+        // Out of order on purpose: parts are sorted before merging
+        book.m4b_files = vec![parts[2].clone(), parts[0].clone(), parts[1].clone()];
+
+        let output = M4bMerger::with_backend(backend.clone(), false)
+            .merge_m4b_files(&book, &dir.path().join("out"))
+            .await
+            .unwrap();
+        assert_eq!(output, dir.path().join("out/merge-fake-test.m4b"));
+        assert!(output.exists());
+        assert!(!dir.path().join("out/.merge-fake-test.partial.m4b").exists());
+
+        let concat = &backend.calls_of(BackendOp::ConcatM4b)[0];
+        let expected: Vec<PathBuf> = parts.iter().map(|p| p.canonicalize().unwrap()).collect();
+        assert_eq!(concat.inputs, expected);
+
+        let chapters = &backend.calls_of(BackendOp::InjectChapters)[0].chapters;
+        let titles: Vec<&str> = chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["One", "Two", "Book - Part 2", "Four"]);
+        assert_eq!(chapters[2].start_time_ms, 120_000);
+        assert_eq!(chapters[3].start_time_ms, 720_000);
+
+        let metadata = backend.calls_of(BackendOp::WriteMetadata)[0].metadata.clone().unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("merge-fake-test"));
+        assert_eq!(metadata.album.as_deref(), Some("merge-fake-test"));
+    }
+}
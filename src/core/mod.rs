@@ -3,23 +3,44 @@
 //! This module contains the main business logic for audiobook processing:
 //! - Scanner: Directory scanning and book folder discovery
 //! - Analyzer: Audio file analysis and quality detection
+//! - Ordering: Track order from file names, tags, titles or a playlist
+//! - Integrity: Missing, duplicated and foreign tracks
 //! - Processor: Single book processing (FFmpeg, metadata, chapters)
 //! - BatchProcessor: Parallel batch processing
+//! - SizeBudget: Output quality that fits a size limit
+//! - Trim: Intro/outro and silence trimming
+//! - DiskSpace: Free space checks for temp files and outputs
+//! - BookTempDir/PartialOutput: Temp files and unfinished outputs that clean up after themselves
+//! - Journal: Transaction log of filesystem changes for `undo`
 
 mod scanner;
 mod analyzer;
+mod ordering;
+mod integrity;
 mod processor;
 mod batch;
 mod progress;
 mod retry;
 mod organizer;
 mod m4b_merger;
+mod journal;
+mod size_budget;
+mod trim;
+mod disk_space;
+mod temp_files;
 
 pub use scanner::Scanner;
 pub use analyzer::Analyzer;
-pub use processor::Processor;
+pub use ordering::{order_tracks, OrderConflict, TrackOrdering};
+pub use integrity::{check_integrity, IntegrityIssue};
+pub use processor::{EncodePlan, OutputProfile, Processor};
 pub use batch::BatchProcessor;
 pub use progress::{BatchProgress, BookProgress, ProcessingStage};
 pub use retry::{RetryConfig, classify_error, retry_async, smart_retry_async, ErrorType};
 pub use organizer::{Organizer, OrganizeResult, OrganizeAction};
 pub use m4b_merger::M4bMerger;
+pub use size_budget::{fit_to_size, format_size, parse_size, plan_for_budget, SizeBudget};
+pub use trim::BookTrim;
+pub use temp_files::{sweep_orphaned_temp_dirs, BookTempDir, PartialOutput, TEMP_DIR_PREFIX};
+pub use disk_space::{available_space, min_free_space, plan_batch, BatchSpace, DiskSpace, FreeSpace, SpaceClaim, SpaceNeeds};
+pub use journal::{Journal, JournalRun, JournalEntry, JournalOp, Fingerprint, RunHeader, PruneReport, UndoReport};
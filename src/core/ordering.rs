@@ -0,0 +1,563 @@
+//! Track ordering
+//!
+//! Puts a book's tracks in order using one of several signals: natural file
+//! name order, disc/track number tags, numbers in the title tags, or an M3U
+//! playlist in the folder. `auto` picks the most consistent signal. Duplicate
+//! numbers, gaps and disagreement between tag and file name order are
+//! reported as [`OrderConflict`]s.
+
+use crate::models::{Track, TrackOrder};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Something inconsistent about a book's track order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OrderConflict {
+    /// Several tracks carry the same number
+    Duplicate {
+        signal: TrackOrder,
+        number: String,
+        files: Vec<String>,
+    },
+    /// Numbers missing from the sequence
+    Gap {
+        signal: TrackOrder,
+        disc: Option<u32>,
+        missing: Vec<u32>,
+    },
+    /// Tracks without a number (ordered after the numbered ones)
+    Unnumbered { signal: TrackOrder, files: Vec<String> },
+    /// Two signals put tracks in a different order
+    Mismatch {
+        signal: TrackOrder,
+        other: TrackOrder,
+        /// First position (1-based) where the orders differ
+        position: usize,
+        file: String,
+        other_file: String,
+    },
+    /// Tracks missing from the order file (appended in file name order)
+    Unlisted { files: Vec<String> },
+}
+
+impl fmt::Display for OrderConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duplicate { signal, number, files } => {
+                write!(f, "duplicate {} number {}: {}", signal, number, files.join(", "))
+            }
+            Self::Gap { signal, disc, missing } => {
+                let missing: Vec<String> = missing.iter().map(u32::to_string).collect();
+                write!(f, "{} numbers missing", signal)?;
+                if let Some(disc) = disc {
+                    write!(f, " on disc {}", disc)?;
+                }
+                write!(f, ": {}", missing.join(", "))
+            }
+            Self::Unnumbered { signal, files } => {
+                write!(f, "no {} number: {}", signal, files.join(", "))
+            }
+            Self::Mismatch { signal, other, position, file, other_file } => write!(
+                f,
+                "{} order differs from {} order at track {} ({} vs {})",
+                signal, other, position, file, other_file
+            ),
+            Self::Unlisted { files } => write!(f, "not in the order file: {}", files.join(", ")),
+        }
+    }
+}
+
+/// How a book's tracks were ordered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrackOrdering {
+    /// Signal the tracks were ordered by (never `auto`)
+    pub strategy: TrackOrder,
+    /// Playlist or override file the order came from
+    pub order_file: Option<PathBuf>,
+    pub conflicts: Vec<OrderConflict>,
+}
+
+impl TrackOrdering {
+    /// Ordering taken as-is from an explicit list
+    pub fn explicit(source: PathBuf) -> Self {
+        Self {
+            strategy: TrackOrder::OrderFile,
+            order_file: Some(source),
+            conflicts: Vec::new(),
+        }
+    }
+}
+
+/// Sort `tracks` by `strategy`, reporting any conflicts
+///
+/// `folder` is searched for a playlist when the strategy is `order_file` or
+/// `auto`. With `order_file` and no playlist, file name order is used.
+pub fn order_tracks(tracks: &mut [Track], strategy: TrackOrder, folder: &Path) -> TrackOrdering {
+    // Everything starts from natural file name order, which breaks ties
+    tracks.sort_by(|a, b| natord::compare(&a.file_path.to_string_lossy(), &b.file_path.to_string_lossy()));
+    let by_filename: Vec<PathBuf> = tracks.iter().map(|t| t.file_path.clone()).collect();
+
+    let playlist = match strategy {
+        TrackOrder::Auto | TrackOrder::OrderFile => find_playlist(folder),
+        _ => None,
+    };
+
+    let tags = Numbering::of(tracks, TrackOrder::Tags, |t| {
+        t.track_number.map(|n| (t.disc_number.unwrap_or(1), n))
+    });
+    let filename = Numbering::of_filenames(tracks);
+    let title = Numbering::of(tracks, TrackOrder::Title, |t| {
+        t.title.as_deref().and_then(first_number).map(|n| (1, n))
+    });
+
+    let chosen = match strategy {
+        TrackOrder::Auto if playlist.is_some() => TrackOrder::OrderFile,
+        TrackOrder::Auto => [&tags, &filename, &title]
+            .into_iter()
+            .max_by_key(|n| (n.is_complete(), n.is_gapless(), std::cmp::Reverse(n.rank())))
+            .filter(|n| n.is_complete())
+            .map_or(TrackOrder::Filename, |n| n.signal),
+        TrackOrder::OrderFile if playlist.is_none() => {
+            tracing::warn!("No .m3u/.m3u8 order file in {}; using file name order", folder.display());
+            TrackOrder::Filename
+        }
+        other => other,
+    };
+
+    let mut ordering = TrackOrdering {
+        strategy: chosen,
+        order_file: None,
+        conflicts: Vec::new(),
+    };
+
+    match chosen {
+        TrackOrder::Tags => {
+            ordering.conflicts = tags.conflicts();
+            sort_by_numbers(tracks, &tags);
+        }
+        TrackOrder::Title => {
+            ordering.conflicts = title.conflicts();
+            sort_by_numbers(tracks, &title);
+        }
+        TrackOrder::OrderFile => {
+            if let Some((path, entries)) = playlist {
+                let unlisted = sort_by_playlist(tracks, &entries);
+                if !unlisted.is_empty() {
+                    ordering.conflicts.push(OrderConflict::Unlisted { files: unlisted });
+                }
+                ordering.order_file = Some(path);
+            }
+        }
+        TrackOrder::Filename | TrackOrder::Auto => {
+            // Names like "Intro.mp3" alone don't make a numbering problem
+            if filename.is_mostly_numbered() {
+                ordering.conflicts = filename.conflicts();
+            }
+        }
+    }
+
+    // Compare with the tags, or with file name order when the tags were used
+    let ordered: Vec<PathBuf> = tracks.iter().map(|t| t.file_path.clone()).collect();
+    let comparison = if chosen == TrackOrder::Tags {
+        Some((TrackOrder::Filename, by_filename))
+    } else if tags.is_complete() {
+        let mut by_tags = tracks.to_vec();
+        sort_by_numbers(&mut by_tags, &tags);
+        Some((TrackOrder::Tags, by_tags.into_iter().map(|t| t.file_path).collect()))
+    } else {
+        None
+    };
+    if let Some((other, other_order)) = comparison {
+        if let Some(position) = ordered.iter().zip(&other_order).position(|(a, b)| a != b) {
+            ordering.conflicts.push(OrderConflict::Mismatch {
+                signal: chosen,
+                other,
+                position: position + 1,
+                file: file_name(&ordered[position]),
+                other_file: file_name(&other_order[position]),
+            });
+        }
+    }
+
+    ordering
+}
+
+/// Numbers one signal gives each track, as (disc, number)
+struct Numbering {
+    signal: TrackOrder,
+    numbers: Vec<(PathBuf, Option<(u32, u32)>)>,
+}
+
+impl Numbering {
+    fn of(tracks: &[Track], signal: TrackOrder, number: impl Fn(&Track) -> Option<(u32, u32)>) -> Self {
+        Self {
+            signal,
+            numbers: tracks.iter().map(|t| (t.file_path.clone(), number(t))).collect(),
+        }
+    }
+
+    /// First number in each file name after the prefix all names share
+    fn of_filenames(tracks: &[Track]) -> Self {
+        let stems: Vec<String> = tracks
+            .iter()
+            .map(|t| t.file_path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default())
+            .collect();
+        let prefix = common_prefix(&stems);
+        let numbers = tracks
+            .iter()
+            .zip(&stems)
+            .map(|(t, stem)| (t.file_path.clone(), first_number(&stem[prefix..]).map(|n| (1, n))))
+            .collect();
+        Self {
+            signal: TrackOrder::Filename,
+            numbers,
+        }
+    }
+
+    /// Preference among equally consistent signals (lower wins)
+    fn rank(&self) -> u8 {
+        match self.signal {
+            TrackOrder::Tags => 0,
+            TrackOrder::Filename => 1,
+            _ => 2,
+        }
+    }
+
+    /// Every track numbered, with no number used twice
+    fn is_complete(&self) -> bool {
+        !self.numbers.is_empty()
+            && self.numbers.iter().all(|(_, n)| n.is_some())
+            && self.duplicates().is_empty()
+    }
+
+    /// More than half of the tracks numbered
+    fn is_mostly_numbered(&self) -> bool {
+        self.numbers.iter().filter(|(_, n)| n.is_some()).count() * 2 > self.numbers.len()
+    }
+
+    fn is_gapless(&self) -> bool {
+        self.gaps().is_empty()
+    }
+
+    fn duplicates(&self) -> BTreeMap<(u32, u32), Vec<&Path>> {
+        let mut seen: BTreeMap<(u32, u32), Vec<&Path>> = BTreeMap::new();
+        for (path, number) in &self.numbers {
+            if let Some(number) = number {
+                seen.entry(*number).or_default().push(path);
+            }
+        }
+        seen.retain(|_, files| files.len() > 1);
+        seen
+    }
+
+    /// Numbers missing per disc, counting from 1 (or 0 if used)
+    fn gaps(&self) -> BTreeMap<u32, Vec<u32>> {
+        let mut discs: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for (disc, number) in self.numbers.iter().filter_map(|(_, n)| *n) {
+            discs.entry(disc).or_default().push(number);
+        }
+        discs
+            .into_iter()
+            .filter_map(|(disc, numbers)| {
+                let first = numbers.iter().copied().min().unwrap_or(1).min(1);
+                let last = numbers.iter().copied().max().unwrap_or(0);
+                let missing: Vec<u32> = (first..=last).filter(|n| !numbers.contains(n)).collect();
+                (!missing.is_empty()).then_some((disc, missing))
+            })
+            .collect()
+    }
+
+    fn conflicts(&self) -> Vec<OrderConflict> {
+        let multi_disc = self.numbers.iter().filter_map(|(_, n)| n.map(|(disc, _)| disc)).any(|d| d != 1);
+        let label = |(disc, number): (u32, u32)| {
+            if multi_disc {
+                format!("{}-{}", disc, number)
+            } else {
+                number.to_string()
+            }
+        };
+
+        let mut conflicts: Vec<OrderConflict> = self
+            .duplicates()
+            .into_iter()
+            .map(|(number, files)| OrderConflict::Duplicate {
+                signal: self.signal,
+                number: label(number),
+                files: files.into_iter().map(file_name).collect(),
+            })
+            .collect();
+
+        conflicts.extend(self.gaps().into_iter().map(|(disc, missing)| OrderConflict::Gap {
+            signal: self.signal,
+            disc: multi_disc.then_some(disc),
+            missing,
+        }));
+
+        let unnumbered: Vec<String> = self
+            .numbers
+            .iter()
+            .filter(|(_, n)| n.is_none())
+            .map(|(path, _)| file_name(path))
+            .collect();
+        if !unnumbered.is_empty() {
+            conflicts.push(OrderConflict::Unnumbered {
+                signal: self.signal,
+                files: unnumbered,
+            });
+        }
+        conflicts
+    }
+}
+
+/// Stable sort by number; unnumbered tracks go last
+fn sort_by_numbers(tracks: &mut [Track], numbering: &Numbering) {
+    let number_of = |track: &Track| {
+        numbering
+            .numbers
+            .iter()
+            .find(|(path, _)| path == &track.file_path)
+            .and_then(|(_, n)| *n)
+    };
+    tracks.sort_by_key(|t| number_of(t).map_or((1, 0, 0), |(disc, n)| (0, disc, n)));
+}
+
+/// Sort by position in a playlist, returning the tracks it doesn't list
+fn sort_by_playlist(tracks: &mut [Track], entries: &[String]) -> Vec<String> {
+    let position = |track: &Track| {
+        let name = file_name(&track.file_path);
+        entries.iter().position(|e| e.eq_ignore_ascii_case(&name))
+    };
+    tracks.sort_by_key(|t| position(t).unwrap_or(usize::MAX));
+    tracks
+        .iter()
+        .filter(|t| position(t).is_none())
+        .map(|t| file_name(&t.file_path))
+        .collect()
+}
+
+/// First `.m3u`/`.m3u8` playlist in a folder, with the file names it lists
+fn find_playlist(folder: &Path) -> Option<(PathBuf, Vec<String>)> {
+    let mut playlists: Vec<PathBuf> = std::fs::read_dir(folder)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case("m3u") || e.eq_ignore_ascii_case("m3u8"))
+        })
+        .collect();
+    crate::utils::natural_sort(&mut playlists);
+    let path = playlists.into_iter().next()?;
+
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            tracing::warn!("Cannot read order file {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    Some((path, parse_playlist(&contents)))
+}
+
+/// File names listed in an M3U playlist
+fn parse_playlist(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.trim().trim_start_matches('\u{feff}'))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.rsplit(['/', '\\']).next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// First run of digits in a string
+fn first_number(text: &str) -> Option<u32> {
+    static NUMBER: OnceLock<Regex> = OnceLock::new();
+    NUMBER
+        .get_or_init(|| Regex::new(r"\d+").unwrap())
+        .find(text)
+        .and_then(|m| m.as_str().parse().ok())
+}
+
+/// Length in bytes of the prefix all strings share, not ending inside a number
+fn common_prefix(strings: &[String]) -> usize {
+    let Some((first, rest)) = strings.split_first() else {
+        return 0;
+    };
+    if rest.is_empty() {
+        return 0;
+    }
+    let mut len = first.len();
+    for other in rest {
+        len = first
+            .char_indices()
+            .zip(other.chars())
+            .take_while(|((i, a), b)| *i < len && a == b)
+            .map(|((i, a), _)| i + a.len_utf8())
+            .last()
+            .unwrap_or(0);
+    }
+    first[..len].trim_end_matches(|c: char| c.is_ascii_digit()).len()
+}
+
+fn file_name(path: impl AsRef<Path>) -> String {
+    let path = path.as_ref();
+    path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::QualityProfile;
+    use tempfile::tempdir;
+
+    fn track(name: &str, disc: Option<u32>, number: Option<u32>, title: Option<&str>) -> Track {
+        let quality = QualityProfile::new(64, 44100, 2, "mp3".to_string(), 60.0).unwrap();
+        let mut track = Track::new(PathBuf::from("/book").join(name), quality);
+        track.disc_number = disc;
+        track.track_number = number;
+        track.title = title.map(str::to_string);
+        track
+    }
+
+    fn names(tracks: &[Track]) -> Vec<String> {
+        tracks.iter().map(|t| file_name(&t.file_path)).collect()
+    }
+
+    #[test]
+    fn test_filename_order_is_natural() {
+        let mut tracks = vec![
+            track("10.mp3", None, None, None),
+            track("2.mp3", None, None, None),
+            track("1.mp3", None, None, None),
+        ];
+        let ordering = order_tracks(&mut tracks, TrackOrder::Filename, Path::new("/nonexistent"));
+        assert_eq!(names(&tracks), vec!["1.mp3", "2.mp3", "10.mp3"]);
+        assert_eq!(ordering.strategy, TrackOrder::Filename);
+        assert_eq!(
+            ordering.conflicts,
+            vec![OrderConflict::Gap { signal: TrackOrder::Filename, disc: None, missing: (3..=9).collect() }]
+        );
+    }
+
+    #[test]
+    fn test_tags_order_across_discs() {
+        let mut tracks = vec![
+            track("a.mp3", Some(2), Some(1), None),
+            track("b.mp3", Some(1), Some(2), None),
+            track("c.mp3", Some(1), Some(1), None),
+        ];
+        let ordering = order_tracks(&mut tracks, TrackOrder::Tags, Path::new("/nonexistent"));
+        assert_eq!(names(&tracks), vec!["c.mp3", "b.mp3", "a.mp3"]);
+        assert_eq!(
+            ordering.conflicts,
+            vec![OrderConflict::Mismatch {
+                signal: TrackOrder::Tags,
+                other: TrackOrder::Filename,
+                position: 1,
+                file: "c.mp3".to_string(),
+                other_file: "a.mp3".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_auto_prefers_consistent_tags() {
+        let mut tracks = vec![
+            track("Intro.mp3", None, Some(1), None),
+            track("Chapter.mp3", None, Some(2), None),
+            track("Epilogue.mp3", None, Some(3), None),
+        ];
+        let ordering = order_tracks(&mut tracks, TrackOrder::Auto, Path::new("/nonexistent"));
+        assert_eq!(ordering.strategy, TrackOrder::Tags);
+        assert_eq!(names(&tracks), vec!["Intro.mp3", "Chapter.mp3", "Epilogue.mp3"]);
+    }
+
+    #[test]
+    fn test_auto_skips_duplicate_tags() {
+        // Ripped with every track tagged "1"; the file names are right
+        let mut tracks = vec![
+            track("Book - 02.mp3", None, Some(1), Some("Chapter 9")),
+            track("Book - 01.mp3", None, Some(1), Some("Chapter 3")),
+        ];
+        let ordering = order_tracks(&mut tracks, TrackOrder::Auto, Path::new("/nonexistent"));
+        assert_eq!(ordering.strategy, TrackOrder::Filename);
+        assert_eq!(names(&tracks), vec!["Book - 01.mp3", "Book - 02.mp3"]);
+
+        let ordering = order_tracks(&mut tracks, TrackOrder::Tags, Path::new("/nonexistent"));
+        assert!(ordering.conflicts.contains(&OrderConflict::Duplicate {
+            signal: TrackOrder::Tags,
+            number: "1".to_string(),
+            files: vec!["Book - 01.mp3".to_string(), "Book - 02.mp3".to_string()],
+        }));
+    }
+
+    #[test]
+    fn test_duplicate_file_numbers_reported() {
+        let mut tracks = vec![
+            track("02 (1).mp3", None, None, None),
+            track("02.mp3", None, None, None),
+            track("01.mp3", None, None, None),
+        ];
+        let ordering = order_tracks(&mut tracks, TrackOrder::Auto, Path::new("/nonexistent"));
+        assert_eq!(ordering.strategy, TrackOrder::Filename);
+        assert_eq!(
+            ordering.conflicts,
+            vec![OrderConflict::Duplicate {
+                signal: TrackOrder::Filename,
+                number: "2".to_string(),
+                files: vec!["02 (1).mp3".to_string(), "02.mp3".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_title_numbers_and_unnumbered_tracks() {
+        let mut tracks = vec![
+            track("x.mp3", None, None, Some("Chapter 2")),
+            track("y.mp3", None, None, Some("Credits")),
+            track("z.mp3", None, None, Some("Chapter 1")),
+        ];
+        let ordering = order_tracks(&mut tracks, TrackOrder::Title, Path::new("/nonexistent"));
+        assert_eq!(names(&tracks), vec!["z.mp3", "x.mp3", "y.mp3"]);
+        assert_eq!(
+            ordering.conflicts,
+            vec![OrderConflict::Unnumbered { signal: TrackOrder::Title, files: vec!["y.mp3".to_string()] }]
+        );
+    }
+
+    #[test]
+    fn test_order_file() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("book.m3u"), "#EXTM3U\n#EXTINF:60,Two\nCD1\\two.mp3\nsub/one.mp3\n").unwrap();
+        let mut tracks = vec![
+            track("one.mp3", None, None, None),
+            track("three.mp3", None, None, None),
+            track("two.mp3", None, None, None),
+        ];
+
+        let ordering = order_tracks(&mut tracks, TrackOrder::Auto, dir.path());
+        assert_eq!(ordering.strategy, TrackOrder::OrderFile);
+        assert_eq!(ordering.order_file, Some(dir.path().join("book.m3u")));
+        assert_eq!(names(&tracks), vec!["two.mp3", "one.mp3", "three.mp3"]);
+        assert_eq!(ordering.conflicts, vec![OrderConflict::Unlisted { files: vec!["three.mp3".to_string()] }]);
+
+        // Without a playlist, order_file falls back to file names
+        let ordering = order_tracks(&mut tracks, TrackOrder::OrderFile, Path::new("/nonexistent"));
+        assert_eq!(ordering.strategy, TrackOrder::Filename);
+        assert_eq!(names(&tracks), vec!["one.mp3", "three.mp3", "two.mp3"]);
+    }
+
+    #[test]
+    fn test_common_prefix_keeps_numbers() {
+        let stems = vec!["Book 2 - 01".to_string(), "Book 2 - 10".to_string()];
+        assert_eq!(common_prefix(&stems), "Book 2 - ".len());
+        let stems = vec!["01".to_string(), "02".to_string()];
+        assert_eq!(common_prefix(&stems), 0);
+    }
+}
@@ -1,7 +1,10 @@
 //! Folder organization for audiobooks
 
+use crate::core::Journal;
 use crate::models::{BookFolder, BookCase, Config};
+use crate::utils::{template, PathTemplate, TemplateValues};
 use anyhow::{Context, Result};
+use serde::Serialize;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -23,7 +26,8 @@ pub struct OrganizeResult {
 }
 
 /// Type of organization action
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum OrganizeAction {
     /// Moved to conversion folder
     MovedToConvert,
@@ -57,6 +61,10 @@ pub struct Organizer {
     convert_folder: String,
     /// Dry run mode (don't actually move files)
     dry_run: bool,
+    /// Library layout for finished M4B files (None = move whole folders)
+    template: Option<PathTemplate>,
+    /// Journal recording every move (None = not recorded)
+    journal: Option<Journal>,
 }
 
 impl Organizer {
@@ -67,6 +75,8 @@ impl Organizer {
             m4b_folder: config.organization.m4b_folder.clone(),
             convert_folder: config.organization.convert_folder.clone(),
             dry_run: false,
+            template: None,
+            journal: None,
         }
     }
 
@@ -77,9 +87,23 @@ impl Organizer {
             m4b_folder: config.organization.m4b_folder.clone(),
             convert_folder: config.organization.convert_folder.clone(),
             dry_run,
+            template: None,
+            journal: None,
         }
     }
 
+    /// Lay out finished M4B files inside the M4B folder using a path template
+    pub fn with_template(mut self, template: Option<PathTemplate>) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Record every filesystem change in a journal so it can be undone
+    pub fn with_journal(mut self, journal: Option<Journal>) -> Self {
+        self.journal = journal;
+        self
+    }
+
     /// Organize a single book folder
     pub fn organize_book(&self, book: &BookFolder) -> Result<OrganizeResult> {
         let book_name = book.name.clone();
@@ -110,6 +134,12 @@ impl Organizer {
 
         let target_folder = self.root.join(target_folder_name);
 
+        if book.case == BookCase::C && book.m4b_files.len() == 1 {
+            if let Some(template) = &self.template {
+                return self.organize_with_template(book, template, &target_folder);
+            }
+        }
+
         // Check if already in target folder
         if let Some(parent) = source_path.parent() {
             if parent == target_folder {
@@ -144,19 +174,11 @@ impl Organizer {
         } else {
             // Create target folder if it doesn't exist
             if !target_folder.exists() {
-                fs::create_dir_all(&target_folder)
-                    .with_context(|| format!("Failed to create folder: {}", target_folder.display()))?;
+                self.create_dir_all(&target_folder)?;
             }
 
             // Move the folder
-            fs::rename(&source_path, &final_destination)
-                .with_context(|| {
-                    format!(
-                        "Failed to move {} to {}",
-                        source_path.display(),
-                        final_destination.display()
-                    )
-                })?;
+            self.rename(&source_path, &final_destination)?;
 
             tracing::info!(
                 "Moved: {} -> {}",
@@ -175,6 +197,76 @@ impl Organizer {
         })
     }
 
+    /// Move a finished book's M4B (and cover) to its templated library path
+    fn organize_with_template(
+        &self,
+        book: &BookFolder,
+        template: &PathTemplate,
+        target_folder: &Path,
+    ) -> Result<OrganizeResult> {
+        let source_file = &book.m4b_files[0];
+
+        let mut values = TemplateValues::from_book(book);
+        values.merge_m4b_tags(source_file);
+        let destination = target_folder.join(template.render(&values)?);
+
+        if destination == *source_file {
+            return Ok(OrganizeResult {
+                book_name: book.name.clone(),
+                source_path: book.folder_path.clone(),
+                destination_path: None,
+                action: OrganizeAction::Skipped,
+                success: true,
+                error_message: None,
+            });
+        }
+
+        let destination = template::resolve_collision(&destination, |p| p.exists())?;
+        let destination_dir = destination.parent().context("Invalid destination path")?;
+
+        if self.dry_run {
+            tracing::info!(
+                "[DRY RUN] Would move: {} -> {}",
+                source_file.display(),
+                destination.display()
+            );
+        } else {
+            self.create_dir_all(destination_dir)?;
+            self.rename(source_file, &destination)?;
+
+            // Keep the cover next to the book
+            if let Some(cover) = &book.cover_file {
+                let ext = cover.extension().and_then(|s| s.to_str()).unwrap_or("jpg");
+                let cover_dest = destination_dir.join(format!("cover.{}", ext));
+                if cover.exists() && !cover_dest.exists() {
+                    self.rename(cover, &cover_dest)?;
+                }
+            }
+
+            // Remove the source folder if nothing else is left in it
+            if fs::read_dir(&book.folder_path).map(|mut d| d.next().is_none()).unwrap_or(false) {
+                if let Err(e) = self.remove_dir(&book.folder_path) {
+                    tracing::debug!("Could not remove {}: {}", book.folder_path.display(), e);
+                }
+            }
+
+            tracing::info!(
+                "Moved: {} -> {}",
+                source_file.display(),
+                destination.display()
+            );
+        }
+
+        Ok(OrganizeResult {
+            book_name: book.name.clone(),
+            source_path: book.folder_path.clone(),
+            destination_path: Some(destination),
+            action: OrganizeAction::MovedToM4B,
+            success: true,
+            error_message: None,
+        })
+    }
+
     /// Organize multiple books
     pub fn organize_batch(&self, books: Vec<BookFolder>) -> Vec<OrganizeResult> {
         let mut results = Vec::new();
@@ -199,6 +291,30 @@ impl Organizer {
         results
     }
 
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        match &self.journal {
+            Some(journal) => journal.rename(from, to),
+            None => fs::rename(from, to)
+                .with_context(|| format!("Failed to move {} to {}", from.display(), to.display())),
+        }
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        match &self.journal {
+            Some(journal) => journal.create_dir_all(path),
+            None => fs::create_dir_all(path)
+                .with_context(|| format!("Failed to create folder: {}", path.display())),
+        }
+    }
+
+    fn remove_dir(&self, path: &Path) -> Result<()> {
+        match &self.journal {
+            Some(journal) => journal.remove_dir(path),
+            None => fs::remove_dir(path)
+                .with_context(|| format!("Failed to remove {}", path.display())),
+        }
+    }
+
     /// Resolve naming conflicts by appending numbers
     fn resolve_naming_conflict(&self, path: &Path) -> Result<PathBuf> {
         if !path.exists() {
@@ -250,6 +366,7 @@ mod tests {
         config.organization = OrganizationConfig {
             m4b_folder: "M4B".to_string(),
             convert_folder: "To_Convert".to_string(),
+            path_template: None,
         };
         config
     }
@@ -348,6 +465,36 @@ mod tests {
         assert!(results[1].success);
     }
 
+    #[test]
+    fn test_organize_with_template() {
+        let dir = tempdir().unwrap();
+        let config = create_test_config();
+        let template = PathTemplate::parse("{author}/{title}/{title}.m4b").unwrap();
+        let organizer = Organizer::new(dir.path().to_path_buf(), &config)
+            .with_template(Some(template));
+
+        let book_dir = dir.path().join("Andy Weir - Project Hail Mary");
+        fs::create_dir(&book_dir).unwrap();
+        let m4b = book_dir.join("book.m4b");
+        fs::write(&m4b, b"not really audio").unwrap();
+        let cover = book_dir.join("folder.jpg");
+        fs::write(&cover, b"jpeg").unwrap();
+
+        let mut book = BookFolder::new(book_dir.clone());
+        book.m4b_files = vec![m4b];
+        book.cover_file = Some(cover);
+        book.case = BookCase::C;
+
+        let result = organizer.organize_book(&book).unwrap();
+        let expected = dir
+            .path()
+            .join("M4B/Unknown Author/Andy Weir - Project Hail Mary/Andy Weir - Project Hail Mary.m4b");
+        assert_eq!(result.destination_path.as_deref(), Some(expected.as_path()));
+        assert!(expected.exists());
+        assert!(expected.with_file_name("cover.jpg").exists());
+        assert!(!book_dir.exists());
+    }
+
     #[test]
     fn test_resolve_naming_conflict() {
         let dir = tempdir().unwrap();
@@ -1,36 +1,200 @@
 //! Single book processor
 
 use crate::audio::{
-    generate_chapters_from_files, inject_chapters_mp4box, inject_metadata_atomicparsley,
-    parse_cue_file, write_mp4box_chapters, AacEncoder, FFmpeg,
+    generate_chapters_from_files, parse_cue_file, AacEncoder, AudioBackend, AudioMetadata,
+    EncodeTarget, FFmpeg, FfmpegBackend,
 };
-use crate::models::{BookFolder, ProcessingResult};
+use crate::core::{BookTempDir, BookTrim, Journal, PartialOutput};
+use crate::error::ForgeError;
+use crate::models::{speed_label, AacProfile, BookFolder, ProcessingResult, QualityProfile, ResolvedProfile, Track, TrackAction, TrackPlan};
+use crate::utils::{stage_span, PathTemplate, TemplateValues};
 use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::Semaphore;
+use tracing::Instrument;
+
+/// A named profile prepared for processing
+#[derive(Debug, Clone)]
+pub struct OutputProfile {
+    /// Resolved profile settings
+    pub settings: ResolvedProfile,
+    /// Encoder to use for this profile
+    pub encoder: AacEncoder,
+    /// Parsed output path template
+    pub template: Option<PathTemplate>,
+}
+
+impl OutputProfile {
+    /// Prepare a resolved profile, parsing its template
+    pub fn new(settings: ResolvedProfile, encoder: AacEncoder) -> Result<Self> {
+        let template = settings
+            .template
+            .as_deref()
+            .map(PathTemplate::parse)
+            .transpose()
+            .map_err(|e| ForgeError::config(format!("Invalid template in profile '{}'", settings.name), e))?;
+
+        Ok(Self {
+            settings,
+            encoder,
+            template,
+        })
+    }
+
+    /// The same output at another playback speed
+    pub fn at_speed(&self, speed: f64) -> Result<Self> {
+        Ok(Self {
+            settings: self.settings.at_speed(speed)?,
+            ..self.clone()
+        })
+    }
+}
+
+/// Per-track choice between stream-copying and transcoding before concatenation
+#[derive(Debug, Clone)]
+pub struct EncodePlan {
+    pub tracks: Vec<TrackPlan>,
+    /// Format of the copied tracks, which transcoded tracks must match
+    copy_format: Option<QualityProfile>,
+}
+
+impl EncodePlan {
+    /// Copy the tracks sharing the most common AAC/ALAC format (including the
+    /// AAC profile, as LC and HE-AAC can't be concatenated); transcode the rest
+    ///
+    /// Copying only pays off when most of the book can be copied, and ALAC
+    /// can't be concatenated with transcoded AAC, so otherwise every track is
+    /// transcoded.
+    pub fn for_tracks(tracks: &[Track]) -> Self {
+        let copy_key = |t: &Track| {
+            let codec = t.quality.codec.to_lowercase();
+            matches!(codec.as_str(), "aac" | "alac")
+                .then_some((codec, t.quality.sample_rate, t.quality.channels, t.quality.aac_profile))
+        };
+
+        // Largest group of copyable tracks with the same format (first wins ties)
+        let mut groups: Vec<((String, u32, u8, AacProfile), usize)> = Vec::new();
+        for key in tracks.iter().filter_map(copy_key) {
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, count)) => *count += 1,
+                None => groups.push((key, 1)),
+            }
+        }
+        let Some((key, count)) = groups.into_iter().rev().max_by_key(|(_, count)| *count) else {
+            return Self::transcode_all(tracks, |t| format!("{} audio can't be stream-copied", t.quality.codec));
+        };
+        if count * 2 < tracks.len() {
+            return Self::transcode_all(tracks, |_| {
+                format!("only {} of {} tracks could be stream-copied", count, tracks.len())
+            });
+        }
+        if key.0 != "aac" && count < tracks.len() {
+            return Self::transcode_all(tracks, |_| format!("{} can't be mixed with transcoded AAC", key.0));
+        }
+
+        let copied: Vec<&Track> = tracks.iter().filter(|t| copy_key(t).as_ref() == Some(&key)).collect();
+        let copy_format = QualityProfile {
+            bitrate: copied.iter().map(|t| t.quality.bitrate).max().unwrap_or_default(),
+            ..copied[0].quality.clone()
+        };
+        let describe = |q: &QualityProfile| {
+            let profile = if q.codec.eq_ignore_ascii_case("aac") && q.aac_profile != AacProfile::Lc {
+                format!(" ({})", q.aac_profile.as_str())
+            } else {
+                String::new()
+            };
+            format!("{}{} {} Hz {}ch", q.codec, profile, q.sample_rate, q.channels)
+        };
+
+        let plan = tracks
+            .iter()
+            .map(|track| {
+                let copy = copy_key(track).as_ref() == Some(&key);
+                TrackPlan {
+                    file: track.file_path.clone(),
+                    action: if copy { TrackAction::Copy } else { TrackAction::Transcode },
+                    reason: (!copy).then(|| {
+                        format!("{} differs from {}", describe(&track.quality), describe(&copy_format))
+                    }),
+                    trimmed: false,
+                }
+            })
+            .collect();
+
+        Self {
+            tracks: plan,
+            copy_format: Some(copy_format),
+        }
+    }
+
+    /// Transcode every track, giving each one's reason
+    pub fn transcode_all(tracks: &[Track], reason: impl Fn(&Track) -> String) -> Self {
+        Self {
+            tracks: tracks
+                .iter()
+                .map(|track| TrackPlan {
+                    file: track.file_path.clone(),
+                    action: TrackAction::Transcode,
+                    reason: Some(reason(track)),
+                    trimmed: false,
+                })
+                .collect(),
+            copy_format: None,
+        }
+    }
+
+    /// Whether every track is stream-copied
+    pub fn copies_all(&self) -> bool {
+        !self.tracks.is_empty() && self.tracks.iter().all(|t| t.action == TrackAction::Copy)
+    }
+
+    /// Whether some tracks are copied and others transcoded
+    pub fn is_mixed(&self) -> bool {
+        !self.copies_all() && self.tracks.iter().any(|t| t.action == TrackAction::Copy)
+    }
+}
+
+/// Adjust a quality profile to the encoder, warning about each fallback
+fn fit_quality(encoder: AacEncoder, quality: &QualityProfile) -> QualityProfile {
+    let (fitted, notes) = encoder.fit_quality(quality);
+    for note in notes {
+        tracing::warn!("{}", note);
+    }
+    fitted
+}
 
 /// Processor for converting a single audiobook
 pub struct Processor {
-    ffmpeg: FFmpeg,
+    backend: Arc<dyn AudioBackend>,
     keep_temp: bool,
     encoder: AacEncoder,
     enable_parallel_encoding: bool,
     max_concurrent_files: usize,
     quality_preset: Option<String>,
+    /// Output profiles (empty = one output from the settings above)
+    profiles: Vec<OutputProfile>,
+    /// Where temp directories are created (None = system temp dir)
+    temp_root: Option<PathBuf>,
+    /// Journal recording the outputs written
+    journal: Option<Journal>,
 }
 
 impl Processor {
     /// Create a new processor
     pub fn new() -> Result<Self> {
         Ok(Self {
-            ffmpeg: FFmpeg::new()?,
+            backend: FfmpegBackend::shared()?,
             keep_temp: false,
             encoder: crate::audio::get_encoder(),
             enable_parallel_encoding: true,
             max_concurrent_files: 8,
             quality_preset: None,
+            profiles: Vec::new(),
+            temp_root: None,
+            journal: None,
         })
     }
 
@@ -42,14 +206,54 @@ impl Processor {
         max_concurrent_files: usize,
         quality_preset: Option<String>,
     ) -> Result<Self> {
-        Ok(Self {
-            ffmpeg: FFmpeg::new()?,
+        Ok(Self::with_backend(
+            FfmpegBackend::shared()?,
+            keep_temp,
+            encoder,
+            enable_parallel_encoding,
+            max_concurrent_files,
+            quality_preset,
+        ))
+    }
+
+    /// Create processor with options and a specific audio backend
+    pub fn with_backend(
+        backend: Arc<dyn AudioBackend>,
+        keep_temp: bool,
+        encoder: AacEncoder,
+        enable_parallel_encoding: bool,
+        max_concurrent_files: usize,
+        quality_preset: Option<String>,
+    ) -> Self {
+        Self {
+            backend,
             keep_temp,
             encoder,
             enable_parallel_encoding,
             max_concurrent_files: max_concurrent_files.clamp(1, 32),
             quality_preset,
-        })
+            profiles: Vec::new(),
+            temp_root: None,
+            journal: None,
+        }
+    }
+
+    /// Produce one output per profile instead of a single default output
+    pub fn with_profiles(mut self, profiles: Vec<OutputProfile>) -> Self {
+        self.profiles = profiles;
+        self
+    }
+
+    /// Create temp directories under `dir` instead of the system temp dir
+    pub fn with_temp_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.temp_root = dir;
+        self
+    }
+
+    /// Record outputs (and the files they replace) in a journal
+    pub fn with_journal(mut self, journal: Option<Journal>) -> Self {
+        self.journal = journal;
+        self
     }
 
     /// Process a single book folder
@@ -70,15 +274,62 @@ impl Processor {
                 .context("Failed to create output directory")?;
         }
 
-        // Determine output file path
-        let output_filename = book_folder.get_output_filename();
-        let output_path = output_dir.join(&output_filename);
+        // Determine output file path (a lone profile may change the container)
+        let mut output_path = book_folder.get_output_path(output_dir);
+        if let [profile] = self.profiles.as_slice() {
+            output_path.set_extension(&profile.settings.format);
+        }
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create output directory")?;
+        }
 
         // Create temp directory
         let temp_dir = self.create_temp_dir(&book_folder.name)?;
 
-        // Check if we can use copy mode
-        let use_copy = book_folder.can_use_concat_copy();
+        // Per-folder overrides may change settings for this book
+        let book_config = book_folder.config.as_deref();
+        let chapter_source = book_config
+            .map(|c| c.quality.chapter_source.as_str())
+            .unwrap_or(chapter_source);
+        let enable_parallel_encoding = book_config
+            .map(|c| c.performance.enable_parallel_encoding)
+            .unwrap_or(self.enable_parallel_encoding);
+        let prefer_stereo = book_config.map(|c| c.quality.prefer_stereo).unwrap_or(true);
+
+        // Cut intros, outros and silence first, so chapters follow the trimmed audio
+        let source = book_folder;
+        let trimmed;
+        let book_folder = match &source.trim {
+            Some(trim) => {
+                let limit = if enable_parallel_encoding { self.max_concurrent_files } else { 1 };
+                trimmed = self
+                    .trim_tracks(source, trim, limit, &temp_dir)
+                    .instrument(stage_span("trim"))
+                    .await?;
+                &trimmed
+            }
+            None => source,
+        };
+
+        // Copy what we can, unless a profile changes the audio
+        let mut plan = if book_folder.target_quality.is_some() {
+            EncodePlan::transcode_all(&book_folder.tracks, |_| "re-encoded to fit the size budget".to_string())
+        } else if self.uses_single_decode() {
+            EncodePlan::transcode_all(&book_folder.tracks, |_| "encoded once for all profiles".to_string())
+        } else if self.profiles.iter().any(|p| p.settings.forces_transcode()) {
+            EncodePlan::transcode_all(&book_folder.tracks, |_| "the profile changes the audio".to_string())
+        } else {
+            EncodePlan::for_tracks(&book_folder.tracks)
+        };
+        // Report the source files, not the trimmed intermediates
+        for ((track_plan, track), original) in plan.tracks.iter_mut().zip(&book_folder.tracks).zip(&source.tracks) {
+            if track.file_path != original.file_path {
+                track_plan.trimmed_from(original.file_path.clone());
+            }
+        }
+        let use_copy = plan.copies_all();
+        let encoder = self.profiles.first().map(|p| p.encoder).unwrap_or(self.encoder);
 
         tracing::info!(
             "Processing {} - {} tracks, copy_mode={}",
@@ -86,11 +337,16 @@ impl Processor {
             book_folder.tracks.len(),
             use_copy
         );
+        for track in &plan.tracks {
+            if let Some(reason) = &track.reason {
+                tracing::debug!("Transcoding {}: {}", track.file.display(), reason);
+            }
+        }
 
         // Get quality profile (auto-detected from source)
         let mut quality = book_folder
-            .get_best_quality_profile(true)
-            .context("No tracks found")?
+            .get_best_quality_profile(prefer_stereo)
+            .ok_or_else(|| ForgeError::corrupt(&book_folder.folder_path, "no audio tracks found"))?
             .clone();
 
         // Apply quality preset override if specified
@@ -98,16 +354,110 @@ impl Processor {
             quality = quality.apply_preset(Some(preset.as_str()));
             tracing::info!("Applying quality preset '{}': {}", preset, quality);
         }
+        if let Some(target) = &book_folder.target_quality {
+            quality = target.clone();
+            tracing::info!("Fitting size budget: {}", quality);
+        }
+
+        let outputs = if self.uses_single_decode() {
+            // Profiles derive their own quality from the source
+            self.encode_profiles(book_folder, output_dir, &output_path, &quality, chapter_source, &temp_dir)
+                .await?
+        } else {
+            if let [profile] = self.profiles.as_slice() {
+                quality = profile.settings.apply_quality(&quality);
+                tracing::info!("Applying profile '{}': {}", profile.settings.name, quality);
+            }
+            if !use_copy {
+                quality = fit_quality(encoder, &quality);
+            }
+            // Finish the file under a temp name, so it only appears once complete
+            let output = PartialOutput::new(&output_path);
+            self.encode(book_folder, output.path(), &quality, &plan, encoder, enable_parallel_encoding, &temp_dir)
+                .instrument(stage_span("encode"))
+                .await?;
+            tracing::info!("Audio processing complete: {}", output_path.display());
+            self.finalize_output(book_folder, output.path(), chapter_source, None, &temp_dir)
+                .await?;
+            vec![output.commit(self.journal.as_ref())?]
+        };
+
+        // Clean up temporary cover files: auto-extracted covers (.extracted_cover.jpg)
+        // and Audible covers downloaded during build (audiobook-forge-cover-*.jpg).
+        if let Some(cover_path) = &book_folder.cover_file {
+            let name = cover_path.file_name().and_then(|n| n.to_str());
+            let is_extracted = name == Some(".extracted_cover.jpg");
+            let is_audible_temp = name
+                .map(|n| n.starts_with("audiobook-forge-cover-"))
+                .unwrap_or(false);
+            if is_extracted || is_audible_temp {
+                if let Err(e) = std::fs::remove_file(cover_path) {
+                    tracing::debug!("Failed to remove temporary cover file: {}", e);
+                } else {
+                    tracing::debug!("Cleaned up temporary cover file");
+                }
+            }
+        }
 
-        if book_folder.tracks.len() == 1 {
+        // Clean up temp directory
+        drop(temp_dir);
+
+        // Calculate processing time
+        let processing_time = start_time.elapsed().as_secs_f64();
+
+        tracing::info!(
+            "=== Completed: {} in {:.1}s ===",
+            book_folder.name,
+            processing_time
+        );
+
+        // Return success result
+        let mut outputs = outputs.into_iter();
+        let output_path = outputs.next().context("No output written")?;
+        Ok(result
+            .success(output_path, processing_time, use_copy)
+            .with_extra_outputs(outputs.collect())
+            .with_tracks(plan.tracks))
+    }
+
+    /// Encode the tracks to one output, using copy mode or parallel encoding when possible
+    #[allow(clippy::too_many_arguments)]
+    async fn encode(
+        &self,
+        book_folder: &BookFolder,
+        output_path: &Path,
+        quality: &QualityProfile,
+        plan: &EncodePlan,
+        encoder: AacEncoder,
+        enable_parallel_encoding: bool,
+        temp_dir: &Path,
+    ) -> Result<()> {
+        let use_copy = plan.copies_all();
+        if let (true, Some(copy_format)) = (plan.is_mixed(), &plan.copy_format) {
+            // Transcode only the odd tracks, to the copied tracks' format
+            let limit = if enable_parallel_encoding { self.max_concurrent_files } else { 1 };
+            let target = QualityProfile {
+                codec: "aac".to_string(),
+                duration: quality.duration,
+                ..copy_format.clone()
+            };
+            tracing::info!(
+                "Stream-copying {} of {} tracks; transcoding the rest to {}",
+                plan.tracks.iter().filter(|t| t.action == TrackAction::Copy).count(),
+                plan.tracks.len(),
+                target
+            );
+            self.transcode_and_concat(book_folder, plan, output_path, &target, encoder, limit, temp_dir)
+                .await?;
+        } else if book_folder.tracks.len() == 1 {
             // Single file - just convert
-            self.ffmpeg
+            self.backend
                 .convert_single_file(
                     &book_folder.tracks[0].file_path,
-                    &output_path,
-                    &quality,
+                    output_path,
+                    quality,
                     use_copy,
-                    self.encoder,
+                    encoder,
                 )
                 .await
                 .context("Failed to convert audio file")?;
@@ -121,96 +471,25 @@ impl Processor {
                 .collect();
             FFmpeg::create_concat_file(&file_refs, &concat_file)?;
 
-            self.ffmpeg
+            self.backend
                 .concat_audio_files(
                     &concat_file,
-                    &output_path,
-                    &quality,
+                    output_path,
+                    quality,
                     use_copy,
-                    self.encoder,
+                    encoder,
                 )
                 .await
                 .context("Failed to concatenate audio files")?;
-        } else if self.enable_parallel_encoding && book_folder.tracks.len() > 1 {
+        } else if enable_parallel_encoding && book_folder.tracks.len() > 1 {
             // Transcode mode - encode files in parallel with throttling
-            let effective_limit = self.max_concurrent_files.min(book_folder.tracks.len());
-
             tracing::info!(
                 "Using parallel encoding: {} files with max {} concurrent",
                 book_folder.tracks.len(),
-                effective_limit
+                self.max_concurrent_files.min(book_folder.tracks.len())
             );
-
-            // Create semaphore to limit concurrent file encodings
-            let semaphore = Arc::new(Semaphore::new(effective_limit));
-
-            // Step 1: Encode all files to AAC/M4A in parallel (with throttling)
-            let mut encoded_files = Vec::new();
-            let mut tasks = Vec::new();
-
-            for (i, track) in book_folder.tracks.iter().enumerate() {
-                let temp_output = temp_dir.join(format!("encoded_{:04}.m4a", i));
-                encoded_files.push(temp_output.clone());
-
-                tracing::info!(
-                    "[{}/{}] Encoding: {} ({:.1} min)",
-                    i + 1,
-                    book_folder.tracks.len(),
-                    track.file_path.file_name().unwrap().to_string_lossy(),
-                    track.quality.duration / 60.0
-                );
-
-                let ffmpeg = self.ffmpeg.clone();
-                let input = track.file_path.clone();
-                let output = temp_output;
-                let quality = quality.clone();
-                let encoder = self.encoder;
-                let sem = Arc::clone(&semaphore);
-
-                // Spawn parallel encoding task with semaphore
-                let task = tokio::spawn(async move {
-                    // Acquire permit before encoding (blocks if limit reached)
-                    let _permit = sem.acquire().await.unwrap();
-
-                    ffmpeg
-                        .convert_single_file(&input, &output, &quality, false, encoder)
-                        .await
-                    // Permit automatically released when _permit drops
-                });
-
-                tasks.push(task);
-            }
-
-            // Wait for all encoding tasks to complete
-            for (i, task) in tasks.into_iter().enumerate() {
-                match task.await {
-                    Ok(Ok(())) => continue,
-                    Ok(Err(e)) => {
-                        return Err(e).context(format!("Track {} encoding failed", i));
-                    }
-                    Err(e) => {
-                        return Err(anyhow::anyhow!("Task {} panicked: {}", i, e));
-                    }
-                }
-            }
-
-            tracing::info!("All {} files encoded, now concatenating...", encoded_files.len());
-
-            // Step 2: Concatenate the encoded files (fast, no re-encoding)
-            let concat_file = temp_dir.join("concat.txt");
-            let file_refs: Vec<&Path> = encoded_files.iter().map(|p| p.as_path()).collect();
-            FFmpeg::create_concat_file(&file_refs, &concat_file)?;
-
-            self.ffmpeg
-                .concat_audio_files(
-                    &concat_file,
-                    &output_path,
-                    &quality,
-                    true, // use copy mode for concatenation
-                    self.encoder,
-                )
-                .await
-                .context("Failed to concatenate encoded files")?;
+            self.transcode_and_concat(book_folder, plan, output_path, quality, encoder, self.max_concurrent_files, temp_dir)
+                .await?;
         } else {
             // Serial mode - concatenate and encode in one FFmpeg call (traditional method)
             tracing::info!("Using serial encoding (parallel encoding disabled in config)");
@@ -223,31 +502,199 @@ impl Processor {
                 .collect();
             FFmpeg::create_concat_file(&file_refs, &concat_file)?;
 
-            self.ffmpeg
+            self.backend
                 .concat_audio_files(
                     &concat_file,
-                    &output_path,
-                    &quality,
+                    output_path,
+                    quality,
                     false, // transcode mode
-                    self.encoder,
+                    encoder,
                 )
                 .await
                 .context("Failed to concatenate audio files")?;
         }
 
-        tracing::info!("Audio processing complete: {}", output_path.display());
+        Ok(())
+    }
+
+    /// Transcode the tracks the plan doesn't copy (up to `limit` at once), then
+    /// stream-copy everything together in track order
+    #[allow(clippy::too_many_arguments)]
+    async fn transcode_and_concat(
+        &self,
+        book_folder: &BookFolder,
+        plan: &EncodePlan,
+        output_path: &Path,
+        quality: &QualityProfile,
+        encoder: AacEncoder,
+        limit: usize,
+        temp_dir: &Path,
+    ) -> Result<()> {
+        // Create semaphore to limit concurrent file encodings
+        let semaphore = Arc::new(Semaphore::new(limit.clamp(1, book_folder.tracks.len().max(1))));
+        let to_transcode = plan.tracks.iter().filter(|t| t.action == TrackAction::Transcode).count();
+
+        // Step 1: Encode the files to AAC/M4A in parallel (with throttling)
+        let mut concat_files = Vec::new();
+        let mut tasks = Vec::new();
+
+        for (i, (track, track_plan)) in book_folder.tracks.iter().zip(&plan.tracks).enumerate() {
+            if track_plan.action == TrackAction::Copy {
+                concat_files.push(track.file_path.clone());
+                continue;
+            }
+            let temp_output = temp_dir.join(format!("encoded_{:04}.m4a", i));
+            concat_files.push(temp_output.clone());
+
+            tracing::info!(
+                "[{}/{}] Encoding: {} ({:.1} min)",
+                tasks.len() + 1,
+                to_transcode,
+                track.file_path.file_name().unwrap().to_string_lossy(),
+                track.quality.duration / 60.0
+            );
+
+            let backend = Arc::clone(&self.backend);
+            let input = track.file_path.clone();
+            let output = temp_output;
+            let quality = quality.clone();
+            let sem = Arc::clone(&semaphore);
+
+            // Spawn parallel encoding task with semaphore
+            let task = tokio::spawn(async move {
+                // Acquire permit before encoding (blocks if limit reached)
+                let _permit = sem.acquire().await.unwrap();
+
+                backend
+                    .convert_single_file(&input, &output, &quality, false, encoder)
+                    .await
+                // Permit automatically released when _permit drops
+            }
+            .in_current_span());
+
+            tasks.push((i, task));
+        }
+
+        // Wait for all encoding tasks to complete
+        for (i, task) in tasks {
+            match task.await {
+                Ok(Ok(())) => continue,
+                Ok(Err(e)) => {
+                    return Err(e).context(format!("Track {} encoding failed", i));
+                }
+                Err(e) => {
+                    return Err(anyhow::anyhow!("Task {} panicked: {}", i, e));
+                }
+            }
+        }
+
+        tracing::info!("All {} files encoded, now concatenating...", to_transcode);
+
+        // Step 2: Concatenate the encoded and copied files (fast, no re-encoding)
+        let concat_file = temp_dir.join("concat.txt");
+        let file_refs: Vec<&Path> = concat_files.iter().map(|p| p.as_path()).collect();
+        FFmpeg::create_concat_file(&file_refs, &concat_file)?;
+
+        self.backend
+            .concat_audio_files(
+                &concat_file,
+                output_path,
+                quality,
+                true, // use copy mode for concatenation
+                encoder,
+            )
+            .await
+            .context("Failed to concatenate encoded files")
+    }
+
+    /// Trim the book's tracks to lossless intermediates (up to `limit` at once)
+    ///
+    /// Returns a copy of the book whose trimmed tracks point at the
+    /// intermediates, with the durations probed from them.
+    async fn trim_tracks(
+        &self,
+        book_folder: &BookFolder,
+        trim: &BookTrim,
+        limit: usize,
+        temp_dir: &Path,
+    ) -> Result<BookFolder> {
+        tracing::info!("Trimming {}", trim);
+        if book_folder.cue_file.is_some() {
+            tracing::warn!("CUE chapter times are not adjusted for trimming");
+        }
+
+        let count = book_folder.tracks.len();
+        let jobs = book_folder.tracks.iter().enumerate().map(|(i, track)| async move {
+            let track_trim = trim.for_track(i, count, track.quality.duration)?;
+            if track_trim.is_empty() {
+                return Ok(None);
+            }
+
+            // Keep the file stem: chapters from files are named after it
+            let dir = temp_dir.join("trimmed").join(format!("{:04}", i));
+            std::fs::create_dir_all(&dir).context("Failed to create temp directory")?;
+            let stem = track.file_path.file_stem().unwrap_or_default().to_string_lossy();
+            let output = dir.join(format!("{}.flac", stem));
 
+            self.backend
+                .trim_track(&track.file_path, &output, &track_trim)
+                .await
+                .with_context(|| format!("Failed to trim {}", track.file_path.display()))?;
+            let (duration_ms, _) = self
+                .backend
+                .probe_duration_and_title(&output)
+                .await
+                .context("Failed to probe trimmed track")?;
+            Ok::<_, anyhow::Error>(Some((output, duration_ms as f64 / 1000.0)))
+        }).collect::<Vec<_>>();
+        let results: Vec<Result<Option<(PathBuf, f64)>>> =
+            stream::iter(jobs).buffered(limit.max(1)).collect().await;
+
+        let mut trimmed = book_folder.clone();
+        for (track, result) in trimmed.tracks.iter_mut().zip(results) {
+            if let Some((output, duration)) = result? {
+                tracing::debug!(
+                    "Trimmed {}: {:.1}s -> {:.1}s",
+                    track.file_path.display(),
+                    track.quality.duration,
+                    duration
+                );
+                track.file_path = output;
+                track.quality.duration = duration;
+                track.quality.codec = "flac".to_string();
+            }
+        }
+        Ok(trimmed)
+    }
+
+    /// Inject chapters and metadata into a finished output file
+    ///
+    /// A `tempo` output gets its chapter times scaled and the speed appended
+    /// to its title.
+    async fn finalize_output(
+        &self,
+        book_folder: &BookFolder,
+        output_path: &Path,
+        chapter_source: &str,
+        tempo: Option<f64>,
+        temp_dir: &Path,
+    ) -> Result<()> {
         // Step 3: Generate and inject chapters
-        let chapters = self.generate_chapters(book_folder, chapter_source)?;
+        let mut chapters = self.generate_chapters(book_folder, chapter_source)?;
+        if let Some(overrides) = &book_folder.overrides {
+            overrides.apply_chapter_names(&mut chapters);
+        }
+        if let Some(tempo) = tempo {
+            chapters = chapters.iter().map(|c| c.at_tempo(tempo)).collect();
+        }
 
         if !chapters.is_empty() {
             tracing::info!("Injecting {} chapters using MP4Box", chapters.len());
 
             let chapters_file = temp_dir.join("chapters.txt");
-            write_mp4box_chapters(&chapters, &chapters_file)
-                .context("Failed to write chapter file")?;
-
-            inject_chapters_mp4box(&output_path, &chapters_file)
+            self.backend
+                .inject_chapters(output_path, &chapters, &chapters_file)
+                .instrument(stage_span("chapters"))
                 .await
                 .context("Failed to inject chapters")?;
 
@@ -255,72 +702,165 @@ impl Processor {
         }
 
         // Step 4: Inject metadata
-        let title = book_folder.get_album_title();
+        let mut title = book_folder.get_album_title();
+        if let (Some(title), Some(tempo)) = (title.as_mut(), tempo) {
+            title.push_str(&format!(" ({})", speed_label(tempo)));
+        }
         let artist = book_folder.get_album_artist();
-        let year = book_folder.get_year();
-        let genre = book_folder.get_genre();
-        let comment = book_folder.get_comment();
-        let composer = book_folder.get_composer();
+        let metadata = AudioMetadata {
+            album: title.clone(), // Use title as album
+            album_artist: artist.clone(),
+            title,
+            artist,
+            year: book_folder.get_year(),
+            genre: book_folder.get_genre(),
+            composer: book_folder.get_composer(),
+            comment: book_folder.get_comment(),
+        };
 
         tracing::info!("Injecting metadata using AtomicParsley");
         tracing::debug!(
             "Metadata: title={:?}, artist={:?}",
-            title,
-            artist
+            metadata.title,
+            metadata.artist
         );
 
-        inject_metadata_atomicparsley(
-            &output_path,
-            title.as_deref(),
-            artist.as_deref(),
-            title.as_deref(), // Use title as album
-            artist.as_deref(), // Album artist
-            year,
-            genre.as_deref(),
-            composer.as_deref(),
-            comment.as_deref(),
-            book_folder.cover_file.as_deref(),
-        )
-        .await
-        .context("Failed to inject metadata")?;
+        self.backend
+            .write_metadata(output_path, &metadata, book_folder.cover_file.as_deref())
+            .instrument(stage_span("metadata"))
+            .await
+            .context("Failed to inject metadata")?;
 
         tracing::info!("✓ Metadata injection complete");
 
-        // Clean up temporary cover files: auto-extracted covers (.extracted_cover.jpg)
-        // and Audible covers downloaded during build (audiobook-forge-cover-*.jpg).
-        if let Some(cover_path) = &book_folder.cover_file {
-            let name = cover_path.file_name().and_then(|n| n.to_str());
-            let is_extracted = name == Some(".extracted_cover.jpg");
-            let is_audible_temp = name
-                .map(|n| n.starts_with("audiobook-forge-cover-"))
-                .unwrap_or(false);
-            if is_extracted || is_audible_temp {
-                if let Err(e) = std::fs::remove_file(cover_path) {
-                    tracing::debug!("Failed to remove temporary cover file: {}", e);
-                } else {
-                    tracing::debug!("Cleaned up temporary cover file");
-                }
+        Ok(())
+    }
+
+    /// Whether outputs are produced by one decode feeding every profile
+    ///
+    /// Used for several profiles (including speed variants), and for loudness
+    /// normalization and tempo changes, which need the whole book in one
+    /// stream rather than per-track encodes.
+    fn uses_single_decode(&self) -> bool {
+        self.profiles.len() > 1
+            || self.profiles.iter().any(|p| p.settings.loudness.is_some() || p.settings.tempo.is_some())
+    }
+
+    /// Encode every profile from a single decode, then add chapters and metadata to each
+    async fn encode_profiles(
+        &self,
+        book_folder: &BookFolder,
+        output_dir: &Path,
+        output_path: &Path,
+        source_quality: &QualityProfile,
+        chapter_source: &str,
+        temp_dir: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        let paths = self.profile_output_paths(book_folder, output_dir, output_path)?;
+        let outputs: Vec<PartialOutput> = paths.iter().map(|p| PartialOutput::new(p)).collect();
+
+        let targets: Vec<EncodeTarget> = self
+            .profiles
+            .iter()
+            .zip(&outputs)
+            .map(|(profile, output)| EncodeTarget {
+                path: output.path().to_path_buf(),
+                quality: fit_quality(profile.encoder, &profile.settings.apply_quality(source_quality)),
+                encoder: profile.encoder,
+                loudness: profile.settings.loudness,
+                tempo: profile.settings.tempo,
+            })
+            .collect();
+
+        for (target, path) in targets.iter().zip(&paths) {
+            tracing::info!("Profile output {}: {}", path.display(), target.quality);
+            if let Some(parent) = target.path.parent() {
+                std::fs::create_dir_all(parent).context("Failed to create output directory")?;
             }
         }
 
-        // Clean up temp directory
-        if !self.keep_temp {
-            if let Err(e) = std::fs::remove_dir_all(&temp_dir) {
-                tracing::warn!("Failed to remove temp directory: {}", e);
+        if let [track] = book_folder.tracks.as_slice() {
+            self.backend
+                .encode_targets(&track.file_path, false, &targets)
+                .instrument(stage_span("encode"))
+                .await
+                .context("Failed to encode audio file")?;
+        } else {
+            let concat_file = temp_dir.join("concat.txt");
+            let file_refs: Vec<&Path> = book_folder
+                .tracks
+                .iter()
+                .map(|t| t.file_path.as_path())
+                .collect();
+            FFmpeg::create_concat_file(&file_refs, &concat_file)?;
+
+            self.backend
+                .encode_targets(&concat_file, true, &targets)
+                .instrument(stage_span("encode"))
+                .await
+                .context("Failed to encode audio files")?;
+        }
+
+        for (profile, output) in self.profiles.iter().zip(&outputs) {
+            // A folder override beats the profile; a lone profile was already applied to the config
+            let chapter_source = match (&book_folder.config, self.profiles.len()) {
+                (None, n) if n > 1 => profile.settings.chapter_source.as_str(),
+                _ => chapter_source,
+            };
+            self.finalize_output(book_folder, output.path(), chapter_source, profile.settings.tempo, temp_dir)
+                .await
+                .with_context(|| format!("Failed to finish '{}' output", profile.settings.name))?;
+        }
+
+        outputs.into_iter().map(|output| output.commit(self.journal.as_ref())).collect()
+    }
+
+    /// Output path for each profile
+    ///
+    /// A lone profile writes to the book's usual path. With several, each uses
+    /// its own template (or the usual path) and same-path outputs get the
+    /// profile name appended. Outputs at another speed get the speed appended.
+    fn profile_output_paths(
+        &self,
+        book_folder: &BookFolder,
+        output_dir: &Path,
+        output_path: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        let with_speed = |path: PathBuf, profile: &OutputProfile| match profile.settings.tempo {
+            Some(tempo) => {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                path.with_file_name(format!("{} ({}).{}", stem, speed_label(tempo), profile.settings.format))
             }
+            None => path.with_extension(&profile.settings.format),
+        };
+        if let [profile] = self.profiles.as_slice() {
+            return Ok(vec![with_speed(output_path.to_path_buf(), profile)]);
         }
 
-        // Calculate processing time
-        let processing_time = start_time.elapsed().as_secs_f64();
+        let mut paths = Vec::with_capacity(self.profiles.len());
+        for profile in &self.profiles {
+            let path = match &profile.template {
+                Some(template) => output_dir.join(template.render(&TemplateValues::from_book(book_folder))?),
+                None => output_path.to_path_buf(),
+            };
+            paths.push(with_speed(path, profile));
+        }
 
-        tracing::info!(
-            "=== Completed: {} in {:.1}s ===",
-            book_folder.name,
-            processing_time
-        );
+        let shared: Vec<bool> = paths
+            .iter()
+            .map(|path| paths.iter().filter(|p| *p == path).count() > 1)
+            .collect();
+        for ((path, profile), shared) in paths.iter_mut().zip(&self.profiles).zip(shared) {
+            if shared {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                *path = path.with_file_name(format!(
+                    "{} ({}).{}",
+                    stem, profile.settings.name, profile.settings.format
+                ));
+            }
+        }
 
-        // Return success result
-        Ok(result.success(output_path, processing_time, use_copy))
+        Ok(paths)
     }
 
     /// Generate chapters for the book
@@ -376,18 +916,12 @@ impl Processor {
     }
 
     /// Create temporary directory for processing
-    fn create_temp_dir(&self, book_name: &str) -> Result<PathBuf> {
-        let temp_base = std::env::temp_dir();
-        let sanitized_name = sanitize_filename::sanitize(book_name);
-        let temp_dir = temp_base.join(format!("audiobook-forge-{}", sanitized_name));
-
-        if temp_dir.exists() {
-            std::fs::remove_dir_all(&temp_dir).ok();
-        }
-
-        std::fs::create_dir_all(&temp_dir).context("Failed to create temp directory")?;
-
-        Ok(temp_dir)
+    ///
+    /// It's removed when dropped (unless temp files are kept), including when
+    /// processing fails or is cancelled.
+    fn create_temp_dir(&self, book_name: &str) -> Result<BookTempDir> {
+        let temp_base = self.temp_root.clone().unwrap_or_else(std::env::temp_dir);
+        BookTempDir::create(&temp_base, "", book_name, self.keep_temp)
     }
 }
 
@@ -400,6 +934,7 @@ impl Default for Processor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audio::{BackendOp, RecordingBackend};
 
     #[test]
     fn test_processor_creation() {
@@ -430,4 +965,252 @@ mod tests {
         // Clean up
         std::fs::remove_dir_all(temp_dir).ok();
     }
+
+    /// A book of `count` placeholder tracks with the given codec
+    fn fake_book(dir: &Path, name: &str, count: usize, codec: &str) -> BookFolder {
+        let mut book = BookFolder::new(dir.join(name));
+        for i in 1..=count {
+            let path = dir.join(format!("{:02}.{}", i, if codec == "aac" { "m4a" } else { "mp3" }));
+            std::fs::write(&path, b"").unwrap();
+            let quality = QualityProfile::new(128, 44100, 2, codec.to_string(), 600.0).unwrap();
+            book.tracks.push(crate::models::Track::new(path, quality));
+        }
+        book
+    }
+
+    fn fake_processor(backend: &Arc<RecordingBackend>, parallel: bool) -> Processor {
+        Processor::with_backend(backend.clone(), false, AacEncoder::Native, parallel, 4, None)
+    }
+
+    #[tokio::test]
+    async fn test_process_book_serial_transcode() {
+        let dir = tempfile::tempdir().unwrap();
+        let book = fake_book(dir.path(), "processor-serial-test", 3, "mp3");
+        let backend = Arc::new(RecordingBackend::new());
+
+        let result = fake_processor(&backend, false)
+            .process_book(&book, &dir.path().join("out"), "files")
+            .await
+            .unwrap();
+
+        let output = dir.path().join("out/processor-serial-test.m4b");
+        assert_eq!(result.output_path.as_deref(), Some(output.as_path()));
+        assert!(!result.used_copy_mode);
+
+        let ops: Vec<BackendOp> = backend.calls().iter().map(|c| c.op).collect();
+        assert_eq!(ops, vec![BackendOp::Concat, BackendOp::InjectChapters, BackendOp::WriteMetadata]);
+
+        let concat = &backend.calls_of(BackendOp::Concat)[0];
+        assert_eq!(concat.inputs.len(), 3);
+        assert!(!concat.use_copy);
+        let chapters = &backend.calls_of(BackendOp::InjectChapters)[0].chapters;
+        assert_eq!(chapters.len(), 3);
+        assert_eq!(chapters[2].end_time_ms, 1_800_000);
+    }
+
+    #[tokio::test]
+    async fn test_process_book_parallel_and_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(RecordingBackend::new());
+
+        // MP3 tracks are encoded one by one, then the encodes are stream-copied together
+        let book = fake_book(dir.path(), "processor-parallel-test", 4, "mp3");
+        fake_processor(&backend, true)
+            .process_book(&book, &dir.path().join("out"), "none")
+            .await
+            .unwrap();
+        assert_eq!(backend.count(BackendOp::Convert), 4);
+        let concat = &backend.calls_of(BackendOp::Concat)[0];
+        assert!(concat.use_copy);
+        assert!(concat.inputs.iter().all(|p| p.to_string_lossy().contains("encoded_")));
+        assert_eq!(backend.count(BackendOp::InjectChapters), 0);
+
+        // A single AAC track is copied straight to the output
+        let aac_dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(RecordingBackend::new());
+        let book = fake_book(aac_dir.path(), "processor-copy-test", 1, "aac");
+        let result = fake_processor(&backend, true)
+            .process_book(&book, &dir.path().join("out"), "none")
+            .await
+            .unwrap();
+        assert!(result.used_copy_mode);
+        let convert = &backend.calls_of(BackendOp::Convert)[0];
+        assert!(convert.use_copy);
+    }
+
+    #[tokio::test]
+    async fn test_process_book_mixed_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(RecordingBackend::new());
+
+        // Three AAC tracks and one stray MP3: only the MP3 is transcoded
+        let mut book = fake_book(dir.path(), "processor-mixed-test", 3, "aac");
+        let mp3 = dir.path().join("04.mp3");
+        std::fs::write(&mp3, b"").unwrap();
+        let quality = QualityProfile::new(320, 48000, 2, "mp3".to_string(), 600.0).unwrap();
+        book.tracks.push(crate::models::Track::new(mp3.clone(), quality));
+
+        let result = fake_processor(&backend, false)
+            .process_book(&book, &dir.path().join("out"), "none")
+            .await
+            .unwrap();
+
+        let converts = backend.calls_of(BackendOp::Convert);
+        assert_eq!(converts.len(), 1);
+        assert_eq!(converts[0].inputs, vec![mp3]);
+        assert!(!converts[0].use_copy);
+
+        let concat = &backend.calls_of(BackendOp::Concat)[0];
+        assert!(concat.use_copy);
+        assert_eq!(&concat.inputs[..3], &book.tracks[..3].iter().map(|t| t.file_path.clone()).collect::<Vec<_>>()[..]);
+        assert!(concat.inputs[3].to_string_lossy().contains("encoded_0003"));
+
+        assert!(!result.used_copy_mode);
+        assert_eq!(result.encode_mode(), "3 copied, 1 transcoded");
+        assert_eq!(
+            result.tracks[3].reason.as_deref(),
+            Some("mp3 48000 Hz 2ch differs from aac 44100 Hz 2ch")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_process_book_size_budget_transcodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(RecordingBackend::new());
+
+        // AAC tracks would be copied, but not with a size budget to meet
+        let mut book = fake_book(dir.path(), "processor-budget-test", 2, "aac");
+        book.target_quality = Some(QualityProfile::new(48, 22050, 1, "aac".to_string(), 1200.0).unwrap());
+
+        let result = fake_processor(&backend, true)
+            .process_book(&book, &dir.path().join("out"), "none")
+            .await
+            .unwrap();
+
+        assert!(backend.calls_of(BackendOp::Convert).iter().all(|c| !c.use_copy));
+        assert_eq!(result.encode_mode(), "transcode");
+        assert_eq!(result.tracks[0].reason.as_deref(), Some("re-encoded to fit the size budget"));
+    }
+
+    #[tokio::test]
+    async fn test_process_book_trimmed_chapters() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(RecordingBackend::new());
+        let mut book = fake_book(dir.path(), "processor-trim-test", 3, "mp3");
+        book.trim = Some(BookTrim { intro_secs: 2.0, outro_secs: 5.0, silence: None });
+
+        let result = fake_processor(&backend, true)
+            .process_book(&book, &dir.path().join("out"), "files")
+            .await
+            .unwrap();
+
+        // Only the first and last tracks are cut; the middle one is used as is
+        let trims = backend.calls_of(BackendOp::Trim);
+        assert_eq!(trims.len(), 2);
+        assert_eq!(trims[0].inputs, vec![book.tracks[0].file_path.clone()]);
+        assert!(trims[1].outputs[0].ends_with("0002/03.flac"));
+        let converts = backend.calls_of(BackendOp::Convert);
+        assert_eq!(converts[1].inputs, vec![book.tracks[1].file_path.clone()]);
+
+        // Chapters follow the trimmed durations and keep the source names
+        let chapters = &backend.calls_of(BackendOp::InjectChapters)[0].chapters;
+        let ends: Vec<u64> = chapters.iter().map(|c| c.end_time_ms).collect();
+        assert_eq!(ends, vec![598_000, 1_198_000, 1_793_000]);
+        assert_eq!(chapters[0].title, "01");
+
+        assert_eq!(result.tracks[0].file, book.tracks[0].file_path);
+        assert!(result.tracks[0].trimmed && !result.tracks[1].trimmed);
+        assert_eq!(result.tracks[0].reason.as_deref(), Some("trimmed before encoding"));
+    }
+
+    #[tokio::test]
+    async fn test_process_book_speed_variants() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(RecordingBackend::new());
+        let book = fake_book(dir.path(), "processor-speed-test", 2, "mp3");
+
+        let settings = crate::models::ProfileConfig::default()
+            .resolve("default", &crate::models::Config::default())
+            .unwrap();
+        let base = OutputProfile::new(settings, AacEncoder::Native).unwrap();
+        let fast = base.at_speed(1.5).unwrap();
+        let result = fake_processor(&backend, true)
+            .with_profiles(vec![base, fast])
+            .process_book(&book, &dir.path().join("out"), "files")
+            .await
+            .unwrap();
+
+        // One decode writes both speeds, under temp names until they're finished
+        let encodes = backend.calls_of(BackendOp::EncodeTargets);
+        assert_eq!(encodes.len(), 1);
+        let out = dir.path().join("out");
+        assert_eq!(
+            encodes[0].outputs,
+            vec![out.join(".processor-speed-test.partial.m4b"), out.join(".processor-speed-test (1.5x).partial.m4b")]
+        );
+        assert_eq!(result.extra_outputs, vec![out.join("processor-speed-test (1.5x).m4b")]);
+        assert!(result.extra_outputs[0].exists());
+        assert!(!encodes[0].outputs[1].exists());
+
+        // Chapter times shrink with the speed, and the title says so
+        let chapters = backend.calls_of(BackendOp::InjectChapters);
+        assert_eq!(chapters[0].chapters[1].end_time_ms, 1_200_000);
+        assert_eq!(chapters[1].chapters[1].end_time_ms, 800_000);
+        let tags = backend.calls_of(BackendOp::WriteMetadata);
+        assert_eq!(tags[1].metadata.as_ref().unwrap().title.as_deref(), Some("processor-speed-test (1.5x)"));
+    }
+
+    #[test]
+    fn test_encode_plan() {
+        let track = |name: &str, codec: &str, sample_rate: u32| {
+            let quality = QualityProfile::new(64, sample_rate, 2, codec.to_string(), 60.0).unwrap();
+            crate::models::Track::new(PathBuf::from(name), quality)
+        };
+        let actions = |plan: &EncodePlan| plan.tracks.iter().map(|t| t.action).collect::<Vec<_>>();
+        use TrackAction::{Copy, Transcode};
+
+        // One track at another sample rate is transcoded to match the rest
+        let plan = EncodePlan::for_tracks(&[track("1", "aac", 44100), track("2", "aac", 22050), track("3", "AAC", 44100)]);
+        assert_eq!(actions(&plan), vec![Copy, Transcode, Copy]);
+        assert!(plan.is_mixed());
+        assert_eq!(plan.copy_format.as_ref().unwrap().sample_rate, 44100);
+
+        // Uniform AAC is copied outright
+        let plan = EncodePlan::for_tracks(&[track("1", "aac", 44100), track("2", "aac", 44100)]);
+        assert!(plan.copies_all());
+
+        // Mostly MP3: everything is transcoded
+        let plan = EncodePlan::for_tracks(&[track("1", "mp3", 44100), track("2", "mp3", 44100), track("3", "aac", 44100)]);
+        assert_eq!(actions(&plan), vec![Transcode, Transcode, Transcode]);
+        assert_eq!(plan.tracks[0].reason.as_deref(), Some("only 1 of 3 tracks could be stream-copied"));
+
+        // HE-AAC can't be concatenated with AAC-LC at the same rate and channels
+        let he = |name: &str| {
+            let mut t = track(name, "aac", 44100);
+            t.quality.aac_profile = AacProfile::He;
+            t
+        };
+        let plan = EncodePlan::for_tracks(&[track("1", "aac", 44100), he("2"), track("3", "aac", 44100)]);
+        assert_eq!(actions(&plan), vec![Copy, Transcode, Copy]);
+        assert_eq!(plan.copy_format.as_ref().unwrap().aac_profile, AacProfile::Lc);
+        assert_eq!(plan.tracks[1].reason.as_deref(), Some("aac (he) 44100 Hz 2ch differs from aac 44100 Hz 2ch"));
+
+        // ALAC can't be mixed with transcoded AAC
+        let plan = EncodePlan::for_tracks(&[track("1", "alac", 44100), track("2", "alac", 44100), track("3", "mp3", 44100)]);
+        assert_eq!(actions(&plan), vec![Transcode, Transcode, Transcode]);
+    }
+
+    #[tokio::test]
+    async fn test_process_book_backend_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let book = fake_book(dir.path(), "processor-failure-test", 2, "mp3");
+        let backend = Arc::new(RecordingBackend::new());
+        backend.fail_next(BackendOp::WriteMetadata, anyhow::anyhow!("AtomicParsley failed"));
+
+        let err = fake_processor(&backend, false)
+            .process_book(&book, &dir.path().join("out"), "files")
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("AtomicParsley failed"));
+    }
 }
@@ -1,5 +1,7 @@
 //! Error recovery and retry logic
 
+use crate::audio::AudibleApiError;
+use crate::error::{http_is_transient, io_is_transient, reqwest_is_transient, ForgeError};
 use anyhow::Result;
 use std::time::Duration;
 use tokio::time::sleep;
@@ -127,88 +129,26 @@ pub enum ErrorType {
 }
 
 /// Classify an error to determine if retry is worthwhile
+///
+/// Looks for a typed error anywhere in the context chain and classifies it by
+/// variant. Untyped errors are treated as transient (conservative approach).
 pub fn classify_error(error: &anyhow::Error) -> ErrorType {
-    let error_msg = error.to_string().to_lowercase();
-
-    // HTTP-specific errors
-    // 429 rate limit is transient
-    if error_msg.contains("429") || error_msg.contains("rate limit") {
-        return ErrorType::Transient;
-    }
-
-    // 5xx server errors are transient
-    if error_msg.contains("500") || error_msg.contains("502")
-        || error_msg.contains("503") || error_msg.contains("504")
-        || error_msg.contains("server error")
-    {
-        return ErrorType::Transient;
-    }
-
-    // 4xx client errors are permanent (except 429 handled above)
-    if error_msg.contains("400") || error_msg.contains("401")
-        || error_msg.contains("403") || error_msg.contains("404")
-        || error_msg.contains("client error")
-    {
-        return ErrorType::Permanent;
-    }
-
-    // Transient errors (worth retrying)
-    if error_msg.contains("timeout")
-        || error_msg.contains("connection")
-        || error_msg.contains("temporarily unavailable")
-        || error_msg.contains("too many open files")
-        || error_msg.contains("resource temporarily unavailable")
-        || error_msg.contains("resource deadlock")
-        || error_msg.contains("try again")
-    {
-        return ErrorType::Transient;
-    }
-
-    // Permanent errors - File system issues
-    if error_msg.contains("file not found")
-        || error_msg.contains("no such file")
-        || error_msg.contains("permission denied")
-        || error_msg.contains("access denied")
-        || error_msg.contains("read-only")
-        || error_msg.contains("disk full")
-        || error_msg.contains("no space left")
-    {
-        return ErrorType::Permanent;
-    }
-
-    // Permanent errors - FFmpeg codec/format issues
-    if error_msg.contains("invalid data found")
-        || error_msg.contains("codec not found")
-        || error_msg.contains("unsupported codec")
-        || error_msg.contains("unknown codec")
-        || error_msg.contains("invalid audio")
-        || error_msg.contains("invalid sample rate")
-        || error_msg.contains("invalid bit rate")
-        || error_msg.contains("invalid channel")
-        || error_msg.contains("not supported")
-        || error_msg.contains("does not contain any stream")
-        || error_msg.contains("no decoder")
-        || error_msg.contains("no encoder")
-        || error_msg.contains("moov atom not found")
-        || error_msg.contains("invalid argument")
-        || error_msg.contains("protocol not found")
-    {
-        return ErrorType::Permanent;
-    }
+    let transient = error.chain().find_map(|cause| {
+        if let Some(e) = cause.downcast_ref::<ForgeError>() {
+            Some(e.is_transient())
+        } else if let Some(e) = cause.downcast_ref::<AudibleApiError>() {
+            Some(http_is_transient(e))
+        } else if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            Some(reqwest_is_transient(e))
+        } else {
+            cause.downcast_ref::<std::io::Error>().map(io_is_transient)
+        }
+    });
 
-    // Permanent errors - Data corruption
-    if error_msg.contains("corrupted")
-        || error_msg.contains("corrupt")
-        || error_msg.contains("truncated")
-        || error_msg.contains("header missing")
-        || error_msg.contains("malformed")
-        || error_msg.contains("end of file")
-    {
-        return ErrorType::Permanent;
+    match transient {
+        Some(false) => ErrorType::Permanent,
+        _ => ErrorType::Transient,
     }
-
-    // Default to transient (conservative approach)
-    ErrorType::Transient
 }
 
 /// Execute with smart retry (only retry transient errors)
@@ -374,14 +314,25 @@ mod tests {
 
     #[test]
     fn test_classify_error() {
-        let transient = anyhow::anyhow!("Connection timeout");
-        assert_eq!(classify_error(&transient), ErrorType::Transient);
+        let transient = ForgeError::io("Read failed", std::io::Error::from(std::io::ErrorKind::TimedOut));
+        assert_eq!(classify_error(&transient.into()), ErrorType::Transient);
 
-        let permanent = anyhow::anyhow!("File not found");
-        assert_eq!(classify_error(&permanent), ErrorType::Permanent);
+        let permanent = ForgeError::io("Read failed", std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(classify_error(&permanent.into()), ErrorType::Permanent);
 
         let unknown = anyhow::anyhow!("Some random error");
         assert_eq!(classify_error(&unknown), ErrorType::Transient);
+
+        // The cause is classified even when wrapped in context
+        let wrapped = anyhow::Error::new(ForgeError::corrupt("01.mp3", "moov atom not found"))
+            .context("Failed to convert audio file");
+        assert_eq!(classify_error(&wrapped), ErrorType::Permanent);
+
+        // Messages are not inspected: paths and titles can contain anything
+        let misleading = anyhow::anyhow!("File not found: /books/500 Connection Errors/01.mp3");
+        assert_eq!(classify_error(&misleading), ErrorType::Transient);
+        let io: Result<()> = Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied).into());
+        assert_eq!(classify_error(&io.unwrap_err().context("timeout")), ErrorType::Permanent);
     }
 
     #[tokio::test]
@@ -393,7 +344,7 @@ mod tests {
             let counter = Arc::clone(&counter);
             async move {
                 counter.fetch_add(1, Ordering::Relaxed);
-                anyhow::bail!("File not found")
+                Err(ForgeError::corrupt("01.mp3", "Header missing").into())
             }
         })
         .await;
@@ -417,7 +368,8 @@ mod tests {
             async move {
                 let count = counter.fetch_add(1, Ordering::Relaxed);
                 if count < 2 {
-                    anyhow::bail!("Connection timeout");
+                    let timeout = std::io::Error::from(std::io::ErrorKind::TimedOut);
+                    return Err(ForgeError::io("Connection timeout", timeout).into());
                 }
                 Ok::<i32, anyhow::Error>(42)
             }
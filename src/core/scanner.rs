@@ -1,6 +1,7 @@
 //! Directory scanner for discovering audiobook folders
 
-use crate::models::{BookFolder, BookCase, Config};
+use crate::core::{Journal, PartialOutput};
+use crate::models::{BookFolder, BookCase, BookOverrides, Config};
 use anyhow::{Context, Result};
 use std::path::Path;
 use walkdir::WalkDir;
@@ -11,6 +12,10 @@ pub struct Scanner {
     cover_filenames: Vec<String>,
     /// Auto-extract embedded cover art
     auto_extract_cover: bool,
+    /// Configuration that per-folder overrides are merged over
+    base_config: Config,
+    /// Journal recording extracted cover files
+    journal: Option<Journal>,
 }
 
 impl Scanner {
@@ -24,6 +29,8 @@ impl Scanner {
                 "folder.png".to_string(),
             ],
             auto_extract_cover: true,
+            base_config: Config::default(),
+            journal: None,
         }
     }
 
@@ -32,6 +39,8 @@ impl Scanner {
         Self {
             cover_filenames,
             auto_extract_cover: true,
+            base_config: Config::default(),
+            journal: None,
         }
     }
 
@@ -40,9 +49,17 @@ impl Scanner {
         Self {
             cover_filenames: config.metadata.cover_filenames.clone(),
             auto_extract_cover: config.metadata.auto_extract_cover,
+            base_config: config.clone(),
+            journal: None,
         }
     }
 
+    /// Record cover files extracted into book folders in a journal
+    pub fn with_journal(mut self, journal: Option<Journal>) -> Self {
+        self.journal = journal;
+        self
+    }
+
     /// Scan a directory for audiobook folders
     pub fn scan_directory(&self, root: &Path) -> Result<Vec<BookFolder>> {
         if !root.exists() {
@@ -101,6 +118,7 @@ impl Scanner {
     /// Scan a single folder and determine if it's an audiobook
     fn scan_folder(&self, path: &Path) -> Result<Option<BookFolder>> {
         let mut book = BookFolder::new(path.to_path_buf());
+        let overrides = BookOverrides::load_from_folder(path)?;
 
         // Find audio files
         for entry in std::fs::read_dir(path).context("Failed to read directory")? {
@@ -142,6 +160,12 @@ impl Scanner {
             }
         }
 
+        // Leave out files excluded by the folder's override file
+        if let Some(overrides) = &overrides {
+            book.mp3_files.retain(|f| !overrides.is_excluded(f));
+            book.m4b_files.retain(|f| !overrides.is_excluded(f));
+        }
+
         // Classify the book
         book.classify();
 
@@ -155,8 +179,28 @@ impl Scanner {
                 crate::utils::sort_by_part_number(&mut book.m4b_files);
             }
 
+            // Apply the folder's override file (track order, pinned ASIN, config)
+            let mut auto_extract_cover = self.auto_extract_cover;
+            if let Some(overrides) = overrides {
+                tracing::info!("Using overrides from {}", overrides.source.display());
+                overrides.apply_to_files(&mut book.mp3_files);
+                overrides.apply_to_files(&mut book.m4b_files);
+
+                if let Some(asin) = &overrides.asin {
+                    book.detected_asin = Some(asin.clone());
+                }
+
+                if overrides.config.is_some() {
+                    let config = overrides.apply_to_config(&self.base_config)?;
+                    auto_extract_cover = config.metadata.auto_extract_cover;
+                    book.config = Some(Box::new(config));
+                }
+
+                book.overrides = Some(overrides);
+            }
+
             // Auto-extract embedded cover art if enabled and no standalone cover found
-            if self.auto_extract_cover && book.cover_file.is_none() {
+            if auto_extract_cover && book.cover_file.is_none() {
                 // Try extracting from first audio file
                 let first_audio = if !book.mp3_files.is_empty() {
                     book.mp3_files.first()
@@ -167,18 +211,23 @@ impl Scanner {
                 };
 
                 if let Some(audio_file) = first_audio {
-                    // Create temp file for extracted cover
-                    let extracted_cover = path.join(".extracted_cover.jpg");
-
-                    match crate::audio::extract_embedded_cover(audio_file, &extracted_cover) {
-                        Ok(true) => {
+                    // Create temp file for extracted cover (journaled, as it's
+                    // written into the book folder)
+                    let extracted_cover = PartialOutput::new(&path.join(".extracted_cover.jpg"));
+
+                    match crate::audio::extract_embedded_cover(audio_file, extracted_cover.path())
+                        .and_then(|found| match found {
+                            true => extracted_cover.commit(self.journal.as_ref()).map(Some),
+                            false => Ok(None),
+                        }) {
+                        Ok(Some(extracted_cover)) => {
                             tracing::info!(
                                 "Extracted embedded cover from: {}",
                                 audio_file.file_name().unwrap_or_default().to_string_lossy()
                             );
                             book.cover_file = Some(extracted_cover);
                         }
-                        Ok(false) => {
+                        Ok(None) => {
                             tracing::debug!("No embedded cover found in first audio file");
                         }
                         Err(e) => {
@@ -186,7 +235,7 @@ impl Scanner {
                         }
                     }
                 }
-            } else if !self.auto_extract_cover && book.cover_file.is_none() {
+            } else if !auto_extract_cover && book.cover_file.is_none() {
                 // auto_extract_cover disabled and no standalone cover found:
                 // any embedded cover in the source audio is dropped during
                 // conversion/merge (-vn), so the output will have no cover art.
@@ -277,6 +326,71 @@ mod tests {
         assert!(books[0].cover_file.is_some());
     }
 
+    #[test]
+    fn test_scan_folder_with_overrides() {
+        let dir = tempdir().unwrap();
+        let book_dir = dir.path().join("Test Book");
+        fs::create_dir(&book_dir).unwrap();
+
+        for name in ["01.mp3", "02.mp3", "03.mp3", "bonus.mp3"] {
+            fs::write(book_dir.join(name), b"fake mp3 data").unwrap();
+        }
+        fs::write(
+            book_dir.join(".audiobook-forge.yaml"),
+            "asin: B002V5D7RU\nexclude: [bonus.mp3]\norder: [03.mp3]\nconfig:\n  quality:\n    chapter_source: none\n",
+        )
+        .unwrap();
+
+        let scanner = Scanner::new();
+        let books = scanner.scan_directory(dir.path()).unwrap();
+
+        assert_eq!(books.len(), 1);
+        let book = &books[0];
+        let names: Vec<_> = book
+            .mp3_files
+            .iter()
+            .map(|f| f.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["03.mp3", "01.mp3", "02.mp3"]);
+        assert_eq!(book.detected_asin.as_deref(), Some("B002V5D7RU"));
+        assert_eq!(book.effective_config(&Config::default()).quality.chapter_source, "none");
+    }
+
+    #[test]
+    fn test_extracted_cover_is_journaled() {
+        use id3::TagLike;
+
+        let dir = tempdir().unwrap();
+        let book_dir = dir.path().join("Test Book");
+        fs::create_dir(&book_dir).unwrap();
+        let audio = book_dir.join("01.mp3");
+        fs::write(&audio, b"fake mp3 data").unwrap();
+        let mut tag = id3::Tag::new();
+        tag.add_frame(id3::frame::Picture {
+            mime_type: "image/jpeg".to_string(),
+            picture_type: id3::frame::PictureType::CoverFront,
+            description: String::new(),
+            data: b"cover".to_vec(),
+        });
+        tag.write_to_path(&audio, id3::Version::Id3v24).unwrap();
+
+        // A cover left by an earlier run is replaced, and kept for undo
+        let extracted = book_dir.join(".extracted_cover.jpg");
+        fs::write(&extracted, b"old cover").unwrap();
+
+        let journal_dir = dir.path().join("journal");
+        let journal = Journal::start(&journal_dir);
+        let scanner = Scanner::new().with_journal(Some(journal.clone()));
+        let books = scanner.scan_directory(dir.path()).unwrap();
+        assert_eq!(books[0].cover_file.as_deref(), Some(extracted.as_path()));
+        assert_eq!(fs::read(&extracted).unwrap(), b"cover");
+
+        let run = crate::core::JournalRun::load(&journal_dir, &journal.run_id()).unwrap();
+        assert_eq!(run.entries.len(), 1);
+        run.undo(false).unwrap();
+        assert_eq!(fs::read(&extracted).unwrap(), b"old cover");
+    }
+
     #[test]
     fn test_hidden_directory_skipped() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,254 @@
+//! Output size budgets
+//!
+//! Picks the highest quality whose estimated output fits a total size
+//! ("300MB") or a size per hour of audio ("40MB"), within what the encoder
+//! supports.
+
+use crate::audio::AacEncoder;
+use crate::models::{AacProfile, BookFolder, QualityProfile, QualityConfig, RateControl};
+use anyhow::{bail, Context, Result};
+use std::fmt;
+
+/// Share of the budget kept free for the container and chapter data
+const CONTAINER_OVERHEAD: f64 = 0.01;
+
+/// Below this bitrate (kbps), HE-AAC sounds better than AAC-LC
+const HE_AAC_MAX_BITRATE: u32 = 48;
+
+/// AAC-LC stereo needs at least this bitrate (kbps); below it the output is mono
+const LC_STEREO_MIN_BITRATE: u32 = 96;
+
+/// Size limit for a book's output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeBudget {
+    /// Bytes for the whole book
+    Total(u64),
+    /// Bytes per hour of audio
+    PerHour(u64),
+}
+
+impl SizeBudget {
+    /// Budget from `quality.target_size` / `quality.size_per_hour`, if either is set
+    pub fn from_config(config: &QualityConfig) -> Result<Option<Self>> {
+        match (&config.target_size, &config.size_per_hour) {
+            (Some(_), Some(_)) => bail!("Set only one of quality.target_size and quality.size_per_hour"),
+            (Some(size), None) => Ok(Some(Self::Total(
+                parse_size(size).context("Invalid quality.target_size")?,
+            ))),
+            (None, Some(size)) => Ok(Some(Self::PerHour(
+                parse_size(size).context("Invalid quality.size_per_hour")?,
+            ))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Bytes available for a book of this length
+    pub fn bytes_for(&self, duration_secs: f64) -> u64 {
+        match self {
+            Self::Total(bytes) => *bytes,
+            Self::PerHour(bytes) => (*bytes as f64 * duration_secs / 3600.0) as u64,
+        }
+    }
+}
+
+impl fmt::Display for SizeBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Total(bytes) => write!(f, "{}", format_size(*bytes)),
+            Self::PerHour(bytes) => write!(f, "{}/h", format_size(*bytes)),
+        }
+    }
+}
+
+/// Parse a size such as "300MB", "1.5 GB", "700MiB" or "52428800"
+///
+/// K/M/G are decimal (1000-based); KiB/MiB/GiB are binary.
+pub fn parse_size(text: &str) -> Result<u64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .with_context(|| format!("'{}' is not a size (e.g. \"300MB\")", text))?;
+
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        other => bail!("Unknown size unit '{}' in '{}' (use KB, MB, GB, KiB, MiB or GiB)", other, text),
+    };
+    let bytes = number * multiplier as f64;
+    if bytes < 1.0 {
+        bail!("Size '{}' must be positive", text);
+    }
+    Ok(bytes as u64)
+}
+
+/// Format a byte count as MB (or GB from 1000 MB)
+pub fn format_size(bytes: u64) -> String {
+    let mb = bytes as f64 / 1_000_000.0;
+    if mb >= 1000.0 {
+        format!("{:.2} GB", mb / 1000.0)
+    } else {
+        format!("{:.1} MB", mb)
+    }
+}
+
+/// Highest quality whose output fits `budget` bytes, given the audio length
+///
+/// The bitrate is capped at the source bitrate and the encoder's range.
+/// Encoders with HE-AAC use it at low bitrates (HE-AACv2 for stereo
+/// sources); otherwise stereo needs 96 kbps and the sample rate drops with
+/// the bitrate per channel.
+pub fn fit_to_size(
+    source: &QualityProfile,
+    duration_secs: f64,
+    budget: u64,
+    encoder: AacEncoder,
+) -> Result<QualityProfile> {
+    if duration_secs <= 0.0 {
+        bail!("Unknown duration; can't fit a size budget");
+    }
+    let audio_bytes = budget as f64 * (1.0 - CONTAINER_OVERHEAD);
+    let available = (audio_bytes * 8.0 / 1000.0 / duration_secs).floor() as u32;
+    let (min, max) = encoder.bitrate_range();
+    if available < min {
+        bail!(
+            "{} for {:.1} h of audio allows {} kbps, below the {} minimum of {} kbps",
+            format_size(budget),
+            duration_secs / 3600.0,
+            available,
+            encoder.name(),
+            min
+        );
+    }
+
+    let mut bitrate = available.min(max).min(source.bitrate.max(min));
+    if bitrate >= 32 {
+        bitrate -= bitrate % 8;
+    }
+
+    let source_rate = source.sample_rate.min(48000);
+    let he = bitrate <= HE_AAC_MAX_BITRATE && encoder.supports_profile(AacProfile::He);
+    let (aac_profile, channels, sample_rate) = if he {
+        if source.channels == 2 && encoder.supports_profile(AacProfile::HeV2) {
+            (AacProfile::HeV2, 2, source_rate.min(44100))
+        } else {
+            (AacProfile::He, 1, source_rate.min(44100))
+        }
+    } else {
+        let channels = if source.channels == 2 && bitrate >= LC_STEREO_MIN_BITRATE { 2 } else { 1 };
+        let per_channel = bitrate / channels as u32;
+        let sample_rate = match per_channel {
+            56.. => source_rate,
+            40..=55 => source_rate.min(44100),
+            28..=39 => source_rate.min(32000),
+            20..=27 => source_rate.min(22050),
+            _ => source_rate.min(16000),
+        };
+        (AacProfile::Lc, channels, sample_rate)
+    };
+
+    Ok(QualityProfile {
+        bitrate,
+        sample_rate,
+        channels,
+        codec: "aac".to_string(),
+        duration: duration_secs,
+        aac_profile,
+        rate_control: RateControl::Cbr,
+    })
+}
+
+/// Quality a book needs to fit its budget, or None if the source already fits
+pub fn plan_for_budget(
+    book: &BookFolder,
+    budget: SizeBudget,
+    encoder: AacEncoder,
+    prefer_stereo: bool,
+) -> Result<Option<QualityProfile>> {
+    let duration = book.get_total_duration();
+    let bytes = budget.bytes_for(duration);
+    if book.estimate_output_size() <= bytes {
+        return Ok(None);
+    }
+    let source = book
+        .get_best_quality_profile(prefer_stereo)
+        .context("No analyzed tracks")?;
+    fit_to_size(source, duration, bytes, encoder).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: f64 = 3600.0;
+
+    fn source(bitrate: u32, channels: u8) -> QualityProfile {
+        QualityProfile::new(bitrate, 44100, channels, "mp3".to_string(), HOUR).unwrap()
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("300MB").unwrap(), 300_000_000);
+        assert_eq!(parse_size("1.5 GB").unwrap(), 1_500_000_000);
+        assert_eq!(parse_size("700MiB").unwrap(), 700 * 1024 * 1024);
+        assert_eq!(parse_size("4096").unwrap(), 4096);
+        assert!(parse_size("lots").is_err());
+        assert!(parse_size("12 parsecs").is_err());
+        assert!(parse_size("0MB").is_err());
+    }
+
+    #[test]
+    fn test_budget_from_config() {
+        let mut config = QualityConfig::default();
+        assert_eq!(SizeBudget::from_config(&config).unwrap(), None);
+
+        config.size_per_hour = Some("30MB".to_string());
+        let budget = SizeBudget::from_config(&config).unwrap().unwrap();
+        assert_eq!(budget.bytes_for(2.0 * HOUR), 60_000_000);
+        assert_eq!(budget.to_string(), "30.0 MB/h");
+
+        config.target_size = Some("300MB".to_string());
+        assert!(SizeBudget::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_fit_to_size_lc() {
+        // 300 MB over 10 h: ~66 kbps -> 64 kbps mono
+        let quality = fit_to_size(&source(128, 2), 10.0 * HOUR, 300_000_000, AacEncoder::Native).unwrap();
+        assert_eq!((quality.bitrate, quality.channels, quality.sample_rate), (64, 1, 44100));
+        assert_eq!(quality.aac_profile, AacProfile::Lc);
+        assert!((quality.bitrate as f64 * 10.0 * HOUR * 125.0) < 300_000_000.0);
+
+        // Never above the source bitrate; stereo kept when there's room
+        let quality = fit_to_size(&source(128, 2), HOUR, 1_000_000_000, AacEncoder::Native).unwrap();
+        assert_eq!((quality.bitrate, quality.channels), (128, 2));
+
+        // Low budgets lower the sample rate
+        let quality = fit_to_size(&source(128, 2), 10.0 * HOUR, 120_000_000, AacEncoder::Native).unwrap();
+        assert_eq!((quality.bitrate, quality.sample_rate), (26, 22050));
+    }
+
+    #[test]
+    fn test_fit_to_size_he_aac() {
+        let quality = fit_to_size(&source(128, 2), 10.0 * HOUR, 180_000_000, AacEncoder::LibFdk).unwrap();
+        assert_eq!((quality.bitrate, quality.channels), (32, 2));
+        assert_eq!(quality.aac_profile, AacProfile::HeV2);
+
+        let quality = fit_to_size(&source(128, 1), 10.0 * HOUR, 180_000_000, AacEncoder::LibFdk).unwrap();
+        assert_eq!((quality.channels, quality.aac_profile), (1, AacProfile::He));
+    }
+
+    #[test]
+    fn test_budget_too_small() {
+        let error = fit_to_size(&source(128, 2), 10.0 * HOUR, 10_000_000, AacEncoder::Native).unwrap_err();
+        assert!(error.to_string().contains("below the aac minimum"), "{}", error);
+    }
+}
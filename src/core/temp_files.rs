@@ -0,0 +1,272 @@
+//! Temp directories and unfinished outputs that clean up after themselves
+//!
+//! Both are removed when dropped — on errors, and when a book is cancelled
+//! by Ctrl-C — so nothing half-written is left behind. A temp directory
+//! holds a lock while in use; one left by a crashed run has a lock nobody
+//! holds, and [`sweep_orphaned_temp_dirs`] removes it on the next build.
+
+use crate::core::Journal;
+use anyhow::{Context, Result};
+use fs2::FileExt;
+use std::fs::{File, OpenOptions};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// Prefix of every temp directory a build creates
+pub const TEMP_DIR_PREFIX: &str = "audiobook-forge-";
+
+/// Lock file held while a temp directory is in use
+const LOCK_FILE: &str = ".lock";
+
+/// A book's temp directory, removed when dropped (unless kept for debugging)
+#[derive(Debug)]
+pub struct BookTempDir {
+    path: PathBuf,
+    lock: Option<File>,
+    keep: bool,
+}
+
+impl BookTempDir {
+    /// Create `<base>/audiobook-forge-<kind><name>`, emptying any leftover one
+    ///
+    /// Fails, touching nothing, if another run holds the directory.
+    pub fn create(base: &Path, kind: &str, name: &str, keep: bool) -> Result<Self> {
+        let sanitized_name = sanitize_filename::sanitize(name);
+        let path = base.join(format!("{}{}{}", TEMP_DIR_PREFIX, kind, sanitized_name));
+        std::fs::create_dir_all(&path).context("Failed to create temp directory")?;
+
+        // Lock first, so a directory in use is never cleared
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path.join(LOCK_FILE))
+            .context("Failed to create temp directory lock")?;
+        lock.try_lock_exclusive()
+            .with_context(|| format!("Temp directory {} is in use by another run", path.display()))?;
+
+        // Clear what a previous run of this book left behind
+        for entry in std::fs::read_dir(&path).context("Failed to read temp directory")?.flatten() {
+            if entry.file_name() == LOCK_FILE {
+                continue;
+            }
+            let entry_path = entry.path();
+            let removed = if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                std::fs::remove_dir_all(&entry_path)
+            } else {
+                std::fs::remove_file(&entry_path)
+            };
+            removed.with_context(|| format!("Failed to clear {}", entry_path.display()))?;
+        }
+
+        Ok(Self { path, lock: Some(lock), keep })
+    }
+
+    /// Path of the directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Deref for BookTempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for BookTempDir {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for BookTempDir {
+    fn drop(&mut self) {
+        // Unlock before removing (Windows can't delete an open file)
+        drop(self.lock.take());
+        if self.keep {
+            // Kept directories aren't orphans
+            std::fs::remove_file(self.path.join(LOCK_FILE)).ok();
+        } else if let Err(e) = std::fs::remove_dir_all(&self.path) {
+            tracing::warn!("Failed to remove temp directory: {}", e);
+        }
+    }
+}
+
+/// Remove temp directories in `base` left by runs that crashed
+///
+/// Only directories with a lock file nobody holds are removed; ones in use
+/// by another run, and ones kept with `--keep-temp`, are left alone.
+pub fn sweep_orphaned_temp_dirs(base: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(base) else {
+        return Vec::new();
+    };
+
+    let mut removed = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_ours = entry.file_name().to_string_lossy().starts_with(TEMP_DIR_PREFIX);
+        if !is_ours || !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        let Ok(lock) = File::open(path.join(LOCK_FILE)) else {
+            continue;
+        };
+        if lock.try_lock_exclusive().is_err() {
+            continue;
+        }
+        drop(lock);
+
+        match std::fs::remove_dir_all(&path) {
+            Ok(()) => {
+                tracing::info!("Removed orphaned temp directory {}", path.display());
+                removed.push(path);
+            }
+            Err(e) => tracing::warn!("Failed to remove orphaned temp directory {}: {}", path.display(), e),
+        }
+    }
+    removed
+}
+
+/// An output written under a hidden name and renamed into place when done
+///
+/// Dropped without [`commit`](Self::commit), the unfinished file is removed,
+/// so an interrupted or failed build never leaves a file that looks finished.
+#[derive(Debug)]
+pub struct PartialOutput {
+    /// Where the output is written
+    path: PathBuf,
+    /// Where it goes when finished
+    target: PathBuf,
+    committed: bool,
+}
+
+impl PartialOutput {
+    /// Unfinished output for `path`, in the same directory (so the rename is atomic)
+    pub fn new(path: &Path) -> Self {
+        // Keep the extension: FFmpeg picks the container from it
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let name = match path.extension() {
+            Some(ext) => format!(".{}.partial.{}", stem, ext.to_string_lossy()),
+            None => format!(".{}.partial", stem),
+        };
+        Self {
+            path: path.with_file_name(name),
+            target: path.to_path_buf(),
+            committed: false,
+        }
+    }
+
+    /// Where the output is written until it's finished
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Move the finished output to its final path
+    ///
+    /// With a journal, the move is recorded, and a file it replaces is kept
+    /// so `undo` can bring it back.
+    pub fn commit(mut self, journal: Option<&Journal>) -> Result<PathBuf> {
+        match journal {
+            Some(journal) => journal.commit_file(&self.path, &self.target),
+            None => std::fs::rename(&self.path, &self.target).map_err(Into::into),
+        }
+        .with_context(|| {
+            format!("Failed to move {} to {}", self.path.display(), self.target.display())
+        })?;
+        self.committed = true;
+        Ok(std::mem::take(&mut self.target))
+    }
+}
+
+impl Drop for PartialOutput {
+    fn drop(&mut self) {
+        if !self.committed && self.path.exists() {
+            if let Err(e) = std::fs::remove_file(&self.path) {
+                tracing::warn!("Failed to remove unfinished output {}: {}", self.path.display(), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_dir_removed_on_drop_unless_kept() {
+        let base = tempfile::tempdir().unwrap();
+
+        let temp_dir = BookTempDir::create(base.path(), "", "Some: Book", false).unwrap();
+        let path = temp_dir.to_path_buf();
+        assert!(path.ends_with("audiobook-forge-Some Book"));
+        std::fs::write(path.join("concat.txt"), "").unwrap();
+        drop(temp_dir);
+        assert!(!path.exists());
+
+        let kept = BookTempDir::create(base.path(), "merge-", "Book", true).unwrap();
+        let path = kept.to_path_buf();
+        drop(kept);
+        assert!(path.exists());
+        assert!(!path.join(LOCK_FILE).exists());
+    }
+
+    #[test]
+    fn test_temp_dir_in_use_is_left_alone() {
+        let base = tempfile::tempdir().unwrap();
+
+        let mut first = BookTempDir::create(base.path(), "", "Book", false).unwrap();
+        std::fs::write(first.join("encoded_0000.m4a"), "audio").unwrap();
+
+        let second = BookTempDir::create(base.path(), "", "Book", false);
+        assert!(second.unwrap_err().to_string().contains("in use by another run"));
+        assert!(first.join("encoded_0000.m4a").exists());
+
+        // Once released (as by a crashed run), a leftover directory is emptied and reused
+        std::fs::create_dir(first.join("trimmed")).unwrap();
+        drop(first.lock.take());
+        let again = BookTempDir::create(base.path(), "", "Book", false).unwrap();
+        assert_eq!(std::fs::read_dir(&*again).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_sweep_removes_only_orphans() {
+        let base = tempfile::tempdir().unwrap();
+
+        // A crashed run leaves its lock file behind
+        let crashed = base.path().join("audiobook-forge-Crashed");
+        std::fs::create_dir(&crashed).unwrap();
+        std::fs::write(crashed.join(LOCK_FILE), "").unwrap();
+        let kept = BookTempDir::create(base.path(), "", "Kept", true).unwrap().to_path_buf();
+        let in_use = BookTempDir::create(base.path(), "", "In Use", false).unwrap();
+        let other = base.path().join("something-else");
+        std::fs::create_dir(&other).unwrap();
+        std::fs::write(other.join(LOCK_FILE), "").unwrap();
+
+        assert_eq!(sweep_orphaned_temp_dirs(base.path()), vec![crashed.clone()]);
+        assert!(!crashed.exists());
+        assert!(kept.exists() && in_use.exists() && other.exists());
+    }
+
+    #[test]
+    fn test_partial_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Book (1.5x).m4b");
+
+        let output = PartialOutput::new(&path);
+        assert_eq!(output.path(), dir.path().join(".Book (1.5x).partial.m4b"));
+        std::fs::write(output.path(), "audio").unwrap();
+        assert_eq!(output.commit(None).unwrap(), path);
+        assert!(path.exists());
+
+        // Abandoned outputs are removed and never reach the final path
+        std::fs::remove_file(&path).unwrap();
+        let output = PartialOutput::new(&path);
+        let partial = output.path().to_path_buf();
+        std::fs::write(&partial, "half").unwrap();
+        drop(output);
+        assert!(!partial.exists() && !path.exists());
+    }
+}
@@ -0,0 +1,112 @@
+//! Intro/outro and silence trimming
+//!
+//! Trimmed tracks are cut to lossless intermediates before encoding and
+//! probed again, so chapters generated from the files follow the trimmed
+//! audio rather than the source durations.
+
+use crate::audio::{SilenceTrim, TrackTrim};
+use crate::error::ForgeError;
+use crate::models::QualityConfig;
+use anyhow::Result;
+use std::fmt;
+
+/// Trimming applied to a book's tracks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookTrim {
+    /// Seconds cut from the start of the first track
+    pub intro_secs: f64,
+    /// Seconds cut from the end of the last track
+    pub outro_secs: f64,
+    /// Silence threshold (dBFS) and seconds of silence kept, if silence is trimmed
+    pub silence: Option<(f64, f64)>,
+}
+
+impl BookTrim {
+    /// Trimming from the quality settings (None if nothing is trimmed)
+    pub fn from_config(config: &QualityConfig) -> Option<Self> {
+        let trim = Self {
+            intro_secs: config.trim_intro.max(0.0),
+            outro_secs: config.trim_outro.max(0.0),
+            silence: config
+                .trim_silence
+                .then_some((config.silence_threshold, config.silence_keep.max(0.0))),
+        };
+        (trim.intro_secs > 0.0 || trim.outro_secs > 0.0 || trim.silence.is_some()).then_some(trim)
+    }
+
+    /// How track `index` of `count` (lasting `duration` seconds) is cut
+    pub fn for_track(&self, index: usize, count: usize, duration: f64) -> Result<TrackTrim> {
+        let start = if index == 0 { self.intro_secs } else { 0.0 };
+        let end = (index + 1 == count && self.outro_secs > 0.0).then_some(duration - self.outro_secs);
+        if end.unwrap_or(duration) <= start {
+            return Err(ForgeError::Config(format!(
+                "Trimming {:.1} s intro and {:.1} s outro leaves nothing of a {:.1} s track",
+                self.intro_secs, self.outro_secs, duration
+            ))
+            .into());
+        }
+
+        Ok(TrackTrim {
+            start,
+            end,
+            silence: self.silence.map(|(threshold_db, keep_secs)| SilenceTrim { threshold_db, keep_secs }),
+        })
+    }
+}
+
+impl fmt::Display for BookTrim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.intro_secs > 0.0 {
+            parts.push(format!("{:.1} s intro", self.intro_secs));
+        }
+        if self.outro_secs > 0.0 {
+            parts.push(format!("{:.1} s outro", self.outro_secs));
+        }
+        if let Some((threshold_db, _)) = self.silence {
+            parts.push(format!("silence below {} dB", threshold_db));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_config() {
+        let mut config = QualityConfig::default();
+        assert_eq!(BookTrim::from_config(&config), None);
+
+        config.trim_intro = 2.0;
+        config.trim_silence = true;
+        let trim = BookTrim::from_config(&config).unwrap();
+        assert_eq!(trim.silence, Some((-50.0, 0.25)));
+        assert_eq!(trim.to_string(), "2.0 s intro, silence below -50 dB");
+    }
+
+    #[test]
+    fn test_for_track() {
+        let trim = BookTrim { intro_secs: 2.0, outro_secs: 5.0, silence: None };
+
+        let first = trim.for_track(0, 3, 600.0).unwrap();
+        assert_eq!((first.start, first.end), (2.0, None));
+        assert!(trim.for_track(1, 3, 600.0).unwrap().is_empty());
+        let last = trim.for_track(2, 3, 600.0).unwrap();
+        assert_eq!((last.start, last.end), (0.0, Some(595.0)));
+
+        // A lone track loses both
+        let only = trim.for_track(0, 1, 600.0).unwrap();
+        assert_eq!((only.start, only.end), (2.0, Some(595.0)));
+        assert!(trim.for_track(0, 1, 6.0).is_err());
+    }
+
+    #[test]
+    fn test_long_tracks_trim_silence() {
+        let trim = BookTrim { intro_secs: 0.0, outro_secs: 0.0, silence: Some((-45.0, 0.5)) };
+        let silence = SilenceTrim { threshold_db: -45.0, keep_secs: 0.5 };
+        assert_eq!(trim.for_track(0, 1, 1800.0).unwrap().silence, Some(silence));
+        assert_eq!(trim.for_track(0, 1, 5.0 * 3600.0).unwrap().silence, Some(silence));
+    }
+}
@@ -0,0 +1,352 @@
+//! Typed errors
+//!
+//! Failures that callers need to tell apart — whether to retry, what to show in
+//! a report, which exit code to use — are raised as a [`ForgeError`] somewhere
+//! in the `anyhow` chain. Contexts added on the way up don't hide it:
+//! [`ErrorKind::of`] and [`core::classify_error`](crate::core::classify_error)
+//! look through the whole chain.
+
+use crate::audio::{AudibleApiError, Tool};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use thiserror::Error;
+
+/// FFmpeg/FFprobe messages meaning the input itself can't be read
+const CORRUPT_INPUT_MARKERS: &[&str] = &[
+    "Invalid data found when processing input",
+    "moov atom not found",
+    "does not contain any stream",
+    "Header missing",
+    "could not find codec parameters",
+    "Invalid frame size",
+    "Error while decoding stream",
+];
+
+/// FFmpeg messages meaning the build lacks something we asked for
+const MISSING_FEATURE_MARKERS: &[&str] = &["Unknown encoder", "Encoder not found", "No such filter"];
+
+/// Errors audiobook-forge distinguishes
+#[derive(Error, Debug)]
+pub enum ForgeError {
+    /// An external tool ran and failed
+    #[error("{operation} failed ({}): {stderr}", exit_status(*.exit_code))]
+    ToolFailed {
+        tool: Tool,
+        /// What was being done, e.g. "FFmpeg conversion"
+        operation: String,
+        /// Exit code (None if killed by a signal)
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+
+    /// An external tool (or a feature of it) is missing, unusable or too old
+    #[error("{message}")]
+    ToolUnavailable { tool: Tool, message: String },
+
+    /// A filesystem operation failed
+    #[error("{message}")]
+    Io {
+        message: String,
+        #[source]
+        source: io::Error,
+    },
+
+    /// An Audible or Audnexus request failed
+    #[error(transparent)]
+    Http(#[from] AudibleApiError),
+
+    /// An input file is corrupt, truncated or unusable
+    #[error("{}: {reason}", .path.display())]
+    CorruptInput { path: PathBuf, reason: String },
+
+    /// Invalid configuration or arguments
+    #[error("{0}")]
+    Config(String),
+
+    /// Stopped by Ctrl-C
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+fn exit_status(code: Option<i32>) -> String {
+    match code {
+        Some(code) => format!("exit code {}", code),
+        None => "killed by signal".to_string(),
+    }
+}
+
+impl ForgeError {
+    /// Error for a tool that exited unsuccessfully
+    ///
+    /// FFmpeg's stderr is checked for messages that mean the input can't be
+    /// decoded (reported as [`CorruptInput`](Self::CorruptInput) of `input`) or
+    /// that the build lacks an encoder or filter
+    /// ([`ToolUnavailable`](Self::ToolUnavailable)).
+    pub fn tool_failed(tool: Tool, operation: &str, output: &Output, input: Option<&Path>) -> Self {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+
+        if matches!(tool, Tool::FFmpeg | Tool::FFprobe) {
+            if let Some(input) = input {
+                if let Some(line) = marker_line(&stderr, CORRUPT_INPUT_MARKERS) {
+                    return Self::CorruptInput {
+                        path: input.to_path_buf(),
+                        reason: line.to_string(),
+                    };
+                }
+            }
+            if let Some(line) = marker_line(&stderr, MISSING_FEATURE_MARKERS) {
+                return Self::ToolUnavailable {
+                    tool,
+                    message: format!(
+                        "{} failed: {}\nTip: Run 'audiobook-forge check' to verify encoder availability",
+                        operation, line
+                    ),
+                };
+            }
+        }
+
+        Self::ToolFailed {
+            tool,
+            operation: operation.to_string(),
+            exit_code: output.status.code(),
+            stderr,
+        }
+    }
+
+    /// Filesystem error with a description of what was being done
+    pub fn io(message: impl Into<String>, source: io::Error) -> Self {
+        Self::Io {
+            message: message.into(),
+            source,
+        }
+    }
+
+    /// Invalid configuration, described by what was being done and why it failed
+    pub fn config(context: impl fmt::Display, error: impl fmt::Display) -> Self {
+        Self::Config(format!("{}: {}", context, error))
+    }
+
+    /// Corrupt or unusable input file
+    pub fn corrupt(path: impl Into<PathBuf>, reason: impl Into<String>) -> Self {
+        Self::CorruptInput {
+            path: path.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Kind of this error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::ToolFailed { .. } => ErrorKind::Tool,
+            Self::ToolUnavailable { .. } => ErrorKind::ToolUnavailable,
+            Self::Io { .. } => ErrorKind::Io,
+            Self::Http(_) => ErrorKind::Http,
+            Self::CorruptInput { .. } => ErrorKind::CorruptInput,
+            Self::Config(_) => ErrorKind::Config,
+            Self::Interrupted => ErrorKind::Interrupted,
+        }
+    }
+
+    /// Whether retrying the same operation could succeed
+    pub fn is_transient(&self) -> bool {
+        match self {
+            // Tools fail transiently when killed or starved of resources; a
+            // deterministic failure shows up as one of the other variants
+            Self::ToolFailed { .. } => true,
+            Self::Io { source, .. } => io_is_transient(source),
+            Self::Http(error) => http_is_transient(error),
+            Self::ToolUnavailable { .. } | Self::CorruptInput { .. } | Self::Config(_) | Self::Interrupted => false,
+        }
+    }
+}
+
+/// First stderr line containing one of the markers
+fn marker_line<'a>(stderr: &'a str, markers: &[&str]) -> Option<&'a str> {
+    stderr
+        .lines()
+        .find(|line| markers.iter().any(|m| line.contains(m)))
+        .map(str::trim)
+}
+
+/// Whether an I/O error could go away on its own
+pub(crate) fn io_is_transient(error: &io::Error) -> bool {
+    use io::ErrorKind::*;
+    !matches!(
+        error.kind(),
+        NotFound
+            | PermissionDenied
+            | AlreadyExists
+            | InvalidInput
+            | InvalidData
+            | Unsupported
+            | StorageFull
+            | ReadOnlyFilesystem
+            | IsADirectory
+            | NotADirectory
+            | InvalidFilename
+    )
+}
+
+/// Whether an Audible request could succeed if repeated
+pub(crate) fn http_is_transient(error: &AudibleApiError) -> bool {
+    match error {
+        AudibleApiError::HttpError { status, .. } => *status == 429 || *status >= 500,
+        AudibleApiError::RateLimitExceeded { .. } => true,
+        AudibleApiError::RequestFailed(e) => reqwest_is_transient(e),
+        AudibleApiError::ParseError(_) | AudibleApiError::NotCached { .. } => false,
+    }
+}
+
+/// Whether a reqwest error could succeed if repeated
+pub(crate) fn reqwest_is_transient(error: &reqwest::Error) -> bool {
+    match error.status() {
+        Some(status) => status.as_u16() == 429 || status.is_server_error(),
+        None => !(error.is_decode() || error.is_builder() || error.is_redirect()),
+    }
+}
+
+/// Broad category of a failure, used in reports and for the exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// An external tool failed
+    Tool,
+    /// An external tool or one of its features is missing
+    ToolUnavailable,
+    /// Filesystem error
+    Io,
+    /// Network or API error
+    Http,
+    /// Corrupt or unusable input
+    CorruptInput,
+    /// Invalid configuration
+    Config,
+    /// Stopped by Ctrl-C
+    Interrupted,
+    /// Anything else
+    Other,
+}
+
+impl ErrorKind {
+    /// Kind of the first typed error in an error's chain
+    pub fn of(error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(e) = cause.downcast_ref::<ForgeError>() {
+                return e.kind();
+            }
+            if cause.is::<AudibleApiError>() || cause.is::<reqwest::Error>() {
+                return Self::Http;
+            }
+            if cause.is::<io::Error>() {
+                return Self::Io;
+            }
+        }
+        Self::Other
+    }
+
+    /// Short name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tool => "tool",
+            Self::ToolUnavailable => "tool_unavailable",
+            Self::Io => "io",
+            Self::Http => "http",
+            Self::CorruptInput => "corrupt_input",
+            Self::Config => "config",
+            Self::Interrupted => "interrupted",
+            Self::Other => "other",
+        }
+    }
+
+    /// Process exit code for a command that failed with this kind of error
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Other | Self::Tool | Self::Io | Self::Http => 1,
+            Self::ToolUnavailable => 3,
+            Self::Config => 4,
+            Self::CorruptInput => 5,
+            Self::Interrupted => crate::utils::interrupt::INTERRUPTED_EXIT_CODE,
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[cfg(unix)]
+    fn output(code: i32, stderr: &str) -> Output {
+        use std::os::unix::process::ExitStatusExt;
+        Output {
+            status: std::process::ExitStatus::from_raw(code << 8),
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_tool_failed_variants() {
+        let input = Path::new("/books/500 Connection Errors/01.mp3");
+
+        let err = ForgeError::tool_failed(Tool::FFmpeg, "FFmpeg conversion", &output(1, "Conversion failed!"), Some(input));
+        assert!(matches!(err, ForgeError::ToolFailed { exit_code: Some(1), .. }));
+        assert_eq!(err.to_string(), "FFmpeg conversion failed (exit code 1): Conversion failed!");
+        assert!(err.is_transient());
+
+        let stderr = "[mp3 @ 0x1] Header missing\n/books/01.mp3: Invalid data found when processing input";
+        let err = ForgeError::tool_failed(Tool::FFmpeg, "FFmpeg conversion", &output(1, stderr), Some(input));
+        assert_eq!(err.kind(), ErrorKind::CorruptInput);
+        assert!(!err.is_transient());
+
+        let err = ForgeError::tool_failed(Tool::FFmpeg, "FFmpeg conversion", &output(1, "Unknown encoder 'libfdk_aac'"), None);
+        assert_eq!(err.kind(), ErrorKind::ToolUnavailable);
+
+        // Only FFmpeg output is interpreted
+        let err = ForgeError::tool_failed(Tool::MP4Box, "MP4Box", &output(1, "Invalid data found when processing input"), Some(input));
+        assert_eq!(err.kind(), ErrorKind::Tool);
+    }
+
+    #[test]
+    fn test_kind_looks_through_context() {
+        let err = anyhow::Error::new(ForgeError::Config("bad".into())).context("Failed to load config");
+        assert_eq!(ErrorKind::of(&err), ErrorKind::Config);
+
+        let err: anyhow::Result<()> = Err(io::Error::from(io::ErrorKind::NotFound)).context("Failed to read file");
+        assert_eq!(ErrorKind::of(&err.unwrap_err()), ErrorKind::Io);
+
+        // Messages don't matter, only types
+        let err = anyhow::anyhow!("HTTP 500: corrupt connection");
+        assert_eq!(ErrorKind::of(&err), ErrorKind::Other);
+    }
+
+    #[test]
+    fn test_transience() {
+        let not_found = ForgeError::io("Failed to read", io::Error::from(io::ErrorKind::NotFound));
+        assert!(!not_found.is_transient());
+        let interrupted = ForgeError::io("Failed to read", io::Error::from(io::ErrorKind::Interrupted));
+        assert!(interrupted.is_transient());
+
+        let http = |status| AudibleApiError::HttpError {
+            status,
+            message: String::new(),
+            url: String::new(),
+            body: String::new(),
+            suggestion: String::new(),
+        };
+        assert!(ForgeError::Http(http(503)).is_transient());
+        assert!(ForgeError::Http(http(429)).is_transient());
+        assert!(!ForgeError::Http(http(404)).is_transient());
+        assert!(!ForgeError::Http(AudibleApiError::NotCached { what: "x".into() }).is_transient());
+    }
+}
@@ -13,11 +13,13 @@ pub mod core;
 pub mod audio;
 pub mod utils;
 pub mod ui;
+pub mod error;
 
 // Re-export commonly used types
 pub use models::{BookFolder, Track, QualityProfile, ProcessingResult, BookCase};
 pub use core::{Scanner, Analyzer, Processor, BatchProcessor};
 pub use utils::Config;
+pub use error::{ErrorKind, ForgeError};
 
 /// Library version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,56 +1,92 @@
 //! Audiobook Forge CLI entry point
 
 use anyhow::{Context, Result};
-use audiobook_forge::cli::{handle_build, handle_check, handle_config, handle_organize, handle_metadata, handle_match, Cli, Commands};
-use audiobook_forge::utils::ConfigManager;
-use audiobook_forge::VERSION;
+use audiobook_forge::cli::output::{set_json_mode, CommandReport};
+use audiobook_forge::cli::{handle_build, handle_check, handle_config, handle_organize, handle_metadata, handle_match, handle_cache, handle_undo, handle_bench, Cli, Commands};
+use audiobook_forge::models::{BookLogMode, LogFormat};
+use audiobook_forge::utils::{interrupt, system_config_path, BookLogLayer, ConfigManager, ConfigSources, LayeredConfig};
+use audiobook_forge::{ForgeError, VERSION};
 use clap::Parser;
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+use std::process::ExitCode;
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry};
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
     // Parse command-line arguments
     let cli = Cli::parse();
+    let command = cli.command_name();
+    let json = cli.json;
+    set_json_mode(json);
+    interrupt::install_handler();
+
+    let report = run(cli).await.unwrap_or_else(CommandReport::failed);
+    if let Some(e) = &report.error {
+        // Same output as returning the error from main, but the exit code
+        // depends on what kind of error it was
+        eprintln!("Error: {:?}", e);
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report.to_json(command)).unwrap_or_default());
+    }
+    ExitCode::from(report.exit_code())
+}
+
+async fn run(cli: Cli) -> Result<CommandReport> {
+    if cli.json && !cli.supports_json() {
+        return Err(ForgeError::Config(format!(
+            "--json is not supported by '{}' (supported: build, organize, bench, check, config show, metadata fetch, match --dry-run)",
+            cli.command_name()
+        ))
+        .into());
+    }
 
-    // Load configuration (or use defaults)
-    let config = load_config()?;
+    // Resolve configuration layers (defaults < files < environment < flags)
+    let layered = load_config(&cli)?;
+    let config = layered.config.clone();
 
     // Initialize logging (needs config for file logging settings)
     init_logging(cli.verbose, &config)?;
+    for warning in layered.warnings.iter().chain(&cli.deprecation_warnings()) {
+        tracing::warn!("{}", warning);
+    }
+
+    // Resolve external tool paths once, before anything runs a subprocess
+    audiobook_forge::audio::init_tools(&config.advanced);
 
     // Execute command
-    match cli.command {
-        Commands::Build(args) => {
-            handle_build(args, config).await?;
-        }
+    let report = match cli.command {
+        Commands::Build(args) => handle_build(args, config).await?,
 
-        Commands::Organize(args) => {
-            handle_organize(args, config)?;
-        }
+        Commands::Organize(args) => handle_organize(args, config)?,
 
-        Commands::Config(command) => {
-            handle_config(command)?;
-        }
+        Commands::Config(command) => handle_config(command, &layered)?,
 
-        Commands::Metadata(command) => {
-            handle_metadata(command, config).await?;
-        }
+        Commands::Metadata(command) => handle_metadata(command, config).await?,
+
+        Commands::Match(args) => handle_match(args, config).await?,
 
-        Commands::Match(args) => {
-            handle_match(args, config).await?;
+        Commands::Cache(command) => {
+            handle_cache(command, config)?;
+            CommandReport::empty()
         }
 
-        Commands::Check => {
-            handle_check()?;
+        Commands::Undo(args) => {
+            handle_undo(args, config)?;
+            CommandReport::empty()
         }
 
+        Commands::Bench(args) => handle_bench(args).await?,
+
+        Commands::Check => handle_check(),
+
         Commands::Version => {
             println!("audiobook-forge {}", VERSION);
             println!("A Rust-powered CLI tool for converting audiobooks to M4B format");
+            CommandReport::empty()
         }
-    }
+    };
 
-    Ok(())
+    Ok(report)
 }
 
 /// Initialize logging based on verbosity level and config
@@ -60,6 +96,7 @@ fn init_logging(verbose: bool, config: &audiobook_forge::models::Config) -> Resu
         "audiobook_forge=debug"
     } else {
         match config.logging.log_level.to_uppercase().as_str() {
+            "TRACE" => "audiobook_forge=trace",
             "DEBUG" => "audiobook_forge=debug",
             "WARNING" | "WARN" => "audiobook_forge=warn",
             "ERROR" => "audiobook_forge=error",
@@ -69,22 +106,30 @@ fn init_logging(verbose: bool, config: &audiobook_forge::models::Config) -> Resu
 
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new(level_str));
+    let json = config.logging.log_format == LogFormat::Json;
+    let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync>> = Vec::new();
 
-    // Console layer (always present)
+    // Console layer (always present; on stderr when stdout carries the JSON report)
     let console_layer = fmt::layer()
+        .with_writer(console_writer as fn() -> Box<dyn std::io::Write>)
         .with_target(false)
-        .with_level(true)
-        .with_filter(filter.clone());
+        .with_level(true);
+    layers.push(if json {
+        console_layer.json().with_filter(filter.clone()).boxed()
+    } else {
+        console_layer.with_filter(filter.clone()).boxed()
+    });
 
     // File layer (optional)
-    if config.logging.log_to_file {
-        let log_file = config.logging.log_file.clone().unwrap_or_else(|| {
-            let home = dirs::home_dir().expect("Cannot determine home directory");
-            let log_dir = home.join(".audiobook-forge").join("logs");
-            std::fs::create_dir_all(&log_dir)
-                .expect("Failed to create log directory");
-            log_dir.join("audiobook-forge.log")
-        });
+    let log_file = if config.logging.log_to_file {
+        let log_file = match config.logging.log_file.clone() {
+            Some(path) => path,
+            None => dirs::home_dir()
+                .context("Cannot determine home directory")?
+                .join(".audiobook-forge")
+                .join("logs")
+                .join("audiobook-forge.log"),
+        };
 
         // Create log directory
         if let Some(parent) = log_file.parent() {
@@ -102,35 +147,61 @@ fn init_logging(verbose: bool, config: &audiobook_forge::models::Config) -> Resu
             .with_writer(file_appender)
             .with_target(true)
             .with_level(true)
-            .with_ansi(false)
-            .with_filter(filter);
+            .with_ansi(false);
+        layers.push(if json {
+            file_layer.json().with_filter(filter).boxed()
+        } else {
+            file_layer.with_filter(filter).boxed()
+        });
+        Some(log_file)
+    } else {
+        None
+    };
+
+    // Per-book logs always get debug output (tool command lines and stderr)
+    if config.logging.book_logs != BookLogMode::Off {
+        layers.push(BookLogLayer.with_filter(EnvFilter::new("audiobook_forge=debug")).boxed());
+    }
 
-        // Both console and file
-        tracing_subscriber::registry()
-            .with(console_layer)
-            .with(file_layer)
-            .init();
+    tracing_subscriber::registry().with(layers).init();
 
+    if let Some(log_file) = log_file {
         tracing::info!("Logging to file: {}", log_file.display());
-    } else {
-        // Console only
-        tracing_subscriber::registry()
-            .with(console_layer)
-            .init();
     }
 
     Ok(())
 }
 
-/// Load configuration file (or use defaults if not found)
-fn load_config() -> Result<audiobook_forge::models::Config> {
-    let config_path = ConfigManager::default_config_path()?;
-
-    if config_path.exists() {
-        ConfigManager::load(&config_path)
+/// Log writer for the console: stdout, or stderr in `--json` mode
+fn console_writer() -> Box<dyn std::io::Write> {
+    if audiobook_forge::cli::output::json_mode() {
+        Box::new(std::io::stderr())
     } else {
-        // Config file not found - use defaults silently
-        tracing::debug!("No config file found, using defaults");
-        Ok(audiobook_forge::models::Config::default())
+        Box::new(std::io::stdout())
     }
 }
+
+/// Resolve the configuration from every layer (missing files are skipped)
+fn load_config(cli: &Cli) -> Result<LayeredConfig> {
+    let user_file = match cli.config_file() {
+        Some(path) => {
+            if !path.is_file() {
+                return Err(ForgeError::Config(format!("Config file not found: {}", path.display())).into());
+            }
+            path
+        }
+        None => ConfigManager::default_config_path()?,
+    };
+    let project_dir = match cli.root_dir() {
+        Some(root) => root,
+        None => std::env::current_dir().context("Cannot determine current directory")?,
+    };
+
+    LayeredConfig::resolve(&ConfigSources {
+        system_file: system_config_path(),
+        user_file: Some(user_file),
+        project_dir: Some(project_dir),
+        env: std::env::vars().collect(),
+        cli: cli.config_overrides(),
+    })
+}
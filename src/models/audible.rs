@@ -31,8 +31,22 @@ pub enum AudibleRegion {
 }
 
 impl AudibleRegion {
+    /// Every region, in the order they're listed to users
+    pub const ALL: [AudibleRegion; 10] = [
+        Self::US,
+        Self::CA,
+        Self::UK,
+        Self::AU,
+        Self::FR,
+        Self::DE,
+        Self::JP,
+        Self::IT,
+        Self::IN,
+        Self::ES,
+    ];
+
     /// Get the region code for Audnexus API (e.g., "us", "uk")
-    pub fn tld(&self) -> &'static str {
+    pub const fn tld(&self) -> &'static str {
         match self {
             Self::US => "us",
             Self::CA => "ca",
@@ -68,18 +82,13 @@ impl FromStr for AudibleRegion {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        match s.to_lowercase().as_str() {
-            "us" => Ok(Self::US),
-            "ca" => Ok(Self::CA),
-            "uk" => Ok(Self::UK),
-            "au" => Ok(Self::AU),
-            "fr" => Ok(Self::FR),
-            "de" => Ok(Self::DE),
-            "jp" => Ok(Self::JP),
-            "it" => Ok(Self::IT),
-            "in" => Ok(Self::IN),
-            "es" => Ok(Self::ES),
-            _ => bail!("Invalid Audible region: {}. Valid regions: us, ca, uk, au, fr, de, jp, it, in, es", s),
+        let code = s.to_lowercase();
+        match Self::ALL.into_iter().find(|region| region.tld() == code) {
+            Some(region) => Ok(region),
+            None => {
+                let valid: Vec<&str> = Self::ALL.iter().map(|region| region.tld()).collect();
+                bail!("Invalid Audible region: {}. Valid regions: {}", s, valid.join(", "))
+            }
         }
     }
 }
@@ -265,6 +274,10 @@ mod tests {
         assert_eq!(AudibleRegion::from_str("UK").unwrap(), AudibleRegion::UK);
         assert_eq!(AudibleRegion::from_str("Ca").unwrap(), AudibleRegion::CA);
         assert!(AudibleRegion::from_str("invalid").is_err());
+
+        for region in AudibleRegion::ALL {
+            assert_eq!(AudibleRegion::from_str(region.tld()).unwrap(), region);
+        }
     }
 
     #[test]
@@ -1,6 +1,6 @@
 //! Audiobook folder model
 
-use super::{QualityProfile, Track, AudibleMetadata};
+use super::{AudibleMetadata, BookOverrides, Config, QualityProfile, TagOverrides, Track};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -63,6 +63,18 @@ pub struct BookFolder {
     pub detected_asin: Option<String>,
     /// Whether a merge pattern was detected for multiple M4B files
     pub merge_pattern_detected: bool,
+    /// Output path relative to the output directory (rendered from a path template)
+    pub output_path: Option<PathBuf>,
+    /// Overrides from the folder's `.audiobook-forge.yaml`, if present
+    pub overrides: Option<BookOverrides>,
+    /// Effective configuration when the override file has a `config` section
+    pub config: Option<Box<Config>>,
+    /// How the tracks were put in order (after analysis)
+    pub track_ordering: Option<crate::core::TrackOrdering>,
+    /// Output quality chosen to fit a size budget (None = from the source)
+    pub target_quality: Option<QualityProfile>,
+    /// Intro/outro and silence trimming (None = tracks used as they are)
+    pub trim: Option<crate::core::BookTrim>,
 }
 
 impl BookFolder {
@@ -86,6 +98,12 @@ impl BookFolder {
             audible_metadata: None,
             detected_asin: None,
             merge_pattern_detected: false,
+            output_path: None,
+            overrides: None,
+            config: None,
+            track_ordering: None,
+            target_quality: None,
+            trim: None,
         }
     }
 
@@ -169,12 +187,21 @@ impl BookFolder {
         format!("{}.m4b", self.name)
     }
 
+    /// Get the full output path inside `output_dir`
+    ///
+    /// Uses the templated `output_path` when set, otherwise the folder name.
+    pub fn get_output_path(&self, output_dir: &std::path::Path) -> PathBuf {
+        match &self.output_path {
+            Some(relative) => output_dir.join(relative),
+            None => output_dir.join(self.get_output_filename()),
+        }
+    }
+
     /// Get estimated file size in bytes (rough estimate)
     pub fn estimate_output_size(&self) -> u64 {
         let duration = self.get_total_duration();
-        if let Some(quality) = self.get_best_quality_profile(true) {
-            // bitrate (kbps) * duration (s) * 1000 / 8 = bytes
-            ((quality.bitrate as f64 * duration * 1000.0) / 8.0) as u64
+        if let Some(quality) = self.target_quality.as_ref().or(self.get_best_quality_profile(true)) {
+            quality.estimate_size(duration)
         } else {
             0
         }
@@ -185,39 +212,61 @@ impl BookFolder {
         matches!(self.case, BookCase::A | BookCase::B | BookCase::E)
     }
 
-    /// Get album artist from tracks (first non-None value)
+    /// Configuration for this book (the folder's overrides, or the global config)
+    pub fn effective_config<'a>(&'a self, global: &'a Config) -> &'a Config {
+        self.config.as_deref().unwrap_or(global)
+    }
+
+    /// Tag overrides from the folder's override file
+    pub fn tag_overrides(&self) -> Option<&TagOverrides> {
+        self.overrides.as_ref().map(|o| &o.tags)
+    }
+
+    /// Get album artist (override, then first non-None track value)
     pub fn get_album_artist(&self) -> Option<String> {
-        self.tracks
-            .iter()
-            .find_map(|t| t.album_artist.clone().or_else(|| t.artist.clone()))
+        self.tag_overrides()
+            .and_then(|t| t.author.clone())
+            .or_else(|| {
+                self.tracks
+                    .iter()
+                    .find_map(|t| t.album_artist.clone().or_else(|| t.artist.clone()))
+            })
     }
 
-    /// Get album title from tracks (first non-None value)
+    /// Get album title (override, then first non-None track value, then folder name)
     pub fn get_album_title(&self) -> Option<String> {
-        self.tracks
-            .iter()
-            .find_map(|t| t.album.clone())
+        self.tag_overrides()
+            .and_then(|t| t.title.clone())
+            .or_else(|| self.tracks.iter().find_map(|t| t.album.clone()))
             .or_else(|| Some(self.name.clone()))
     }
 
-    /// Get year from tracks (first non-None value)
+    /// Get year (override, then first non-None track value)
     pub fn get_year(&self) -> Option<u32> {
-        self.tracks.iter().find_map(|t| t.year)
+        self.tag_overrides()
+            .and_then(|t| t.year)
+            .or_else(|| self.tracks.iter().find_map(|t| t.year))
     }
 
-    /// Get genre from tracks (first non-None value)
+    /// Get genre (override, then first non-None track value)
     pub fn get_genre(&self) -> Option<String> {
-        self.tracks.iter().find_map(|t| t.genre.clone())
+        self.tag_overrides()
+            .and_then(|t| t.genre.clone())
+            .or_else(|| self.tracks.iter().find_map(|t| t.genre.clone()))
     }
 
-    /// Get comment from tracks (first non-None value)
+    /// Get comment (override, then first non-None track value)
     pub fn get_comment(&self) -> Option<String> {
-        self.tracks.iter().find_map(|t| t.comment.clone())
+        self.tag_overrides()
+            .and_then(|t| t.comment.clone())
+            .or_else(|| self.tracks.iter().find_map(|t| t.comment.clone()))
     }
 
-    /// Get composer from tracks (first non-None value)
+    /// Get composer, i.e. the narrator (override, then first non-None track value)
     pub fn get_composer(&self) -> Option<String> {
-        self.tracks.iter().find_map(|t| t.composer.clone())
+        self.tag_overrides()
+            .and_then(|t| t.narrator.clone())
+            .or_else(|| self.tracks.iter().find_map(|t| t.composer.clone()))
     }
 }
 
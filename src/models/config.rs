@@ -1,11 +1,19 @@
 //! Configuration model
 
+use super::{ProfileConfig, ResolvedProfile};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+/// Current config file format version (see `utils::migrate_config`)
+pub const CONFIG_VERSION: u32 = 2;
+
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Config file format version
+    #[serde(default = "default_config_version")]
+    pub version: u32,
     #[serde(default)]
     pub directories: DirectoryConfig,
     #[serde(default)]
@@ -22,11 +30,15 @@ pub struct Config {
     pub logging: LoggingConfig,
     #[serde(default)]
     pub advanced: AdvancedConfig,
+    /// Named output profiles selectable with `build --profile`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             directories: DirectoryConfig::default(),
             performance: PerformanceConfig::default(),
             processing: ProcessingConfig::default(),
@@ -35,6 +47,28 @@ impl Default for Config {
             organization: OrganizationConfig::default(),
             logging: LoggingConfig::default(),
             advanced: AdvancedConfig::default(),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_config_version() -> u32 {
+    CONFIG_VERSION
+}
+
+impl Config {
+    /// Look up a named profile and resolve it against this configuration
+    pub fn profile(&self, name: &str) -> anyhow::Result<ResolvedProfile> {
+        match self.profiles.get(name) {
+            Some(profile) => profile.resolve(name, self),
+            None if self.profiles.is_empty() => {
+                anyhow::bail!("Unknown profile '{}': no profiles are defined in the config file", name)
+            }
+            None => anyhow::bail!(
+                "Unknown profile '{}'. Defined profiles: {}",
+                name,
+                self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+            ),
         }
     }
 }
@@ -78,6 +112,9 @@ pub struct PerformanceConfig {
     /// Maximum concurrent file encodings per book (prevents resource exhaustion)
     #[serde(default = "default_max_concurrent_files_per_book")]
     pub max_concurrent_files_per_book: String,
+    /// Benchmark the encoders on the first build if no results are cached
+    #[serde(default)]
+    pub calibrate: bool,
 }
 
 impl Default for PerformanceConfig {
@@ -87,6 +124,7 @@ impl Default for PerformanceConfig {
             enable_parallel_encoding: true,
             encoding_preset: "balanced".to_string(),
             max_concurrent_files_per_book: "8".to_string(),
+            calibrate: false,
         }
     }
 }
@@ -127,6 +165,29 @@ pub struct ProcessingConfig {
     /// Initial retry delay in seconds
     #[serde(default = "default_retry_delay")]
     pub retry_delay: u64,
+    /// Record file moves and deletions so they can be reverted with `undo`
+    #[serde(default = "default_true")]
+    pub journal: bool,
+    /// Days journal runs (and the originals they keep) are kept (0 = forever)
+    #[serde(default = "default_journal_retention_days")]
+    pub journal_retention_days: u32,
+    /// How tracks are put in order before merging
+    #[serde(default)]
+    pub track_order: TrackOrder,
+    /// Check free temp and output space before the build and each book
+    #[serde(default = "default_true")]
+    pub check_disk_space: bool,
+    /// Space always left free on the temp and output filesystems
+    #[serde(default = "default_min_free_space")]
+    pub min_free_space: String,
+}
+
+fn default_journal_retention_days() -> u32 {
+    30
+}
+
+fn default_min_free_space() -> String {
+    "500MB".to_string()
 }
 
 impl Default for ProcessingConfig {
@@ -139,10 +200,51 @@ impl Default for ProcessingConfig {
             keep_temp_files: false,
             max_retries: 2,
             retry_delay: 1,
+            journal: true,
+            journal_retention_days: default_journal_retention_days(),
+            track_order: TrackOrder::default(),
+            check_disk_space: true,
+            min_free_space: default_min_free_space(),
+        }
+    }
+}
+
+/// Signal used to put a book's tracks in order
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackOrder {
+    /// The most consistent of the signals below
+    #[default]
+    Auto,
+    /// Natural order of the file names
+    Filename,
+    /// Disc and track number tags
+    Tags,
+    /// Numbers in the track title tags
+    Title,
+    /// An M3U playlist in the book folder
+    OrderFile,
+}
+
+impl TrackOrder {
+    /// Name as written in the config
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Filename => "filename",
+            Self::Tags => "tags",
+            Self::Title => "title",
+            Self::OrderFile => "order_file",
         }
     }
 }
 
+impl std::fmt::Display for TrackOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 fn default_max_retries() -> u8 {
     2
 }
@@ -174,6 +276,30 @@ pub struct QualityConfig {
     /// Default sample rate in Hz ("auto" or specific: 44100, 48000)
     #[serde(default = "default_sample_rate")]
     pub default_sample_rate: String,
+    /// Fit each book's output in this size (e.g. "300MB"); unset = no limit
+    #[serde(default)]
+    pub target_size: Option<String>,
+    /// Fit each book's output in this size per hour of audio (e.g. "30MB")
+    #[serde(default)]
+    pub size_per_hour: Option<String>,
+    /// Trim leading and trailing silence from every track
+    #[serde(default)]
+    pub trim_silence: bool,
+    /// Level (dBFS) below which audio counts as silence
+    #[serde(default = "default_silence_threshold")]
+    pub silence_threshold: f64,
+    /// Seconds of silence kept where silence is trimmed
+    #[serde(default = "default_silence_keep")]
+    pub silence_keep: f64,
+    /// Seconds cut from the start of the book (e.g. a publisher intro)
+    #[serde(default)]
+    pub trim_intro: f64,
+    /// Seconds cut from the end of the book
+    #[serde(default)]
+    pub trim_outro: f64,
+    /// Extra outputs at these playback speeds (e.g. [1.25, 1.5])
+    #[serde(default)]
+    pub speeds: Vec<f64>,
 }
 
 impl Default for QualityConfig {
@@ -183,6 +309,14 @@ impl Default for QualityConfig {
             chapter_source: "auto".to_string(),
             default_bitrate: "auto".to_string(),
             default_sample_rate: "auto".to_string(),
+            target_size: None,
+            size_per_hour: None,
+            trim_silence: false,
+            silence_threshold: default_silence_threshold(),
+            silence_keep: default_silence_keep(),
+            trim_intro: 0.0,
+            trim_outro: 0.0,
+            speeds: Vec::new(),
         }
     }
 }
@@ -199,6 +333,14 @@ fn default_sample_rate() -> String {
     "auto".to_string()
 }
 
+fn default_silence_threshold() -> f64 {
+    -50.0
+}
+
+fn default_silence_keep() -> f64 {
+    0.25
+}
+
 /// Metadata configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetadataConfig {
@@ -289,9 +431,21 @@ pub struct AudibleConfig {
     /// Fetch chapter data from Audnex API during build
     #[serde(default)]
     pub fetch_chapters: bool,
+    /// Serve Audible data from the local cache only (no network access)
+    #[serde(default)]
+    pub offline: bool,
     /// Cache metadata locally (hours, 0 = no cache)
     #[serde(default = "default_cache_duration")]
     pub cache_duration_hours: u64,
+    /// Cache search results (hours, 0 = no cache)
+    #[serde(default = "default_search_cache_hours")]
+    pub search_cache_hours: u64,
+    /// Cache Audnex chapter lists (hours, 0 = no cache)
+    #[serde(default = "default_cache_duration")]
+    pub chapter_cache_hours: u64,
+    /// Cache downloaded cover images (hours, 0 = no cache)
+    #[serde(default = "default_cover_cache_hours")]
+    pub cover_cache_hours: u64,
     /// Rate limit (requests per minute)
     #[serde(default = "default_rate_limit")]
     pub rate_limit_per_minute: u32,
@@ -314,7 +468,11 @@ impl Default for AudibleConfig {
             auto_match: false,
             download_covers: true,
             fetch_chapters: false,
+            offline: false,
             cache_duration_hours: 168, // 7 days
+            search_cache_hours: 24,
+            chapter_cache_hours: 168,
+            cover_cache_hours: 720, // 30 days
             rate_limit_per_minute: 100,
             api_max_retries: 3,
             api_retry_delay_secs: 1,
@@ -331,6 +489,14 @@ fn default_cache_duration() -> u64 {
     168 // 7 days
 }
 
+fn default_search_cache_hours() -> u64 {
+    24
+}
+
+fn default_cover_cache_hours() -> u64 {
+    720 // 30 days
+}
+
 fn default_rate_limit() -> u32 {
     100
 }
@@ -356,6 +522,10 @@ pub struct OrganizationConfig {
     /// Name for conversion queue folder
     #[serde(default = "default_convert_folder")]
     pub convert_folder: String,
+    /// Path template for built M4B files and organized library layout
+    /// (e.g. "{author}/{series}/{title}/{title}.m4b"; unset = folder name)
+    #[serde(default)]
+    pub path_template: Option<String>,
 }
 
 impl Default for OrganizationConfig {
@@ -363,6 +533,7 @@ impl Default for OrganizationConfig {
         Self {
             m4b_folder: "M4B".to_string(),
             convert_folder: "To_Convert".to_string(),
+            path_template: None,
         }
     }
 }
@@ -383,9 +554,18 @@ pub struct LoggingConfig {
     pub log_to_file: bool,
     /// Custom log file path
     pub log_file: Option<PathBuf>,
-    /// Log level ("INFO", "DEBUG", "WARNING", "ERROR")
+    /// Log level ("TRACE", "DEBUG", "INFO", "WARN"/"WARNING", "ERROR"; any case)
     #[serde(default = "default_log_level")]
     pub log_level: String,
+    /// Format of console and file logs
+    #[serde(default)]
+    pub log_format: LogFormat,
+    /// Where to write a separate log file for each book
+    #[serde(default)]
+    pub book_logs: BookLogMode,
+    /// Directory for per-run book log folders (default: ~/.audiobook-forge/logs/runs)
+    #[serde(default)]
+    pub book_log_dir: Option<PathBuf>,
 }
 
 impl Default for LoggingConfig {
@@ -394,10 +574,37 @@ impl Default for LoggingConfig {
             log_to_file: false,
             log_file: None,
             log_level: "INFO".to_string(),
+            log_format: LogFormat::default(),
+            book_logs: BookLogMode::default(),
+            book_log_dir: None,
         }
     }
 }
 
+/// Log line format
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line, with the book and tool spans as fields
+    Json,
+}
+
+/// Where per-book log files go
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BookLogMode {
+    /// No per-book logs
+    #[default]
+    Off,
+    /// `<output>.log` next to each book's output file
+    Output,
+    /// `<book name>.log` in a directory per run under `book_log_dir`
+    RunDir,
+}
+
 fn default_log_level() -> String {
     "INFO".to_string()
 }
@@ -411,16 +618,15 @@ fn default_aac_encoder() -> String {
 pub struct AdvancedConfig {
     /// Custom FFmpeg binary path
     pub ffmpeg_path: Option<PathBuf>,
+    /// Custom FFprobe binary path (default: next to ffmpeg_path, then PATH)
+    #[serde(default)]
+    pub ffprobe_path: Option<PathBuf>,
     /// Custom AtomicParsley binary path
     pub atomic_parsley_path: Option<PathBuf>,
     /// Custom MP4Box binary path
     pub mp4box_path: Option<PathBuf>,
     /// Custom temporary files location
     pub temp_directory: Option<PathBuf>,
-    /// DEPRECATED: Use aac_encoder instead
-    /// Use Apple Silicon hardware encoder (aac_at)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub use_apple_silicon_encoder: Option<bool>,
     /// AAC encoder preference: "auto", "aac_at", "libfdk_aac", "aac"
     #[serde(default = "default_aac_encoder")]
     pub aac_encoder: String,
@@ -430,10 +636,10 @@ impl Default for AdvancedConfig {
     fn default() -> Self {
         Self {
             ffmpeg_path: None,
+            ffprobe_path: None,
             atomic_parsley_path: None,
             mp4box_path: None,
             temp_directory: None,
-            use_apple_silicon_encoder: None,
             aac_encoder: default_aac_encoder(),
         }
     }
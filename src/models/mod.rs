@@ -7,11 +7,17 @@ mod config;
 mod result;
 mod audible;
 mod match_models;
+mod review_queue;
+mod overrides;
+mod profile;
 
 pub use book::{BookFolder, BookCase};
 pub use track::Track;
-pub use quality::QualityProfile;
-pub use config::{Config, DirectoryConfig, ProcessingConfig, QualityConfig, MetadataConfig, AudibleConfig, OrganizationConfig, LoggingConfig, AdvancedConfig, MatchMode};
-pub use result::ProcessingResult;
+pub use quality::{AacProfile, QualityProfile, RateControl, QUALITY_PRESETS};
+pub use config::{Config, CONFIG_VERSION, DirectoryConfig, ProcessingConfig, TrackOrder, QualityConfig, MetadataConfig, AudibleConfig, OrganizationConfig, LoggingConfig, LogFormat, BookLogMode, AdvancedConfig, MatchMode};
+pub use result::{ProcessingResult, TrackAction, TrackPlan};
 pub use audible::{AudibleMetadata, AudibleAuthor, AudibleSeries, AudibleRegion, AudibleSearchResult, AudibleChapter, AudnexChaptersResponse};
 pub use match_models::{MatchCandidate, MetadataDistance, MatchConfidence, CurrentMetadata, MetadataSource};
+pub use review_queue::{ReviewQueue, ReviewEntry, ReviewDecision, REVIEW_QUEUE_VERSION};
+pub use overrides::{BookOverrides, TagOverrides, OVERRIDE_FILENAMES};
+pub use profile::{speed_label, ProfileConfig, ResolvedProfile, PROFILE_FORMATS, TEMPO_RANGE};
@@ -0,0 +1,354 @@
+//! Per-folder override file
+//!
+//! A book folder may contain `.audiobook-forge.yaml` (or `.yml` / `.toml`)
+//! with one-off fixes for that book: a pinned ASIN, corrected tags, excluded
+//! or reordered tracks, explicit chapter names, and a partial `config`
+//! section that is merged over the global configuration. Only the settings
+//! in [`BOOK_CONFIG_KEYS`] are read per book; any other key is rejected.
+
+use super::Config;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Override file names, in lookup order
+pub const OVERRIDE_FILENAMES: [&str; 3] = [
+    ".audiobook-forge.yaml",
+    ".audiobook-forge.yml",
+    ".audiobook-forge.toml",
+];
+
+/// Settings (or whole sections) a folder's `config` section may override
+pub const BOOK_CONFIG_KEYS: &[&str] = &[
+    "processing.skip_existing",
+    "performance.enable_parallel_encoding",
+    "quality.chapter_source",
+    "quality.prefer_stereo",
+    "quality.trim_intro",
+    "quality.trim_outro",
+    "quality.trim_silence",
+    "quality.silence_threshold",
+    "quality.silence_keep",
+    "quality.target_size",
+    "quality.size_per_hour",
+    "metadata.auto_extract_cover",
+    "metadata.audible",
+];
+
+/// Tag values that replace whatever the tracks (or Audible) provide
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TagOverrides {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub narrator: Option<String>,
+    #[serde(default)]
+    pub series: Option<String>,
+    #[serde(default)]
+    pub series_index: Option<String>,
+    #[serde(default)]
+    pub year: Option<u32>,
+    #[serde(default)]
+    pub genre: Option<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// Contents of a per-folder override file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BookOverrides {
+    /// Audible ASIN to use instead of detection or search
+    #[serde(default)]
+    pub asin: Option<String>,
+    /// Tag values for the output file
+    #[serde(default)]
+    pub tags: TagOverrides,
+    /// Audio files to leave out (file names or glob patterns, case-insensitive)
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Audio files to put first, in this order (remaining files follow in natural order)
+    #[serde(default)]
+    pub order: Vec<String>,
+    /// Chapter names, applied in order to the generated chapters
+    #[serde(default)]
+    pub chapters: Vec<String>,
+    /// Partial configuration merged over the global config for this book
+    #[serde(default)]
+    pub config: Option<serde_yaml::Value>,
+    /// File the overrides were loaded from
+    #[serde(skip)]
+    pub source: PathBuf,
+}
+
+impl BookOverrides {
+    /// Find the override file in a folder, if any
+    pub fn find(folder: &Path) -> Option<PathBuf> {
+        OVERRIDE_FILENAMES
+            .iter()
+            .map(|name| folder.join(name))
+            .find(|path| path.is_file())
+    }
+
+    /// Load the override file from a folder (`Ok(None)` when there is none)
+    pub fn load_from_folder(folder: &Path) -> Result<Option<Self>> {
+        match Self::find(folder) {
+            Some(path) => Self::load(&path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Load an override file (TOML if the extension is `.toml`, YAML otherwise)
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read override file: {}", path.display()))?;
+
+        let mut overrides: BookOverrides = if path.extension().and_then(|e| e.to_str()) == Some("toml") {
+            toml::from_str(&contents)
+                .with_context(|| format!("Failed to parse override file: {}", path.display()))?
+        } else if contents.trim().is_empty() {
+            BookOverrides::default()
+        } else {
+            serde_yaml::from_str(&contents)
+                .with_context(|| format!("Failed to parse override file: {}", path.display()))?
+        };
+
+        overrides.source = path.to_path_buf();
+        Ok(overrides)
+    }
+
+    /// Merge the `config` section over a base configuration
+    pub fn apply_to_config(&self, base: &Config) -> Result<Config> {
+        let Some(patch) = &self.config else {
+            return Ok(base.clone());
+        };
+
+        // Other settings are resolved once per run, so a per-book value would be ignored
+        let mut keys = Vec::new();
+        leaf_keys("", patch, &mut keys);
+        if let Some(key) = keys.iter().find(|key| !is_book_config_key(key)) {
+            bail!(
+                "{} can't be set per book (in {}); a folder's config section may only set: {}",
+                key,
+                self.source.display(),
+                BOOK_CONFIG_KEYS.join(", ")
+            );
+        }
+
+        let mut merged = serde_yaml::to_value(base).context("Failed to serialize config")?;
+        crate::utils::merge_yaml(&mut merged, patch.clone());
+
+        serde_yaml::from_value(merged).with_context(|| {
+            format!("Invalid config section in {}", self.source.display())
+        })
+    }
+
+    /// Whether a file should be left out of the book
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return false;
+        };
+        let options = glob::MatchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+
+        self.exclude.iter().any(|pattern| {
+            pattern.eq_ignore_ascii_case(name)
+                || glob::Pattern::new(pattern)
+                    .map(|p| p.matches_with(name, options))
+                    .unwrap_or(false)
+        })
+    }
+
+    /// Drop excluded files and move files listed in `order` to the front
+    ///
+    /// `files` should already be in natural order; unlisted files keep it.
+    pub fn apply_to_files(&self, files: &mut Vec<PathBuf>) {
+        files.retain(|f| !self.is_excluded(f));
+
+        if self.order.is_empty() {
+            return;
+        }
+
+        let mut ordered = Vec::with_capacity(files.len());
+        for name in &self.order {
+            match files.iter().position(|f| file_name_matches(f, name)) {
+                Some(index) => ordered.push(files.remove(index)),
+                None => tracing::warn!(
+                    "Track '{}' listed in {} was not found",
+                    name,
+                    self.source.display()
+                ),
+            }
+        }
+        ordered.append(files);
+        *files = ordered;
+    }
+
+    /// Rename generated chapters with the names from the override file
+    pub fn apply_chapter_names(&self, chapters: &mut [crate::audio::Chapter]) {
+        if self.chapters.is_empty() {
+            return;
+        }
+
+        if self.chapters.len() != chapters.len() {
+            tracing::warn!(
+                "{} lists {} chapter name(s) but {} chapter(s) were generated",
+                self.source.display(),
+                self.chapters.len(),
+                chapters.len()
+            );
+        }
+
+        for (chapter, name) in chapters.iter_mut().zip(&self.chapters) {
+            chapter.title = name.clone();
+        }
+    }
+}
+
+fn file_name_matches(path: &Path, name: &str) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.eq_ignore_ascii_case(name))
+        .unwrap_or(false)
+}
+
+/// Whether a dotted key is one of, or inside one of, [`BOOK_CONFIG_KEYS`]
+fn is_book_config_key(key: &str) -> bool {
+    BOOK_CONFIG_KEYS.iter().any(|allowed| {
+        key == *allowed || key.strip_prefix(allowed).is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+/// Dotted paths of the non-mapping values in a YAML tree
+fn leaf_keys(prefix: &str, value: &serde_yaml::Value, out: &mut Vec<String>) {
+    let serde_yaml::Value::Mapping(map) = value else {
+        out.push(prefix.to_string());
+        return;
+    };
+    for (key, child) in map {
+        let key = key.as_str().map(str::to_string).unwrap_or_else(|| format!("{:?}", key));
+        let path = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+        leaf_keys(&path, child, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_load_yaml_overrides() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".audiobook-forge.yaml"),
+            "asin: B002V5D7RU\ntags:\n  author: Jane Doe\n  year: 2001\nexclude: [\"bonus*.mp3\"]\nconfig:\n  quality:\n    chapter_source: cue\n",
+        )
+        .unwrap();
+
+        let overrides = BookOverrides::load_from_folder(dir.path()).unwrap().unwrap();
+        assert_eq!(overrides.asin.as_deref(), Some("B002V5D7RU"));
+        assert_eq!(overrides.tags.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(overrides.tags.year, Some(2001));
+
+        let config = overrides.apply_to_config(&Config::default()).unwrap();
+        assert_eq!(config.quality.chapter_source, "cue");
+        // Untouched settings keep their values
+        assert!(config.quality.prefer_stereo);
+        assert_eq!(config.processing.parallel_workers, 2);
+    }
+
+    #[test]
+    fn test_load_toml_overrides() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".audiobook-forge.toml"),
+            "chapters = [\"Prologue\", \"One\"]\n\n[tags]\ntitle = \"Fixed\"\n\n[config.metadata]\nauto_extract_cover = false\n",
+        )
+        .unwrap();
+
+        let overrides = BookOverrides::load_from_folder(dir.path()).unwrap().unwrap();
+        assert_eq!(overrides.tags.title.as_deref(), Some("Fixed"));
+        assert_eq!(overrides.chapters, vec!["Prologue", "One"]);
+
+        let config = overrides.apply_to_config(&Config::default()).unwrap();
+        assert!(!config.metadata.auto_extract_cover);
+    }
+
+    #[test]
+    fn test_unknown_keys_rejected() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join(".audiobook-forge.yaml"), "tags:\n  titel: Typo\n").unwrap();
+        assert!(BookOverrides::load_from_folder(dir.path()).is_err());
+
+        let overrides = BookOverrides {
+            config: Some(serde_yaml::from_str("quality:\n  prefer_stereo: maybe\n").unwrap()),
+            ..Default::default()
+        };
+        assert!(overrides.apply_to_config(&Config::default()).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_book_settings_rejected() {
+        let overrides = |config: &str| BookOverrides {
+            config: Some(serde_yaml::from_str(config).unwrap()),
+            source: PathBuf::from("/books/Dune/.audiobook-forge.yaml"),
+            ..Default::default()
+        };
+
+        let config = overrides("metadata:
+  audible:
+    region: uk
+processing:
+  skip_existing: false
+")
+            .apply_to_config(&Config::default())
+            .unwrap();
+        assert!(!config.processing.skip_existing);
+
+        for (setting, key) in [
+            ("advanced:
+  aac_encoder: aac
+", "advanced.aac_encoder"),
+            ("quality:
+  speeds: [1.5]
+", "quality.speeds"),
+            ("organization:
+  path_template: \"{title}.m4b\"
+", "organization.path_template"),
+        ] {
+            let err = overrides(setting).apply_to_config(&Config::default()).unwrap_err().to_string();
+            assert!(err.starts_with(key), "{}", err);
+            assert!(err.contains("/books/Dune/.audiobook-forge.yaml"));
+        }
+    }
+
+    #[test]
+    fn test_apply_to_files() {
+        let overrides = BookOverrides {
+            exclude: vec!["Bonus*.mp3".to_string(), "sample.mp3".to_string()],
+            order: vec!["03.mp3".to_string(), "missing.mp3".to_string()],
+            ..Default::default()
+        };
+        let mut files: Vec<PathBuf> = ["01.mp3", "02.mp3", "03.mp3", "bonus track.mp3", "SAMPLE.mp3"]
+            .iter()
+            .map(|n| PathBuf::from("/book").join(n))
+            .collect();
+
+        overrides.apply_to_files(&mut files);
+
+        let names: Vec<_> = files.iter().map(|f| f.file_name().unwrap().to_str().unwrap()).collect();
+        assert_eq!(names, vec!["03.mp3", "01.mp3", "02.mp3"]);
+    }
+
+    #[test]
+    fn test_no_override_file() {
+        let dir = tempdir().unwrap();
+        assert!(BookOverrides::load_from_folder(dir.path()).unwrap().is_none());
+    }
+}
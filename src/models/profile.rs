@@ -0,0 +1,350 @@
+//! Named output profiles
+//!
+//! Profiles are defined under `profiles:` in the config file and bundle the
+//! settings for one kind of output (e.g. "archive", "mobile", "car"). Unset
+//! fields fall back to the built-in preset named by `preset`, then to the
+//! global configuration or the source audio.
+
+use super::{AacProfile, Config, QualityProfile, RateControl, QUALITY_PRESETS};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Output formats a profile can produce
+pub const PROFILE_FORMATS: [&str; 2] = ["m4b", "m4a"];
+
+/// Chapter sources accepted by profiles (same as `quality.chapter_source`)
+const CHAPTER_SOURCES: [&str; 5] = ["auto", "files", "cue", "id3", "none"];
+
+/// Playback speeds a profile or speed variant can use
+pub const TEMPO_RANGE: std::ops::RangeInclusive<f64> = 0.5..=4.0;
+
+/// Suffix for a speed variant's file name and title (e.g. "1.25x")
+pub fn speed_label(tempo: f64) -> String {
+    format!("{}x", tempo)
+}
+
+/// A profile as written in the config file (every field optional)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// Built-in quality preset to start from (low, medium, high, ultra, maximum, speech, speech_stereo, source)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// AAC encoder ("auto", "aac_at", "libfdk_aac", "aac")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoder: Option<String>,
+    /// Bitrate in kbps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<u32>,
+    /// AAC object type ("lc", "he", "he_v2")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aac_profile: Option<AacProfile>,
+    /// VBR quality level 1-5 instead of a constant bitrate (libfdk_aac)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vbr: Option<u8>,
+    /// Sample rate in Hz
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<u32>,
+    /// Channels (1 = mono, 2 = stereo)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channels: Option<u8>,
+    /// Chapter source ("auto", "files", "cue", "id3", "none")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chapter_source: Option<String>,
+    /// Target integrated loudness in LUFS (e.g. -16); unset = no normalization
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loudness: Option<f64>,
+    /// Playback speed, pitch preserved (e.g. 1.25); unset = 1x
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tempo: Option<f64>,
+    /// Output container ("m4b" or "m4a")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// Output path template (see `organization.path_template`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+/// A profile with every setting resolved against the global configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedProfile {
+    /// Profile name
+    pub name: String,
+    /// Built-in quality preset (None = keep source quality)
+    pub preset: Option<String>,
+    /// AAC encoder name
+    pub encoder: String,
+    /// Bitrate in kbps (None = from preset or source)
+    pub bitrate: Option<u32>,
+    /// AAC object type (None = from preset, else AAC-LC)
+    pub aac_profile: Option<AacProfile>,
+    /// VBR quality level (None = constant bitrate)
+    pub vbr: Option<u8>,
+    /// Sample rate in Hz (None = from preset or source)
+    pub sample_rate: Option<u32>,
+    /// Channels (None = from preset or source)
+    pub channels: Option<u8>,
+    /// Chapter source
+    pub chapter_source: String,
+    /// Target integrated loudness in LUFS
+    pub loudness: Option<f64>,
+    /// Playback speed (None = 1x)
+    pub tempo: Option<f64>,
+    /// Output container
+    pub format: String,
+    /// Output path template
+    pub template: Option<String>,
+}
+
+impl ProfileConfig {
+    /// Fill unset fields from the global configuration and validate the result
+    pub fn resolve(&self, name: &str, config: &Config) -> Result<ResolvedProfile> {
+        let resolved = ResolvedProfile {
+            name: name.to_string(),
+            preset: self.preset.clone(),
+            encoder: self
+                .encoder
+                .clone()
+                .unwrap_or_else(|| config.advanced.aac_encoder.clone()),
+            bitrate: self.bitrate,
+            aac_profile: self.aac_profile,
+            vbr: self.vbr,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            chapter_source: self
+                .chapter_source
+                .clone()
+                .unwrap_or_else(|| config.quality.chapter_source.clone()),
+            loudness: self.loudness,
+            tempo: self.tempo,
+            format: self
+                .format
+                .clone()
+                .unwrap_or_else(|| "m4b".to_string())
+                .to_lowercase(),
+            template: self
+                .template
+                .clone()
+                .or_else(|| config.organization.path_template.clone()),
+        };
+
+        resolved.validate()?;
+        Ok(resolved)
+    }
+}
+
+impl ResolvedProfile {
+    fn validate(&self) -> Result<()> {
+        let name = &self.name;
+
+        if let Some(preset) = &self.preset {
+            if !QUALITY_PRESETS.contains(&preset.to_lowercase().as_str()) {
+                bail!(
+                    "Profile '{}': unknown preset '{}'. Valid options: {}",
+                    name,
+                    preset,
+                    QUALITY_PRESETS.join(", ")
+                );
+            }
+        }
+        if self.bitrate == Some(0) || self.sample_rate == Some(0) {
+            bail!("Profile '{}': bitrate and sample_rate must be positive", name);
+        }
+        if let Some(vbr) = self.vbr {
+            if !(1..=5).contains(&vbr) {
+                bail!("Profile '{}': vbr must be between 1 and 5, got {}", name, vbr);
+            }
+        }
+        if let Some(channels) = self.channels {
+            if channels != 1 && channels != 2 {
+                bail!("Profile '{}': channels must be 1 or 2, got {}", name, channels);
+            }
+        }
+        if !CHAPTER_SOURCES.contains(&self.chapter_source.as_str()) {
+            bail!(
+                "Profile '{}': unknown chapter_source '{}'. Valid options: {}",
+                name,
+                self.chapter_source,
+                CHAPTER_SOURCES.join(", ")
+            );
+        }
+        if let Some(loudness) = self.loudness {
+            if !(-70.0..=-5.0).contains(&loudness) {
+                bail!(
+                    "Profile '{}': loudness must be between -70 and -5 LUFS, got {}",
+                    name,
+                    loudness
+                );
+            }
+        }
+        if let Some(tempo) = self.tempo {
+            if !TEMPO_RANGE.contains(&tempo) {
+                bail!(
+                    "Profile '{}': tempo must be between {} and {}, got {}",
+                    name,
+                    TEMPO_RANGE.start(),
+                    TEMPO_RANGE.end(),
+                    tempo
+                );
+            }
+        }
+        if !PROFILE_FORMATS.contains(&self.format.as_str()) {
+            bail!(
+                "Profile '{}': unknown format '{}'. Valid options: {}",
+                name,
+                self.format,
+                PROFILE_FORMATS.join(", ")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// A speed variant of this profile, named after the speed
+    pub fn at_speed(&self, speed: f64) -> Result<Self> {
+        let variant = Self {
+            name: format!("{} {}", self.name, speed_label(speed)),
+            tempo: Some(self.tempo.unwrap_or(1.0) * speed),
+            ..self.clone()
+        };
+        variant.validate()?;
+        Ok(variant)
+    }
+
+    /// Output quality for a source: the preset first, then explicit settings
+    pub fn apply_quality(&self, source: &QualityProfile) -> QualityProfile {
+        let mut quality = source.apply_preset(self.preset.as_deref());
+        if let Some(bitrate) = self.bitrate {
+            quality.bitrate = bitrate;
+        }
+        if let Some(sample_rate) = self.sample_rate {
+            quality.sample_rate = sample_rate;
+        }
+        if let Some(channels) = self.channels {
+            quality.channels = channels;
+        }
+        if let Some(aac_profile) = self.aac_profile {
+            quality.aac_profile = aac_profile;
+        }
+        if let Some(vbr) = self.vbr {
+            quality.rate_control = RateControl::Vbr(vbr);
+        }
+        if self.bitrate.is_some()
+            || self.sample_rate.is_some()
+            || self.channels.is_some()
+            || self.aac_profile.is_some()
+            || self.vbr.is_some()
+        {
+            quality.codec = "aac".to_string();
+        }
+        quality
+    }
+
+    /// Whether the output has to be re-encoded regardless of the source
+    pub fn forces_transcode(&self) -> bool {
+        self.loudness.is_some()
+            || self.tempo.is_some()
+            || self.bitrate.is_some()
+            || self.sample_rate.is_some()
+            || self.channels.is_some()
+            || self.aac_profile.is_some()
+            || self.vbr.is_some()
+            || self.preset.as_deref().is_some_and(|p| !p.eq_ignore_ascii_case("source"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> QualityProfile {
+        QualityProfile::new(128, 44100, 2, "mp3".to_string(), 3600.0).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_falls_back_to_config() {
+        let mut config = Config::default();
+        config.quality.chapter_source = "cue".to_string();
+        config.organization.path_template = Some("{author}/{title}.m4b".to_string());
+
+        let resolved = ProfileConfig::default().resolve("plain", &config).unwrap();
+        assert_eq!(resolved.name, "plain");
+        assert_eq!(resolved.encoder, "auto");
+        assert_eq!(resolved.chapter_source, "cue");
+        assert_eq!(resolved.format, "m4b");
+        assert_eq!(resolved.template.as_deref(), Some("{author}/{title}.m4b"));
+        assert!(!resolved.forces_transcode());
+        assert_eq!(resolved.apply_quality(&source()), source());
+    }
+
+    #[test]
+    fn test_apply_quality_preset_then_fields() {
+        let profile = ProfileConfig {
+            preset: Some("low".to_string()),
+            bitrate: Some(48),
+            ..Default::default()
+        };
+        let resolved = profile.resolve("mobile", &Config::default()).unwrap();
+        let quality = resolved.apply_quality(&source());
+
+        assert_eq!(quality.bitrate, 48);
+        assert_eq!(quality.sample_rate, 22050);
+        assert_eq!(quality.channels, 1);
+        assert_eq!(quality.codec, "aac");
+        assert!(resolved.forces_transcode());
+    }
+
+    #[test]
+    fn test_invalid_profiles_rejected() {
+        let config = Config::default();
+        let invalid = [
+            ProfileConfig { channels: Some(6), ..Default::default() },
+            ProfileConfig { format: Some("mp3".to_string()), ..Default::default() },
+            ProfileConfig { loudness: Some(3.0), ..Default::default() },
+            ProfileConfig { preset: Some("huge".to_string()), ..Default::default() },
+            ProfileConfig { chapter_source: Some("epub".to_string()), ..Default::default() },
+            ProfileConfig { vbr: Some(6), ..Default::default() },
+            ProfileConfig { tempo: Some(8.0), ..Default::default() },
+        ];
+        for profile in invalid {
+            assert!(profile.resolve("bad", &config).is_err(), "{:?}", profile);
+        }
+    }
+
+    #[test]
+    fn test_aac_profile_and_vbr() {
+        let yaml = "profiles:\n  voice:\n    preset: speech\n    aac_profile: he_v2\n    channels: 2\n    vbr: 2\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+
+        let voice = config.profile("voice").unwrap();
+        assert!(voice.forces_transcode());
+        let quality = voice.apply_quality(&source());
+        assert_eq!(quality.aac_profile, AacProfile::HeV2);
+        assert_eq!(quality.rate_control, RateControl::Vbr(2));
+        assert_eq!(quality.codec, "aac");
+    }
+
+    #[test]
+    fn test_speed_variants() {
+        let plain = ProfileConfig::default().resolve("default", &Config::default()).unwrap();
+        let fast = plain.at_speed(1.5).unwrap();
+        assert_eq!(fast.name, "default 1.5x");
+        assert_eq!(fast.tempo, Some(1.5));
+        assert!(fast.forces_transcode());
+        assert!(plain.at_speed(10.0).is_err());
+
+        // Variants of a profile with its own tempo compound
+        assert_eq!(fast.at_speed(2.0).unwrap().tempo, Some(3.0));
+    }
+
+    #[test]
+    fn test_profiles_in_config_yaml() {
+        let yaml = "profiles:\n  car:\n    preset: medium\n    loudness: -16\n    format: m4a\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+
+        let car = config.profile("car").unwrap();
+        assert_eq!(car.loudness, Some(-16.0));
+        assert_eq!(car.format, "m4a");
+        assert!(config.profile("boat").is_err());
+    }
+}
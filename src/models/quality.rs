@@ -3,6 +3,69 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+/// Names accepted by [`QualityProfile::from_preset`]
+pub const QUALITY_PRESETS: [&str; 8] = ["low", "medium", "high", "ultra", "maximum", "speech", "speech_stereo", "source"];
+
+/// AAC object type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AacProfile {
+    /// Low Complexity (AAC-LC), supported by every encoder
+    #[default]
+    Lc,
+    /// HE-AAC (AAC-LC + SBR), for speech at 32-64 kbps
+    He,
+    /// HE-AACv2 (HE-AAC + parametric stereo), for stereo below 48 kbps
+    HeV2,
+}
+
+impl AacProfile {
+    /// Name in config files and reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lc => "lc",
+            Self::He => "he",
+            Self::HeV2 => "he_v2",
+        }
+    }
+
+    /// FFmpeg `-profile:a` value
+    pub fn ffmpeg_name(&self) -> &'static str {
+        match self {
+            Self::Lc => "aac_low",
+            Self::He => "aac_he",
+            Self::HeV2 => "aac_he_v2",
+        }
+    }
+
+    /// Parse an ffprobe stream `profile` ("LC", "HE-AAC", "HE-AACv2")
+    pub fn from_ffprobe(profile: &str) -> Option<Self> {
+        match profile {
+            "LC" => Some(Self::Lc),
+            "HE-AAC" => Some(Self::He),
+            "HE-AACv2" => Some(Self::HeV2),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AacProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Rate control of the AAC encoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateControl {
+    /// Constant bitrate at `bitrate`
+    #[default]
+    Cbr,
+    /// Variable bitrate at a quality level from 1 (smallest) to 5 (best)
+    Vbr(u8),
+}
+
 /// Audio quality profile with bitrate, sample rate, channels, and codec
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct QualityProfile {
@@ -16,6 +79,12 @@ pub struct QualityProfile {
     pub codec: String,
     /// Duration in seconds
     pub duration: f64,
+    /// AAC object type (for AAC output)
+    #[serde(default)]
+    pub aac_profile: AacProfile,
+    /// Rate control (for AAC output; `bitrate` is only a target with VBR)
+    #[serde(default)]
+    pub rate_control: RateControl,
 }
 
 impl QualityProfile {
@@ -37,6 +106,8 @@ impl QualityProfile {
             channels,
             codec,
             duration,
+            aac_profile: AacProfile::Lc,
+            rate_control: RateControl::Cbr,
         })
     }
 
@@ -78,6 +149,7 @@ impl QualityProfile {
             && self.sample_rate == other.sample_rate
             && self.channels == other.channels
             && self.codec.to_lowercase() == other.codec.to_lowercase()
+            && self.aac_profile == other.aac_profile
     }
 
     /// Convert to AAC profile with equivalent or better quality
@@ -91,6 +163,8 @@ impl QualityProfile {
             channels: self.channels,
             codec: "aac".to_string(),
             duration: self.duration,
+            aac_profile: self.aac_profile,
+            rate_control: RateControl::Cbr,
         }
     }
 
@@ -104,6 +178,8 @@ impl QualityProfile {
                 channels: 1, // mono
                 codec: "aac".to_string(),
                 duration: source.duration,
+                aac_profile: AacProfile::Lc,
+                rate_control: RateControl::Cbr,
             }),
             "medium" => Some(QualityProfile {
                 bitrate: 96,
@@ -111,6 +187,8 @@ impl QualityProfile {
                 channels: 2, // stereo
                 codec: "aac".to_string(),
                 duration: source.duration,
+                aac_profile: AacProfile::Lc,
+                rate_control: RateControl::Cbr,
             }),
             "high" => Some(QualityProfile {
                 bitrate: 128,
@@ -118,6 +196,8 @@ impl QualityProfile {
                 channels: 2, // stereo
                 codec: "aac".to_string(),
                 duration: source.duration,
+                aac_profile: AacProfile::Lc,
+                rate_control: RateControl::Cbr,
             }),
             "ultra" => Some(QualityProfile {
                 bitrate: 192,
@@ -125,6 +205,8 @@ impl QualityProfile {
                 channels: 2, // stereo
                 codec: "aac".to_string(),
                 duration: source.duration,
+                aac_profile: AacProfile::Lc,
+                rate_control: RateControl::Cbr,
             }),
             "maximum" => Some(QualityProfile {
                 bitrate: 256,
@@ -132,11 +214,39 @@ impl QualityProfile {
                 channels: 2, // stereo
                 codec: "aac".to_string(),
                 duration: source.duration,
+                aac_profile: AacProfile::Lc,
+                rate_control: RateControl::Cbr,
+            }),
+            // Speech presets: HE-AAC needs libfdk_aac or aac_at; other
+            // encoders fall back to AAC-LC at a higher bitrate
+            "speech" => Some(QualityProfile {
+                bitrate: 32,
+                sample_rate: 44100,
+                channels: 1, // mono
+                codec: "aac".to_string(),
+                duration: source.duration,
+                aac_profile: AacProfile::He,
+                rate_control: RateControl::Cbr,
+            }),
+            "speech_stereo" => Some(QualityProfile {
+                bitrate: 48,
+                sample_rate: 44100,
+                channels: 2, // stereo
+                codec: "aac".to_string(),
+                duration: source.duration,
+                aac_profile: AacProfile::HeV2,
+                rate_control: RateControl::Cbr,
             }),
             "source" | _ => None, // Use auto-detected quality from source
         }
     }
 
+    /// Estimated encoded size in bytes for this much audio
+    pub fn estimate_size(&self, duration_secs: f64) -> u64 {
+        // bitrate (kbps) * duration (s) * 1000 / 8 = bytes
+        (self.bitrate as f64 * duration_secs * 125.0) as u64
+    }
+
     /// Apply quality preset override if specified
     pub fn apply_preset(&self, preset: Option<&str>) -> QualityProfile {
         preset
@@ -151,7 +261,14 @@ impl fmt::Display for QualityProfile {
             f,
             "{}kbps, {}Hz, {}ch, {}, {:.1}s",
             self.bitrate, self.sample_rate, self.channels, self.codec, self.duration
-        )
+        )?;
+        if self.aac_profile != AacProfile::Lc {
+            write!(f, ", {}", self.aac_profile)?;
+        }
+        if let RateControl::Vbr(level) = self.rate_control {
+            write!(f, ", VBR {}", level)?;
+        }
+        Ok(())
     }
 }
 
@@ -184,6 +301,23 @@ mod tests {
         assert!(!low.is_better_than(&high, true));
     }
 
+    #[test]
+    fn test_speech_presets() {
+        let source = QualityProfile::new(128, 44100, 2, "mp3".to_string(), 3600.0).unwrap();
+
+        let speech = source.apply_preset(Some("speech"));
+        assert_eq!((speech.bitrate, speech.channels, speech.aac_profile), (32, 1, AacProfile::He));
+        assert_eq!(speech.to_string(), "32kbps, 44100Hz, 1ch, aac, 3600.0s, he");
+
+        let stereo = source.apply_preset(Some("speech_stereo"));
+        assert_eq!((stereo.channels, stereo.aac_profile), (2, AacProfile::HeV2));
+
+        let vbr = QualityProfile { rate_control: RateControl::Vbr(4), ..source.clone() };
+        assert!(vbr.to_string().ends_with(", VBR 4"));
+        assert_eq!(AacProfile::from_ffprobe("HE-AACv2"), Some(AacProfile::HeV2));
+        assert_eq!(AacProfile::from_ffprobe("Main"), None);
+    }
+
     #[test]
     fn test_compatibility() {
         let profile1 = QualityProfile::new(128, 44100, 2, "aac".to_string(), 3600.0).unwrap();
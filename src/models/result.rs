@@ -1,5 +1,6 @@
 //! Processing result model
 
+use crate::error::ErrorKind;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -16,10 +17,55 @@ pub struct ProcessingResult {
     pub processing_time: f64,
     /// Error message (if failed)
     pub error_message: Option<String>,
+    /// Kind of failure (if failed)
+    #[serde(default)]
+    pub error_kind: Option<ErrorKind>,
     /// Size of output file in bytes (if successful)
     pub output_size: Option<u64>,
     /// Whether copy mode was used (no re-encoding)
     pub used_copy_mode: bool,
+    /// Further outputs written in the same run (one per extra profile)
+    #[serde(default)]
+    pub extra_outputs: Vec<PathBuf>,
+    /// Which source tracks were stream-copied and which transcoded
+    #[serde(default)]
+    pub tracks: Vec<TrackPlan>,
+}
+
+/// What is done with a source track's audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackAction {
+    /// Stream-copied without re-encoding
+    Copy,
+    /// Re-encoded to AAC
+    Transcode,
+}
+
+/// How one source track is encoded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackPlan {
+    pub file: PathBuf,
+    pub action: TrackAction,
+    /// Why the track is transcoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Whether the track was trimmed first (trimmed tracks are always transcoded)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trimmed: bool,
+}
+
+impl TrackPlan {
+    /// Mark the track as trimmed from `source`, which it's reported under
+    ///
+    /// The trimmed intermediate is lossless, so it's transcoded because of
+    /// the trim whatever else the plan said about it.
+    pub fn trimmed_from(&mut self, source: PathBuf) {
+        self.file = source;
+        self.trimmed = true;
+        self.action = TrackAction::Transcode;
+        self.reason = Some("trimmed before encoding".to_string());
+    }
 }
 
 impl ProcessingResult {
@@ -31,8 +77,11 @@ impl ProcessingResult {
             output_path: None,
             processing_time: 0.0,
             error_message: None,
+            error_kind: None,
             output_size: None,
             used_copy_mode: false,
+            extra_outputs: Vec::new(),
+            tracks: Vec::new(),
         }
     }
 
@@ -51,6 +100,30 @@ impl ProcessingResult {
         self
     }
 
+    /// Record further outputs written alongside the main one
+    pub fn with_extra_outputs(mut self, extra_outputs: Vec<PathBuf>) -> Self {
+        self.extra_outputs = extra_outputs;
+        self
+    }
+
+    /// Record what was done with each source track
+    pub fn with_tracks(mut self, tracks: Vec<TrackPlan>) -> Self {
+        self.tracks = tracks;
+        self
+    }
+
+    /// "copy mode", "transcode", or how many tracks were copied and transcoded
+    pub fn encode_mode(&self) -> String {
+        let copied = self.tracks.iter().filter(|t| t.action == TrackAction::Copy).count();
+        if copied > 0 && copied < self.tracks.len() {
+            format!("{} copied, {} transcoded", copied, self.tracks.len() - copied)
+        } else if self.used_copy_mode {
+            "copy mode".to_string()
+        } else {
+            "transcode".to_string()
+        }
+    }
+
     /// Mark as failed with error message
     pub fn failure(mut self, error_message: String, processing_time: f64) -> Self {
         self.success = false;
@@ -59,6 +132,13 @@ impl ProcessingResult {
         self
     }
 
+    /// Mark as failed from an error, recording its kind
+    pub fn failure_from(self, error: &anyhow::Error, processing_time: f64) -> Self {
+        let mut result = self.failure(format!("{:#}", error), processing_time);
+        result.error_kind = Some(ErrorKind::of(error));
+        result
+    }
+
     /// Get output file size in MB
     pub fn output_size_mb(&self) -> Option<f64> {
         self.output_size.map(|size| size as f64 / (1024.0 * 1024.0))
@@ -73,20 +153,18 @@ impl std::fmt::Display for ProcessingResult {
                 "✓ {} ({:.1}s, {})",
                 self.book_name,
                 self.processing_time,
-                if self.used_copy_mode { "copy mode" } else { "transcode" }
+                self.encode_mode()
             )?;
             if let Some(size_mb) = self.output_size_mb() {
                 write!(f, " - {:.1} MB", size_mb)?;
             }
             Ok(())
         } else {
-            write!(
-                f,
-                "✗ {} ({:.1}s) - {}",
-                self.book_name,
-                self.processing_time,
-                self.error_message.as_deref().unwrap_or("Unknown error")
-            )
+            write!(f, "✗ {} ({:.1}s) - ", self.book_name, self.processing_time)?;
+            if let Some(kind) = self.error_kind {
+                write!(f, "[{}] ", kind)?;
+            }
+            write!(f, "{}", self.error_message.as_deref().unwrap_or("Unknown error"))
         }
     }
 }
@@ -106,6 +184,18 @@ mod tests {
         assert!(result.output_path.is_some());
     }
 
+    #[test]
+    fn test_encode_mode() {
+        let plan = |action| TrackPlan { file: PathBuf::from("01.m4a"), action, reason: None, trimmed: false };
+        let result = ProcessingResult::new("Test Book".to_string())
+            .success(PathBuf::from("/output/test.m4b"), 1.0, false)
+            .with_tracks(vec![plan(TrackAction::Copy), plan(TrackAction::Copy), plan(TrackAction::Transcode)]);
+        assert_eq!(result.encode_mode(), "2 copied, 1 transcoded");
+
+        let result = result.with_tracks(vec![plan(TrackAction::Transcode)]);
+        assert_eq!(result.encode_mode(), "transcode");
+    }
+
     #[test]
     fn test_result_failure() {
         let result = ProcessingResult::new("Test Book".to_string())
@@ -0,0 +1,283 @@
+//! Review queue for non-interactive metadata matching
+//!
+//! `match --queue <file>` writes every scored candidate for each M4B into a
+//! YAML/JSON document that a team can review offline. Reviewers set each
+//! entry's `decision` (and optionally `selected`), then `match --apply-queue`
+//! applies the approved choices.
+
+use super::{AudibleMetadata, CurrentMetadata, MatchCandidate, MatchConfidence};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Current review queue file format version
+pub const REVIEW_QUEUE_VERSION: u32 = 1;
+
+/// Reviewer decision for a queued file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewDecision {
+    /// Not reviewed yet (ignored by `--apply-queue`)
+    #[default]
+    Pending,
+    /// Apply the candidate at `selected`
+    Accept,
+    /// Leave the file unchanged
+    Reject,
+}
+
+/// A single file awaiting review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewEntry {
+    /// M4B file the decision applies to
+    pub file: PathBuf,
+    /// Reviewer decision
+    #[serde(default)]
+    pub decision: ReviewDecision,
+    /// 1-based index of the chosen candidate (defaults to the best match)
+    #[serde(default = "default_selected")]
+    pub selected: usize,
+    /// Metadata the candidates were scored against
+    pub current: CurrentMetadata,
+    /// Scored candidates, best first
+    #[serde(default)]
+    pub candidates: Vec<MatchCandidate>,
+}
+
+fn default_selected() -> usize {
+    1
+}
+
+impl ReviewEntry {
+    /// Create an entry for a file, pre-accepting strong matches
+    pub fn new(file: PathBuf, current: CurrentMetadata, candidates: Vec<MatchCandidate>) -> Self {
+        let decision = match candidates.first().map(|c| c.confidence) {
+            Some(MatchConfidence::Strong) => ReviewDecision::Accept,
+            _ => ReviewDecision::Pending,
+        };
+
+        Self {
+            file,
+            decision,
+            selected: 1,
+            current,
+            candidates,
+        }
+    }
+
+    /// Resolve the metadata to apply for an accepted entry
+    ///
+    /// Returns `Ok(None)` for pending or rejected entries, and an error when the
+    /// entry is accepted but `selected` doesn't point at a candidate.
+    pub fn resolve(&self) -> Result<Option<&AudibleMetadata>> {
+        if self.decision != ReviewDecision::Accept {
+            return Ok(None);
+        }
+
+        if self.selected == 0 || self.selected > self.candidates.len() {
+            bail!(
+                "Invalid selection {} for {} ({} candidate(s))",
+                self.selected,
+                self.file.display(),
+                self.candidates.len()
+            );
+        }
+
+        Ok(Some(&self.candidates[self.selected - 1].metadata))
+    }
+}
+
+/// Reviewable queue of match candidates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewQueue {
+    /// File format version
+    pub version: u32,
+    /// Audible region the candidates were fetched from
+    pub region: String,
+    /// Queued files
+    #[serde(default)]
+    pub entries: Vec<ReviewEntry>,
+}
+
+impl ReviewQueue {
+    /// Create an empty queue for a region
+    pub fn new(region: &str) -> Self {
+        Self {
+            version: REVIEW_QUEUE_VERSION,
+            region: region.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Load a queue file (JSON if the extension is `.json`, YAML otherwise)
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read review queue: {}", path.display()))?;
+
+        let queue: ReviewQueue = if is_json(path) {
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse review queue: {}", path.display()))?
+        } else {
+            serde_yaml::from_str(&contents)
+                .with_context(|| format!("Failed to parse review queue: {}", path.display()))?
+        };
+
+        if queue.version > REVIEW_QUEUE_VERSION {
+            bail!(
+                "Review queue version {} is newer than supported version {}",
+                queue.version,
+                REVIEW_QUEUE_VERSION
+            );
+        }
+
+        Ok(queue)
+    }
+
+    /// Save the queue (JSON if the extension is `.json`, YAML otherwise)
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = if is_json(path) {
+            serde_json::to_string_pretty(self).context("Failed to serialize review queue")?
+        } else {
+            serde_yaml::to_string(self).context("Failed to serialize review queue")?
+        };
+
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write review queue: {}", path.display()))?;
+
+        Ok(())
+    }
+
+    /// Count entries with the given decision
+    pub fn count(&self, decision: ReviewDecision) -> usize {
+        self.entries.iter().filter(|e| e.decision == decision).count()
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("json"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MetadataDistance, MetadataSource};
+    use tempfile::tempdir;
+
+    fn candidate(asin: &str, title: &str, confidence: MatchConfidence) -> MatchCandidate {
+        let mut distance = MetadataDistance::new();
+        distance.add_penalty("title", 0.1, 0.4);
+        MatchCandidate {
+            distance,
+            metadata: AudibleMetadata {
+                asin: asin.to_string(),
+                title: title.to_string(),
+                subtitle: None,
+                authors: vec![],
+                narrators: vec![],
+                publisher: None,
+                published_year: None,
+                description: None,
+                cover_url: None,
+                isbn: None,
+                genres: vec![],
+                tags: vec![],
+                series: vec![],
+                language: None,
+                runtime_length_ms: None,
+                rating: None,
+                is_abridged: None,
+            },
+            confidence,
+        }
+    }
+
+    fn current() -> CurrentMetadata {
+        CurrentMetadata {
+            title: Some("Dune".to_string()),
+            author: None,
+            year: None,
+            duration: None,
+            source: MetadataSource::Filename,
+        }
+    }
+
+    #[test]
+    fn test_strong_match_is_pre_accepted() {
+        let entry = ReviewEntry::new(
+            PathBuf::from("Dune.m4b"),
+            current(),
+            vec![candidate("B001", "Dune", MatchConfidence::Strong)],
+        );
+        assert_eq!(entry.decision, ReviewDecision::Accept);
+
+        let entry = ReviewEntry::new(
+            PathBuf::from("Dune.m4b"),
+            current(),
+            vec![candidate("B001", "Dune", MatchConfidence::Low)],
+        );
+        assert_eq!(entry.decision, ReviewDecision::Pending);
+    }
+
+    #[test]
+    fn test_resolve_selected_candidate() {
+        let mut entry = ReviewEntry::new(
+            PathBuf::from("Dune.m4b"),
+            current(),
+            vec![
+                candidate("B001", "Dune", MatchConfidence::Medium),
+                candidate("B002", "Dune Messiah", MatchConfidence::Low),
+            ],
+        );
+        assert!(entry.resolve().unwrap().is_none());
+
+        entry.decision = ReviewDecision::Accept;
+        entry.selected = 2;
+        assert_eq!(entry.resolve().unwrap().unwrap().asin, "B002");
+
+        entry.selected = 3;
+        assert!(entry.resolve().is_err());
+
+        entry.decision = ReviewDecision::Reject;
+        assert!(entry.resolve().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_queue_roundtrip_yaml_and_json() {
+        let dir = tempdir().unwrap();
+        let mut queue = ReviewQueue::new("us");
+        queue.entries.push(ReviewEntry::new(
+            PathBuf::from("Dune.m4b"),
+            current(),
+            vec![candidate("B001", "Dune", MatchConfidence::Strong)],
+        ));
+
+        for name in ["queue.yaml", "queue.json"] {
+            let path = dir.path().join(name);
+            queue.save(&path).unwrap();
+            let loaded = ReviewQueue::load(&path).unwrap();
+            assert_eq!(loaded.entries.len(), 1);
+            assert_eq!(loaded.entries[0].candidates[0].metadata.asin, "B001");
+            assert_eq!(loaded.count(ReviewDecision::Accept), 1);
+        }
+    }
+
+    #[test]
+    fn test_reviewer_edits_parse() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("queue.yaml");
+        let mut queue = ReviewQueue::new("uk");
+        queue.entries.push(ReviewEntry::new(PathBuf::from("a.m4b"), current(), vec![]));
+        queue.save(&path).unwrap();
+
+        let edited = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("decision: pending", "decision: reject");
+        std::fs::write(&path, edited).unwrap();
+
+        let loaded = ReviewQueue::load(&path).unwrap();
+        assert_eq!(loaded.entries[0].decision, ReviewDecision::Reject);
+    }
+}
@@ -15,6 +15,8 @@ pub struct Track {
     pub title: Option<String>,
     /// Track number
     pub track_number: Option<u32>,
+    /// Disc number
+    pub disc_number: Option<u32>,
     /// Album/book title
     pub album: Option<String>,
     /// Artist/author
@@ -39,6 +41,7 @@ impl Track {
             quality,
             title: None,
             track_number: None,
+            disc_number: None,
             album: None,
             artist: None,
             album_artist: None,
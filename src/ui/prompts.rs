@@ -1,5 +1,6 @@
 //! Interactive prompts for metadata matching
 
+use crate::cli::output::say;
 use crate::models::{AudibleMetadata, CurrentMetadata, MatchCandidate, MatchConfidence, AudibleAuthor};
 use anyhow::Result;
 use console::style;
@@ -20,13 +21,13 @@ pub fn prompt_match_selection(
     current: &CurrentMetadata,
     candidates: &[MatchCandidate],
 ) -> Result<UserChoice> {
-    println!("\n{}", style("Match Candidates:").bold().cyan());
-    println!(
+    say!("\n{}", style("Match Candidates:").bold().cyan());
+    say!(
         "Current: {} by {}",
         current.title.as_deref().unwrap_or("Unknown"),
         current.author.as_deref().unwrap_or("Unknown")
     );
-    println!();
+    say!();
 
     // Build options for inquire::Select
     let mut options = Vec::new();
@@ -109,8 +110,8 @@ pub fn confirm_match(
     current: &CurrentMetadata,
     selected: &MatchCandidate,
 ) -> Result<bool> {
-    println!("\n{}", style("Metadata Changes:").bold().cyan());
-    println!();
+    say!("\n{}", style("Metadata Changes:").bold().cyan());
+    say!();
 
     show_field_change(
         "Title",
@@ -151,7 +152,7 @@ pub fn confirm_match(
         show_field_change("Publisher", None, Some(publisher));
     }
 
-    println!();
+    say!();
 
     Ok(Confirm::new("Apply these changes?")
         .with_default(true)
@@ -160,7 +161,7 @@ pub fn confirm_match(
 
 /// Prompt for manual metadata entry
 pub fn prompt_manual_metadata() -> Result<AudibleMetadata> {
-    println!("\n{}", style("Enter Metadata Manually:").bold().cyan());
+    say!("\n{}", style("Enter Metadata Manually:").bold().cyan());
 
     let title = Text::new("Title:").prompt()?;
 
@@ -205,7 +206,7 @@ pub fn prompt_manual_metadata() -> Result<AudibleMetadata> {
 
 /// Prompt for custom search terms
 pub fn prompt_custom_search() -> Result<(Option<String>, Option<String>)> {
-    println!("\n{}", style("Custom Search:").bold().cyan());
+    say!("\n{}", style("Custom Search:").bold().cyan());
 
     let title = Text::new("Title (optional):")
         .with_default("")
@@ -235,14 +236,14 @@ fn show_field_change(field: &str, old: Option<&str>, new: Option<&str>) {
     let new_display = new.unwrap_or("(none)");
 
     if old != new {
-        println!(
+        say!(
             "  {}: {} → {}",
             style(field).bold(),
             style(old_display).dim(),
             style(new_display).green()
         );
     } else {
-        println!(
+        say!(
             "  {}: {}",
             style(field).bold(),
             style(new_display).dim()
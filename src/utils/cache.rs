@@ -1,15 +1,173 @@
-//! Filesystem cache for Audible metadata
+//! Filesystem cache for Audible metadata, search results, chapters and covers
 
 use anyhow::{Context, Result};
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::time::{Duration, SystemTime};
 
-use crate::models::AudibleMetadata;
+use crate::models::{AudibleChapter, AudibleConfig, AudibleMetadata, AudibleRegion};
+
+/// Kind of cached Audible data
+///
+/// Book metadata lives directly in the cache root (`{ASIN}.json`) for
+/// compatibility with older caches; every other kind has its own subdirectory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheKind {
+    /// Book metadata keyed by ASIN
+    Metadata,
+    /// Search responses keyed by normalized title/author/region
+    Search,
+    /// Audnex chapter lists keyed by ASIN
+    Chapters,
+    /// Cover images keyed by URL
+    Covers,
+}
+
+impl CacheKind {
+    /// All cache kinds
+    pub const ALL: [CacheKind; 4] = [
+        CacheKind::Metadata,
+        CacheKind::Search,
+        CacheKind::Chapters,
+        CacheKind::Covers,
+    ];
+
+    /// Subdirectory (relative to the cache root) holding this kind
+    fn subdir(&self) -> Option<&'static str> {
+        match self {
+            CacheKind::Metadata => None,
+            CacheKind::Search => Some("search"),
+            CacheKind::Chapters => Some("chapters"),
+            CacheKind::Covers => Some("covers"),
+        }
+    }
+
+    /// Whether a file in this kind's directory is a cache entry
+    fn is_entry(&self, path: &Path) -> bool {
+        if !path.is_file() {
+            return false;
+        }
+        match self {
+            CacheKind::Covers => true,
+            _ => path.extension().and_then(|s| s.to_str()) == Some("json"),
+        }
+    }
+}
+
+impl fmt::Display for CacheKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CacheKind::Metadata => "metadata",
+            CacheKind::Search => "search",
+            CacheKind::Chapters => "chapters",
+            CacheKind::Covers => "covers",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for CacheKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "metadata" => Ok(CacheKind::Metadata),
+            "search" => Ok(CacheKind::Search),
+            "chapters" => Ok(CacheKind::Chapters),
+            "covers" | "cover" => Ok(CacheKind::Covers),
+            _ => anyhow::bail!(
+                "Invalid cache kind: {}. Valid options: metadata, search, chapters, covers",
+                s
+            ),
+        }
+    }
+}
+
+/// Time-to-live for each cache kind (zero disables caching for that kind)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheTtls {
+    pub metadata: Duration,
+    pub search: Duration,
+    pub chapters: Duration,
+    pub covers: Duration,
+}
+
+impl CacheTtls {
+    /// Use the same TTL for every kind
+    pub fn uniform(ttl: Duration) -> Self {
+        Self {
+            metadata: ttl,
+            search: ttl,
+            chapters: ttl,
+            covers: ttl,
+        }
+    }
+
+    /// Build TTLs from the Audible config section (hours)
+    pub fn from_config(config: &AudibleConfig) -> Self {
+        let hours = |h: u64| Duration::from_secs(h * 3600);
+        Self {
+            metadata: hours(config.cache_duration_hours),
+            search: hours(config.search_cache_hours),
+            chapters: hours(config.chapter_cache_hours),
+            covers: hours(config.cover_cache_hours),
+        }
+    }
+
+    /// TTL for a cache kind
+    pub fn get(&self, kind: CacheKind) -> Duration {
+        match kind {
+            CacheKind::Metadata => self.metadata,
+            CacheKind::Search => self.search,
+            CacheKind::Chapters => self.chapters,
+            CacheKind::Covers => self.covers,
+        }
+    }
+}
+
+/// Cached search response
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedSearch {
+    /// Normalized query the results belong to
+    query: String,
+    results: Vec<AudibleMetadata>,
+}
+
+/// Current cache bundle format version
+pub const CACHE_BUNDLE_VERSION: u32 = 1;
+
+/// Format marker written into every bundle
+const CACHE_BUNDLE_FORMAT: &str = "audiobook-forge-cache";
 
-/// Filesystem cache for Audible metadata
+/// Single-file cache export, used to move a warmed cache between machines
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheBundle {
+    format: String,
+    version: u32,
+    entries: Vec<BundleEntry>,
+}
+
+/// One cache file inside a bundle
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleEntry {
+    kind: String,
+    name: String,
+    /// Modification time (seconds since the Unix epoch), which drives the TTL
+    modified: u64,
+    /// Base64-encoded file contents
+    data: String,
+}
+
+/// Filesystem cache for Audible data
+#[derive(Debug, Clone)]
 pub struct AudibleCache {
     cache_dir: PathBuf,
-    ttl: Duration,
+    ttls: CacheTtls,
+    serve_stale: bool,
 }
 
 impl AudibleCache {
@@ -18,96 +176,144 @@ impl AudibleCache {
         Self::with_ttl(Duration::from_secs(7 * 24 * 3600))
     }
 
-    /// Create a new cache with custom TTL
+    /// Create a new cache with the same TTL for every kind
     pub fn with_ttl(ttl: Duration) -> Result<Self> {
+        Self::with_ttls(CacheTtls::uniform(ttl))
+    }
+
+    /// Create a new cache with TTL from config (in hours)
+    pub fn with_ttl_hours(hours: u64) -> Result<Self> {
+        // 0 hours = no caching
+        Self::with_ttl(Duration::from_secs(hours * 3600))
+    }
+
+    /// Create a new cache with per-kind TTLs from the Audible config section
+    pub fn from_config(config: &AudibleConfig) -> Result<Self> {
+        Self::with_ttls(CacheTtls::from_config(config))
+    }
+
+    /// Create a new cache in the default location with per-kind TTLs
+    pub fn with_ttls(ttls: CacheTtls) -> Result<Self> {
         let cache_dir = dirs::cache_dir()
             .context("No cache directory found")?
             .join("audiobook-forge")
             .join("audible");
 
+        Self::with_dir(cache_dir, ttls)
+    }
+
+    /// Create a cache rooted at a specific directory
+    pub fn with_dir(cache_dir: PathBuf, ttls: CacheTtls) -> Result<Self> {
         // Create cache directory if it doesn't exist
         std::fs::create_dir_all(&cache_dir)
             .context("Failed to create cache directory")?;
 
-        Ok(Self { cache_dir, ttl })
+        Ok(Self {
+            cache_dir,
+            ttls,
+            serve_stale: false,
+        })
     }
 
-    /// Create a new cache with TTL from config (in hours)
-    pub fn with_ttl_hours(hours: u64) -> Result<Self> {
-        if hours == 0 {
-            // No caching - use 0 duration
-            Self::with_ttl(Duration::from_secs(0))
-        } else {
-            Self::with_ttl(Duration::from_secs(hours * 3600))
-        }
+    /// Serve entries regardless of age (used in offline mode)
+    ///
+    /// Expired entries are kept on disk and returned; writes still honour TTLs.
+    pub fn serving_stale(mut self) -> Self {
+        self.serve_stale = true;
+        self
+    }
+
+    /// Get the TTLs in use
+    pub fn ttls(&self) -> CacheTtls {
+        self.ttls
     }
 
     /// Get cached metadata for an ASIN
     pub async fn get(&self, asin: &str) -> Option<AudibleMetadata> {
-        // If TTL is 0, caching is disabled
-        if self.ttl.as_secs() == 0 {
-            return None;
-        }
+        self.read_entry(CacheKind::Metadata, &self.cache_path(asin)).await
+    }
 
-        let cache_path = self.cache_path(asin);
+    /// Store metadata in cache for an ASIN
+    pub async fn set(&self, asin: &str, metadata: &AudibleMetadata) -> Result<()> {
+        self.write_entry(CacheKind::Metadata, &self.cache_path(asin), metadata).await
+    }
 
-        if !cache_path.exists() {
-            tracing::debug!("Cache miss for ASIN: {}", asin);
+    /// Get cached search results for a query
+    pub async fn get_search(
+        &self,
+        title: Option<&str>,
+        author: Option<&str>,
+        region: AudibleRegion,
+    ) -> Option<Vec<AudibleMetadata>> {
+        let query = search_key(title, author, region);
+        let path = self.entry_path(CacheKind::Search, &format!("{}.json", hash_key(&query)));
+
+        let cached: CachedSearch = self.read_entry(CacheKind::Search, &path).await?;
+        // Guard against hash collisions
+        if cached.query != query {
             return None;
         }
+        Some(cached.results)
+    }
 
-        // Check if cache is expired
-        if let Ok(metadata) = std::fs::metadata(&cache_path) {
-            if let Ok(modified) = metadata.modified() {
-                if let Ok(elapsed) = SystemTime::now().duration_since(modified) {
-                    if elapsed > self.ttl {
-                        tracing::debug!("Cache expired for ASIN: {} (age: {:?})", asin, elapsed);
-                        // Clean up expired cache file
-                        let _ = std::fs::remove_file(&cache_path);
-                        return None;
-                    }
-                }
-            }
-        }
+    /// Store search results for a query
+    pub async fn set_search(
+        &self,
+        title: Option<&str>,
+        author: Option<&str>,
+        region: AudibleRegion,
+        results: &[AudibleMetadata],
+    ) -> Result<()> {
+        let query = search_key(title, author, region);
+        let path = self.entry_path(CacheKind::Search, &format!("{}.json", hash_key(&query)));
+
+        let cached = CachedSearch {
+            query,
+            results: results.to_vec(),
+        };
+        self.write_entry(CacheKind::Search, &path, &cached).await
+    }
 
-        // Read and deserialize cache file
-        match tokio::fs::read_to_string(&cache_path).await {
-            Ok(content) => match serde_json::from_str::<AudibleMetadata>(&content) {
-                Ok(metadata) => {
-                    tracing::debug!("Cache hit for ASIN: {}", asin);
-                    Some(metadata)
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to parse cache file for {}: {}", asin, e);
-                    // Clean up corrupted cache file
-                    let _ = std::fs::remove_file(&cache_path);
-                    None
-                }
-            },
-            Err(e) => {
-                tracing::debug!("Failed to read cache file for {}: {}", asin, e);
-                None
-            }
+    /// Get cached chapters for an ASIN
+    pub async fn get_chapters(&self, asin: &str) -> Option<Vec<AudibleChapter>> {
+        let path = self.entry_path(CacheKind::Chapters, &format!("{}.json", asin));
+        self.read_entry(CacheKind::Chapters, &path).await
+    }
+
+    /// Store chapters for an ASIN
+    pub async fn set_chapters(&self, asin: &str, chapters: &[AudibleChapter]) -> Result<()> {
+        let path = self.entry_path(CacheKind::Chapters, &format!("{}.json", asin));
+        self.write_entry(CacheKind::Chapters, &path, &chapters).await
+    }
+
+    /// Get the cached cover image file for a URL
+    pub fn get_cover(&self, cover_url: &str) -> Option<PathBuf> {
+        let path = self.cover_path(cover_url);
+        if self.is_fresh(CacheKind::Covers, &path) {
+            tracing::debug!("Cache hit for cover: {}", cover_url);
+            Some(path)
+        } else {
+            None
         }
     }
 
-    /// Store metadata in cache for an ASIN
-    pub async fn set(&self, asin: &str, metadata: &AudibleMetadata) -> Result<()> {
-        // If TTL is 0, caching is disabled
-        if self.ttl.as_secs() == 0 {
+    /// Store cover image bytes for a URL
+    pub async fn set_cover(&self, cover_url: &str, bytes: &[u8]) -> Result<()> {
+        if self.ttls.covers.is_zero() {
             return Ok(());
         }
 
-        let cache_path = self.cache_path(asin);
-
-        let json = serde_json::to_string_pretty(metadata)
-            .context("Failed to serialize metadata")?;
-
-        tokio::fs::write(&cache_path, json)
+        let path = self.cover_path(cover_url);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .context("Failed to create cache directory")?;
+        }
+        tokio::fs::write(&path, bytes)
             .await
-            .context("Failed to write cache file")?;
+            .context("Failed to write cached cover")?;
 
-        tracing::debug!("Cached metadata for ASIN: {} at {}", asin, cache_path.display());
+        tracing::debug!("Cached cover {} at {}", cover_url, path.display());
 
         Ok(())
     }
@@ -125,21 +331,184 @@ impl AudibleCache {
         Ok(())
     }
 
-    /// Clear all cached metadata
+    /// Clear every cache kind
     pub fn clear_all(&self) -> Result<()> {
-        if self.cache_dir.exists() {
-            for entry in std::fs::read_dir(&self.cache_dir)? {
-                if let Ok(entry) = entry {
-                    let path = entry.path();
-                    if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
-                        let _ = std::fs::remove_file(&path);
-                    }
+        for kind in CacheKind::ALL {
+            self.clear_kind(kind)?;
+        }
+        tracing::debug!("Cleared all Audible cache");
+
+        Ok(())
+    }
+
+    /// Clear all entries of one kind, returning how many were removed
+    pub fn clear_kind(&self, kind: CacheKind) -> Result<usize> {
+        let mut removed = 0;
+        for path in self.entries(kind)? {
+            if std::fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+        tracing::debug!("Cleared {} {} cache entries", removed, kind);
+
+        Ok(removed)
+    }
+
+    /// Remove expired entries of every kind, returning how many were removed
+    pub fn prune(&self) -> Result<usize> {
+        let mut removed = 0;
+        for kind in CacheKind::ALL {
+            for path in self.entries(kind)? {
+                if self.is_expired(kind, &path) && std::fs::remove_file(&path).is_ok() {
+                    removed += 1;
                 }
             }
-            tracing::debug!("Cleared all Audible cache");
         }
+        tracing::debug!("Pruned {} expired cache entries", removed);
 
-        Ok(())
+        Ok(removed)
+    }
+
+    /// Copy every cache entry into `dest`, keeping the cache layout and timestamps
+    pub fn export_to(&self, dest: &Path) -> Result<usize> {
+        let mut copied = 0;
+        for kind in CacheKind::ALL {
+            let target_dir = kind_dir(dest, kind);
+            std::fs::create_dir_all(&target_dir)
+                .with_context(|| format!("Failed to create {}", target_dir.display()))?;
+
+            for path in self.entries(kind)? {
+                let target = target_dir.join(path.file_name().unwrap_or_default());
+                copy_preserving_mtime(&path, &target)?;
+                copied += 1;
+            }
+        }
+
+        Ok(copied)
+    }
+
+    /// Import entries exported with [`export_to`](Self::export_to)
+    ///
+    /// Existing entries are only replaced when the imported copy is newer.
+    pub fn import_from(&self, src: &Path) -> Result<usize> {
+        if !src.is_dir() {
+            anyhow::bail!("Not a cache export directory: {}", src.display());
+        }
+
+        let mut imported = 0;
+        for kind in CacheKind::ALL {
+            let source_dir = kind_dir(src, kind);
+            if !source_dir.is_dir() {
+                continue;
+            }
+
+            let target_dir = kind_dir(&self.cache_dir, kind);
+            std::fs::create_dir_all(&target_dir)
+                .context("Failed to create cache directory")?;
+
+            for entry in std::fs::read_dir(&source_dir)?.flatten() {
+                let path = entry.path();
+                if !kind.is_entry(&path) {
+                    continue;
+                }
+
+                let target = target_dir.join(entry.file_name());
+                if modified(&target).is_some_and(|existing| modified(&path).is_some_and(|new| new <= existing)) {
+                    continue;
+                }
+
+                copy_preserving_mtime(&path, &target)?;
+                imported += 1;
+            }
+        }
+
+        Ok(imported)
+    }
+
+    /// Write every cache entry into a single bundle file
+    pub fn export_bundle(&self, path: &Path) -> Result<usize> {
+        let mut entries = Vec::new();
+        for kind in CacheKind::ALL {
+            for entry_path in self.entries(kind)? {
+                let data = std::fs::read(&entry_path)
+                    .with_context(|| format!("Failed to read {}", entry_path.display()))?;
+                let modified = modified(&entry_path)
+                    .and_then(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+
+                entries.push(BundleEntry {
+                    kind: kind.to_string(),
+                    name: entry_path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+                    modified,
+                    data: base64::engine::general_purpose::STANDARD.encode(data),
+                });
+            }
+        }
+
+        let count = entries.len();
+        let bundle = CacheBundle {
+            format: CACHE_BUNDLE_FORMAT.to_string(),
+            version: CACHE_BUNDLE_VERSION,
+            entries,
+        };
+
+        let json = serde_json::to_string(&bundle).context("Failed to serialize cache bundle")?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write cache bundle: {}", path.display()))?;
+
+        Ok(count)
+    }
+
+    /// Import a bundle written by [`export_bundle`](Self::export_bundle)
+    ///
+    /// Existing entries are only replaced when the bundled copy is newer.
+    pub fn import_bundle(&self, path: &Path) -> Result<usize> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read cache bundle: {}", path.display()))?;
+        let bundle: CacheBundle = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse cache bundle: {}", path.display()))?;
+
+        if bundle.format != CACHE_BUNDLE_FORMAT {
+            anyhow::bail!("Not an audiobook-forge cache bundle: {}", path.display());
+        }
+        if bundle.version > CACHE_BUNDLE_VERSION {
+            anyhow::bail!(
+                "Cache bundle version {} is newer than supported version {}",
+                bundle.version,
+                CACHE_BUNDLE_VERSION
+            );
+        }
+
+        let mut imported = 0;
+        for entry in bundle.entries {
+            let kind = CacheKind::from_str(&entry.kind)?;
+
+            // Entry names must be plain file names inside the kind directory
+            if entry.name.is_empty() || Path::new(&entry.name).file_name() != Some(entry.name.as_ref()) {
+                anyhow::bail!("Invalid entry name in cache bundle: {}", entry.name);
+            }
+
+            let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(entry.modified);
+            let target = self.entry_path(kind, &entry.name);
+            if modified(&target).is_some_and(|existing| mtime <= existing) {
+                continue;
+            }
+
+            let data = base64::engine::general_purpose::STANDARD
+                .decode(&entry.data)
+                .with_context(|| format!("Invalid data for cache entry {}", entry.name))?;
+
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).context("Failed to create cache directory")?;
+            }
+            std::fs::write(&target, data)
+                .with_context(|| format!("Failed to write {}", target.display()))?;
+            std::fs::File::options().write(true).open(&target)?.set_modified(mtime)?;
+            imported += 1;
+        }
+
+        Ok(imported)
     }
 
     /// Get the cache file path for an ASIN
@@ -147,35 +516,214 @@ impl AudibleCache {
         self.cache_dir.join(format!("{}.json", asin))
     }
 
+    /// Get the cache file path for a cover URL
+    fn cover_path(&self, cover_url: &str) -> PathBuf {
+        let ext = cover_url
+            .rsplit('/')
+            .next()
+            .and_then(|name| name.split('?').next())
+            .and_then(|name| Path::new(name).extension())
+            .and_then(|e| e.to_str())
+            .filter(|e| e.len() <= 4 && e.chars().all(|c| c.is_ascii_alphanumeric()))
+            .unwrap_or("jpg")
+            .to_lowercase();
+
+        self.entry_path(CacheKind::Covers, &format!("{}.{}", hash_key(cover_url), ext))
+    }
+
+    fn entry_path(&self, kind: CacheKind, file_name: &str) -> PathBuf {
+        kind_dir(&self.cache_dir, kind).join(file_name)
+    }
+
     /// Get cache directory path
     pub fn cache_dir(&self) -> &Path {
         &self.cache_dir
     }
 
-    /// Get cache statistics (number of files, total size)
+    /// Get cache statistics (number of files, total size), overall and per kind
     pub fn stats(&self) -> Result<CacheStats> {
-        let mut count = 0;
-        let mut total_size = 0u64;
-
-        if self.cache_dir.exists() {
-            for entry in std::fs::read_dir(&self.cache_dir)? {
-                if let Ok(entry) = entry {
-                    let path = entry.path();
-                    if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
-                        count += 1;
-                        if let Ok(metadata) = std::fs::metadata(&path) {
-                            total_size += metadata.len();
-                        }
-                    }
+        let mut stats = CacheStats {
+            file_count: 0,
+            total_size_bytes: 0,
+            kinds: Vec::new(),
+        };
+
+        for kind in CacheKind::ALL {
+            let mut kind_stats = KindStats {
+                kind,
+                file_count: 0,
+                total_size_bytes: 0,
+                expired_count: 0,
+            };
+
+            for path in self.entries(kind)? {
+                kind_stats.file_count += 1;
+                if let Ok(metadata) = std::fs::metadata(&path) {
+                    kind_stats.total_size_bytes += metadata.len();
+                }
+                if self.is_expired(kind, &path) {
+                    kind_stats.expired_count += 1;
                 }
             }
+
+            stats.file_count += kind_stats.file_count;
+            stats.total_size_bytes += kind_stats.total_size_bytes;
+            stats.kinds.push(kind_stats);
         }
 
-        Ok(CacheStats {
-            file_count: count,
-            total_size_bytes: total_size,
-        })
+        Ok(stats)
+    }
+
+    /// List the entry files of one kind
+    fn entries(&self, kind: CacheKind) -> Result<Vec<PathBuf>> {
+        let dir = kind_dir(&self.cache_dir, kind);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(std::fs::read_dir(&dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| kind.is_entry(path))
+            .collect())
+    }
+
+    /// Whether an entry is older than its kind's TTL (always true when disabled)
+    fn is_expired(&self, kind: CacheKind, path: &Path) -> bool {
+        let ttl = self.ttls.get(kind);
+        if ttl.is_zero() {
+            return true;
+        }
+
+        modified(path)
+            .and_then(|m| SystemTime::now().duration_since(m).ok())
+            .map(|age| age > ttl)
+            .unwrap_or(false)
+    }
+
+    /// Whether an entry exists and is within its TTL, removing it if expired
+    fn is_fresh(&self, kind: CacheKind, path: &Path) -> bool {
+        if self.serve_stale {
+            return path.exists();
+        }
+
+        // If TTL is 0, caching is disabled for this kind
+        if self.ttls.get(kind).is_zero() || !path.exists() {
+            return false;
+        }
+
+        if self.is_expired(kind, path) {
+            tracing::debug!("Cache expired: {}", path.display());
+            // Clean up expired cache file
+            let _ = std::fs::remove_file(path);
+            return false;
+        }
+
+        true
+    }
+
+    async fn read_entry<T: DeserializeOwned>(&self, kind: CacheKind, path: &Path) -> Option<T> {
+        if !self.is_fresh(kind, path) {
+            tracing::debug!("Cache miss ({}): {}", kind, path.display());
+            return None;
+        }
+
+        // Read and deserialize cache file
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) => match serde_json::from_str::<T>(&content) {
+                Ok(value) => {
+                    tracing::debug!("Cache hit ({}): {}", kind, path.display());
+                    Some(value)
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to parse cache file {}: {}", path.display(), e);
+                    // Clean up corrupted cache file
+                    let _ = std::fs::remove_file(path);
+                    None
+                }
+            },
+            Err(e) => {
+                tracing::debug!("Failed to read cache file {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    async fn write_entry<T: Serialize + ?Sized>(&self, kind: CacheKind, path: &Path, value: &T) -> Result<()> {
+        // If TTL is 0, caching is disabled for this kind
+        if self.ttls.get(kind).is_zero() {
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .context("Failed to create cache directory")?;
+        }
+
+        let json = serde_json::to_string_pretty(value)
+            .context("Failed to serialize cache entry")?;
+
+        tokio::fs::write(path, json)
+            .await
+            .context("Failed to write cache file")?;
+
+        tracing::debug!("Cached {} at {}", kind, path.display());
+
+        Ok(())
+    }
+}
+
+/// Directory holding one kind of entry under a cache root
+fn kind_dir(root: &Path, kind: CacheKind) -> PathBuf {
+    match kind.subdir() {
+        Some(sub) => root.join(sub),
+        None => root.to_path_buf(),
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Copy a file and carry over its modification time (TTLs are mtime based)
+fn copy_preserving_mtime(src: &Path, dest: &Path) -> Result<()> {
+    std::fs::copy(src, dest)
+        .with_context(|| format!("Failed to copy {} to {}", src.display(), dest.display()))?;
+
+    if let Some(mtime) = modified(src) {
+        let file = std::fs::File::options().write(true).open(dest)?;
+        file.set_modified(mtime)
+            .with_context(|| format!("Failed to set modification time on {}", dest.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Normalize a search query so trivially different spellings share a cache entry
+fn search_key(title: Option<&str>, author: Option<&str>, region: AudibleRegion) -> String {
+    fn normalize(s: Option<&str>) -> String {
+        s.unwrap_or_default()
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    format!("{}|{}|{}", region.tld(), normalize(title), normalize(author))
+}
+
+/// Stable 64-bit FNV-1a hash, hex encoded, for use in file names
+fn hash_key(key: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in key.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
     }
+    format!("{:016x}", hash)
 }
 
 /// Cache statistics
@@ -183,6 +731,8 @@ impl AudibleCache {
 pub struct CacheStats {
     pub file_count: usize,
     pub total_size_bytes: u64,
+    /// Breakdown by cache kind
+    pub kinds: Vec<KindStats>,
 }
 
 impl CacheStats {
@@ -192,10 +742,21 @@ impl CacheStats {
     }
 }
 
+/// Statistics for a single cache kind
+#[derive(Debug, Clone)]
+pub struct KindStats {
+    pub kind: CacheKind,
+    pub file_count: usize,
+    pub total_size_bytes: u64,
+    /// Entries older than the kind's TTL (removed by `prune`)
+    pub expired_count: usize,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::models::{AudibleAuthor, AudibleSeries};
+    use tempfile::tempdir;
 
     fn create_test_metadata() -> AudibleMetadata {
         AudibleMetadata {
@@ -222,6 +783,10 @@ mod tests {
         }
     }
 
+    fn temp_cache(dir: &Path) -> AudibleCache {
+        AudibleCache::with_dir(dir.to_path_buf(), CacheTtls::uniform(Duration::from_secs(3600))).unwrap()
+    }
+
     #[tokio::test]
     async fn test_cache_set_and_get() {
         let cache = AudibleCache::new().unwrap();
@@ -272,4 +837,144 @@ mod tests {
         assert!(stats.file_count >= 0);
         assert!(stats.total_size_bytes >= 0);
     }
+
+    #[tokio::test]
+    async fn test_search_cache_normalizes_query() {
+        let dir = tempdir().unwrap();
+        let cache = temp_cache(dir.path());
+        let results = vec![create_test_metadata()];
+
+        cache
+            .set_search(Some("The  Hobbit!"), Some("Tolkien"), AudibleRegion::US, &results)
+            .await
+            .unwrap();
+
+        let hit = cache
+            .get_search(Some("the hobbit"), Some("TOLKIEN"), AudibleRegion::US)
+            .await;
+        assert_eq!(hit.unwrap()[0].asin, "B001");
+
+        // Different region is a different query
+        assert!(cache
+            .get_search(Some("the hobbit"), Some("tolkien"), AudibleRegion::UK)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_chapters_and_covers() {
+        let dir = tempdir().unwrap();
+        let cache = temp_cache(dir.path());
+
+        let chapters = vec![AudibleChapter {
+            title: "Chapter 1".to_string(),
+            length_ms: 1000,
+            start_offset_ms: 0,
+            start_offset_sec: Some(0),
+        }];
+        cache.set_chapters("B001", &chapters).await.unwrap();
+        assert_eq!(cache.get_chapters("B001").await.unwrap().len(), 1);
+
+        let url = "https://m.media-amazon.com/images/I/51abc._SL500_.jpg";
+        assert!(cache.get_cover(url).is_none());
+        cache.set_cover(url, b"jpeg").await.unwrap();
+        let cover = cache.get_cover(url).unwrap();
+        assert_eq!(cover.extension().unwrap(), "jpg");
+        assert_eq!(std::fs::read(cover).unwrap(), b"jpeg");
+    }
+
+    #[tokio::test]
+    async fn test_per_kind_ttl_and_prune() {
+        let dir = tempdir().unwrap();
+        let ttls = CacheTtls {
+            search: Duration::ZERO,
+            ..CacheTtls::uniform(Duration::from_secs(3600))
+        };
+        let cache = AudibleCache::with_dir(dir.path().to_path_buf(), ttls).unwrap();
+
+        cache.set("B001", &create_test_metadata()).await.unwrap();
+        cache.set_search(Some("x"), None, AudibleRegion::US, &[]).await.unwrap();
+        assert!(cache.get_search(Some("x"), None, AudibleRegion::US).await.is_none());
+
+        // Age the metadata entry past its TTL
+        let path = dir.path().join("B001.json");
+        let old = SystemTime::now() - Duration::from_secs(7200);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(old).unwrap();
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.file_count, 1);
+        assert_eq!(stats.kinds[0].expired_count, 1);
+
+        assert_eq!(cache.prune().unwrap(), 1);
+        assert_eq!(cache.stats().unwrap().file_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_export_import_roundtrip() {
+        let src_dir = tempdir().unwrap();
+        let export_dir = tempdir().unwrap();
+        let dst_dir = tempdir().unwrap();
+
+        let src = temp_cache(src_dir.path());
+        src.set("B001", &create_test_metadata()).await.unwrap();
+        src.set_cover("https://example.com/c.png", b"png").await.unwrap();
+
+        assert_eq!(src.export_to(export_dir.path()).unwrap(), 2);
+
+        let dst = temp_cache(dst_dir.path());
+        assert_eq!(dst.import_from(export_dir.path()).unwrap(), 2);
+        assert!(dst.get("B001").await.is_some());
+        assert!(dst.get_cover("https://example.com/c.png").is_some());
+
+        // Re-importing the same entries is a no-op
+        assert_eq!(dst.import_from(export_dir.path()).unwrap(), 0);
+
+        dst.clear_all().unwrap();
+        assert_eq!(dst.stats().unwrap().file_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_bundle_roundtrip_preserves_age() {
+        let src_dir = tempdir().unwrap();
+        let dst_dir = tempdir().unwrap();
+        let bundle_dir = tempdir().unwrap();
+        let bundle = bundle_dir.path().join("cache.json");
+
+        let src = temp_cache(src_dir.path());
+        src.set("B001", &create_test_metadata()).await.unwrap();
+        src.set_search(Some("Dune"), None, AudibleRegion::US, &[create_test_metadata()]).await.unwrap();
+        src.set_cover("https://example.com/c.jpg", &[0xff, 0xd8, 0x00]).await.unwrap();
+
+        let old = SystemTime::now() - Duration::from_secs(600);
+        let path = src_dir.path().join("B001.json");
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(old).unwrap();
+
+        assert_eq!(src.export_bundle(&bundle).unwrap(), 3);
+
+        let dst = temp_cache(dst_dir.path());
+        assert_eq!(dst.import_bundle(&bundle).unwrap(), 3);
+        assert!(dst.get_search(Some("dune"), None, AudibleRegion::US).await.is_some());
+        assert_eq!(std::fs::read(dst.get_cover("https://example.com/c.jpg").unwrap()).unwrap(), [0xff, 0xd8, 0x00]);
+
+        let imported = modified(&dst_dir.path().join("B001.json")).unwrap();
+        let age = SystemTime::now().duration_since(imported).unwrap();
+        assert!(age >= Duration::from_secs(590));
+
+        assert_eq!(dst.import_bundle(&bundle).unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_serving_stale_ignores_ttl() {
+        let dir = tempdir().unwrap();
+        let cache = temp_cache(dir.path());
+        cache.set("B001", &create_test_metadata()).await.unwrap();
+
+        let path = dir.path().join("B001.json");
+        let old = SystemTime::now() - Duration::from_secs(7200);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(old).unwrap();
+
+        let stale = cache.clone().serving_stale();
+        assert!(stale.get("B001").await.is_some());
+        assert!(cache.get("B001").await.is_none());
+    }
 }
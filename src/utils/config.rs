@@ -1,7 +1,13 @@
 //! Configuration file management
 
-use crate::models::Config;
+use crate::audio::AacEncoder;
+use crate::core::{min_free_space, SizeBudget};
+use crate::error::ForgeError;
+use super::{migrate_config, MigrationReport};
+use crate::models::{AudibleRegion, Config, QualityProfile, TEMPO_RANGE};
 use anyhow::{Context, Result};
+use std::path::Path;
+use std::str::FromStr;
 use std::fs;
 use std::path::PathBuf;
 
@@ -46,13 +52,21 @@ impl ConfigManager {
         }
     }
 
-    /// Load configuration from file
+    /// Load configuration from file, upgrading older file versions
     pub fn load(path: &PathBuf) -> Result<Config> {
         let contents = fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file: {}", path.display()))?;
 
-        let config: Config = serde_yaml::from_str(&contents)
-            .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+        let mut value: serde_yaml::Value = serde_yaml::from_str(&contents)
+            .map_err(|e| ForgeError::config(format!("Failed to parse config file {}", path.display()), e))?;
+        let report = migrate_config(&mut value)
+            .map_err(|e| ForgeError::config(format!("Failed to migrate config file {}", path.display()), e))?;
+        if report.is_outdated() {
+            tracing::warn!("{}", outdated_message(path, &report));
+        }
+
+        let config: Config = serde_yaml::from_value(value)
+            .map_err(|e| ForgeError::config(format!("Failed to parse config file {}", path.display()), e))?;
 
         Ok(config)
     }
@@ -140,6 +154,74 @@ impl ConfigManager {
             ));
         }
 
+        // Validate Audible region
+        if let Err(e) = AudibleRegion::from_str(&config.metadata.audible.region) {
+            warnings.push(format!("metadata.audible.region: {}", e));
+        }
+
+        // Validate encoder and default bitrate / sample rate
+        let encoder = &config.advanced.aac_encoder;
+        if !encoder.eq_ignore_ascii_case("auto") && AacEncoder::from_str(encoder).is_none() {
+            warnings.push(format!(
+                "aac_encoder '{}' is not recognized. Valid options: auto, aac_at, libfdk_aac, aac",
+                encoder
+            ));
+        }
+        for (key, value) in [
+            ("performance.max_concurrent_encodes", &config.performance.max_concurrent_encodes),
+            ("performance.max_concurrent_files_per_book", &config.performance.max_concurrent_files_per_book),
+            ("quality.default_bitrate", &config.quality.default_bitrate),
+            ("quality.default_sample_rate", &config.quality.default_sample_rate),
+        ] {
+            if value != "auto" && !value.parse::<u32>().is_ok_and(|n| n > 0) {
+                warnings.push(format!("{} '{}' should be \"auto\" or a positive number", key, value));
+            }
+        }
+        if let Err(e) = SizeBudget::from_config(&config.quality) {
+            warnings.push(format!("{:#}", e));
+        }
+        if let Err(e) = min_free_space(&config.processing) {
+            warnings.push(format!("{:#}", e));
+        }
+        for speed in &config.quality.speeds {
+            if !TEMPO_RANGE.contains(speed) {
+                warnings.push(format!(
+                    "quality.speeds: {} is outside {}-{}",
+                    speed,
+                    TEMPO_RANGE.start(),
+                    TEMPO_RANGE.end()
+                ));
+            }
+        }
+        if let Ok(bitrate) = config.quality.default_bitrate.parse::<u32>() {
+            warnings.extend(check_bitrate("quality.default_bitrate", bitrate, encoder));
+        }
+        if let Ok(sample_rate) = config.quality.default_sample_rate.parse::<u32>() {
+            warnings.extend(check_sample_rate("quality.default_sample_rate", sample_rate));
+        }
+
+        // Validate profiles
+        for name in config.profiles.keys() {
+            match config.profile(name) {
+                Ok(profile) => {
+                    let key = format!("profiles.{}", name);
+                    if let Some(bitrate) = profile.bitrate {
+                        warnings.extend(check_bitrate(&format!("{}.bitrate", key), bitrate, &profile.encoder));
+                    }
+                    if let Some(sample_rate) = profile.sample_rate {
+                        warnings.extend(check_sample_rate(&format!("{}.sample_rate", key), sample_rate));
+                    }
+                    if let Some(encoder) = AacEncoder::from_str(&profile.encoder) {
+                        // HE-AAC and VBR depend on the encoder
+                        let source = QualityProfile::new(128, 44100, 2, "aac".to_string(), 0.0)?;
+                        let (_, notes) = encoder.fit_quality(&profile.apply_quality(&source));
+                        warnings.extend(notes.into_iter().map(|note| format!("{}: {}", key, note)));
+                    }
+                }
+                Err(e) => warnings.push(e.to_string()),
+            }
+        }
+
         // Check if custom paths exist
         if let Some(ref path) = config.directories.source {
             if !path.exists() {
@@ -162,6 +244,55 @@ impl ConfigManager {
     }
 }
 
+/// Warning shown when a config file predates the current format
+pub(crate) fn outdated_message(path: &Path, report: &MigrationReport) -> String {
+    format!(
+        "Config file {} is version {} (current is {}); it was upgraded in memory. Run `audiobook-forge config migrate` to update it.",
+        path.display(),
+        report.from,
+        report.to
+    )
+}
+
+/// Sample rates AAC can encode
+const AAC_SAMPLE_RATES: [u32; 12] = [
+    8000, 11025, 12000, 16000, 22050, 24000, 32000, 44100, 48000, 64000, 88200, 96000,
+];
+
+/// Check a bitrate against what the encoder (or, for "auto", every candidate) supports
+fn check_bitrate(key: &str, bitrate: u32, encoder: &str) -> Option<String> {
+    let candidates = match AacEncoder::from_str(encoder) {
+        Some(encoder) => vec![encoder],
+        None => vec![AacEncoder::AppleSilicon, AacEncoder::LibFdk, AacEncoder::Native],
+    };
+
+    let unsupported: Vec<String> = candidates
+        .iter()
+        .filter(|e| {
+            let (min, max) = e.bitrate_range();
+            bitrate < min || bitrate > max
+        })
+        .map(|e| {
+            let (min, max) = e.bitrate_range();
+            format!("{} supports {}-{} kbps", e.name(), min, max)
+        })
+        .collect();
+
+    if unsupported.is_empty() {
+        None
+    } else {
+        Some(format!("{} ({} kbps) doesn't fit the encoder: {}", key, bitrate, unsupported.join(", ")))
+    }
+}
+
+fn check_sample_rate(key: &str, sample_rate: u32) -> Option<String> {
+    if AAC_SAMPLE_RATES.contains(&sample_rate) {
+        None
+    } else {
+        Some(format!("{} ({} Hz) is not a sample rate AAC supports", key, sample_rate))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,4 +318,38 @@ mod tests {
         let warnings = ConfigManager::validate(&config).unwrap();
         assert!(!warnings.is_empty());
     }
+
+    #[test]
+    fn test_validate_semantic_checks() {
+        assert!(ConfigManager::validate(&Config::default()).unwrap().is_empty());
+
+        let mut config = Config::default();
+        config.metadata.audible.region = "mars".to_string();
+        config.advanced.aac_encoder = "libfdk_aac".to_string();
+        config.quality.default_bitrate = "400".to_string();
+        config.quality.default_sample_rate = "44000".to_string();
+
+        let warnings = ConfigManager::validate(&config).unwrap();
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(warnings[0].contains("Invalid Audible region: mars"));
+        assert!(warnings[1].contains("libfdk_aac supports 8-320 kbps"));
+        assert!(warnings[2].contains("44000 Hz"));
+
+        // "auto" warns only about the encoders that can't take the bitrate
+        config.advanced.aac_encoder = "auto".to_string();
+        config.quality.default_bitrate = "400".to_string();
+        let warnings = ConfigManager::validate(&config).unwrap();
+        assert!(warnings[1].contains("aac_at") && !warnings[1].contains("aac supports 8-512"));
+    }
+
+    #[test]
+    fn test_validate_profile_encoder_features() {
+        let yaml = "profiles:\n  voice:\n    preset: speech\n    encoder: aac\n    vbr: 3\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+
+        let warnings = ConfigManager::validate(&config).unwrap();
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(warnings[0].starts_with("profiles.voice: aac can't encode he"));
+        assert!(warnings[1].contains("no VBR mode 3"));
+    }
 }
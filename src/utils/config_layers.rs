@@ -0,0 +1,454 @@
+//! Layered configuration resolution
+//!
+//! Settings are resolved from, lowest to highest priority: built-in defaults,
+//! the system file, the user file, a project-local `audiobook-forge.yaml` in
+//! the library root, `AUDIOBOOK_FORGE_*` environment variables, and finally
+//! CLI flags. Each value remembers the layer that set it (`config show --origin`).
+
+use crate::error::ForgeError;
+use crate::models::Config;
+use anyhow::{Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Prefix of environment variables that override settings
+pub const ENV_PREFIX: &str = "AUDIOBOOK_FORGE_";
+
+/// Name of the project-local config file looked up in the library root
+pub const PROJECT_CONFIG_FILENAME: &str = "audiobook-forge.yaml";
+
+/// Where a setting came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigOrigin {
+    /// Built-in default
+    Default,
+    /// System-wide config file
+    System(PathBuf),
+    /// User config file (or `--config`)
+    User(PathBuf),
+    /// `audiobook-forge.yaml` in the library root
+    Project(PathBuf),
+    /// Environment variable
+    Env(String),
+    /// Command-line flag
+    Cli(String),
+}
+
+impl fmt::Display for ConfigOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigOrigin::Default => write!(f, "default"),
+            ConfigOrigin::System(path) => write!(f, "system ({})", path.display()),
+            ConfigOrigin::User(path) => write!(f, "user ({})", path.display()),
+            ConfigOrigin::Project(path) => write!(f, "project ({})", path.display()),
+            ConfigOrigin::Env(var) => write!(f, "env ({})", var),
+            ConfigOrigin::Cli(flag) => write!(f, "cli ({})", flag),
+        }
+    }
+}
+
+/// Inputs for layered resolution
+#[derive(Debug, Default)]
+pub struct ConfigSources {
+    /// System-wide config file (None = don't read one)
+    pub system_file: Option<PathBuf>,
+    /// User config file
+    pub user_file: Option<PathBuf>,
+    /// Directory searched for `audiobook-forge.yaml`
+    pub project_dir: Option<PathBuf>,
+    /// Environment variables (all of them; non-matching ones are ignored)
+    pub env: Vec<(String, String)>,
+    /// Settings from CLI flags: dotted key, value and the flag that set it
+    pub cli: Vec<(String, Value, String)>,
+}
+
+/// A resolved configuration plus the origin of every value
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    /// The resolved configuration
+    pub config: Config,
+    /// Config files that were read, lowest priority first
+    pub files: Vec<ConfigOrigin>,
+    /// Problems that didn't prevent loading (e.g. unknown environment variables)
+    pub warnings: Vec<String>,
+    origins: HashMap<String, ConfigOrigin>,
+    merged: Value,
+}
+
+impl LayeredConfig {
+    /// Resolve every layer in priority order
+    pub fn resolve(sources: &ConfigSources) -> Result<Self> {
+        let mut merged = serde_yaml::to_value(Config::default()).context("Failed to serialize defaults")?;
+        let mut origins = HashMap::new();
+        for (key, _) in leaves(&merged) {
+            origins.insert(key, ConfigOrigin::Default);
+        }
+        let defaults = merged.clone();
+
+        let mut layered = Self {
+            config: Config::default(),
+            files: Vec::new(),
+            warnings: Vec::new(),
+            origins,
+            merged: Value::Null,
+        };
+
+        let files = [
+            sources.system_file.clone().map(ConfigOrigin::System),
+            sources.user_file.clone().map(ConfigOrigin::User),
+            sources
+                .project_dir
+                .as_ref()
+                .map(|dir| ConfigOrigin::Project(dir.join(PROJECT_CONFIG_FILENAME))),
+        ];
+        for origin in files.into_iter().flatten() {
+            let path = match &origin {
+                ConfigOrigin::System(p) | ConfigOrigin::User(p) | ConfigOrigin::Project(p) => p.clone(),
+                _ => unreachable!(),
+            };
+            if !path.is_file() {
+                continue;
+            }
+            let mut value = read_file(&path)?;
+            let report = super::migrate_config(&mut value)
+                .map_err(|e| ForgeError::config(format!("Failed to migrate config file {}", path.display()), e))?;
+            if report.is_outdated() {
+                layered.warnings.push(super::config::outdated_message(&path, &report));
+            }
+            // The version describes the file, not a setting
+            if let Value::Mapping(map) = &mut value {
+                map.remove("version");
+            }
+            layered.apply(&mut merged, value, &origin);
+            layered.files.push(origin);
+        }
+
+        for (var, raw) in &sources.env {
+            let Some(rest) = var.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            match env_key(&defaults, &rest.to_lowercase()) {
+                Some((key, leaf)) => {
+                    let value = parse_env_value(raw, leaf);
+                    layered.apply(&mut merged, nested(&key, value), &ConfigOrigin::Env(var.clone()));
+                }
+                None => layered
+                    .warnings
+                    .push(format!("Ignoring unknown setting in environment variable {}", var)),
+            }
+        }
+
+        for (key, value, flag) in &sources.cli {
+            layered.apply(&mut merged, nested(key, value.clone()), &ConfigOrigin::Cli(flag.clone()));
+        }
+
+        layered.config = serde_yaml::from_value(merged.clone()).map_err(|e| {
+            let layers: Vec<String> = layered.files.iter().map(|o| o.to_string()).collect();
+            ForgeError::config(format!("Invalid configuration (layers: defaults, {})", layers.join(", ")), e)
+        })?;
+        layered.merged = merged;
+
+        Ok(layered)
+    }
+
+    /// Origin of a setting by dotted key (e.g. "processing.parallel_workers")
+    pub fn origin(&self, key: &str) -> Option<&ConfigOrigin> {
+        self.origins.get(key)
+    }
+
+    /// Every setting with its value (as YAML) and origin, in config order
+    ///
+    /// `version` is left out: it describes the file, not a setting.
+    pub fn entries(&self) -> Vec<(String, String, ConfigOrigin)> {
+        leaves(&self.merged)
+            .into_iter()
+            .filter(|(key, _)| key != "version")
+            .map(|(key, value)| {
+                let origin = self.origins.get(&key).cloned().unwrap_or(ConfigOrigin::Default);
+                (key, display_value(value), origin)
+            })
+            .collect()
+    }
+
+    fn apply(&mut self, merged: &mut Value, patch: Value, origin: &ConfigOrigin) {
+        for (key, _) in leaves(&patch) {
+            // A new value replaces everything below it, e.g. a whole list
+            self.origins.retain(|k, _| !k.starts_with(&format!("{}.", key)));
+            self.origins.insert(key, origin.clone());
+        }
+        merge_yaml(merged, patch);
+    }
+}
+
+/// Default system-wide config file
+pub fn system_config_path() -> Option<PathBuf> {
+    if cfg!(windows) {
+        std::env::var_os("ProgramData")
+            .map(|dir| PathBuf::from(dir).join("audiobook-forge").join("config.yaml"))
+    } else {
+        Some(PathBuf::from("/etc/audiobook-forge/config.yaml"))
+    }
+}
+
+/// Recursively merge `patch` into `base` (mappings are merged, everything else replaced)
+pub(crate) fn merge_yaml(base: &mut Value, patch: Value) {
+    match (base, patch) {
+        (Value::Mapping(base), Value::Mapping(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+fn read_file(path: &Path) -> Result<Value> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    if contents.trim().is_empty() {
+        return Ok(Value::Mapping(Mapping::new()));
+    }
+
+    let value: Value = serde_yaml::from_str(&contents)
+        .map_err(|e| ForgeError::config(format!("Failed to parse config file {}", path.display()), e))?;
+    match value {
+        Value::Mapping(_) => Ok(value),
+        Value::Null => Ok(Value::Mapping(Mapping::new())),
+        _ => Err(ForgeError::Config(format!("Config file is not a mapping: {}", path.display())).into()),
+    }
+}
+
+/// Leaf settings of a config tree as (dotted key, value); lists count as leaves
+fn leaves(value: &Value) -> Vec<(String, &Value)> {
+    fn walk<'a>(prefix: &str, value: &'a Value, out: &mut Vec<(String, &'a Value)>) {
+        match value {
+            Value::Mapping(map) if !map.is_empty() => {
+                for (key, child) in map {
+                    let Some(key) = key.as_str() else { continue };
+                    let path = if prefix.is_empty() {
+                        key.to_string()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(&path, child, out);
+                }
+            }
+            _ if !prefix.is_empty() => out.push((prefix.to_string(), value)),
+            _ => {}
+        }
+    }
+
+    let mut out = Vec::new();
+    walk("", value, &mut out);
+    out
+}
+
+/// Map an environment variable suffix (lowercased, `_`-separated) onto a
+/// setting, e.g. `metadata_audible_region` → `metadata.audible.region`
+fn env_key<'a>(tree: &'a Value, rest: &str) -> Option<(String, &'a Value)> {
+    let Value::Mapping(map) = tree else {
+        return None;
+    };
+
+    for (key, child) in map {
+        let Some(key) = key.as_str() else { continue };
+        if rest == key && !matches!(child, Value::Mapping(m) if !m.is_empty()) {
+            return Some((key.to_string(), child));
+        }
+        let Some(tail) = rest.strip_prefix(key) else { continue };
+        // Accept `__` as an explicit separator as well as `_`
+        let Some(tail) = tail.strip_prefix("__").or_else(|| tail.strip_prefix('_')) else {
+            continue;
+        };
+        if let Some((sub, leaf)) = env_key(child, tail) {
+            return Some((format!("{}.{}", key, sub), leaf));
+        }
+    }
+
+    None
+}
+
+/// Parse an environment value using the default value's type
+fn parse_env_value(raw: &str, default: &Value) -> Value {
+    match default {
+        Value::Bool(_) => match raw.trim().to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Value::Bool(true),
+            "0" | "false" | "no" | "off" => Value::Bool(false),
+            _ => Value::String(raw.to_string()),
+        },
+        Value::Number(_) => serde_yaml::from_str(raw.trim()).unwrap_or_else(|_| Value::String(raw.to_string())),
+        Value::Sequence(items) => {
+            // Lists of strings stay strings ("1984" is a name); others are typed per item
+            let strings = items.iter().any(Value::is_string);
+            Value::Sequence(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| {
+                        if strings {
+                            Value::String(s.to_string())
+                        } else {
+                            serde_yaml::from_str(s).unwrap_or_else(|_| Value::String(s.to_string()))
+                        }
+                    })
+                    .collect(),
+            )
+        }
+        _ => Value::String(raw.to_string()),
+    }
+}
+
+/// Build `{a: {b: value}}` from "a.b"
+fn nested(key: &str, value: Value) -> Value {
+    key.rsplit('.').fold(value, |inner, part| {
+        let mut map = Mapping::new();
+        map.insert(Value::String(part.to_string()), inner);
+        Value::Mapping(map)
+    })
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::String(s) => format!("{:?}", s),
+        Value::Sequence(_) | Value::Mapping(_) => serde_json::to_string(value).unwrap_or_default(),
+        other => serde_yaml::to_string(other).unwrap_or_default().trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn env(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_defaults_only() {
+        let layered = LayeredConfig::resolve(&ConfigSources::default()).unwrap();
+        assert_eq!(layered.config.processing.parallel_workers, 2);
+        assert_eq!(layered.origin("processing.parallel_workers"), Some(&ConfigOrigin::Default));
+        assert!(layered.files.is_empty());
+    }
+
+    #[test]
+    fn test_layer_priority() {
+        let dir = tempdir().unwrap();
+        let system = dir.path().join("system.yaml");
+        let user = dir.path().join("user.yaml");
+        std::fs::write(&system, "version: 2\nprocessing:\n  parallel_workers: 3\n  max_retries: 5\n").unwrap();
+        std::fs::write(&user, "version: 2\nprocessing:\n  parallel_workers: 4\n").unwrap();
+        std::fs::write(
+            dir.path().join(PROJECT_CONFIG_FILENAME),
+            "version: 2\nquality:\n  chapter_source: cue\n",
+        )
+        .unwrap();
+
+        let sources = ConfigSources {
+            system_file: Some(system.clone()),
+            user_file: Some(user.clone()),
+            project_dir: Some(dir.path().to_path_buf()),
+            env: env(&[("AUDIOBOOK_FORGE_PROCESSING_PARALLEL_WORKERS", "6"), ("PATH", "/bin")]),
+            cli: vec![(
+                "quality.chapter_source".to_string(),
+                Value::String("files".to_string()),
+                "--chapter-source".to_string(),
+            )],
+        };
+        let layered = LayeredConfig::resolve(&sources).unwrap();
+
+        assert_eq!(layered.config.processing.parallel_workers, 6);
+        assert_eq!(layered.config.processing.max_retries, 5);
+        assert_eq!(layered.config.quality.chapter_source, "files");
+        assert_eq!(layered.origin("processing.max_retries"), Some(&ConfigOrigin::System(system)));
+        assert_eq!(
+            layered.origin("processing.parallel_workers"),
+            Some(&ConfigOrigin::Env("AUDIOBOOK_FORGE_PROCESSING_PARALLEL_WORKERS".to_string()))
+        );
+        assert_eq!(
+            layered.origin("quality.chapter_source"),
+            Some(&ConfigOrigin::Cli("--chapter-source".to_string()))
+        );
+        assert_eq!(layered.files.len(), 3);
+        assert!(layered.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_outdated_file_is_migrated() {
+        let dir = tempdir().unwrap();
+        let user = dir.path().join("user.yaml");
+        std::fs::write(&user, "advanced:\n  use_apple_silicon_encoder: false\n").unwrap();
+
+        let sources = ConfigSources {
+            user_file: Some(user.clone()),
+            ..Default::default()
+        };
+        let layered = LayeredConfig::resolve(&sources).unwrap();
+
+        assert_eq!(layered.config.advanced.aac_encoder, "aac");
+        assert_eq!(layered.origin("advanced.aac_encoder"), Some(&ConfigOrigin::User(user)));
+        assert_eq!(layered.origin("version"), Some(&ConfigOrigin::Default));
+        assert_eq!(layered.warnings.len(), 1);
+        assert!(layered.warnings[0].contains("config migrate"));
+    }
+
+    #[test]
+    fn test_env_values_are_typed() {
+        let sources = ConfigSources {
+            env: env(&[
+                ("AUDIOBOOK_FORGE_METADATA_AUDIBLE_REGION", "uk"),
+                ("AUDIOBOOK_FORGE_METADATA__AUDIBLE__ENABLED", "yes"),
+                ("AUDIOBOOK_FORGE_METADATA_COVER_FILENAMES", "art.jpg, front.png"),
+                ("AUDIOBOOK_FORGE_PERFORMANCE_MAX_CONCURRENT_ENCODES", "4"),
+                ("AUDIOBOOK_FORGE_DIRECTORIES_SOURCE", "/books"),
+                ("AUDIOBOOK_FORGE_QUALITY_SPEEDS", "1.25, 1.5,2"),
+                ("AUDIOBOOK_FORGE_NOT_A_SETTING", "1"),
+            ]),
+            ..Default::default()
+        };
+        let layered = LayeredConfig::resolve(&sources).unwrap();
+        let config = &layered.config;
+
+        assert_eq!(config.metadata.audible.region, "uk");
+        assert!(config.metadata.audible.enabled);
+        assert_eq!(config.metadata.cover_filenames, vec!["art.jpg", "front.png"]);
+        assert_eq!(config.performance.max_concurrent_encodes, "4");
+        assert_eq!(config.directories.source, Some(PathBuf::from("/books")));
+        assert_eq!(config.quality.speeds, vec![1.25, 1.5, 2.0]);
+        assert_eq!(layered.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_env_value_fails() {
+        let sources = ConfigSources {
+            env: env(&[("AUDIOBOOK_FORGE_PROCESSING_PARALLEL_WORKERS", "many")]),
+            ..Default::default()
+        };
+        assert!(LayeredConfig::resolve(&sources).is_err());
+    }
+
+    #[test]
+    fn test_entries_report_origins() {
+        let sources = ConfigSources {
+            env: env(&[("AUDIOBOOK_FORGE_LOGGING_LOG_LEVEL", "DEBUG")]),
+            ..Default::default()
+        };
+        let layered = LayeredConfig::resolve(&sources).unwrap();
+        let entries = layered.entries();
+
+        let (_, value, origin) = entries.iter().find(|(k, _, _)| k == "logging.log_level").unwrap();
+        assert_eq!(value, "\"DEBUG\"");
+        assert!(matches!(origin, ConfigOrigin::Env(_)));
+        assert!(entries.iter().any(|(k, _, o)| k == "processing.journal" && *o == ConfigOrigin::Default));
+        assert!(!entries.iter().any(|(k, _, _)| k == "version"));
+    }
+}
@@ -0,0 +1,204 @@
+//! Config file versioning and migration
+//!
+//! Files without a `version` key are version 1. Each migration upgrades a
+//! file by one version, so an old file is brought up to date step by step
+//! before it's deserialized.
+
+use crate::models::CONFIG_VERSION;
+use anyhow::{bail, Result};
+use serde_yaml::{Mapping, Value};
+
+/// One upgrade step (from `from` to `from + 1`)
+struct Migration {
+    from: u32,
+    apply: fn(&mut Mapping, &mut Vec<String>),
+}
+
+const MIGRATIONS: &[Migration] = &[Migration { from: 1, apply: migrate_v1_to_v2 }];
+
+/// What a migration changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrationReport {
+    /// Version the file was written for
+    pub from: u32,
+    /// Version after migration
+    pub to: u32,
+    /// Human-readable description of each change
+    pub changes: Vec<String>,
+}
+
+impl MigrationReport {
+    /// Whether the file was outdated
+    pub fn is_outdated(&self) -> bool {
+        self.from < self.to
+    }
+}
+
+/// Version a config file was written for (1 if it has no `version` key)
+pub fn config_version(value: &Value) -> Result<u32> {
+    match value.get("version") {
+        None | Some(Value::Null) => Ok(1),
+        Some(version) => match version.as_u64() {
+            Some(v) if v >= 1 && v <= u32::MAX as u64 => Ok(v as u32),
+            _ => bail!("Invalid config version: {}", serde_yaml::to_string(version)?.trim()),
+        },
+    }
+}
+
+/// Upgrade a parsed config file to the current version in place
+pub fn migrate_config(value: &mut Value) -> Result<MigrationReport> {
+    let from = config_version(value)?;
+    if from > CONFIG_VERSION {
+        bail!(
+            "Config file is version {}, but this audiobook-forge only understands up to version {}. Please upgrade audiobook-forge.",
+            from,
+            CONFIG_VERSION
+        );
+    }
+
+    let mut report = MigrationReport { from, to: CONFIG_VERSION, changes: Vec::new() };
+    if from == CONFIG_VERSION {
+        return Ok(report);
+    }
+
+    if value.is_null() {
+        *value = Value::Mapping(Mapping::new());
+    }
+    let Value::Mapping(map) = value else {
+        bail!("Config file is not a mapping");
+    };
+
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= from) {
+        (migration.apply)(map, &mut report.changes);
+    }
+    // Keep the version at the top of the file
+    map.remove("version");
+    let mut versioned = Mapping::new();
+    versioned.insert(Value::from("version"), Value::from(CONFIG_VERSION));
+    versioned.extend(std::mem::take(map));
+    *map = versioned;
+    report.changes.push(format!("set version: {}", CONFIG_VERSION));
+
+    Ok(report)
+}
+
+/// Version 2: `use_apple_silicon_encoder` becomes `aac_encoder`, and numeric
+/// settings that are written as strings ("auto" or a number) are quoted
+fn migrate_v1_to_v2(map: &mut Mapping, changes: &mut Vec<String>) {
+    if let Some(Value::Mapping(advanced)) = map.get_mut("advanced") {
+        if let Some(legacy) = advanced.remove("use_apple_silicon_encoder") {
+            // The legacy flag used to take precedence over aac_encoder
+            match legacy.as_bool() {
+                Some(flag) => {
+                    let encoder = if flag { "aac_at" } else { "aac" };
+                    advanced.insert(Value::from("aac_encoder"), Value::from(encoder));
+                    changes.push(format!(
+                        "advanced.use_apple_silicon_encoder: {} -> advanced.aac_encoder: {}",
+                        flag, encoder
+                    ));
+                }
+                None => changes.push("removed advanced.use_apple_silicon_encoder (unset)".to_string()),
+            }
+        }
+    }
+
+    for (section, key) in [
+        ("performance", "max_concurrent_encodes"),
+        ("performance", "max_concurrent_files_per_book"),
+        ("quality", "default_bitrate"),
+        ("quality", "default_sample_rate"),
+    ] {
+        let Some(Value::Mapping(section_map)) = map.get_mut(section) else {
+            continue;
+        };
+        if let Some(value @ Value::Number(_)) = section_map.get_mut(key) {
+            let text = serde_yaml::to_string(value).unwrap_or_default().trim().to_string();
+            changes.push(format!("{}.{}: {} -> \"{}\"", section, key, text, text));
+            *value = Value::String(text);
+        }
+    }
+}
+
+/// Line diff between two texts (`-` removed, `+` added, ` ` unchanged)
+pub fn line_diff(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence table
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push(format!("  {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            diff.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Config;
+
+    #[test]
+    fn test_migrate_v1() {
+        let mut value: Value = serde_yaml::from_str(
+            "advanced:\n  use_apple_silicon_encoder: true\n  aac_encoder: libfdk_aac\nperformance:\n  max_concurrent_encodes: 4\nquality:\n  default_bitrate: auto\n",
+        )
+        .unwrap();
+
+        let report = migrate_config(&mut value).unwrap();
+        assert!(report.is_outdated());
+        assert_eq!((report.from, report.to), (1, CONFIG_VERSION));
+        assert_eq!(report.changes.len(), 3, "{:?}", report.changes);
+
+        let config: Config = serde_yaml::from_value(value.clone()).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.advanced.aac_encoder, "aac_at");
+        assert_eq!(config.performance.max_concurrent_encodes, "4");
+        assert_eq!(config.quality.default_bitrate, "auto");
+        assert!(value["advanced"].get("use_apple_silicon_encoder").is_none());
+
+        // Already current: nothing to do
+        let report = migrate_config(&mut value).unwrap();
+        assert!(!report.is_outdated());
+        assert!(report.changes.is_empty());
+    }
+
+    #[test]
+    fn test_version_checks() {
+        let mut empty = Value::Null;
+        assert!(migrate_config(&mut empty).unwrap().is_outdated());
+
+        let mut future: Value = serde_yaml::from_str(&format!("version: {}\n", CONFIG_VERSION + 1)).unwrap();
+        assert!(migrate_config(&mut future).is_err());
+
+        let mut invalid: Value = serde_yaml::from_str("version: two\n").unwrap();
+        assert!(migrate_config(&mut invalid).is_err());
+    }
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff("a\nb\nc\n", "a\nc\nd\n");
+        assert_eq!(diff, vec!["  a", "- b", "  c", "+ d"]);
+    }
+}
@@ -0,0 +1,534 @@
+//! JSON Schema export and strict checking of config files
+//!
+//! The field table below mirrors `Config`; a test keeps the two in sync.
+//! `config schema` prints the schema for editor autocompletion, and
+//! `config validate --strict` uses the same table to report unknown keys
+//! with their line numbers and a "did you mean" suggestion.
+
+use crate::models::{AudibleRegion, Config, CONFIG_VERSION};
+use serde_json::{json, Value};
+use std::fmt;
+
+/// Kind of value a setting accepts
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Bool,
+    Integer { min: i64, max: i64 },
+    Number { min: f64, max: f64 },
+    String,
+    /// Filesystem path
+    Path,
+    /// One of a fixed set of strings
+    Enum(&'static [&'static str]),
+    /// One of a fixed set of strings, in any case
+    AnyCaseEnum(&'static [&'static str]),
+    /// "auto" or a positive number, written as a string
+    AutoOrNumber,
+    /// List of strings
+    List,
+    /// List of numbers
+    NumberList { min: f64, max: f64 },
+}
+
+/// One setting in the config file
+struct Field {
+    key: &'static str,
+    kind: Kind,
+    /// Whether `null` is accepted (unset)
+    nullable: bool,
+    description: &'static str,
+}
+
+const fn field(key: &'static str, kind: Kind, description: &'static str) -> Field {
+    Field { key, kind, nullable: false, description }
+}
+
+const fn optional(key: &'static str, kind: Kind, description: &'static str) -> Field {
+    Field { key, kind, nullable: true, description }
+}
+
+const U8: Kind = Kind::Integer { min: 0, max: u8::MAX as i64 };
+const U32: Kind = Kind::Integer { min: 0, max: u32::MAX as i64 };
+const U64: Kind = Kind::Integer { min: 0, max: i64::MAX };
+
+/// Valid `quality.chapter_source` / profile `chapter_source` values
+const CHAPTER_SOURCES: &[&str] = &["auto", "files", "cue", "id3", "none"];
+/// Valid `advanced.aac_encoder` / profile `encoder` values
+const ENCODERS: &[&str] = &["auto", "aac_at", "libfdk_aac", "aac"];
+/// Valid Audible regions
+const AUDIBLE_REGIONS: &[&str] = &{
+    let mut codes = [""; AudibleRegion::ALL.len()];
+    let mut i = 0;
+    while i < codes.len() {
+        codes[i] = AudibleRegion::ALL[i].tld();
+        i += 1;
+    }
+    codes
+};
+/// Valid `logging.log_level` values (matched case-insensitively)
+const LOG_LEVELS: &[&str] = &["TRACE", "DEBUG", "INFO", "WARN", "WARNING", "ERROR"];
+
+const CONFIG_FIELDS: &[Field] = &[
+    field("version", Kind::Integer { min: 1, max: CONFIG_VERSION as i64 }, "Config file format version"),
+    optional("directories.source", Kind::Path, "Source directory for audiobooks (overrides --root)"),
+    field("directories.output", Kind::String, "Output directory (\"same_as_source\" or a custom path)"),
+    field("performance.max_concurrent_encodes", Kind::AutoOrNumber, "Maximum number of files to encode in parallel (\"auto\" = from the encoder benchmark, or all CPU cores)"),
+    field("performance.enable_parallel_encoding", Kind::Bool, "Enable parallel file encoding (faster but more CPU/memory)"),
+    field("performance.encoding_preset", Kind::Enum(&["fast", "balanced", "high"]), "Encoding quality preset"),
+    field("performance.max_concurrent_files_per_book", Kind::AutoOrNumber, "Maximum concurrent file encodings per book (\"auto\" = from the encoder benchmark, or all CPU cores)"),
+    field("performance.calibrate", Kind::Bool, "Benchmark the encoders on the first build if no results are cached"),
+    field("processing.parallel_workers", Kind::Integer { min: 1, max: 8 }, "Number of books processed in parallel (1-8)"),
+    field("processing.skip_existing", Kind::Bool, "Skip folders with existing M4B files"),
+    field("processing.force_reprocess", Kind::Bool, "Always reprocess, overwriting existing files"),
+    field("processing.normalize_existing", Kind::Bool, "Normalize existing M4B files (fix metadata)"),
+    field("processing.keep_temp_files", Kind::Bool, "Keep temporary files for debugging"),
+    field("processing.max_retries", U8, "Maximum number of retry attempts"),
+    field("processing.retry_delay", U64, "Initial retry delay in seconds"),
+    field("processing.journal", Kind::Bool, "Record file moves and deletions so they can be reverted with `undo`"),
+    field("processing.journal_retention_days", U32, "Days journal runs, and the originals they keep, are kept before being pruned (0 = forever)"),
+    field("processing.check_disk_space", Kind::Bool, "Check free temp and output space before the build and before each book"),
+    field("processing.min_free_space", Kind::String, "Space always left free on the temp and output filesystems, e.g. \"500MB\" (\"0\" = none)"),
+    field("processing.track_order", Kind::Enum(&["auto", "filename", "tags", "title", "order_file"]), "How tracks are put in order (\"auto\" = the most consistent signal)"),
+    field("quality.prefer_stereo", Kind::Bool, "Prefer stereo over mono when quality is equal"),
+    field("quality.chapter_source", Kind::Enum(CHAPTER_SOURCES), "Chapter source priority"),
+    field("quality.default_bitrate", Kind::AutoOrNumber, "Default bitrate in kbps (\"auto\" = match source)"),
+    field("quality.default_sample_rate", Kind::AutoOrNumber, "Default sample rate in Hz (\"auto\" = match source)"),
+    optional("quality.target_size", Kind::String, "Fit each book's output in this size, e.g. \"300MB\" (lowers bitrate/sample rate as needed)"),
+    optional("quality.size_per_hour", Kind::String, "Fit each book's output in this size per hour of audio, e.g. \"30MB\""),
+    field("quality.trim_silence", Kind::Bool, "Trim leading and trailing silence from every track"),
+    field("quality.silence_threshold", Kind::Number { min: -90.0, max: -10.0 }, "Level in dBFS below which audio counts as silence"),
+    field("quality.silence_keep", Kind::Number { min: 0.0, max: 10.0 }, "Seconds of silence kept where silence is trimmed"),
+    field("quality.trim_intro", Kind::Number { min: 0.0, max: 3600.0 }, "Seconds cut from the start of each book (e.g. a publisher intro)"),
+    field("quality.trim_outro", Kind::Number { min: 0.0, max: 3600.0 }, "Seconds cut from the end of each book"),
+    field("quality.speeds", Kind::NumberList { min: 0.5, max: 4.0 }, "Also write each output at these playback speeds (e.g. [1.25, 1.5]), from the same decode"),
+    field("metadata.default_language", Kind::String, "Default language for metadata (ISO 639-1)"),
+    field("metadata.cover_filenames", Kind::List, "Cover art filenames to search for"),
+    field("metadata.auto_extract_cover", Kind::Bool, "Extract embedded cover art from audio files as fallback"),
+    field("metadata.match_mode", Kind::Enum(&["disabled", "auto", "interactive"]), "Matching mode for the build command"),
+    field("metadata.audible.enabled", Kind::Bool, "Enable Audible metadata fetching"),
+    field("metadata.audible.region", Kind::Enum(AUDIBLE_REGIONS), "Default Audible region for queries"),
+    field("metadata.audible.auto_match", Kind::Bool, "Auto-match books by folder name during build"),
+    field("metadata.audible.download_covers", Kind::Bool, "Download and embed cover art from Audible"),
+    field("metadata.audible.fetch_chapters", Kind::Bool, "Fetch chapter data from the Audnex API during build"),
+    field("metadata.audible.offline", Kind::Bool, "Serve Audible data from the local cache only"),
+    field("metadata.audible.cache_duration_hours", U64, "Cache metadata locally (hours, 0 = no cache)"),
+    field("metadata.audible.search_cache_hours", U64, "Cache search results (hours, 0 = no cache)"),
+    field("metadata.audible.chapter_cache_hours", U64, "Cache Audnex chapter lists (hours, 0 = no cache)"),
+    field("metadata.audible.cover_cache_hours", U64, "Cache downloaded cover images (hours, 0 = no cache)"),
+    field("metadata.audible.rate_limit_per_minute", U32, "Rate limit (requests per minute)"),
+    field("metadata.audible.api_max_retries", U8, "Maximum retry attempts for API failures (0 = no retry)"),
+    field("metadata.audible.api_retry_delay_secs", U64, "Initial retry delay in seconds"),
+    field("metadata.audible.api_max_retry_delay_secs", U64, "Maximum retry delay in seconds"),
+    field("organization.m4b_folder", Kind::String, "Name for completed audiobooks folder"),
+    field("organization.convert_folder", Kind::String, "Name for conversion queue folder"),
+    optional("organization.path_template", Kind::String, "Path template for built files and organized layout (e.g. \"{author}/{title}/{title}.m4b\")"),
+    field("logging.log_to_file", Kind::Bool, "Enable automatic log file creation"),
+    optional("logging.log_file", Kind::Path, "Custom log file path"),
+    field("logging.log_level", Kind::AnyCaseEnum(LOG_LEVELS), "Log level"),
+    field("logging.log_format", Kind::Enum(&["text", "json"]), "Format of console and file logs"),
+    field("logging.book_logs", Kind::Enum(&["off", "output", "run_dir"]), "Write a log file per book (\"output\" = next to the output file, \"run_dir\" = one directory per run)"),
+    optional("logging.book_log_dir", Kind::Path, "Directory for per-run book log folders (default: ~/.audiobook-forge/logs/runs)"),
+    optional("advanced.ffmpeg_path", Kind::Path, "Custom FFmpeg binary path"),
+    optional("advanced.ffprobe_path", Kind::Path, "Custom FFprobe binary path (default: next to ffmpeg_path, then PATH)"),
+    optional("advanced.atomic_parsley_path", Kind::Path, "Custom AtomicParsley binary path"),
+    optional("advanced.mp4box_path", Kind::Path, "Custom MP4Box binary path"),
+    optional("advanced.temp_directory", Kind::Path, "Custom temporary files location"),
+    field("advanced.aac_encoder", Kind::Enum(ENCODERS), "AAC encoder preference"),
+];
+
+const PROFILE_FIELDS: &[Field] = &[
+    field("preset", Kind::Enum(&crate::models::QUALITY_PRESETS), "Built-in quality preset to start from"),
+    field("encoder", Kind::Enum(ENCODERS), "AAC encoder"),
+    field("bitrate", Kind::Integer { min: 1, max: u32::MAX as i64 }, "Bitrate in kbps"),
+    field("aac_profile", Kind::Enum(&["lc", "he", "he_v2"]), "AAC object type (HE-AAC needs libfdk_aac or aac_at)"),
+    field("vbr", Kind::Integer { min: 1, max: 5 }, "VBR quality level instead of a constant bitrate (libfdk_aac)"),
+    field("sample_rate", Kind::Integer { min: 1, max: u32::MAX as i64 }, "Sample rate in Hz"),
+    field("channels", Kind::Integer { min: 1, max: 2 }, "Channels (1 = mono, 2 = stereo)"),
+    field("chapter_source", Kind::Enum(CHAPTER_SOURCES), "Chapter source"),
+    field("loudness", Kind::Number { min: -70.0, max: -5.0 }, "Target integrated loudness in LUFS"),
+    field("tempo", Kind::Number { min: 0.5, max: 4.0 }, "Playback speed, pitch preserved (e.g. 1.25)"),
+    field("format", Kind::Enum(&crate::models::PROFILE_FORMATS), "Output container"),
+    field("template", Kind::String, "Output path template"),
+];
+
+/// JSON Schema (draft-07) describing the config file
+pub fn config_schema() -> Value {
+    let defaults = serde_json::to_value(Config::default()).unwrap_or(Value::Null);
+
+    let mut root = object_schema();
+    for field in CONFIG_FIELDS {
+        let mut schema = field_schema(field);
+        if let Some(default) = defaults.pointer(&format!("/{}", field.key.replace('.', "/"))) {
+            schema["default"] = default.clone();
+        }
+        insert_path(&mut root, field.key, schema);
+    }
+
+    let mut profile = object_schema();
+    for field in PROFILE_FIELDS {
+        insert_path(&mut profile, field.key, field_schema(field));
+    }
+    root["properties"]["profiles"] = json!({
+        "type": "object",
+        "description": "Named output profiles selectable with `build --profile`",
+        "additionalProperties": profile,
+    });
+
+    root["$schema"] = json!("http://json-schema.org/draft-07/schema#");
+    root["title"] = json!("audiobook-forge configuration");
+    root
+}
+
+fn object_schema() -> Value {
+    json!({ "type": "object", "additionalProperties": false, "properties": {} })
+}
+
+fn field_schema(field: &Field) -> Value {
+    let mut schema = match field.kind {
+        Kind::Bool => json!({ "type": "boolean" }),
+        Kind::Integer { min, max } => json!({ "type": "integer", "minimum": min, "maximum": max }),
+        Kind::Number { min, max } => json!({ "type": "number", "minimum": min, "maximum": max }),
+        Kind::String | Kind::Path => json!({ "type": "string" }),
+        Kind::Enum(values) => json!({ "type": "string", "enum": values }),
+        Kind::AnyCaseEnum(values) => json!({ "type": "string", "pattern": any_case_pattern(values) }),
+        Kind::AutoOrNumber => json!({ "type": "string", "pattern": "^(auto|[0-9]+)$" }),
+        Kind::List => json!({ "type": "array", "items": { "type": "string" } }),
+        Kind::NumberList { min, max } => {
+            json!({ "type": "array", "items": { "type": "number", "minimum": min, "maximum": max } })
+        }
+    };
+    if field.nullable {
+        let kind = schema["type"].clone();
+        schema["type"] = json!([kind, "null"]);
+        if let Some(values) = schema.get_mut("enum").and_then(Value::as_array_mut) {
+            values.push(Value::Null);
+        }
+    }
+    schema["description"] = json!(field.description);
+    schema
+}
+
+/// Pattern matching any of `values` regardless of case
+///
+/// JSON Schema patterns have no case-insensitive flag, so each letter becomes
+/// a `[xX]` class.
+fn any_case_pattern(values: &[&str]) -> String {
+    let alternatives: Vec<String> = values
+        .iter()
+        .map(|value| {
+            value
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphabetic() {
+                        format!("[{}{}]", c.to_ascii_lowercase(), c.to_ascii_uppercase())
+                    } else {
+                        regex::escape(&c.to_string())
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    format!("^({})$", alternatives.join("|"))
+}
+
+/// Insert a schema at a dotted key, creating intermediate object schemas
+fn insert_path(root: &mut Value, key: &str, schema: Value) {
+    let mut node = root;
+    let mut parts = key.split('.').peekable();
+    while let Some(part) = parts.next() {
+        let properties = node["properties"].as_object_mut().expect("object schema");
+        if parts.peek().is_none() {
+            properties.insert(part.to_string(), schema);
+            return;
+        }
+        node = properties.entry(part.to_string()).or_insert_with(object_schema);
+    }
+}
+
+/// A key in a config file that isn't a setting
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownKey {
+    /// Dotted key as written (e.g. "processing.paralel_workers")
+    pub key: String,
+    /// 1-based line number, if it could be located
+    pub line: Option<usize>,
+    /// Closest valid key
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        write!(f, "unknown key '{}'", self.key)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (did you mean '{}'?)", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Report every key in a YAML config file that `Config` doesn't know
+pub fn find_unknown_keys(contents: &str) -> anyhow::Result<Vec<UnknownKey>> {
+    let value: serde_yaml::Value = serde_yaml::from_str(contents)?;
+    let lines = key_lines(contents);
+
+    let mut keys = Vec::new();
+    collect_keys("", &value, &mut keys);
+
+    let mut unknown = Vec::new();
+    for key in keys {
+        let Some((parent, name, known)) = check_key(&key) else {
+            continue;
+        };
+        let suggestion = suggest(name, &known).map(|s| join(&parent, &s)).or_else(|| {
+            // Right name, wrong section (e.g. processing.chapter_source)
+            CONFIG_FIELDS
+                .iter()
+                .find(|f| f.key.rsplit('.').next() == Some(name))
+                .map(|f| f.key.to_string())
+        });
+        unknown.push(UnknownKey {
+            line: lines.iter().find(|(k, _)| *k == key).map(|(_, line)| *line),
+            key,
+            suggestion,
+        });
+    }
+
+    Ok(unknown)
+}
+
+/// Every mapping key in a YAML tree, as dotted paths (parents before children)
+fn collect_keys(prefix: &str, value: &serde_yaml::Value, out: &mut Vec<String>) {
+    let serde_yaml::Value::Mapping(map) = value else {
+        return;
+    };
+    for (key, child) in map {
+        let key = match key {
+            serde_yaml::Value::String(s) => s.clone(),
+            other => serde_yaml::to_string(other).unwrap_or_default().trim().to_string(),
+        };
+        let path = join(prefix, &key);
+        out.push(path.clone());
+        // Stop at unknown keys so their children aren't reported too
+        if check_key(&path).is_none() && !is_leaf(&path) {
+            collect_keys(&path, child, out);
+        }
+    }
+}
+
+/// `None` if the key is valid, else (parent, name, valid sibling names)
+fn check_key(key: &str) -> Option<(String, &str, Vec<String>)> {
+    let (parent, name) = match key.rsplit_once('.') {
+        Some((parent, name)) => (parent.to_string(), name),
+        None => (String::new(), key),
+    };
+
+    // profiles.<name>.<field>
+    let segments: Vec<&str> = key.split('.').collect();
+    if segments.first() == Some(&"profiles") {
+        return match segments.len() {
+            1 | 2 => None,
+            3 if PROFILE_FIELDS.iter().any(|f| f.key == name) => None,
+            _ => Some((parent, name, PROFILE_FIELDS.iter().map(|f| f.key.to_string()).collect())),
+        };
+    }
+
+    let mut known: Vec<String> = CONFIG_FIELDS
+        .iter()
+        .filter_map(|f| {
+            let rest = if parent.is_empty() {
+                Some(f.key)
+            } else {
+                f.key.strip_prefix(parent.as_str()).and_then(|r| r.strip_prefix('.'))
+            };
+            rest.map(|r| r.split('.').next().unwrap_or(r).to_string())
+        })
+        .collect();
+    if parent.is_empty() {
+        known.push("profiles".to_string());
+    }
+    known.sort();
+    known.dedup();
+
+    if known.iter().any(|k| k == name) {
+        None
+    } else {
+        Some((parent, name, known))
+    }
+}
+
+fn is_leaf(key: &str) -> bool {
+    CONFIG_FIELDS.iter().any(|f| f.key == key)
+}
+
+/// Closest candidate to a misspelled key, if any is close enough
+fn suggest(name: &str, candidates: &[String]) -> Option<String> {
+    candidates
+        .iter()
+        .map(|c| (strsim::jaro_winkler(name, c), c))
+        .filter(|(score, _)| *score >= 0.8)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, c)| c.clone())
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// Line numbers of block-style mapping keys, as (dotted key, 1-based line)
+fn key_lines(contents: &str) -> Vec<(String, usize)> {
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut lines = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('-') {
+            continue;
+        }
+        let Some((key, _)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().trim_matches(|c| c == '"' || c == '\'');
+        if key.is_empty() || key.contains(' ') {
+            continue;
+        }
+
+        let indent = line.len() - trimmed.len();
+        while stack.last().is_some_and(|(i, _)| *i >= indent) {
+            stack.pop();
+        }
+        let path = join(stack.last().map(|(_, p)| p.as_str()).unwrap_or(""), key);
+        lines.push((path.clone(), index + 1));
+        stack.push((indent, path));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProfileConfig;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_field_table_matches_config() {
+        let defaults = serde_json::to_value(Config::default()).unwrap();
+        let mut leaves = Vec::new();
+        fn walk(prefix: &str, value: &Value, out: &mut Vec<String>) {
+            match value.as_object() {
+                Some(map) => map.iter().for_each(|(k, v)| walk(&join(prefix, k), v, out)),
+                None => out.push(prefix.to_string()),
+            }
+        }
+        walk("", &defaults, &mut leaves);
+
+        for leaf in &leaves {
+            assert!(is_leaf(leaf), "{} is missing from CONFIG_FIELDS", leaf);
+        }
+        for field in CONFIG_FIELDS {
+            assert!(
+                leaves.contains(&field.key.to_string()) || field.nullable,
+                "{} is not a Config setting",
+                field.key
+            );
+        }
+
+        let profile = ProfileConfig {
+            preset: Some(String::new()),
+            encoder: Some(String::new()),
+            bitrate: Some(1),
+            aac_profile: Some(crate::models::AacProfile::Lc),
+            vbr: Some(1),
+            sample_rate: Some(1),
+            channels: Some(1),
+            chapter_source: Some(String::new()),
+            loudness: Some(0.0),
+            tempo: Some(1.0),
+            format: Some(String::new()),
+            template: Some(String::new()),
+        };
+        let profile = serde_json::to_value(profile).unwrap();
+        assert_eq!(profile.as_object().unwrap().len(), PROFILE_FIELDS.len());
+        for field in PROFILE_FIELDS {
+            assert!(profile.get(field.key).is_some(), "{} is not a profile setting", field.key);
+        }
+    }
+
+    #[test]
+    fn test_schema_shape() {
+        let schema = config_schema();
+        let workers = &schema["properties"]["processing"]["properties"]["parallel_workers"];
+        assert_eq!(workers["type"], "integer");
+        assert_eq!(workers["default"], 2);
+        assert_eq!(workers["maximum"], 8);
+        assert_eq!(schema["properties"]["processing"]["additionalProperties"], false);
+
+        let source = &schema["properties"]["directories"]["properties"]["source"];
+        assert_eq!(source["type"], json!(["string", "null"]));
+
+        let region = &schema["properties"]["metadata"]["properties"]["audible"]["properties"]["region"];
+        let regions = region["enum"].as_array().unwrap();
+        assert_eq!(regions.len(), AudibleRegion::ALL.len());
+        for code in regions {
+            assert!(AudibleRegion::from_str(code.as_str().unwrap()).is_ok(), "{code} is not a region");
+        }
+
+        // Log levels are read in any case
+        let level = &schema["properties"]["logging"]["properties"]["log_level"];
+        let pattern = regex::Regex::new(level["pattern"].as_str().unwrap()).unwrap();
+        for value in ["INFO", "info", "Warning", "trace"] {
+            assert!(pattern.is_match(value), "{value}");
+        }
+        assert!(!pattern.is_match("verbose"));
+        assert!(!pattern.is_match("INFO2"));
+
+        let profile = &schema["properties"]["profiles"]["additionalProperties"];
+        assert_eq!(profile["properties"]["channels"]["maximum"], 2);
+    }
+
+    #[test]
+    fn test_unknown_keys_with_lines_and_suggestions() {
+        let yaml = "\
+processing:
+  paralel_workers: 4
+  chapter_source: cue
+quality:
+  prefer_stereo: true
+metdata:
+  audible:
+    region: uk
+profiles:
+  car:
+    bitrat: 64
+";
+        let unknown = find_unknown_keys(yaml).unwrap();
+        let summary: Vec<(String, Option<usize>, Option<String>)> = unknown
+            .into_iter()
+            .map(|u| (u.key, u.line, u.suggestion))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("processing.paralel_workers".to_string(), Some(2), Some("processing.parallel_workers".to_string())),
+                ("processing.chapter_source".to_string(), Some(3), Some("quality.chapter_source".to_string())),
+                ("metdata".to_string(), Some(6), Some("metadata".to_string())),
+                ("profiles.car.bitrat".to_string(), Some(11), Some("profiles.car.bitrate".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_valid_file_has_no_unknown_keys() {
+        let yaml = serde_yaml::to_string(&Config::default()).unwrap();
+        assert!(find_unknown_keys(&yaml).unwrap().is_empty());
+        assert!(find_unknown_keys("").unwrap().is_empty());
+    }
+}
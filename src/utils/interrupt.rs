@@ -0,0 +1,73 @@
+//! Ctrl-C handling
+//!
+//! While a command that cleans up after itself is running (see
+//! [`cleanup_scope`]), the first Ctrl-C only marks the run as interrupted:
+//! work in progress is cancelled, its tools are killed and its temp files are
+//! removed before the command exits. A second Ctrl-C, or one outside such a
+//! command, exits right away.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use tokio::sync::Notify;
+
+/// Exit code for a run stopped by Ctrl-C (128 + SIGINT)
+pub const INTERRUPTED_EXIT_CODE: u8 = 130;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static SCOPES: AtomicUsize = AtomicUsize::new(0);
+static NOTIFY: Notify = Notify::const_new();
+
+/// Listen for Ctrl-C for the rest of the process
+///
+/// Must be called from within a Tokio runtime.
+pub fn install_handler() {
+    tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if SCOPES.load(Ordering::SeqCst) == 0 || INTERRUPTED.load(Ordering::SeqCst) {
+                std::process::exit(INTERRUPTED_EXIT_CODE.into());
+            }
+            eprintln!("\nInterrupted: stopping and cleaning up (press Ctrl-C again to quit now)");
+            interrupt();
+        }
+    });
+}
+
+/// Mark the run as interrupted and wake everything waiting for it
+pub fn interrupt() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    NOTIFY.notify_waiters();
+}
+
+/// Whether Ctrl-C was pressed
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Wait until Ctrl-C is pressed
+pub async fn interrupted() {
+    loop {
+        let notified = NOTIFY.notified();
+        tokio::pin!(notified);
+        // Register before checking, so an interrupt in between isn't missed
+        notified.as_mut().enable();
+        if is_interrupted() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+/// Handle Ctrl-C gracefully until the returned guard is dropped
+pub fn cleanup_scope() -> CleanupScope {
+    SCOPES.fetch_add(1, Ordering::SeqCst);
+    CleanupScope(())
+}
+
+/// Ctrl-C is handled gracefully while this is alive
+#[derive(Debug)]
+pub struct CleanupScope(());
+
+impl Drop for CleanupScope {
+    fn drop(&mut self) {
+        SCOPES.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,274 @@
+//! Per-book log files
+//!
+//! Each book is processed inside a `book` span (name, case) with nested
+//! `stage` spans, and every external tool runs inside a `tool` span. A book
+//! span given a `log_file` gets its own file: [`BookLogLayer`] writes every
+//! event under that span there, so books processed in parallel don't
+//! interleave.
+
+use crate::models::{BookFolder, BookLogMode, LoggingConfig};
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Span, Subscriber};
+use tracing_subscriber::layer::{Context as LayerContext, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Span field naming the file a book's events go to
+const LOG_FILE_FIELD: &str = "log_file";
+
+/// Where per-book log files are written
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BookLogs {
+    /// No per-book logs
+    #[default]
+    Off,
+    /// `<output>.log` next to each book's output file
+    NextToOutput,
+    /// `<book name>.log` in this run's directory
+    RunDir(PathBuf),
+}
+
+impl BookLogs {
+    /// Per-book log setting from the config; `run_dir` gets a fresh directory per run
+    pub fn from_config(config: &LoggingConfig) -> Result<Self> {
+        Ok(match config.book_logs {
+            BookLogMode::Off => Self::Off,
+            BookLogMode::Output => Self::NextToOutput,
+            BookLogMode::RunDir => {
+                let root = match &config.book_log_dir {
+                    Some(dir) => dir.clone(),
+                    None => dirs::home_dir()
+                        .context("Cannot determine home directory")?
+                        .join(".audiobook-forge")
+                        .join("logs")
+                        .join("runs"),
+                };
+                Self::RunDir(root.join(chrono::Local::now().format("%Y%m%d-%H%M%S").to_string()))
+            }
+        })
+    }
+
+    /// Log file for a book written to `output_dir`
+    pub fn path_for(&self, book: &BookFolder, output_dir: &Path) -> Option<PathBuf> {
+        match self {
+            Self::Off => None,
+            Self::NextToOutput => Some(book.get_output_path(output_dir).with_extension("log")),
+            Self::RunDir(dir) => Some(dir.join(format!("{}.log", sanitize_filename::sanitize(&book.name)))),
+        }
+    }
+}
+
+/// Span for processing one book, logging to `log_file` if given
+pub fn book_span(book: &BookFolder, log_file: Option<&Path>) -> Span {
+    let span = tracing::info_span!(
+        "book",
+        name = %book.name,
+        case = book.case.as_str(),
+        log_file = tracing::field::Empty
+    );
+    if let Some(path) = log_file {
+        span.record(LOG_FILE_FIELD, tracing::field::display(path.display()));
+    }
+    span
+}
+
+/// Span for one stage of processing a book ("encode", "chapters", ...)
+pub fn stage_span(stage: &'static str) -> Span {
+    tracing::info_span!("stage", stage)
+}
+
+/// Layer writing events to the log file of their `book` span
+pub struct BookLogLayer;
+
+/// Open log file, stored on the span that named it
+struct BookLog(Mutex<File>);
+
+/// A span's fields as text
+struct SpanFields(String);
+
+impl BookLog {
+    fn open(path: &Path) -> Option<Self> {
+        let opened = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| OpenOptions::new().create(true).append(true).open(path));
+        match opened {
+            Ok(file) => Some(Self(Mutex::new(file))),
+            Err(e) => {
+                // Logging from inside the subscriber would recurse
+                eprintln!("Cannot open book log {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+}
+
+impl<S> Layer<S> for BookLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut fields = FieldText::default();
+        attrs.record(&mut fields);
+
+        let mut extensions = span.extensions_mut();
+        if let Some(log) = fields.log_file.as_deref().and_then(BookLog::open) {
+            extensions.insert(log);
+        }
+        extensions.insert(SpanFields(fields.text));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut fields = FieldText::default();
+        values.record(&mut fields);
+
+        let mut extensions = span.extensions_mut();
+        if extensions.get_mut::<BookLog>().is_none() {
+            if let Some(log) = fields.log_file.as_deref().and_then(BookLog::open) {
+                extensions.insert(log);
+            }
+        }
+        match extensions.get_mut::<SpanFields>() {
+            Some(SpanFields(text)) => push_field_text(text, &fields.text),
+            None => extensions.insert(SpanFields(fields.text)),
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        let Some(scope) = ctx.event_scope(event) else { return };
+
+        // Spans from the event up to the nearest one with a log file
+        let mut spans = Vec::new();
+        for span in scope {
+            let extensions = span.extensions();
+            let fields = extensions.get::<SpanFields>().map_or("", |f| f.0.as_str());
+            spans.push(format!("{}{{{}}}", span.name(), fields));
+
+            if let Some(BookLog(file)) = extensions.get::<BookLog>() {
+                let mut message = FieldText::default();
+                event.record(&mut message);
+                spans.reverse();
+
+                let line = format!(
+                    "{} {:>5} {}: {}\n",
+                    chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%.3f"),
+                    event.metadata().level(),
+                    spans.join(":"),
+                    message.line()
+                );
+                if let Ok(mut file) = file.lock() {
+                    let _ = file.write_all(line.as_bytes());
+                }
+                return;
+            }
+        }
+    }
+}
+
+/// Collects fields as `key=value` text, apart from the message and log file
+#[derive(Default)]
+struct FieldText {
+    message: String,
+    text: String,
+    log_file: Option<PathBuf>,
+}
+
+impl FieldText {
+    /// Message followed by the other fields
+    fn line(&self) -> String {
+        let mut line = self.message.clone();
+        push_field_text(&mut line, &self.text);
+        line
+    }
+}
+
+impl Visit for FieldText {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{}", value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{:?}", value),
+            LOG_FILE_FIELD => self.log_file = Some(PathBuf::from(format!("{:?}", value))),
+            name => push_field_text(&mut self.text, &format!("{}={:?}", name, value)),
+        }
+    }
+}
+
+fn push_field_text(text: &mut String, more: &str) {
+    if !more.is_empty() {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(more);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BookCase;
+    use tempfile::tempdir;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn book(name: &str) -> BookFolder {
+        let mut book = BookFolder::new(PathBuf::from(format!("/library/{}", name)));
+        book.case = BookCase::A;
+        book
+    }
+
+    #[test]
+    fn test_events_go_to_their_book_log() {
+        let dir = tempdir().unwrap();
+        let (first, second) = (dir.path().join("first.log"), dir.path().join("second.log"));
+        let subscriber = tracing_subscriber::registry().with(BookLogLayer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("outside any book");
+            book_span(&book("First"), Some(&first)).in_scope(|| {
+                tracing::info!("first starts");
+                stage_span("encode").in_scope(|| {
+                    let _tool = tracing::info_span!("tool", tool = "FFmpeg").entered();
+                    tracing::debug!(command = "ffmpeg -i 'a b.mp3' out.m4a", "Running FFmpeg");
+                });
+            });
+            book_span(&book("Second"), Some(&second)).in_scope(|| tracing::warn!("second warns"));
+            book_span(&book("Third"), None).in_scope(|| tracing::info!("no log file"));
+        });
+
+        let first = std::fs::read_to_string(first).unwrap();
+        let lines: Vec<&str> = first.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains(" INFO book{name=First case=A}: first starts"));
+        assert!(lines[1].contains(
+            "DEBUG book{name=First case=A}:stage{stage=encode}:tool{tool=FFmpeg}: Running FFmpeg command=ffmpeg -i 'a b.mp3' out.m4a"
+        ));
+
+        let second = std::fs::read_to_string(second).unwrap();
+        assert!(second.contains(" WARN book{name=Second case=A}: second warns"));
+        assert!(!second.contains("first") && !second.contains("outside"));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_log_paths() {
+        let book = book("Dune: Part 1");
+        let output_dir = Path::new("/out");
+        assert_eq!(BookLogs::Off.path_for(&book, output_dir), None);
+        assert_eq!(
+            BookLogs::NextToOutput.path_for(&book, output_dir),
+            Some(book.get_output_path(output_dir).with_extension("log"))
+        );
+        let run = BookLogs::RunDir(PathBuf::from("/logs/run"));
+        assert_eq!(run.path_for(&book, output_dir), Some(PathBuf::from("/logs/run/Dune Part 1.log")));
+    }
+}
@@ -1,17 +1,29 @@
 //! Utility modules
 
 mod config;
+mod config_layers;
+mod config_schema;
+mod config_migration;
 mod validation;
 mod sorting;
 mod merge_patterns;
 pub mod cache;
 pub mod scoring;
 pub mod extraction;
+pub mod logging;
+pub mod template;
+pub mod interrupt;
 
 pub use config::ConfigManager;
+pub(crate) use config_layers::merge_yaml;
+pub use config_migration::{config_version, line_diff, migrate_config, MigrationReport};
+pub use config_schema::{config_schema, find_unknown_keys, UnknownKey};
+pub use config_layers::{system_config_path, ConfigOrigin, ConfigSources, LayeredConfig, ENV_PREFIX, PROJECT_CONFIG_FILENAME};
 pub use validation::DependencyChecker;
 pub use sorting::natural_sort;
-pub use cache::{AudibleCache, CacheStats};
+pub use cache::{AudibleCache, CacheKind, CacheStats, CacheTtls, KindStats, CACHE_BUNDLE_VERSION};
+pub use logging::{book_span, stage_span, BookLogLayer, BookLogs};
+pub use template::{PathTemplate, TemplateValues, TEMPLATE_FIELDS};
 pub use merge_patterns::{detect_merge_pattern, sort_by_part_number, MergePatternResult, MergePatternType};
 
 // Re-export Config for convenience
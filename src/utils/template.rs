@@ -0,0 +1,479 @@
+//! Path templates for output filenames and library layout
+//!
+//! A template such as
+//! `{author}/{series}/{series_index:02} - {title} ({year})/{title}.m4b`
+//! is split on `/` into path components. Each `{field}` is replaced with a
+//! sanitized value; `{field:0N}` zero-pads numbers and `{field|text}` supplies
+//! a fallback for missing values. Components that end up empty are dropped and
+//! dangling separators such as ` - ` or `()` left by missing fields are cleaned up.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::models::BookFolder;
+
+/// Fields available in path templates
+pub const TEMPLATE_FIELDS: &[&str] = &[
+    "title",
+    "subtitle",
+    "author",
+    "narrator",
+    "series",
+    "series_index",
+    "year",
+    "genre",
+    "publisher",
+    "language",
+    "asin",
+    "folder",
+];
+
+/// Author used when neither Audible nor the tags provide one
+const UNKNOWN_AUTHOR: &str = "Unknown Author";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    Field {
+        name: String,
+        width: Option<usize>,
+        fallback: Option<String>,
+    },
+}
+
+/// Parsed path template
+#[derive(Debug, Clone)]
+pub struct PathTemplate {
+    source: String,
+    components: Vec<Vec<Token>>,
+}
+
+impl PathTemplate {
+    /// Parse a template string
+    pub fn parse(template: &str) -> Result<Self> {
+        let template = template.trim();
+        if template.is_empty() {
+            bail!("Path template is empty");
+        }
+        if template.starts_with('/') {
+            bail!("Path template must be relative: {}", template);
+        }
+
+        let components = template
+            .split('/')
+            .filter(|c| !c.is_empty())
+            .map(parse_component)
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("Invalid path template: {}", template))?;
+
+        Ok(Self {
+            source: template.to_string(),
+            components,
+        })
+    }
+
+    /// Original template string
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Render the template into a relative path ending in `.m4b`
+    pub fn render(&self, values: &TemplateValues) -> Result<PathBuf> {
+        let mut path = PathBuf::new();
+        let last = self.components.len() - 1;
+
+        for (idx, tokens) in self.components.iter().enumerate() {
+            let mut rendered = String::new();
+            for token in tokens {
+                match token {
+                    Token::Literal(text) => rendered.push_str(text),
+                    Token::Field { name, width, fallback } => {
+                        let value = values
+                            .get(name)
+                            .map(|v| pad(v, *width))
+                            .or_else(|| fallback.clone())
+                            .unwrap_or_default();
+                        rendered.push_str(&sanitize_filename::sanitize(value));
+                    }
+                }
+            }
+
+            let mut component = clean_component(&sanitize_filename::sanitize(rendered));
+
+            if idx == last {
+                let stem = component
+                    .strip_suffix(".m4b")
+                    .map(clean_component)
+                    .unwrap_or(component);
+                if stem.is_empty() {
+                    bail!("Path template '{}' rendered an empty filename", self.source);
+                }
+                component = format!("{}.m4b", stem);
+            }
+
+            if !component.is_empty() {
+                path.push(component);
+            }
+        }
+
+        Ok(path)
+    }
+}
+
+/// Values substituted into a path template
+#[derive(Debug, Clone, Default)]
+pub struct TemplateValues {
+    values: HashMap<String, String>,
+}
+
+impl TemplateValues {
+    /// Create an empty value set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a field (blank values are ignored)
+    pub fn set(&mut self, field: &str, value: impl Into<String>) {
+        let value = value.into();
+        let value = value.trim();
+        if !value.is_empty() {
+            self.values.insert(field.to_string(), value.to_string());
+        }
+    }
+
+    /// Set a field only if it has no value yet
+    pub fn set_if_missing(&mut self, field: &str, value: Option<impl Into<String>>) {
+        if !self.values.contains_key(field) {
+            if let Some(value) = value {
+                self.set(field, value);
+            }
+        }
+    }
+
+    /// Get a field value
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.values.get(field).map(|s| s.as_str())
+    }
+
+    /// Collect values for a book: Audible metadata first, then track tags,
+    /// then the folder name
+    pub fn from_book(book: &BookFolder) -> Self {
+        let mut values = Self::new();
+
+        if let Some(audible) = &book.audible_metadata {
+            values.set("title", audible.title.clone());
+            values.set_if_missing("subtitle", audible.subtitle.clone());
+            values.set_if_missing("author", audible.authors.first().map(|a| a.name.clone()));
+            values.set_if_missing("narrator", audible.narrators.first().cloned());
+            if let Some(series) = audible.series.first() {
+                values.set("series", series.name.clone());
+                values.set_if_missing(
+                    "series_index",
+                    series.sequence.as_deref().map(crate::audio::clean_sequence),
+                );
+            }
+            values.set_if_missing("year", audible.published_year.map(|y| y.to_string()));
+            values.set_if_missing("genre", audible.genres.first().cloned());
+            values.set_if_missing("publisher", audible.publisher.clone());
+            values.set_if_missing("language", audible.language.clone());
+            values.set("asin", audible.asin.clone());
+        }
+
+        // Values pinned in the folder's override file win over everything else
+        if let Some(tags) = book.tag_overrides() {
+            let pinned = [
+                ("title", tags.title.clone()),
+                ("author", tags.author.clone()),
+                ("narrator", tags.narrator.clone()),
+                ("series", tags.series.clone()),
+                ("series_index", tags.series_index.clone()),
+                ("year", tags.year.map(|y| y.to_string())),
+                ("genre", tags.genre.clone()),
+            ];
+            for (field, value) in pinned {
+                if let Some(value) = value {
+                    values.set(field, value);
+                }
+            }
+        }
+
+        values.set_if_missing("title", book.get_album_title());
+        values.set_if_missing("author", book.get_album_artist());
+        values.set_if_missing("narrator", book.get_composer());
+        values.set_if_missing("year", book.get_year().map(|y| y.to_string()));
+        values.set_if_missing("genre", book.get_genre());
+        values.set_if_missing("asin", book.detected_asin.clone());
+
+        values.set("folder", book.name.clone());
+        values.set_if_missing("title", Some(book.name.clone()));
+        values.set_if_missing("author", Some(UNKNOWN_AUTHOR));
+
+        values
+    }
+
+    /// Fill missing fields from an M4B file's embedded tags
+    pub fn merge_m4b_tags(&mut self, path: &Path) {
+        let tag = match mp4ameta::Tag::read_from_path(path) {
+            Ok(tag) => tag,
+            Err(e) => {
+                tracing::debug!("Could not read tags from {}: {}", path.display(), e);
+                return;
+            }
+        };
+
+        // Tags beat the folder-name fallbacks, but not Audible or track metadata
+        if self.get("title") == self.get("folder") {
+            self.values.remove("title");
+        }
+        if self.get("author") == Some(UNKNOWN_AUTHOR) {
+            self.values.remove("author");
+        }
+
+        self.set_if_missing("title", tag.album().or(tag.title()));
+        self.set_if_missing("author", tag.album_artist().or(tag.artist()));
+        self.set_if_missing("narrator", tag.composer());
+        self.set_if_missing("year", tag.year());
+        self.set_if_missing("genre", tag.genre());
+
+        let folder = self.get("folder").map(|s| s.to_string());
+        self.set_if_missing("title", folder);
+        self.set_if_missing("author", Some(UNKNOWN_AUTHOR));
+    }
+}
+
+/// Append `_2`, `_3`, ... to the file stem until `is_taken` returns false
+pub fn resolve_collision(path: &Path, is_taken: impl Fn(&Path) -> bool) -> Result<PathBuf> {
+    if !is_taken(path) {
+        return Ok(path.to_path_buf());
+    }
+
+    let parent = path.parent().unwrap_or_else(|| Path::new(""));
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .context("Invalid filename")?;
+    let ext = path.extension().and_then(|s| s.to_str());
+
+    for i in 2..=999 {
+        let name = match ext {
+            Some(ext) => format!("{}_{}.{}", stem, i, ext),
+            None => format!("{}_{}", stem, i),
+        };
+        let candidate = parent.join(name);
+        if !is_taken(&candidate) {
+            tracing::warn!("Naming conflict: {} -> {}", path.display(), candidate.display());
+            return Ok(candidate);
+        }
+    }
+
+    bail!("Could not resolve naming conflict for {}", path.display())
+}
+
+fn parse_component(component: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut chars = component.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut spec = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => spec.push(c),
+                        None => bail!("Unclosed '{{' in '{}'", component),
+                    }
+                }
+                if !literal.is_empty() {
+                    tokens.push(Token::Literal(std::mem::take(&mut literal)));
+                }
+                tokens.push(parse_field(&spec)?);
+            }
+            '}' => bail!("Unmatched '}}' in '{}'", component),
+            _ => literal.push(c),
+        }
+    }
+
+    if !literal.is_empty() {
+        tokens.push(Token::Literal(literal));
+    }
+
+    Ok(tokens)
+}
+
+fn parse_field(spec: &str) -> Result<Token> {
+    let (spec, fallback) = match spec.split_once('|') {
+        Some((spec, fallback)) => (spec, Some(fallback.to_string())),
+        None => (spec, None),
+    };
+    let (name, width) = match spec.split_once(':') {
+        Some((name, format)) => {
+            let width = format
+                .parse::<usize>()
+                .ok()
+                .filter(|_| format.starts_with('0'))
+                .with_context(|| format!("Unsupported format '{}' for {{{}}} (use e.g. :02)", format, name))?;
+            (name.trim(), Some(width))
+        }
+        None => (spec.trim(), None),
+    };
+
+    if !TEMPLATE_FIELDS.contains(&name) {
+        bail!(
+            "Unknown template field '{{{}}}'. Valid fields: {}",
+            name,
+            TEMPLATE_FIELDS.join(", ")
+        );
+    }
+
+    Ok(Token::Field {
+        name: name.to_string(),
+        width,
+        fallback,
+    })
+}
+
+/// Zero-pad the integer part of a numeric value (`1.5` -> `01.5`)
+fn pad(value: &str, width: Option<usize>) -> String {
+    let Some(width) = width else {
+        return value.to_string();
+    };
+
+    let (int, rest) = match value.find('.') {
+        Some(pos) => value.split_at(pos),
+        None => (value, ""),
+    };
+
+    if !int.is_empty() && int.chars().all(|c| c.is_ascii_digit()) {
+        format!("{:0>width$}{}", int, rest, width = width)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Remove separators and brackets left dangling by missing fields
+fn clean_component(component: &str) -> String {
+    let mut s = component.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    loop {
+        let before = s.clone();
+        for empty in ["()", "[]", "( )", "[ ]"] {
+            s = s.replace(empty, "");
+        }
+        s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+        s = s.replace(" - - ", " - ");
+        s = s
+            .trim_matches(|c: char| c == '-' || c == '_' || c == '.' || c.is_whitespace())
+            .to_string();
+        if s == before {
+            break;
+        }
+    }
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> TemplateValues {
+        let mut values = TemplateValues::new();
+        for (k, v) in pairs {
+            values.set(k, *v);
+        }
+        values
+    }
+
+    const LAYOUT: &str = "{author}/{series}/{series_index:02} - {title} ({year})/{title}.m4b";
+
+    #[test]
+    fn test_render_full_layout() {
+        let template = PathTemplate::parse(LAYOUT).unwrap();
+        let path = template
+            .render(&values(&[
+                ("author", "Frank Herbert"),
+                ("series", "Dune"),
+                ("series_index", "1"),
+                ("title", "Dune"),
+                ("year", "1965"),
+            ]))
+            .unwrap();
+        assert_eq!(path, PathBuf::from("Frank Herbert/Dune/01 - Dune (1965)/Dune.m4b"));
+    }
+
+    #[test]
+    fn test_missing_fields_are_cleaned_up() {
+        let template = PathTemplate::parse(LAYOUT).unwrap();
+        let path = template
+            .render(&values(&[("author", "Andy Weir"), ("title", "Project Hail Mary")]))
+            .unwrap();
+        assert_eq!(
+            path,
+            PathBuf::from("Andy Weir/Project Hail Mary/Project Hail Mary.m4b")
+        );
+    }
+
+    #[test]
+    fn test_fallback_and_sanitization() {
+        let template = PathTemplate::parse("{narrator|Unknown Narrator}/{title}").unwrap();
+        let path = template
+            .render(&values(&[("title", "AC/DC: Live?")]))
+            .unwrap();
+        assert_eq!(path.components().count(), 2);
+        assert!(path.starts_with("Unknown Narrator"));
+        let file = path.file_name().unwrap().to_str().unwrap();
+        assert!(file.ends_with(".m4b"));
+        assert!(!file.contains('/') && !file.contains(':') && !file.contains('?'));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(PathTemplate::parse("").is_err());
+        assert!(PathTemplate::parse("/abs/{title}").is_err());
+        assert!(PathTemplate::parse("{bogus}").is_err());
+        assert!(PathTemplate::parse("{title").is_err());
+        assert!(PathTemplate::parse("{series_index:x}").is_err());
+        assert!(PathTemplate::parse("{{literal}} {title}").is_ok());
+    }
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad("1", Some(2)), "01");
+        assert_eq!(pad("1.5", Some(2)), "01.5");
+        assert_eq!(pad("12", Some(2)), "12");
+        assert_eq!(pad("Prologue", Some(2)), "Prologue");
+    }
+
+    #[test]
+    fn test_values_from_book() {
+        let mut book = BookFolder::new(PathBuf::from("/library/Some Folder"));
+        let values = TemplateValues::from_book(&book);
+        assert_eq!(values.get("title"), Some("Some Folder"));
+        assert_eq!(values.get("author"), Some(UNKNOWN_AUTHOR));
+        assert_eq!(values.get("series"), None);
+
+        book.detected_asin = Some("B002V5D7RU".to_string());
+        assert_eq!(TemplateValues::from_book(&book).get("asin"), Some("B002V5D7RU"));
+    }
+
+    #[test]
+    fn test_resolve_collision() {
+        let taken = [PathBuf::from("a/Dune.m4b"), PathBuf::from("a/Dune_2.m4b")];
+        let resolved = resolve_collision(Path::new("a/Dune.m4b"), |p| taken.iter().any(|t| t == p)).unwrap();
+        assert_eq!(resolved, PathBuf::from("a/Dune_3.m4b"));
+
+        let free = resolve_collision(Path::new("a/Other.m4b"), |p| taken.iter().any(|t| t == p)).unwrap();
+        assert_eq!(free, PathBuf::from("a/Other.m4b"));
+    }
+}
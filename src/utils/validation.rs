@@ -1,7 +1,6 @@
 //! Dependency validation utilities
 
-use std::process::Command;
-use which::which;
+use crate::audio::{tools, Tool};
 
 /// Dependency checker for external tools
 pub struct DependencyChecker;
@@ -12,70 +11,43 @@ pub struct DependencyStatus {
     pub found: bool,
     pub version: Option<String>,
     pub path: Option<String>,
+    /// Why the tool can't be used (missing configured path, too old), if it can't
+    pub problem: Option<String>,
 }
 
 impl DependencyChecker {
+    /// Check a tool through the tool registry
+    pub fn check(tool: Tool) -> DependencyStatus {
+        let registry = tools();
+        let path = registry.path(tool).ok();
+
+        DependencyStatus {
+            name: tool.binary().to_string(),
+            found: path.is_some(),
+            version: path.and_then(|_| registry.version(tool)).map(str::to_string),
+            path: path.map(|p| p.display().to_string()),
+            problem: registry.check(tool).err().map(|e| e.to_string()),
+        }
+    }
+
     /// Check if FFmpeg is installed and get version
     pub fn check_ffmpeg() -> DependencyStatus {
-        match which("ffmpeg") {
-            Ok(path) => {
-                let version = Self::get_ffmpeg_version();
-                DependencyStatus {
-                    name: "ffmpeg".to_string(),
-                    found: true,
-                    version,
-                    path: Some(path.display().to_string()),
-                }
-            }
-            Err(_) => DependencyStatus {
-                name: "ffmpeg".to_string(),
-                found: false,
-                version: None,
-                path: None,
-            },
-        }
+        Self::check(Tool::FFmpeg)
+    }
+
+    /// Check if FFprobe is installed and get version
+    pub fn check_ffprobe() -> DependencyStatus {
+        Self::check(Tool::FFprobe)
     }
 
     /// Check if AtomicParsley is installed
     pub fn check_atomic_parsley() -> DependencyStatus {
-        match which("AtomicParsley") {
-            Ok(path) => {
-                let version = Self::get_atomic_parsley_version();
-                DependencyStatus {
-                    name: "AtomicParsley".to_string(),
-                    found: true,
-                    version,
-                    path: Some(path.display().to_string()),
-                }
-            }
-            Err(_) => DependencyStatus {
-                name: "AtomicParsley".to_string(),
-                found: false,
-                version: None,
-                path: None,
-            },
-        }
+        Self::check(Tool::AtomicParsley)
     }
 
     /// Check if MP4Box is installed
     pub fn check_mp4box() -> DependencyStatus {
-        match which("MP4Box") {
-            Ok(path) => {
-                let version = Self::get_mp4box_version();
-                DependencyStatus {
-                    name: "MP4Box".to_string(),
-                    found: true,
-                    version,
-                    path: Some(path.display().to_string()),
-                }
-            }
-            Err(_) => DependencyStatus {
-                name: "MP4Box".to_string(),
-                found: false,
-                version: None,
-                path: None,
-            },
-        }
+        Self::check(Tool::MP4Box)
     }
 
     /// Check all dependencies
@@ -89,67 +61,12 @@ impl DependencyChecker {
 
     /// Check if all dependencies are satisfied
     pub fn all_dependencies_met() -> bool {
-        Self::check_all().iter().all(|dep| dep.found)
-    }
-
-    /// Get FFmpeg version
-    fn get_ffmpeg_version() -> Option<String> {
-        let output = Command::new("ffmpeg")
-            .arg("-version")
-            .output()
-            .ok()?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        stdout
-            .lines()
-            .next()
-            .and_then(|line| line.split_whitespace().nth(2))
-            .map(|s| s.to_string())
-    }
-
-    /// Get AtomicParsley version
-    fn get_atomic_parsley_version() -> Option<String> {
-        let output = Command::new("AtomicParsley")
-            .arg("--version")
-            .output()
-            .ok()?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        stdout
-            .lines()
-            .find(|line| line.contains("version"))
-            .and_then(|line| line.split_whitespace().last())
-            .map(|s| s.to_string())
-    }
-
-    /// Get MP4Box version
-    fn get_mp4box_version() -> Option<String> {
-        let output = Command::new("MP4Box")
-            .arg("-version")
-            .output()
-            .ok()?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        stdout
-            .lines()
-            .next()
-            .and_then(|line| line.split_whitespace().find(|s| s.contains("version")))
-            .map(|s| s.to_string())
+        Tool::ALL.iter().all(|&tool| tools().check(tool).is_ok())
     }
 
     /// Check if Apple Silicon AAC encoder is available
     pub fn check_aac_at_support() -> bool {
-        // Check if ffmpeg supports aac_at encoder
-        let output = Command::new("ffmpeg")
-            .args(&["-encoders"])
-            .output();
-
-        if let Ok(output) = output {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            stdout.contains("aac_at")
-        } else {
-            false
-        }
+        tools().has_encoder("aac_at")
     }
 
     /// Get all available AAC encoders
@@ -176,6 +93,9 @@ impl std::fmt::Display for DependencyStatus {
             if let Some(ref path) = self.path {
                 write!(f, "\n  Path: {}", path)?;
             }
+            if let Some(ref problem) = self.problem {
+                write!(f, "\n  {}", problem)?;
+            }
             Ok(())
         } else {
             write!(f, "✗ {} - NOT FOUND", self.name)